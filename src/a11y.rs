@@ -0,0 +1,70 @@
+use eframe::egui;
+use egui::accesskit::{Live, Role};
+
+use crate::{AppState, YtMp3App};
+
+pub trait AccessibleName {
+    // What screen readers call an icon-only widget
+    fn accessible_name(self, name: &str) -> Self;
+}
+
+impl AccessibleName for egui::Response {
+    fn accessible_name(self, name: &str) -> Self {
+        self.ctx.accesskit_node_builder(self.id, |builder| builder.set_name(name));
+        self
+    }
+}
+
+// Screen readers read a live region out whenever its text changes; errors interrupt, the rest waits its turn
+pub fn live_region(response: &egui::Response, urgent: bool) {
+    response.ctx.accesskit_node_builder(response.id, |builder| {
+        if urgent {
+            builder.set_role(Role::Alert);
+            builder.set_live(Live::Assertive);
+        } else {
+            builder.set_role(Role::Status);
+            builder.set_live(Live::Polite);
+        }
+    });
+}
+
+// The last state change worth reading out
+#[derive(Debug, Default)]
+pub struct Announcement {
+    // Which step was announced, so each one is read once
+    step: &'static str,
+    pub text: String,
+    pub urgent: bool,
+}
+
+impl YtMp3App {
+    // Called every frame: turns state changes into a short sentence for the live region
+    pub(crate) fn update_announcement(&mut self) {
+        let (step, text, urgent) = match &self.state {
+            AppState::Input => ("input", String::new(), false),
+            AppState::Loading => ("loading", "Fetching video information".to_string(), false),
+            AppState::VideoInfo(info) => ("info", format!("Video found: {}. Ready to download", info.title), false),
+            AppState::Downloading { progress, .. } => {
+                // MP4s download video and audio separately, so progress starts over once; halfway stays announced
+                if *progress >= 0.5 || self.announcement.step == "halfway" {
+                    ("halfway", "Download 50% done".to_string(), false)
+                } else {
+                    ("downloading", "Download started".to_string(), false)
+                }
+            }
+            AppState::Error(error) => ("error", format!("Failed: {}", error.trim_start_matches("❌ ")), true),
+            AppState::AgeRestricted(_) => ("age", "This video is age-restricted and needs you to sign in".to_string(), true),
+            AppState::Success(path) => ("success", format!("Download completed, saved to {}", path), false),
+            AppState::NeedsRedownload { reason, .. } => ("redownload", format!("Download finished but the file looks broken: {}", reason), true),
+        };
+        if step != self.announcement.step {
+            self.announcement = Announcement { step, text, urgent };
+        }
+    }
+
+    // Always drawn, even when empty, as screen readers only pick up changes to a region they already know
+    pub(crate) fn show_announcement(&self, ui: &mut egui::Ui) {
+        let response = ui.label(egui::RichText::new(&self.announcement.text).small().weak());
+        live_region(&response, self.announcement.urgent);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::events;
+use crate::{AutoDownload, YtMp3App};
+
+// Things macro pads, Stream Decks and MIDI bridges can trigger, through global hotkeys or the
+// local API (see events.rs)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    // Lets queued downloads start again
+    StartQueue,
+    // Holds the queue; the download that's running finishes
+    PauseQueue,
+    // Fetches and downloads the link on the clipboard
+    PasteAndDownload,
+}
+
+impl Action {
+    pub const ALL: [Action; 3] = [Action::StartQueue, Action::PauseQueue, Action::PasteAndDownload];
+
+    // The name used by the local API
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::StartQueue => "start_queue",
+            Action::PauseQueue => "pause_queue",
+            Action::PasteAndDownload => "paste_and_download",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase().replace('-', "_");
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+impl YtMp3App {
+    // Called every frame with what the hotkeys, the local API and paired browser extensions asked for
+    pub(crate) fn handle_actions(&mut self) {
+        let mut requested: Vec<(Action, &str)> = Vec::new();
+        if let Some(hotkeys) = &self.global_hotkeys {
+            requested.extend(hotkeys.pressed().into_iter().map(|action| (action, "Hotkey")));
+        }
+        if let Some(server) = &self.event_server {
+            requested.extend(server.actions().into_iter().map(|action| (action, "Local API")));
+        }
+
+        for (action, source) in requested {
+            self.run_action(action, source);
+        }
+
+        let extension_requests = self.event_server.as_ref().map(|server| server.extension_requests()).unwrap_or_default();
+        for request in extension_requests {
+            self.handle_extension_request(request);
+        }
+    }
+
+    pub(crate) fn run_action(&mut self, action: Action, source: &str) {
+        match action {
+            Action::StartQueue => {
+                if self.queue_paused {
+                    self.log_console(format!("{}: queue started", source));
+                }
+                self.queue_paused = false;
+                // URLs waiting to be loaded only move on by hand otherwise
+                if self.download_queue.is_empty() && !self.pending_urls.is_empty() && !self.is_busy() && self.receiver.is_none() {
+                    self.load_next_pending_url();
+                }
+            }
+            Action::PauseQueue => {
+                if !self.queue_paused {
+                    self.log_console(format!("{}: queue paused, the running downloads finish", source));
+                }
+                self.queue_paused = true;
+            }
+            Action::PasteAndDownload => {
+                let url = match self.clipboard_url() {
+                    Ok(url) => url,
+                    Err(e) => {
+                        self.log_console(format!("{}: {}", source, e));
+                        return;
+                    }
+                };
+
+                // Queued rather than fetched here, so it waits its turn when the main view or every
+                // download slot is in use; start_next_auto_download picks it up on the next frame
+                let note = if self.queue_paused { " (the queue is paused)" } else { "" };
+                self.log_console(format!("{}: queued {}{}", source, url, note));
+                self.download_queue.push_back(AutoDownload {
+                    url,
+                    format: self.download_format,
+                    output_path: self.output_path.clone(),
+                });
+            }
+        }
+        self.publish_queue_changes();
+        events::publish(events::Event::Action { action: action.name(), source: source.to_string() });
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+
+// Passed by the login entry so the app comes up minimized
+pub const MINIMIZED_ARG: &str = "--minimized";
+
+#[cfg(target_os = "linux")]
+const APP_NAME: &str = "YouTube MP3/MP4 Downloader";
+
+pub fn set_launch_at_login(enabled: bool, minimized: bool) -> Result<()> {
+    let exe_path = std::env::current_exe()?;
+    let exe = exe_path.to_string_lossy().to_string();
+    platform::set_launch_at_login(enabled, &exe, minimized)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+    const VALUE_NAME: &str = "ytmp3";
+
+    pub fn set_launch_at_login(enabled: bool, exe: &str, minimized: bool) -> Result<()> {
+        let mut command = Command::new("reg");
+        if enabled {
+            let mut value = format!("\"{}\"", exe);
+            if minimized {
+                value.push(' ');
+                value.push_str(MINIMIZED_ARG);
+            }
+            command.args(["add", RUN_KEY, "/v", VALUE_NAME, "/t", "REG_SZ", "/d", &value, "/f"]);
+        } else {
+            command.args(["delete", RUN_KEY, "/v", VALUE_NAME, "/f"]);
+        }
+        command.creation_flags(0x08000000);
+
+        let output = command.output()?;
+        // Deleting a value that was never added fails, which is fine
+        if enabled && !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "Could not update the Run registry key: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    fn escape_xml(text: &str) -> String {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+    }
+
+    pub fn set_launch_at_login(enabled: bool, exe: &str, minimized: bool) -> Result<()> {
+        let agents_dir = dirs::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+            .join("Library")
+            .join("LaunchAgents");
+        let plist_path = agents_dir.join("com.ytmp3.downloader.plist");
+
+        if !enabled {
+            if plist_path.exists() {
+                std::fs::remove_file(&plist_path)?;
+            }
+            return Ok(());
+        }
+
+        let mut arguments = format!("<string>{}</string>", escape_xml(exe));
+        if minimized {
+            arguments.push_str(&format!("<string>{}</string>", MINIMIZED_ARG));
+        }
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.ytmp3.downloader</string>
+    <key>ProgramArguments</key>
+    <array>{}</array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            arguments
+        );
+
+        std::fs::create_dir_all(&agents_dir)?;
+        std::fs::write(&plist_path, plist)?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    pub fn set_launch_at_login(enabled: bool, exe: &str, minimized: bool) -> Result<()> {
+        let autostart_dir = dirs::config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?
+            .join("autostart");
+        let desktop_path = autostart_dir.join("ytmp3.desktop");
+
+        if !enabled {
+            if desktop_path.exists() {
+                std::fs::remove_file(&desktop_path)?;
+            }
+            return Ok(());
+        }
+
+        let mut exec = format!("\"{}\"", exe.replace('"', "\\\""));
+        if minimized {
+            exec.push(' ');
+            exec.push_str(MINIMIZED_ARG);
+        }
+        let entry = format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec={}\nX-GNOME-Autostart-enabled=true\n",
+            APP_NAME, exec
+        );
+
+        std::fs::create_dir_all(&autostart_dir)?;
+        std::fs::write(&desktop_path, entry)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+mod platform {
+    use super::*;
+
+    pub fn set_launch_at_login(_enabled: bool, _exe: &str, _minimized: bool) -> Result<()> {
+        Err(anyhow::anyhow!("Launching at login is not supported on this platform"))
+    }
+}
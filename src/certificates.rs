@@ -0,0 +1,68 @@
+use eframe::egui;
+
+use crate::a11y::AccessibleName;
+use crate::help;
+use crate::settings::Settings;
+use crate::YtMp3App;
+
+// For the app's own requests (updates, mirrors, feeds, webhooks): trusts the same extra CA
+// bundle as yt-dlp, or skips the checks like it does
+pub fn client_builder(settings: &Settings) -> reqwest::ClientBuilder {
+    let mut builder = reqwest::Client::builder();
+    if settings.no_check_certificates {
+        return builder.danger_accept_invalid_certs(true);
+    }
+    let bundle = settings.ca_bundle.trim();
+    if bundle.is_empty() {
+        return builder;
+    }
+    match std::fs::read(bundle).map_err(anyhow::Error::from).and_then(|pem| Ok(reqwest::Certificate::from_pem_bundle(&pem)?)) {
+        Ok(certificates) => {
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        Err(e) => log::warn!("Could not load the CA bundle {}: {}", bundle, e),
+    }
+    builder
+}
+
+impl YtMp3App {
+    pub(crate) fn show_certificate_settings(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🔒 Advanced: certificates for TLS-inspecting proxies")
+            .id_source("certificate_settings")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Only change these if your network's proxy decrypts HTTPS traffic.");
+                    help::help_button(ui, "certificates");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Extra CA bundle (PEM):");
+                    ui.add_enabled(
+                        !self.settings.no_check_certificates,
+                        egui::TextEdit::singleline(&mut self.settings.ca_bundle)
+                            .desired_width(220.0)
+                            .hint_text("None"),
+                    );
+                    if ui.add_enabled(!self.settings.no_check_certificates, egui::Button::new("📂"))
+                        .accessible_name("Choose CA bundle")
+                        .clicked()
+                    {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Certificates", &["pem", "crt", "cer"])
+                            .pick_file()
+                        {
+                            self.settings.ca_bundle = path.display().to_string();
+                        }
+                    }
+                });
+                ui.checkbox(&mut self.settings.no_check_certificates, "Don't check certificates at all (insecure)");
+                if self.settings.no_check_certificates {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(230, 150, 0),
+                        "⚠ Anyone on the network can read and change what's downloaded. Use a CA bundle if you can.",
+                    );
+                }
+            });
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::mpsc::{self, SendError, TryRecvError};
+use std::sync::{Arc, Mutex};
+
+use crate::AppMessage;
+
+// Messages a download can get ahead of the UI by. Past this the worker waits, which in turn
+// stops it reading yt-dlp's output, so a chatty yt-dlp slows down instead of filling memory
+const CAPACITY: usize = 256;
+
+// Progress only matters as its latest value: while one update is still waiting in the channel,
+// newer ones replace its value instead of queueing behind it
+#[derive(Default)]
+struct ProgressSlot {
+    in_flight: bool,
+    latest: Option<(f32, String)>,
+}
+
+#[derive(Clone)]
+pub struct AppSender {
+    tx: mpsc::SyncSender<AppMessage>,
+    progress: Arc<Mutex<ProgressSlot>>,
+}
+
+pub struct AppReceiver {
+    rx: mpsc::Receiver<AppMessage>,
+    progress: Arc<Mutex<ProgressSlot>>,
+}
+
+pub fn app_channel() -> (AppSender, AppReceiver) {
+    let (tx, rx) = mpsc::sync_channel(CAPACITY);
+    let progress = Arc::new(Mutex::new(ProgressSlot::default()));
+    (AppSender { tx, progress: progress.clone() }, AppReceiver { rx, progress })
+}
+
+impl AppSender {
+    pub fn send(&self, message: AppMessage) -> Result<(), SendError<AppMessage>> {
+        if let AppMessage::DownloadProgress(progress, status) = message {
+            let mut slot = self.progress.lock().unwrap();
+            if slot.in_flight {
+                slot.latest = Some((progress, status));
+                return Ok(());
+            }
+            slot.in_flight = true;
+            drop(slot);
+            return self.tx.send(AppMessage::DownloadProgress(progress, status));
+        }
+        self.tx.send(message)
+    }
+}
+
+impl AppReceiver {
+    pub fn try_recv(&self) -> Result<AppMessage, TryRecvError> {
+        match self.rx.try_recv()? {
+            AppMessage::DownloadProgress(progress, status) => {
+                // The update keeps its place in the queue but carries the newest value
+                let mut slot = self.progress.lock().unwrap();
+                slot.in_flight = false;
+                let (progress, status) = slot.latest.take().unwrap_or((progress, status));
+                Ok(AppMessage::DownloadProgress(progress, status))
+            }
+            message => Ok(message),
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use eframe::egui;
+use std::io::{self, Read};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::a11y::AccessibleName;
+use crate::{locale, output, process, YtMp3App};
+
+// Only this session's commands are kept, the oldest are dropped first
+const MAX_RECORDS: usize = 200;
+// yt-dlp's error output is kept for failed runs, trimmed to the end where the actual error is
+const MAX_STDERR_LINES: usize = 30;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandExit {
+    Running,
+    Exited(i32),
+    // Stopped by the app (stall, cancel) or by a signal
+    Killed,
+    FailedToStart(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandRecord {
+    pub id: u64,
+    pub program: String,
+    pub args: Vec<String>,
+    pub started_at: chrono::DateTime<chrono::Local>,
+    started: Instant,
+    pub duration: Option<Duration>,
+    pub exit: CommandExit,
+    pub stderr: Vec<String>,
+}
+
+impl CommandRecord {
+    // The command as it would be typed into a terminal
+    pub fn command_line(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|arg| shell_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn status_text(&self) -> (String, egui::Color32) {
+        match &self.exit {
+            CommandExit::Running => ("⏳ running".to_string(), egui::Color32::LIGHT_BLUE),
+            CommandExit::Exited(0) => ("✅ exit 0".to_string(), egui::Color32::GREEN),
+            CommandExit::Exited(code) => (format!("❌ exit {}", code), egui::Color32::RED),
+            CommandExit::Killed => ("⏹ killed".to_string(), egui::Color32::YELLOW),
+            CommandExit::FailedToStart(_) => ("❌ did not start".to_string(), egui::Color32::RED),
+        }
+    }
+}
+
+// Quoting for cmd.exe
+pub fn cmd_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "\"&|<>^%".contains(c)) {
+        return arg.to_string();
+    }
+    format!("\"{}\"", arg.replace('"', "\\\""))
+}
+
+// Quoting for sh and compatible shells
+pub fn posix_quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c)) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(target_os = "windows")]
+fn shell_quote(arg: &str) -> String {
+    cmd_quote(arg)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_quote(arg: &str) -> String {
+    posix_quote(arg)
+}
+
+struct CommandLog {
+    next_id: u64,
+    records: Vec<CommandRecord>,
+}
+
+// Commands are run from worker threads all over the app, so the log lives in one place
+fn log() -> &'static Mutex<CommandLog> {
+    static LOG: OnceLock<Mutex<CommandLog>> = OnceLock::new();
+    LOG.get_or_init(|| Mutex::new(CommandLog { next_id: 1, records: Vec::new() }))
+}
+
+// Adds the command to the log as running and returns its ID for `finished`
+pub fn started(command: &Command) -> u64 {
+    let mut log = log().lock().unwrap();
+    let id = log.next_id;
+    log.next_id += 1;
+    log.records.push(CommandRecord {
+        id,
+        program: command.get_program().to_string_lossy().into_owned(),
+        args: command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect(),
+        started_at: chrono::Local::now(),
+        started: Instant::now(),
+        duration: None,
+        exit: CommandExit::Running,
+        stderr: Vec::new(),
+    });
+    if log.records.len() > MAX_RECORDS {
+        log.records.remove(0);
+    }
+    id
+}
+
+pub fn finished(id: u64, exit: CommandExit, stderr: &str) {
+    let mut log = log().lock().unwrap();
+    if let Some(record) = log.records.iter_mut().find(|record| record.id == id) {
+        record.duration = Some(record.started.elapsed());
+        record.exit = exit;
+        let lines: Vec<&str> = stderr.lines().filter(|line| !line.trim().is_empty()).collect();
+        let skip = lines.len().saturating_sub(MAX_STDERR_LINES);
+        record.stderr = lines[skip..].iter().map(|line| line.to_string()).collect();
+    }
+}
+
+pub fn exit_of(status: ExitStatus) -> CommandExit {
+    status.code().map(CommandExit::Exited).unwrap_or(CommandExit::Killed)
+}
+
+// `Command::output` that also goes into the log
+pub fn output(command: &mut Command) -> io::Result<Output> {
+    let id = started(command);
+    match command.output() {
+        Ok(result) => {
+            finished(id, exit_of(result.status), &output::clean_output(&result.stderr));
+            Ok(result)
+        }
+        Err(e) => {
+            finished(id, CommandExit::FailedToStart(e.to_string()), "");
+            Err(e)
+        }
+    }
+}
+
+// Read on its own thread, or a process writing a lot would block on a full pipe
+fn drain_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_end(&mut buffer).ok();
+        }
+        buffer
+    })
+}
+
+// `output` with a time limit: when it runs out the process tree is killed and None returned
+pub fn output_with_timeout(command: &mut Command, timeout: Option<Duration>) -> io::Result<Option<Output>> {
+    let Some(timeout) = timeout else {
+        return output(command).map(Some);
+    };
+
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+    let id = started(command);
+    let mut tree = match process::ProcessTree::spawn(command) {
+        Ok(tree) => tree,
+        Err(e) => {
+            finished(id, CommandExit::FailedToStart(e.to_string()), "");
+            return Err(e);
+        }
+    };
+
+    let stdout = drain_pipe(tree.child.stdout.take());
+    let stderr = drain_pipe(tree.child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match tree.child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() >= deadline => {
+                tree.kill();
+                break None;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                tree.kill();
+                finished(id, CommandExit::Killed, "");
+                return Err(e);
+            }
+        }
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    match status {
+        Some(status) => {
+            finished(id, exit_of(status), &output::clean_output(&stderr));
+            Ok(Some(Output { status, stdout, stderr }))
+        }
+        None => {
+            finished(id, CommandExit::Killed, &output::clean_output(&stderr));
+            Ok(None)
+        }
+    }
+}
+
+pub fn entries() -> Vec<CommandRecord> {
+    log().lock().unwrap().records.clone()
+}
+
+fn clear() {
+    log().lock().unwrap().records.retain(|record| record.exit == CommandExit::Running);
+}
+
+// Runs a logged command again in the background; the new run shows up in the log by itself
+fn rerun(record: &CommandRecord, ctx: egui::Context) {
+    let mut command = Command::new(&record.program);
+    command.args(&record.args);
+    command.stdout(Stdio::null());
+    command.stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+
+    thread::spawn(move || {
+        let id = started(&command);
+        // Started as a process tree so closing the app stops it along with everything else
+        let mut tree = match process::ProcessTree::spawn(&mut command) {
+            Ok(tree) => tree,
+            Err(e) => {
+                finished(id, CommandExit::FailedToStart(e.to_string()), "");
+                ctx.request_repaint();
+                return;
+            }
+        };
+        ctx.request_repaint();
+
+        let mut stderr = Vec::new();
+        if let Some(mut pipe) = tree.child.stderr.take() {
+            pipe.read_to_end(&mut stderr).ok();
+        }
+        let exit = match tree.child.wait() {
+            Ok(status) => exit_of(status),
+            Err(e) => CommandExit::FailedToStart(e.to_string()),
+        };
+        finished(id, exit, &output::clean_output(&stderr));
+        ctx.request_repaint();
+    });
+}
+
+impl YtMp3App {
+    pub(crate) fn show_command_history_window(&mut self, ctx: &egui::Context) {
+        if !self.show_command_history {
+            return;
+        }
+
+        let records = entries();
+        let mut open = true;
+        egui::Window::new("🧾 Command history")
+            .open(&mut open)
+            .default_size([700.0, 450.0])
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} yt-dlp commands this session", records.len()));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.add_enabled(!records.is_empty(), egui::Button::new("🗑 Clear")).clicked() {
+                            clear();
+                        }
+                    });
+                });
+                ui.separator();
+
+                if records.is_empty() {
+                    ui.label(egui::RichText::new("No commands have run yet").color(egui::Color32::GRAY));
+                    return;
+                }
+
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    for record in records.iter().rev() {
+                        let (status, color) = record.status_text();
+                        ui.horizontal(|ui| {
+                            ui.label(locale::time(&record.started_at));
+                            ui.colored_label(color, status);
+                            if let Some(duration) = record.duration {
+                                ui.label(egui::RichText::new(format!("{:.1}s", duration.as_secs_f32())).color(egui::Color32::GRAY));
+                            }
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button("🔁").accessible_name("Run again").on_hover_text("Run again").clicked() {
+                                    rerun(record, ctx.clone());
+                                }
+                                if ui.small_button("📋").accessible_name("Copy command").on_hover_text("Copy command").clicked() {
+                                    ui.ctx().copy_text(record.command_line());
+                                }
+                            });
+                        });
+                        ui.label(egui::RichText::new(record.command_line()).monospace().small());
+                        if let CommandExit::FailedToStart(error) = &record.exit {
+                            ui.colored_label(egui::Color32::RED, error);
+                        }
+                        if !record.stderr.is_empty() {
+                            egui::CollapsingHeader::new(format!("Error output ({} lines)", record.stderr.len()))
+                                .id_source(("command_stderr", record.id))
+                                .show(ui, |ui| {
+                                    ui.label(egui::RichText::new(record.stderr.join("\n")).monospace().small());
+                                });
+                        }
+                        ui.separator();
+                    }
+                });
+            });
+        self.show_command_history = open;
+    }
+}
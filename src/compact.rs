@@ -0,0 +1,109 @@
+use eframe::egui;
+
+use crate::a11y::AccessibleName;
+use crate::rtl;
+use crate::{AppState, DownloadFormat, YtMp3App};
+
+pub const NORMAL_WINDOW_SIZE: [f32; 2] = [900.0, 800.0];
+pub const COMPACT_WINDOW_SIZE: [f32; 2] = [480.0, 190.0];
+pub const MIN_WINDOW_SIZE: [f32; 2] = [400.0, 150.0];
+
+// Below this width (in points) the full layout no longer fits, so fall back to the compact one
+const AUTO_COMPACT_WIDTH: f32 = 560.0;
+
+impl YtMp3App {
+    pub(crate) fn is_compact(&self, ctx: &egui::Context) -> bool {
+        self.settings.compact_mode || ctx.screen_rect().width() < AUTO_COMPACT_WIDTH
+    }
+
+    pub(crate) fn set_compact_mode(&mut self, ctx: &egui::Context, compact: bool) {
+        self.settings.compact_mode = compact;
+        if let Err(e) = self.settings.save() {
+            self.log_console(format!("WARNING: Could not save settings: {}", e));
+        }
+
+        let size = if compact { COMPACT_WINDOW_SIZE } else { NORMAL_WINDOW_SIZE };
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size.into()));
+    }
+
+    fn compact_status_line(&self) -> (String, Option<f32>) {
+        match &self.state {
+            AppState::Input => ("Paste a URL and press 🔍".to_string(), None),
+            AppState::Loading => ("Fetching video information...".to_string(), None),
+            AppState::VideoInfo(info) => (format!("Ready: {}", rtl::display(&info.title)), None),
+            AppState::Downloading { progress, status, .. } => (status.clone(), Some(*progress)),
+            AppState::Error(error) => (format!("❌ {}", error), None),
+            AppState::AgeRestricted(_) => ("🔞 Age-restricted: open the full window to sign in".to_string(), None),
+            AppState::Success(path) => (format!("✅ Saved to: {}", path), Some(1.0)),
+            AppState::NeedsRedownload { reason, .. } => (format!("⚠ Needs re-download: {}", reason), None),
+        }
+    }
+
+    pub(crate) fn show_compact_ui(&mut self, ctx: &egui::Context) {
+        let mut should_fetch = false;
+        let mut should_start_download = false;
+        let mut should_paste = false;
+        let mut expand = false;
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let busy = matches!(self.state, AppState::Loading | AppState::Downloading { .. }) || self.is_offline();
+                let ready = matches!(self.state, AppState::VideoInfo(_));
+
+                ui.add(egui::TextEdit::singleline(&mut self.url_input)
+                    .desired_width(ui.available_width() - 170.0)
+                    .hint_text("Paste YouTube URL here..."));
+
+                let (button_text, button_name) = if ready { ("⬇", "Download") } else { ("🔍", "Fetch info") };
+                if ui.add_enabled(!busy, egui::Button::new(button_text)).accessible_name(button_name).clicked() {
+                    if ready {
+                        should_start_download = true;
+                    } else if !self.url_input.is_empty() {
+                        should_fetch = true;
+                    }
+                }
+
+                if ui.add_enabled(!busy, egui::Button::new("📋")).accessible_name("Paste & Go").on_hover_text("Paste & Go").clicked() {
+                    should_paste = true;
+                }
+                self.pin_button(ui);
+                self.mute_button(ui);
+                if ui.button("🗖").accessible_name("Exit compact mode").on_hover_text("Exit compact mode").clicked() {
+                    expand = true;
+                }
+            });
+
+            ui.add_visible_ui(!self.is_simple(), |ui| ui.horizontal(|ui| {
+                let before = self.download_format;
+                ui.radio_value(&mut self.download_format, DownloadFormat::Mp4, "🎥 MP4");
+                ui.radio_value(&mut self.download_format, DownloadFormat::Mp3, "🎵 MP3");
+                if self.download_format != before {
+                    self.follow_format_folder(before);
+                }
+            }));
+
+            let (status, progress) = self.compact_status_line();
+            if let Some(progress) = progress {
+                ui.add(egui::ProgressBar::new(progress).text(status));
+            } else {
+                ui.add(egui::Label::new(status).truncate());
+            }
+            self.show_announcement(ui);
+            self.show_offline_banner(ui);
+            self.show_metered_banner(ui);
+        });
+
+        if should_fetch {
+            self.fetch_video_info();
+        }
+        if should_start_download {
+            self.start_download();
+        }
+        if should_paste {
+            self.paste_and_go();
+        }
+        if expand {
+            self.set_compact_mode(ctx, false);
+        }
+    }
+}
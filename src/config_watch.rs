@@ -0,0 +1,60 @@
+use eframe::egui;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::settings::Settings;
+use crate::YtMp3App;
+
+// How often config.toml is looked at for changes made in a text editor
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Default)]
+pub struct ConfigWatch {
+    modified: Option<SystemTime>,
+    last_check: Option<Instant>,
+    // A file that doesn't parse is reported once, not on every check
+    reported_error: Option<String>,
+}
+
+impl YtMp3App {
+    // Called every frame; takes in settings edited outside the app without a restart. The
+    // app's own saves come back unchanged and are ignored
+    pub(crate) fn poll_config_file(&mut self, ctx: &egui::Context) {
+        if self.config_watch.last_check.is_some_and(|last_check| last_check.elapsed() < CHECK_INTERVAL) {
+            return;
+        }
+        self.config_watch.last_check = Some(Instant::now());
+
+        let modified = Settings::modified_time();
+        let previous = std::mem::replace(&mut self.config_watch.modified, modified);
+        if previous.is_none() || modified.is_none() || previous == modified {
+            return;
+        }
+
+        let mut settings = match Settings::reload() {
+            Ok(settings) => settings,
+            Err(e) => {
+                let error = e.to_string();
+                if self.config_watch.reported_error.as_ref() != Some(&error) {
+                    self.log_console(format!("WARNING: config.toml was changed but can't be read, keeping the current settings: {}", error));
+                    self.config_watch.reported_error = Some(error);
+                }
+                return;
+            }
+        };
+        self.config_watch.reported_error = None;
+        // Not part of the file
+        settings.system_proxy = self.settings.system_proxy.clone();
+        settings.burn_subtitles = self.settings.burn_subtitles.clone();
+        settings.format_selector = self.settings.format_selector.clone();
+        if settings == self.settings {
+            return;
+        }
+
+        let before = std::mem::replace(&mut self.settings, settings);
+        if self.output_path == before.default_output_dir(self.download_format) {
+            self.output_path = self.settings.default_output_dir(self.download_format);
+        }
+        self.apply_changed_settings(ctx, &before);
+        self.log_console("Reloaded the settings from config.toml".to_string());
+    }
+}
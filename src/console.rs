@@ -0,0 +1,98 @@
+use eframe::egui;
+
+use crate::jobs::JobId;
+use crate::output::is_progress_line;
+use crate::{crash, YtMp3App};
+
+// Keep a bounded history so output from earlier jobs stays filterable without growing forever
+const MAX_CONSOLE_LINES: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct ConsoleLine {
+    pub job: Option<JobId>,
+    pub text: String,
+}
+
+pub fn push_line(console: &mut Vec<ConsoleLine>, job: Option<JobId>, text: String) {
+    crash::remember_line(&text);
+    if is_progress_line(&text) {
+        if let Some(last) = console.last_mut().filter(|last| last.job == job && is_progress_line(&last.text)) {
+            last.text = text;
+            return;
+        }
+    }
+
+    console.push(ConsoleLine { job, text });
+    if console.len() > MAX_CONSOLE_LINES {
+        console.remove(0);
+    }
+}
+
+impl YtMp3App {
+    pub(crate) fn log_console(&mut self, text: String) {
+        push_line(&mut self.console_output, None, text);
+    }
+
+    pub(crate) fn show_console(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label("📺 yt-dlp Console Output:");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("🧾 Commands")
+                            .on_hover_text("Every yt-dlp command run this session, to copy or run again")
+                            .clicked()
+                        {
+                            self.show_command_history = !self.show_command_history;
+                        }
+                        let selected_text = match self.console_filter {
+                            None => "All jobs".to_string(),
+                            Some(id) => format!("Job #{}", id),
+                        };
+                        egui::ComboBox::from_id_source("console_job_filter")
+                            .selected_text(selected_text)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.console_filter, None, "All jobs");
+                                for job in &self.jobs {
+                                    ui.selectable_value(
+                                        &mut self.console_filter,
+                                        Some(job.id),
+                                        format!("Job #{} — {}", job.id, job.video_info.title),
+                                    );
+                                }
+                            });
+                        ui.label("Show:");
+                    });
+                });
+                ui.add_space(5.0);
+
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                            let mut shown = 0;
+                            for line in &self.console_output {
+                                let text = match (self.console_filter, line.job) {
+                                    (None, Some(job)) => format!("[#{}] {}", job, line.text),
+                                    (None, None) => line.text.clone(),
+                                    (Some(filter), Some(job)) if filter == job => line.text.clone(),
+                                    _ => continue,
+                                };
+                                shown += 1;
+                                ui.label(egui::RichText::new(text)
+                                    .font(egui::FontId::monospace(12.0))
+                                    .color(egui::Color32::LIGHT_GRAY));
+                            }
+
+                            if shown == 0 {
+                                ui.label(egui::RichText::new("Waiting for yt-dlp output...")
+                                    .font(egui::FontId::monospace(12.0))
+                                    .color(egui::Color32::DARK_GRAY));
+                            }
+                        });
+                    });
+            });
+        });
+    }
+}
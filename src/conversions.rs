@@ -0,0 +1,314 @@
+use anyhow::Result;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::time::Duration;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::channel::AppSender;
+use crate::process::{self, ProcessTree};
+use crate::settings::{Settings, VideoEncoder};
+use crate::ytdlp_config;
+use crate::AppMessage;
+
+// How often a conversion waiting for a slot checks whether its download was paused
+const PAUSE_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+// Re-encodes are bound by the processor (or the graphics card with a hardware encoder) while
+// downloads are bound by the network, so conversions have their own limit. Software and hardware
+// encodes are counted separately since they don't compete for the same chip
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConversionPool {
+    Cpu,
+    Gpu,
+}
+
+impl ConversionPool {
+    pub fn for_encoder(encoder: VideoEncoder) -> Self {
+        match encoder {
+            VideoEncoder::Software => ConversionPool::Cpu,
+            _ => ConversionPool::Gpu,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Running {
+    cpu: usize,
+    gpu: usize,
+}
+
+impl Running {
+    fn count(&mut self, pool: ConversionPool) -> &mut usize {
+        match pool {
+            ConversionPool::Cpu => &mut self.cpu,
+            ConversionPool::Gpu => &mut self.gpu,
+        }
+    }
+}
+
+fn running() -> &'static (Mutex<Running>, Condvar) {
+    static RUNNING: OnceLock<(Mutex<Running>, Condvar)> = OnceLock::new();
+    RUNNING.get_or_init(|| (Mutex::new(Running::default()), Condvar::new()))
+}
+
+// Held while a conversion runs; dropping it lets the next waiting conversion start
+pub struct ConversionSlot {
+    pool: ConversionPool,
+}
+
+impl Drop for ConversionSlot {
+    fn drop(&mut self) {
+        let (lock, freed) = running();
+        let mut running = lock.lock().unwrap();
+        *running.count(self.pool) -= 1;
+        freed.notify_all();
+    }
+}
+
+// Blocks until fewer than `limit` conversions of the pool are running. `on_wait` is called once
+// if the caller has to wait, to tell the user why nothing is happening. Gives up as soon as the
+// download is paused, which then doesn't keep its place in line
+pub fn acquire(pool: ConversionPool, limit: u32, paused: &AtomicBool, on_wait: impl FnOnce()) -> Option<ConversionSlot> {
+    let limit = limit.max(1) as usize;
+    let (lock, freed) = running();
+    let mut running = lock.lock().unwrap();
+    if *running.count(pool) >= limit {
+        // Not called under the lock: it may block on a full message channel
+        drop(running);
+        on_wait();
+        running = lock.lock().unwrap();
+        while *running.count(pool) >= limit {
+            if paused.load(Ordering::Relaxed) {
+                return None;
+            }
+            running = freed.wait_timeout(running, PAUSE_CHECK_INTERVAL).unwrap().0;
+        }
+    }
+    if paused.load(Ordering::Relaxed) {
+        return None;
+    }
+    *running.count(pool) += 1;
+    Some(ConversionSlot { pool })
+}
+
+// Runs an ffmpeg conversion of a download in a slot of the pool. Pausing the download stops
+// ffmpeg and gives the slot to the next conversion; once resumed it waits for a slot again and
+// starts over, since ffmpeg can't continue a file it was writing
+pub fn convert(
+    command: &mut Command,
+    pool: ConversionPool,
+    label: &str,
+    duration_seconds: Option<f64>,
+    settings: &Settings,
+    paused: &AtomicBool,
+    progress_sender: &AppSender,
+) -> Result<()> {
+    loop {
+        let slot = acquire(pool, settings.max_conversions, paused, || {
+            progress_sender.send(AppMessage::DownloadProgress(0.0, "Waiting for another conversion to finish...".to_string())).ok();
+        });
+        if slot.is_some() {
+            progress_sender.send(AppMessage::DownloadProgress(0.0, format!("{}...", label))).ok();
+            if run_ffmpeg(command, label, duration_seconds, paused, progress_sender)? {
+                return Ok(());
+            }
+            progress_sender.send(AppMessage::ConsoleOutput(format!("{} paused, it starts over when resumed", label))).ok();
+        }
+        drop(slot);
+
+        progress_sender.send(AppMessage::DownloadProgress(0.0, "⏸ Paused".to_string())).ok();
+        while paused.load(Ordering::Relaxed) {
+            std::thread::sleep(PAUSE_CHECK_INTERVAL);
+        }
+    }
+}
+
+// A re-encode that runs after the download: the encoder, ffmpeg's output options and what the
+// status says meanwhile
+pub struct Reencode {
+    pub encoder: VideoEncoder,
+    pub args: String,
+    pub label: &'static str,
+}
+
+// Re-encodes a finished download with ffmpeg on its own, so only the conversion and not the
+// download takes a slot. The result replaces the download as an .mp4; returns its path
+pub fn reencode(
+    video_path: &str,
+    plan: &Reencode,
+    duration_seconds: Option<f64>,
+    settings: &Settings,
+    paused: &AtomicBool,
+    progress_sender: &AppSender,
+) -> Result<String> {
+    let video = Path::new(video_path);
+    let output = if video.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("mp4")) {
+        video.to_path_buf()
+    } else {
+        unique_sibling(&video.with_extension("mp4"), "converted")
+    };
+    let temp_output = video.with_extension("reencode.mp4");
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(["-hide_banner", "-y", "-i"])
+        .arg(video)
+        .args(ytdlp_config::split_args(&plan.args))
+        .args(["-progress", "pipe:1", "-nostats"])
+        .args(settings.ffmpeg_thread_args())
+        .arg(&temp_output)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    if settings.low_priority {
+        process::lower_priority(&mut command);
+    }
+
+    let pool = ConversionPool::for_encoder(plan.encoder);
+    if let Err(e) = convert(&mut command, pool, plan.label, duration_seconds, settings, paused, progress_sender) {
+        std::fs::remove_file(&temp_output).ok();
+        return Err(e);
+    }
+
+    std::fs::remove_file(video)?;
+    std::fs::rename(&temp_output, &output)?;
+    progress_sender.send(AppMessage::ConsoleOutput(format!("{}: saved {}", plan.label, output.display()))).ok();
+    Ok(output.display().to_string())
+}
+
+// Runs ffmpeg with `-progress pipe:1`, reporting "<label>... n%" as it goes. Returns false if
+// it was stopped because the download was paused
+fn run_ffmpeg(
+    command: &mut Command,
+    label: &str,
+    duration_seconds: Option<f64>,
+    paused: &AtomicBool,
+    progress_sender: &AppSender,
+) -> Result<bool> {
+    let mut tree = ProcessTree::spawn(command)?;
+    let stderr = tree.child.stderr.take();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut errors = Vec::new();
+        if let Some(stderr) = stderr {
+            for line in BufReader::new(stderr).lines().map_while(|line| line.ok()) {
+                errors.push(line);
+            }
+        }
+        errors
+    });
+
+    // -progress writes a key=value block about twice a second; out_time_us is the position in
+    // the output so far
+    if let Some(stdout) = tree.child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+            if paused.load(Ordering::Relaxed) {
+                tree.kill();
+                stderr_reader.join().ok();
+                return Ok(false);
+            }
+            let (Some(duration), Some(micros)) = (duration_seconds, line.strip_prefix("out_time_us=")) else {
+                continue;
+            };
+            if let Ok(micros) = micros.trim().parse::<f64>() {
+                let fraction = ((micros / 1_000_000.0) / duration).clamp(0.0, 1.0) as f32;
+                progress_sender.send(AppMessage::DownloadProgress(
+                    fraction,
+                    format!("{}... {:.0}%", label, fraction * 100.0),
+                )).ok();
+            }
+        }
+    }
+
+    let status = tree.child.wait()?;
+    let errors = stderr_reader.join().unwrap_or_default();
+    if status.success() {
+        Ok(true)
+    } else {
+        let tail: Vec<&str> = errors.iter().rev().take(5).rev().map(String::as_str).collect();
+        Err(anyhow::anyhow!("{} failed: {}", label, tail.join("\n")))
+    }
+}
+
+// A new file must not take the place of an unrelated one with the same name, so it gets
+// " (<tag> n)" added instead
+pub fn unique_sibling(path: &Path, tag: &str) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|ext| ext.to_string_lossy().to_string()).unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{} ({} {}).{}", stem, tag, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn waiting_stops_when_paused() {
+        let never = AtomicBool::new(false);
+        let held = acquire(ConversionPool::Gpu, 1, &never, || {}).unwrap();
+
+        let paused = Arc::new(AtomicBool::new(false));
+        let pauser = std::thread::spawn({
+            let paused = paused.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(300));
+                paused.store(true, Ordering::Relaxed);
+            }
+        });
+        let mut waited = false;
+        assert!(acquire(ConversionPool::Gpu, 1, &paused, || waited = true).is_none());
+        assert!(waited);
+        pauser.join().unwrap();
+
+        // The paused one didn't take the slot, so it's free again once the running one is done
+        drop(held);
+        assert!(acquire(ConversionPool::Gpu, 1, &never, || panic!("the slot should be free")).is_some());
+    }
+
+    // A stand-in for ffmpeg that reports progress for a second
+    #[cfg(unix)]
+    #[test]
+    fn pausing_stops_the_conversion_and_frees_the_slot() {
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "for i in 1 2 3 4 5 6 7 8 9 10; do echo out_time_us=${i}00000; sleep 0.1; done"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let settings = Settings::default();
+        let (tx, rx) = crate::channel::app_channel();
+        let paused = Arc::new(AtomicBool::new(false));
+
+        let converter = std::thread::spawn({
+            let paused = paused.clone();
+            move || convert(&mut command, ConversionPool::Cpu, "Converting", Some(1.0), &settings, &paused, &tx)
+        });
+        std::thread::sleep(Duration::from_millis(300));
+        paused.store(true, Ordering::Relaxed);
+        std::thread::sleep(Duration::from_millis(300));
+        // Paused, so the slot is free for another conversion
+        let other = acquire(ConversionPool::Cpu, 1, &AtomicBool::new(false), || panic!("the paused conversion kept its slot"));
+        drop(other);
+        paused.store(false, Ordering::Relaxed);
+
+        converter.join().unwrap().unwrap();
+        let mut restarted = false;
+        while let Ok(message) = rx.try_recv() {
+            if let AppMessage::ConsoleOutput(line) = message {
+                restarted |= line.contains("starts over");
+            }
+        }
+        assert!(restarted);
+    }
+}
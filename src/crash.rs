@@ -0,0 +1,215 @@
+use anyhow::Result;
+use eframe::egui;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::output::is_progress_line;
+use crate::settings::get_data_dir;
+use crate::{database, help, profiles, YtMp3App};
+
+// Console lines kept for the report
+const RECENT_LINES: usize = 200;
+// Names the report the next start hasn't shown yet
+const UNSEEN_MARKER: &str = "unseen";
+
+fn recent_lines() -> &'static Mutex<VecDeque<String>> {
+    static RECENT: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+    RECENT.get_or_init(|| Mutex::new(VecDeque::new()))
+}
+
+fn queued_urls() -> &'static Mutex<Vec<String>> {
+    static QUEUED: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+    QUEUED.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+// Called for every console line, from any job
+pub fn remember_line(text: &str) {
+    if is_progress_line(text) {
+        return;
+    }
+    if let Ok(mut lines) = recent_lines().lock() {
+        lines.push_back(text.to_string());
+        if lines.len() > RECENT_LINES {
+            lines.pop_front();
+        }
+    }
+}
+
+// Called when the saved queue changes. The database has it too; the copy goes in the report
+pub fn remember_queue(urls: Vec<String>) {
+    if let Ok(mut queued) = queued_urls().lock() {
+        *queued = urls;
+    }
+}
+
+fn crash_dir() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("crashes"))
+}
+
+// The panic is printed as before, then written to crashes/ in the data folder. Release builds
+// abort right after, so nothing here may wait on a lock the panicking thread could hold
+pub fn install_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        match write_report(info) {
+            Ok(path) => eprintln!("Crash report saved to {}", path.display()),
+            Err(e) => eprintln!("Could not save a crash report: {}", e),
+        }
+    }));
+}
+
+fn write_report(info: &PanicHookInfo) -> Result<PathBuf> {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    let location = info.location().map(|location| location.to_string()).unwrap_or_default();
+    let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+    let now = chrono::Local::now();
+
+    let mut report = String::new();
+    writeln!(report, "{} {} crashed at {}", crate::APP_TITLE, env!("CARGO_PKG_VERSION"), now.format("%Y-%m-%d %H:%M:%S %:z"))?;
+    writeln!(report, "OS: {} {}", std::env::consts::OS, std::env::consts::ARCH)?;
+    writeln!(report, "Profile: {}", profiles::active().as_deref().unwrap_or("default"))?;
+    writeln!(report, "Thread: {}", thread)?;
+    writeln!(report, "Panic: {}", message)?;
+    writeln!(report, "Location: {}", location)?;
+
+    let queued = queued_urls().try_lock().map(|queued| queued.clone()).unwrap_or_default();
+    writeln!(report, "\nQueue ({}):", queued.len())?;
+    for url in &queued {
+        writeln!(report, "  {}", url)?;
+    }
+
+    let lines = recent_lines().try_lock().map(|lines| lines.clone()).unwrap_or_default();
+    writeln!(report, "\nRecent log:")?;
+    for line in &lines {
+        writeln!(report, "  {}", line)?;
+    }
+
+    writeln!(report, "\nBacktrace:\n{}", std::backtrace::Backtrace::force_capture())?;
+
+    let dir = crash_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, report)?;
+    std::fs::write(dir.join(UNSEEN_MARKER), path.to_string_lossy().as_bytes())?;
+    Ok(path)
+}
+
+// The report of a crash since the last start, once
+fn take_unseen_report() -> Option<PathBuf> {
+    let marker = crash_dir().ok()?.join(UNSEEN_MARKER);
+    let path = std::fs::read_to_string(&marker).ok()?;
+    std::fs::remove_file(&marker).ok();
+    Some(PathBuf::from(path.trim()))
+}
+
+fn open_report(path: &Path) {
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("notepad")
+            .arg(path)
+            .spawn()
+            .ok();
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(path)
+            .spawn()
+            .ok();
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(path)
+            .spawn()
+            .ok();
+    }
+}
+
+// Asked instead of restoring the queue after a crash, in case something in it caused the crash
+pub struct CrashPrompt {
+    report: PathBuf,
+    queued: usize,
+}
+
+impl YtMp3App {
+    // Restores the saved queue, unless the app crashed last time; then the user decides. Without
+    // a window nobody can, so the daemon carries on with the queue
+    pub(crate) fn restore_queue(&mut self) {
+        let Some(report) = take_unseen_report() else {
+            self.restore_saved_queue();
+            return;
+        };
+
+        self.log_console(format!("WARNING: The app crashed last time. The crash report is in {}", report.display()));
+        if self.daemon {
+            self.restore_saved_queue();
+            return;
+        }
+        let queued = database::saved_queue_len().unwrap_or_default();
+        self.crash_prompt = Some(CrashPrompt { report, queued });
+    }
+
+    pub(crate) fn show_crash_prompt(&mut self, ctx: &egui::Context) {
+        let Some(prompt) = &self.crash_prompt else {
+            return;
+        };
+
+        let mut answer = None;
+        egui::Window::new("💥 The app closed unexpectedly")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("Sorry, the app crashed last time. A crash report with what it was doing was saved.");
+                if prompt.queued > 0 {
+                    ui.label(format!(
+                        "{} download(s) were queued or running. Restore them, or start with an empty queue if one of them keeps crashing the app.",
+                        prompt.queued
+                    ));
+                }
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    if prompt.queued > 0 {
+                        if ui.button("↺ Restore the queue").clicked() {
+                            answer = Some(true);
+                        }
+                        if ui.button("Start with an empty queue").clicked() {
+                            answer = Some(false);
+                        }
+                    } else if ui.button("OK").clicked() {
+                        answer = Some(false);
+                    }
+                    if ui.button("📄 Open the report").clicked() {
+                        open_report(&prompt.report);
+                    }
+                    help::help_button(ui, "crash");
+                });
+            });
+
+        match answer {
+            Some(true) => {
+                self.crash_prompt = None;
+                self.restore_saved_queue();
+            }
+            Some(false) => {
+                self.crash_prompt = None;
+                if let Err(e) = database::clear_saved_queue() {
+                    self.log_console(format!("WARNING: Could not clear the saved queue: {}", e));
+                }
+            }
+            None => {}
+        }
+    }
+}
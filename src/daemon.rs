@@ -0,0 +1,116 @@
+use eframe::egui;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::output::is_progress_line;
+use crate::{instance, locale, pairing, process, profiles, webui, DownloadFormat, YtMp3App};
+
+// Runs the queue, the queue templates and the local API without a window:
+// `ytmp3 --daemon [--pair] [URL...]`
+pub const DAEMON_ARG: &str = "--daemon";
+// Prints a pairing code for a browser extension or remote at startup
+pub const PAIR_ARG: &str = "--pair";
+
+// How often the engine runs; the window does it every frame
+const TICK: Duration = Duration::from_millis(100);
+
+static STOP: AtomicBool = AtomicBool::new(false);
+
+// `docker stop` and Ctrl+C; the downloads' process trees are killed on the way out. On Windows
+// they're in a job object that closes with the app anyway
+#[cfg(unix)]
+fn stop_on_signals() {
+    extern "C" fn stop(_: libc::c_int) {
+        STOP.store(true, Ordering::Relaxed);
+    }
+    let handler = stop as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+#[cfg(not(unix))]
+fn stop_on_signals() {}
+
+pub fn run(urls: Vec<String>) {
+    stop_on_signals();
+    profiles::set_active(profiles::Profiles::load().last_used());
+
+    // Background threads ask it for repaints, which go nowhere without a window
+    let ctx = egui::Context::default();
+    let mut app = YtMp3App { daemon: true, ..YtMp3App::default() };
+    app.download_format = app.settings.simple_format;
+    app.output_path = app.simple_folder();
+    app.apply_daemon_settings(&ctx);
+    app.instance_receiver = instance::start_listener(ctx.clone());
+    app.restore_queue();
+    app.receive_urls(urls);
+
+    while !STOP.load(Ordering::Relaxed) {
+        app.run_engine(&ctx);
+        app.print_console();
+        thread::sleep(TICK);
+    }
+
+    writeln!(std::io::stdout(), "Stopping").ok();
+    process::kill_all();
+}
+
+impl YtMp3App {
+    // apply_startup_settings without what only makes sense with a window and a keyboard
+    fn apply_daemon_settings(&mut self, ctx: &egui::Context) {
+        locale::set(&self.settings.locale);
+        self.apply_event_stream_settings(ctx);
+        self.apply_system_proxy_settings();
+        self.apply_offline_detection_settings(ctx);
+        self.apply_metered_settings(ctx);
+
+        self.log_console(format!(
+            "Running without a window. Downloads go to {} as {}",
+            self.output_path,
+            if self.download_format == DownloadFormat::Mp3 { "MP3" } else { "MP4" }
+        ));
+        if !self.settings.event_stream_enabled {
+            self.log_console("The local API is off; set event_stream_enabled = true in config.toml to queue URLs over HTTP".to_string());
+            return;
+        }
+        if !self.settings.event_stream_lan {
+            self.log_console(format!(
+                "The local API only listens on 127.0.0.1:{}; set event_stream_lan = true in config.toml to reach it from other computers or outside a container",
+                self.settings.event_stream_port
+            ));
+        }
+        self.log_console(format!(
+            "Web page for queueing downloads: {}",
+            webui::page_url(self.settings.event_stream_lan, self.settings.event_stream_port)
+        ));
+        if self.settings.paired_extensions.is_empty() || std::env::args().any(|arg| arg == PAIR_ARG) {
+            pairing::start();
+            if let Some((code, left)) = pairing::active_code() {
+                self.log_console(format!(
+                    "Pairing code for a browser extension, remote or the web page: {} (for the next {} minutes)",
+                    code,
+                    left.as_secs().div_ceil(60)
+                ));
+            }
+        }
+    }
+
+    // The console goes to standard output, where `docker logs` and journald pick it up.
+    // Progress lines would flood it. A closed pipe loses the lines instead of stopping the daemon
+    pub(crate) fn print_console(&mut self) {
+        let mut stdout = std::io::stdout().lock();
+        for line in self.console_output.drain(..) {
+            if !is_progress_line(&line.text) {
+                match line.job {
+                    Some(job) => writeln!(stdout, "[job {}] {}", job, line.text).ok(),
+                    None => writeln!(stdout, "{}", line.text).ok(),
+                };
+            }
+        }
+        stdout.flush().ok();
+    }
+}
@@ -0,0 +1,350 @@
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::events::format_name;
+use crate::history;
+use crate::jobs::JobStatus;
+use crate::settings::get_data_dir;
+use crate::{crash, AutoDownload, DownloadFormat, YtMp3App};
+
+const DATABASE_FILE_NAME: &str = "ytmp3.db";
+// The window and a daemon on the same profile take turns writing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Lists kept as one JSON row per entry, in order
+pub const QUEUE_TEMPLATES: &str = "queue_templates";
+pub const RETRY_LATER: &str = "retry_later";
+pub const SAVED_FOR_LATER: &str = "saved_for_later";
+pub const FAILED_DOWNLOADS: &str = "failed_downloads";
+
+// Each step moves the schema one version on; the database's user_version is how many have run
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE history (
+        id INTEGER PRIMARY KEY,
+        video_id TEXT,
+        url TEXT NOT NULL,
+        title TEXT NOT NULL,
+        uploader TEXT NOT NULL,
+        channel_url TEXT,
+        format TEXT NOT NULL,
+        path TEXT NOT NULL,
+        completed_at INTEGER NOT NULL,
+        deleted INTEGER NOT NULL DEFAULT 0,
+        tags TEXT NOT NULL DEFAULT '[]',
+        height INTEGER,
+        replaced INTEGER NOT NULL DEFAULT 0,
+        missing INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX history_video_id ON history (video_id);
+    CREATE INDEX history_url ON history (url);
+    CREATE INDEX history_completed_at ON history (completed_at);
+    CREATE TABLE queue (
+        position INTEGER PRIMARY KEY,
+        url TEXT NOT NULL,
+        format TEXT,
+        output_path TEXT
+    );
+    CREATE TABLE queue_templates (position INTEGER PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE retry_later (position INTEGER PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE saved_for_later (position INTEGER PRIMARY KEY, data TEXT NOT NULL);
+    CREATE TABLE failed_downloads (position INTEGER PRIMARY KEY, data TEXT NOT NULL);
+", "
+    CREATE TABLE download_outcomes (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL,
+        extractor TEXT NOT NULL,
+        format TEXT NOT NULL,
+        succeeded INTEGER NOT NULL,
+        finished_at INTEGER NOT NULL
+    );
+"];
+
+// Files of earlier versions, read into their tables when the database is created and then
+// renamed to *.json.imported: (file, the list's key in it, table)
+const LEGACY_LISTS: &[(&str, &str, &str)] = &[
+    ("queue_templates.json", "templates", QUEUE_TEMPLATES),
+    ("retry_later.json", "entries", RETRY_LATER),
+    ("saved_for_later.json", "entries", SAVED_FOR_LATER),
+    ("failed_downloads.json", "entries", FAILED_DOWNLOADS),
+];
+const LEGACY_HISTORY: &str = "history.json";
+
+pub fn parse_format(name: &str) -> Option<DownloadFormat> {
+    [DownloadFormat::Mp3, DownloadFormat::Mp4].into_iter().find(|format| format_name(*format) == name)
+}
+
+// The active profile's database, created and brought up to date as needed. Connections are
+// cheap enough to open for each read or write, which keeps profile switches and a daemon
+// running next to the window simple
+pub fn open() -> Result<Connection> {
+    let data_dir = get_data_dir()?;
+    std::fs::create_dir_all(&data_dir)?;
+    let mut connection = Connection::open(data_dir.join(DATABASE_FILE_NAME))?;
+    connection.busy_timeout(BUSY_TIMEOUT)?;
+    connection.pragma_update(None, "journal_mode", "WAL")?;
+
+    let version: usize = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version < MIGRATIONS.len() {
+        let transaction = connection.transaction()?;
+        for migration in &MIGRATIONS[version..] {
+            transaction.execute_batch(migration)?;
+        }
+        let imported = if version == 0 { import_legacy_files(&transaction, &data_dir) } else { Vec::new() };
+        transaction.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        transaction.commit()?;
+
+        for file in imported {
+            let path = data_dir.join(file);
+            if let Err(e) = std::fs::rename(&path, path.with_extension("json.imported")) {
+                log::warn!("Could not rename {}: {}", path.display(), e);
+            }
+        }
+    }
+    Ok(connection)
+}
+
+// Fills the new tables from the JSON files; returns the files that were read
+fn import_legacy_files(connection: &Connection, data_dir: &Path) -> Vec<&'static str> {
+    let mut imported = Vec::new();
+
+    if let Ok(contents) = std::fs::read_to_string(data_dir.join(LEGACY_HISTORY)) {
+        match serde_json::from_str::<history::History>(&contents) {
+            Ok(old) => match history::insert_entries(connection, &old.entries) {
+                Ok(()) => imported.push(LEGACY_HISTORY),
+                Err(e) => log::warn!("Could not import {}: {}", LEGACY_HISTORY, e),
+            },
+            Err(e) => log::warn!("Could not parse {}: {}", LEGACY_HISTORY, e),
+        }
+    }
+
+    for (file, key, table) in LEGACY_LISTS {
+        let Ok(contents) = std::fs::read_to_string(data_dir.join(file)) else {
+            continue;
+        };
+        let entries = match serde_json::from_str::<serde_json::Value>(&contents) {
+            Ok(mut value) => value.get_mut(*key).map(serde_json::Value::take).unwrap_or_default(),
+            Err(e) => {
+                log::warn!("Could not parse {}: {}", file, e);
+                continue;
+            }
+        };
+        let entries = entries.as_array().cloned().unwrap_or_default();
+        match insert_list(connection, table, &entries) {
+            Ok(()) => imported.push(*file),
+            Err(e) => log::warn!("Could not import {}: {}", file, e),
+        }
+    }
+    imported
+}
+
+fn insert_list<T: Serialize>(connection: &Connection, table: &str, entries: &[T]) -> Result<()> {
+    let mut insert = connection.prepare(&format!("INSERT INTO {} (data) VALUES (?1)", table))?;
+    for entry in entries {
+        insert.execute(params![serde_json::to_string(entry)?])?;
+    }
+    Ok(())
+}
+
+// Entries that no longer parse, e.g. after a downgrade, are skipped rather than losing the list
+pub fn load_list<T: DeserializeOwned>(table: &str) -> Result<Vec<T>> {
+    let connection = open()?;
+    let mut select = connection.prepare(&format!("SELECT data FROM {} ORDER BY position", table))?;
+    let rows = select.query_map([], |row| row.get::<_, String>(0))?;
+    let mut entries = Vec::new();
+    for data in rows {
+        match serde_json::from_str(&data?) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => log::warn!("Skipping an entry of {} that can't be read: {}", table, e),
+        }
+    }
+    Ok(entries)
+}
+
+pub fn save_list<T: Serialize>(table: &str, entries: &[T]) -> Result<()> {
+    let mut connection = open()?;
+    let transaction = connection.transaction()?;
+    transaction.execute(&format!("DELETE FROM {}", table), [])?;
+    insert_list(&transaction, table, entries)?;
+    transaction.commit()?;
+    Ok(())
+}
+
+// A row of the saved queue. URLs waiting to be loaded have no format or folder yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedUrl {
+    pub url: String,
+    pub download: Option<(DownloadFormat, String)>,
+}
+
+// With each row's position, which stays the same for as long as the row is kept
+fn load_queue() -> Result<Vec<(i64, QueuedUrl)>> {
+    let connection = open()?;
+    let mut select = connection.prepare("SELECT position, url, format, output_path FROM queue ORDER BY position")?;
+    let rows = select.query_map([], |row| {
+        let format: Option<String> = row.get(2)?;
+        let output_path: Option<String> = row.get(3)?;
+        Ok((row.get(0)?, QueuedUrl {
+            url: row.get(1)?,
+            download: format.as_deref().and_then(parse_format).zip(output_path),
+        }))
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+pub fn saved_queue_len() -> Result<usize> {
+    Ok(open()?.query_row("SELECT COUNT(*) FROM queue", [], |row| row.get(0))?)
+}
+
+pub fn clear_saved_queue() -> Result<()> {
+    open()?.execute("DELETE FROM queue", [])?;
+    Ok(())
+}
+
+// Brings the table from `saved`, the rows as last written, to `queue`. Rows still in the same
+// order keep their place and the others are deleted; what's left of the queue goes at the end.
+// A download finishing or a URL added at the back is a single delete or insert however long
+// the queue is, and only rows behind something moved or put in the middle are written again
+fn write_queue_changes(connection: &Connection, saved: &mut Vec<(i64, QueuedUrl)>, queue: &[QueuedUrl]) -> Result<()> {
+    let mut kept = Vec::new();
+    let mut delete = connection.prepare("DELETE FROM queue WHERE position = ?1")?;
+    for (position, old) in saved.drain(..) {
+        if queue.get(kept.len()) == Some(&old) {
+            kept.push((position, old));
+        } else {
+            delete.execute(params![position])?;
+        }
+    }
+
+    let mut insert = connection.prepare("INSERT INTO queue (url, format, output_path) VALUES (?1, ?2, ?3)")?;
+    for queued in &queue[kept.len()..] {
+        let (format, output_path) = match &queued.download {
+            Some((format, output_path)) => (Some(format_name(*format)), Some(output_path.as_str())),
+            None => (None, None),
+        };
+        insert.execute(params![queued.url, format, output_path])?;
+        kept.push((connection.last_insert_rowid(), queued.clone()));
+    }
+    *saved = kept;
+    Ok(())
+}
+
+fn save_queue(saved: &mut Vec<(i64, QueuedUrl)>, queue: &[QueuedUrl]) -> Result<()> {
+    let mut connection = open()?;
+    let transaction = connection.transaction()?;
+    write_queue_changes(&transaction, saved, queue)?;
+    transaction.commit()?;
+    Ok(())
+}
+
+impl YtMp3App {
+    // The running downloads, then the automatic downloads, then the URLs waiting to be loaded
+    fn current_queue(&self) -> Vec<QueuedUrl> {
+        let running = self.jobs.iter().filter(|job| {
+            self.downloads.iter().any(|download| download.job_id == job.id) && matches!(job.status, JobStatus::Running { .. })
+        });
+        running
+            .map(|job| QueuedUrl { url: job.url.clone(), download: Some((job.format, job.output_path.clone())) })
+            .chain(self.download_queue.iter().map(|download| QueuedUrl {
+                url: download.url.clone(),
+                download: Some((download.format, download.output_path.clone())),
+            }))
+            .chain(self.pending_urls.iter().map(|url| QueuedUrl { url: url.clone(), download: None }))
+            .collect()
+    }
+
+    // Called every frame; writes the queue when it changes, so it survives a crash or restart.
+    // The queue of a crashed session stays untouched until the user decides what to do with it
+    pub(crate) fn persist_queue(&mut self) {
+        if self.crash_prompt.is_some() {
+            return;
+        }
+        let queue = self.current_queue();
+        if queue.iter().eq(self.saved_queue.iter().map(|(_, queued)| queued)) {
+            return;
+        }
+        if let Err(e) = save_queue(&mut self.saved_queue, &queue) {
+            // Read back, so the next try starts from what the table really holds
+            log::warn!("Could not save the queue: {}", e);
+            self.saved_queue = load_queue().unwrap_or_default();
+        }
+        crash::remember_queue(queue.iter().map(|queued| queued.url.clone()).collect());
+    }
+
+    // Puts back what was queued or downloading when the app last closed
+    pub(crate) fn restore_saved_queue(&mut self) {
+        let queue = match load_queue() {
+            Ok(queue) => queue,
+            Err(e) => {
+                self.log_console(format!("WARNING: Could not read the saved queue: {}", e));
+                return;
+            }
+        };
+        if queue.is_empty() {
+            return;
+        }
+
+        self.log_console(format!("Restored {} download(s) queued when the app last closed", queue.len()));
+        let mut downloads = Vec::new();
+        for (_, queued) in &queue {
+            match &queued.download {
+                Some((format, output_path)) => downloads.push(AutoDownload {
+                    url: queued.url.clone(),
+                    format: *format,
+                    output_path: output_path.clone(),
+                }),
+                None => self.pending_urls.push_back(queued.url.clone()),
+            }
+        }
+        self.queue_auto_downloads(downloads);
+        crash::remember_queue(queue.iter().map(|(_, queued)| queued.url.clone()).collect());
+        self.saved_queue = queue;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued(url: &str) -> QueuedUrl {
+        QueuedUrl { url: url.to_string(), download: Some((DownloadFormat::Mp3, "/music".to_string())) }
+    }
+
+    fn rows(connection: &Connection) -> Vec<(i64, String)> {
+        let mut select = connection.prepare("SELECT position, url FROM queue ORDER BY position").unwrap();
+        let rows = select.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        rows.collect::<Result<_, _>>().unwrap()
+    }
+
+    // Each step is checked against the table, and rows outside the change keep their position
+    #[test]
+    fn queue_changes_touch_only_changed_rows() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection.execute_batch(MIGRATIONS[0]).unwrap();
+        let mut saved = Vec::new();
+        let mut save = |queue: &[&str]| {
+            let queue: Vec<QueuedUrl> = queue.iter().map(|url| queued(url)).collect();
+            write_queue_changes(&connection, &mut saved, &queue).unwrap();
+            let table = rows(&connection);
+            let urls: Vec<&str> = table.iter().map(|(_, url)| url.as_str()).collect();
+            assert_eq!(urls, queue.iter().map(|queued| queued.url.as_str()).collect::<Vec<_>>());
+            assert_eq!(table, saved.iter().map(|(position, queued)| (*position, queued.url.clone())).collect::<Vec<_>>());
+            table
+        };
+
+        let first = save(&["a", "b", "c"]);
+        // The first download finished and one was added at the back
+        let second = save(&["b", "c", "d"]);
+        assert_eq!(second[..2], first[1..]);
+        // One taken out of the middle
+        let third = save(&["b", "d"]);
+        assert_eq!(third, [second[0].clone(), second[2].clone()]);
+        // Moved to the front
+        save(&["d", "b"]);
+        save(&[]);
+        save(&["e"]);
+    }
+}
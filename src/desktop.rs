@@ -0,0 +1,181 @@
+use anyhow::Result;
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{help, YtMp3App};
+
+// Links like ytmp3://youtu.be/... or ytmp3:https://... open in the app once the entry is installed
+const SCHEME: &str = "ytmp3";
+const DESKTOP_FILE: &str = "ytmp3.desktop";
+const APP_NAME: &str = "YouTube MP3/MP4 Downloader";
+
+// $XDG_DATA_HOME/applications, where the desktop looks for the user's own entries
+fn applications_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    Ok(data_dir.join("applications"))
+}
+
+// An AppImage runs from a temporary mount; $APPIMAGE is the file that stays put
+fn exe_path() -> Result<PathBuf> {
+    match std::env::var_os("APPIMAGE") {
+        Some(appimage) => Ok(PathBuf::from(appimage)),
+        None => Ok(std::env::current_exe()?),
+    }
+}
+
+// Quoted for the Exec key: the spec's own escapes, then the backslashes once more for the file's string rules
+fn exec_quoted(path: &Path) -> String {
+    let mut quoted = String::from("\"");
+    for c in path.to_string_lossy().chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted.replace('\\', "\\\\")
+}
+
+fn desktop_entry(exe: &Path) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name={}\n\
+         GenericName=Video Downloader\n\
+         Comment=Download videos as MP3 or MP4 with yt-dlp\n\
+         Exec={} %U\n\
+         Icon=folder-download\n\
+         Terminal=false\n\
+         Categories=AudioVideo;Network;\n\
+         MimeType=x-scheme-handler/{};text/uri-list;\n\
+         StartupNotify=true\n",
+        APP_NAME,
+        exec_quoted(exe),
+        SCHEME
+    )
+}
+
+#[derive(PartialEq)]
+enum EntryStatus {
+    Missing,
+    Installed,
+    // Points at another copy of the app, e.g. after it was moved
+    Outdated,
+}
+
+fn entry_status() -> EntryStatus {
+    let (Ok(dir), Ok(exe)) = (applications_dir(), exe_path()) else {
+        return EntryStatus::Missing;
+    };
+    match std::fs::read_to_string(dir.join(DESKTOP_FILE)) {
+        Ok(entry) if entry == desktop_entry(&exe) => EntryStatus::Installed,
+        Ok(_) => EntryStatus::Outdated,
+        Err(_) => EntryStatus::Missing,
+    }
+}
+
+// Adds the app to the applications menu and makes it the handler of ytmp3: links
+pub fn install() -> Result<PathBuf> {
+    let dir = applications_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(DESKTOP_FILE);
+    std::fs::write(&path, desktop_entry(&exe_path()?))?;
+
+    // Both are best effort; without them the desktop picks the entry up at the next login
+    Command::new("update-desktop-database").arg(&dir).output().ok();
+    Command::new("xdg-mime")
+        .args(["default", DESKTOP_FILE, &format!("x-scheme-handler/{}", SCHEME)])
+        .output()
+        .ok();
+    Ok(path)
+}
+
+pub fn uninstall() -> Result<()> {
+    let dir = applications_dir()?;
+    let path = dir.join(DESKTOP_FILE);
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    Command::new("update-desktop-database").arg(&dir).output().ok();
+    Ok(())
+}
+
+// What the entry hands over, as URLs to load: ytmp3: links become the web address they carry,
+// and a URL list opened with the app (a path, or a file:// URI from %U) becomes its URLs
+pub fn expand_args(args: Vec<String>) -> Vec<String> {
+    let mut urls = Vec::new();
+    for arg in args {
+        if let Some(rest) = arg.strip_prefix(SCHEME).and_then(|rest| rest.strip_prefix(':')) {
+            let rest = rest.strip_prefix("//").unwrap_or(rest);
+            if rest.starts_with("http://") || rest.starts_with("https://") {
+                urls.push(rest.to_string());
+            } else {
+                urls.push(format!("https://{}", rest));
+            }
+            continue;
+        }
+        let file = match url::Url::parse(&arg) {
+            Ok(uri) if uri.scheme() == "file" => uri.to_file_path().ok(),
+            Ok(_) => None,
+            Err(_) => Some(PathBuf::from(&arg)).filter(|path| path.is_file()),
+        };
+        match file.and_then(|path| std::fs::read_to_string(path).ok()) {
+            // text/uri-list allows comment lines starting with #
+            Some(list) => urls.extend(
+                list.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            ),
+            None => urls.push(arg),
+        }
+    }
+    urls
+}
+
+impl YtMp3App {
+    pub(crate) fn show_desktop_integration_settings(&mut self, ui: &mut egui::Ui) {
+        let mut install_entry = false;
+        let mut remove_entry = false;
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🐧 Desktop Integration:", "desktop_entry");
+                ui.add_space(5.0);
+                let status = entry_status();
+                ui.label(match status {
+                    EntryStatus::Missing => "Not in the applications menu",
+                    EntryStatus::Installed => "✔ In the applications menu and opens ytmp3: links",
+                    EntryStatus::Outdated => "⚠ The menu entry opens another copy of the app",
+                });
+                ui.horizontal(|ui| {
+                    let label = if status == EntryStatus::Missing { "➕ Add to applications menu" } else { "🔄 Update entry" };
+                    if ui.add_enabled(status != EntryStatus::Installed, egui::Button::new(label)).clicked() {
+                        install_entry = true;
+                    }
+                    if status != EntryStatus::Missing && ui.button("🗑 Remove").clicked() {
+                        remove_entry = true;
+                    }
+                });
+            });
+        });
+
+        if install_entry {
+            match install() {
+                Ok(path) => self.log_console(format!("Added {} to the applications menu", path.display())),
+                Err(e) => self.log_console(format!("WARNING: Could not add the applications menu entry: {}", e)),
+            }
+        }
+        if remove_entry {
+            match uninstall() {
+                Ok(()) => self.log_console("Removed the applications menu entry".to_string()),
+                Err(e) => self.log_console(format!("WARNING: Could not remove the applications menu entry: {}", e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use eframe::egui;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::thread;
+use std::time::Duration;
+
+use crate::a11y::AccessibleName;
+use crate::certificates;
+use crate::help;
+use crate::settings::Settings;
+use crate::{locale, rtl, YtMp3App};
+
+// Finished runs listed in the digest window
+const KEEP_DIGESTS: usize = 20;
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Outcome {
+    // Queued, or left for the user to download
+    Waiting,
+    Downloaded { path: String },
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestItem {
+    pub url: String,
+    pub title: Option<String>,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+// What one run of a queue template found and what became of it
+#[derive(Debug, Clone, Serialize)]
+pub struct RunDigest {
+    pub template: String,
+    // Unix timestamps (seconds)
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    pub items: Vec<DigestItem>,
+    // Channels, playlists and feeds of the run that haven't been read yet
+    #[serde(skip)]
+    sources: usize,
+}
+
+impl RunDigest {
+    pub fn downloaded(&self) -> usize {
+        self.items.iter().filter(|item| matches!(item.outcome, Outcome::Downloaded { .. })).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.items.iter().filter(|item| matches!(item.outcome, Outcome::Failed { .. })).count()
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "\"{}\": {} new video(s) found, {} downloaded, {} failed",
+            self.template,
+            self.items.len(),
+            self.downloaded(),
+            self.failed()
+        )
+    }
+
+    // The summary and one line per item, for chat webhooks
+    pub fn text(&self) -> String {
+        let mut text = self.summary();
+        for item in &self.items {
+            let mark = match &item.outcome {
+                Outcome::Waiting => "…",
+                Outcome::Downloaded { .. } => "✔",
+                Outcome::Failed { .. } => "✖",
+            };
+            match &item.title {
+                Some(title) => text.push_str(&format!("\n{} {} {}", mark, title, item.url)),
+                None => text.push_str(&format!("\n{} {}", mark, item.url)),
+            }
+        }
+        text
+    }
+
+    fn is_settled(&self) -> bool {
+        self.sources == 0 && !self.items.iter().any(|item| matches!(item.outcome, Outcome::Waiting))
+    }
+}
+
+#[derive(Default)]
+pub struct RunDigests {
+    pub open: bool,
+    running: Vec<RunDigest>,
+    finished: VecDeque<RunDigest>,
+    // Which run a channel or playlist belongs to, until its videos are listed
+    sources: HashMap<String, String>,
+}
+
+impl RunDigests {
+    fn running_mut(&mut self, template: &str) -> Option<&mut RunDigest> {
+        self.running.iter_mut().find(|digest| digest.template == template)
+    }
+
+    // A template run by hand while its last run is still going adds to that run's digest
+    pub fn start(&mut self, template: &str) {
+        if self.running_mut(template).is_none() {
+            self.running.push(RunDigest {
+                template: template.to_string(),
+                started_at: chrono::Utc::now().timestamp(),
+                finished_at: None,
+                items: Vec::new(),
+                sources: 0,
+            });
+        }
+    }
+
+    pub fn add_items(&mut self, template: &str, items: Vec<(String, Option<String>)>) {
+        if let Some(digest) = self.running_mut(template) {
+            digest.items.extend(items.into_iter().map(|(url, title)| DigestItem { url, title, outcome: Outcome::Waiting }));
+        }
+    }
+
+    // `url` is the channel or playlist whose videos are added once yt-dlp lists them; feeds
+    // are added by the template itself
+    pub fn add_source(&mut self, template: &str, url: Option<&str>) {
+        if let Some(digest) = self.running_mut(template) {
+            digest.sources += 1;
+            if let Some(url) = url {
+                self.sources.insert(url.to_string(), template.to_string());
+            }
+        }
+    }
+
+    pub fn take_source(&mut self, url: &str) -> Option<String> {
+        self.sources.remove(url)
+    }
+
+    pub fn source_done(&mut self, template: &str) {
+        if let Some(digest) = self.running_mut(template) {
+            digest.sources = digest.sources.saturating_sub(1);
+        }
+    }
+
+    fn waiting_item(&mut self, url: &str) -> Option<&mut DigestItem> {
+        self.running
+            .iter_mut()
+            .flat_map(|digest| digest.items.iter_mut())
+            .find(|item| item.url == url && matches!(item.outcome, Outcome::Waiting))
+    }
+
+    pub fn record_title(&mut self, url: &str, title: &str) {
+        if let Some(item) = self.waiting_item(url) {
+            item.title = Some(title.to_string());
+        }
+    }
+
+    pub fn record(&mut self, url: &str, outcome: Outcome) {
+        if let Some(item) = self.waiting_item(url) {
+            item.outcome = outcome;
+        }
+    }
+
+    // Runs with nothing left to wait for; once the app is idle, whatever is still waiting
+    // stays that way in the digest
+    fn take_finished(&mut self, idle: bool) -> Vec<RunDigest> {
+        let (finished, running) = std::mem::take(&mut self.running)
+            .into_iter()
+            .partition(|digest| idle || digest.is_settled());
+        self.running = running;
+        if idle {
+            self.sources.clear();
+        }
+        finished
+    }
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    // Slack and most chat services read "text", Discord reads "content"
+    text: String,
+    content: String,
+    digest: &'a RunDigest,
+}
+
+fn send_webhook(url: String, digest: &RunDigest, settings: &Settings) {
+    let text = digest.text();
+    let payload = WebhookPayload { content: text.clone(), text, digest };
+    let body = match serde_json::to_string(&payload) {
+        Ok(body) => body,
+        Err(e) => {
+            log::warn!("Could not encode the run digest: {}", e);
+            return;
+        }
+    };
+    let proxy = settings.active_proxy().cloned();
+    let builder = certificates::client_builder(settings);
+    thread::spawn(move || {
+        let rt = match tokio::runtime::Runtime::new() {
+            Ok(rt) => rt,
+            Err(e) => {
+                log::warn!("Digest webhook: {}", e);
+                return;
+            }
+        };
+        let result: anyhow::Result<()> = rt.block_on(async {
+            let mut client = builder.timeout(WEBHOOK_TIMEOUT);
+            if let Some(proxy) = proxy {
+                client = client.proxy(proxy.client_proxy()?);
+            }
+            let response = client
+                .build()?
+                .post(&url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!("HTTP {}", response.status()));
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            log::warn!("Digest webhook {}: {}", url, e);
+        }
+    });
+}
+
+impl YtMp3App {
+    // Called every frame; reports template runs that are done
+    pub(crate) fn poll_run_digests(&mut self) {
+        let idle = !self.is_busy()
+            && self.receiver.is_none()
+            && self.downloads.is_empty()
+            && self.download_queue.is_empty()
+            && self.playlist_fetch.is_none()
+            && !self.reading_feeds();
+        for mut digest in self.run_digests.take_finished(idle) {
+            digest.finished_at = Some(chrono::Utc::now().timestamp());
+            self.log_console(format!("Template run finished: {}", digest.summary()));
+
+            let webhook = self.settings.digest_webhook_url.trim();
+            if !webhook.is_empty() {
+                let settings = self.settings.with_proxy(self.proxy.as_deref());
+                send_webhook(webhook.to_string(), &digest, &settings);
+            }
+            if self.settings.show_run_digest && !digest.items.is_empty() {
+                self.run_digests.open = true;
+            }
+
+            self.run_digests.finished.push_front(digest);
+            self.run_digests.finished.truncate(KEEP_DIGESTS);
+        }
+    }
+
+    pub(crate) fn show_digest_window(&mut self, ctx: &egui::Context) {
+        if !self.run_digests.open {
+            return;
+        }
+
+        let mut open = true;
+        let mut settings_changed = false;
+        egui::Window::new("📬 Template run digests")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    settings_changed |= ui.checkbox(&mut self.settings.show_run_digest, "Open this window when a run finds new videos")
+                        .changed();
+                    help::help_button(ui, "run_digest");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Webhook:");
+                    settings_changed |= ui.add(egui::TextEdit::singleline(&mut self.settings.digest_webhook_url)
+                        .desired_width(300.0)
+                        .hint_text("https://hooks.example.com/..."))
+                        .accessible_name("Digest webhook URL")
+                        .on_hover_text("Each digest is POSTed here as JSON, e.g. to a Slack, Discord or email relay webhook")
+                        .lost_focus();
+                });
+                ui.separator();
+
+                if self.run_digests.finished.is_empty() {
+                    ui.label(egui::RichText::new("No template runs have finished yet.").color(egui::Color32::GRAY));
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for (index, digest) in self.run_digests.finished.iter().enumerate() {
+                        let finished = digest.finished_at.and_then(locale::timestamp).unwrap_or_default();
+                        egui::CollapsingHeader::new(format!("{} ({})", digest.summary(), finished))
+                            .id_source(("run_digest", index))
+                            .default_open(index == 0)
+                            .show(ui, |ui| {
+                                if digest.items.is_empty() {
+                                    ui.label("Nothing new.");
+                                }
+                                for item in &digest.items {
+                                    ui.horizontal(|ui| {
+                                        let title = item.title.as_deref().unwrap_or(&item.url);
+                                        match &item.outcome {
+                                            Outcome::Waiting => {
+                                                ui.label("…").on_hover_text("Not downloaded");
+                                            }
+                                            Outcome::Downloaded { path } => {
+                                                ui.colored_label(egui::Color32::GREEN, "✔").on_hover_text(path);
+                                            }
+                                            Outcome::Failed { error } => {
+                                                ui.colored_label(egui::Color32::RED, "✖").on_hover_text(error);
+                                            }
+                                        }
+                                        ui.hyperlink_to(rtl::display(title).to_string(), &item.url);
+                                    });
+                                }
+                            });
+                    }
+                });
+            });
+        self.run_digests.open = open;
+
+        if settings_changed {
+            if let Err(e) = self.settings.save() {
+                self.log_console(format!("WARNING: Could not save settings: {}", e));
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+// A disconnected network share can block file system calls for a long time
+const REACHABLE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DriveKind {
+    Local,
+    Network,
+    Removable,
+}
+
+impl DriveKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DriveKind::Local => "💾 Local drive",
+            DriveKind::Network => "🌐 Network drive",
+            DriveKind::Removable => "💽 Removable drive",
+        }
+    }
+}
+
+// Looking this up means reading the mount table, so remember the answer per folder
+pub fn drive_kind(path: &str) -> DriveKind {
+    static CACHE: OnceLock<Mutex<HashMap<String, DriveKind>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(kind) = cache.lock().unwrap().get(path) {
+        return *kind;
+    }
+
+    let kind = platform::drive_kind(Path::new(path));
+    cache.lock().unwrap().insert(path.to_string(), kind);
+    kind
+}
+
+// Whether the folder can be reached right now, giving up instead of hanging on a dead share
+pub fn is_reachable(path: &str) -> bool {
+    let (tx, rx) = mpsc::channel();
+    let path = path.to_string();
+    thread::spawn(move || {
+        tx.send(Path::new(&path).is_dir()).ok();
+    });
+    rx.recv_timeout(REACHABLE_TIMEOUT).unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::fileapi::GetDriveTypeW;
+    use winapi::um::winbase::{DRIVE_REMOTE, DRIVE_REMOVABLE};
+
+    pub fn drive_kind(path: &Path) -> DriveKind {
+        let text = path.to_string_lossy();
+        if text.starts_with(r"\\") && !text.starts_with(r"\\?\") {
+            return DriveKind::Network;
+        }
+
+        let text = text.trim_start_matches(r"\\?\");
+        let mut chars = text.chars();
+        let (Some(letter), Some(':')) = (chars.next(), chars.next()) else {
+            return DriveKind::Local;
+        };
+        let root: Vec<u16> = std::ffi::OsStr::new(&format!("{}:\\", letter))
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+
+        match unsafe { GetDriveTypeW(root.as_ptr()) } {
+            DRIVE_REMOTE => DriveKind::Network,
+            DRIVE_REMOVABLE => DriveKind::Removable,
+            _ => DriveKind::Local,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    const NETWORK_FILESYSTEMS: &[&str] = &[
+        "nfs", "nfs4", "cifs", "smb3", "smbfs", "fuse.sshfs", "9p", "afs", "ceph", "fuse.rclone", "davfs",
+    ];
+
+    pub fn drive_kind(path: &Path) -> DriveKind {
+        let Ok(mounts) = std::fs::read_to_string("/proc/mounts") else {
+            return DriveKind::Local;
+        };
+
+        // The mount with the longest mount point containing the path is the one it lives on
+        let mount = mounts
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let _device = fields.next()?;
+                let mount_point = fields.next()?.replace("\\040", " ");
+                let fs_type = fields.next()?.to_string();
+                Some((mount_point, fs_type))
+            })
+            .filter(|(mount_point, _)| path.starts_with(mount_point))
+            .max_by_key(|(mount_point, _)| mount_point.len());
+
+        match mount {
+            Some((_, fs_type)) if NETWORK_FILESYSTEMS.contains(&fs_type.as_str()) => DriveKind::Network,
+            Some((mount_point, _)) if mount_point.starts_with("/media/") || mount_point.starts_with("/run/media/") => {
+                DriveKind::Removable
+            }
+            _ => DriveKind::Local,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use std::process::Command;
+
+    const NETWORK_FILESYSTEMS: &[&str] = &["smbfs", "nfs", "afpfs", "webdav", "macfuse", "osxfuse"];
+
+    pub fn drive_kind(path: &Path) -> DriveKind {
+        // Lines look like "//user@server/share on /Volumes/share (smbfs, nodev, nosuid, mounted by user)"
+        let Ok(output) = Command::new("mount").output() else {
+            return DriveKind::Local;
+        };
+        let mounts = String::from_utf8_lossy(&output.stdout);
+
+        let mount = mounts
+            .lines()
+            .filter_map(|line| {
+                let (_, rest) = line.split_once(" on ")?;
+                let (mount_point, options) = rest.rsplit_once(" (")?;
+                let fs_type = options.split(',').next()?.trim().to_string();
+                Some((mount_point.to_string(), fs_type))
+            })
+            .filter(|(mount_point, _)| path.starts_with(mount_point))
+            .max_by_key(|(mount_point, _)| mount_point.len());
+
+        match mount {
+            Some((_, fs_type)) if NETWORK_FILESYSTEMS.contains(&fs_type.as_str()) => DriveKind::Network,
+            Some((mount_point, _)) if mount_point.starts_with("/Volumes/") => DriveKind::Removable,
+            _ => DriveKind::Local,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+mod platform {
+    use super::*;
+
+    pub fn drive_kind(_path: &Path) -> DriveKind {
+        DriveKind::Local
+    }
+}
@@ -0,0 +1,157 @@
+use eframe::egui;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::a11y::AccessibleName;
+use crate::help;
+use crate::settings::VideoEncoder;
+use crate::theme;
+use crate::{DownloadFormat, YtMp3App};
+
+pub enum EncoderSupport {
+    Unknown,
+    Checking(mpsc::Receiver<Result<Vec<VideoEncoder>, String>>),
+    // Encoders that are built into ffmpeg and actually work on this machine
+    Available(Vec<VideoEncoder>),
+    Unavailable(String),
+}
+
+pub(crate) fn ffmpeg_command() -> Command {
+    let mut command = Command::new("ffmpeg");
+    command.arg("-hide_banner");
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    command
+}
+
+// An encoder being compiled in says nothing about the GPU, so encode a few blank frames with it
+fn encoder_works(encoder: VideoEncoder) -> bool {
+    ffmpeg_command()
+        .args([
+            "-v", "error",
+            "-f", "lavfi",
+            "-i", "color=black:s=256x256:d=0.2",
+            "-c:v", encoder.ffmpeg_name(),
+            "-f", "null",
+            "-",
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn detect_encoders(ctx: egui::Context) -> mpsc::Receiver<Result<Vec<VideoEncoder>, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = match ffmpeg_command().arg("-encoders").output() {
+            Ok(output) => {
+                // Lines look like " V....D h264_nvenc           NVIDIA NVENC H.264 encoder (codec h264)"
+                let listing = String::from_utf8_lossy(&output.stdout);
+                let compiled: Vec<&str> = listing
+                    .lines()
+                    .filter_map(|line| line.split_whitespace().nth(1))
+                    .collect();
+                let available: Vec<VideoEncoder> = VideoEncoder::ALL
+                    .into_iter()
+                    .filter(|encoder| compiled.contains(&encoder.ffmpeg_name()))
+                    .filter(|encoder| *encoder == VideoEncoder::Software || encoder_works(*encoder))
+                    .collect();
+                if available.is_empty() {
+                    Err("ffmpeg was built without an H.264 encoder".to_string())
+                } else {
+                    Ok(available)
+                }
+            }
+            Err(e) => Err(format!("Could not run ffmpeg: {}", e)),
+        };
+        tx.send(result).ok();
+        ctx.request_repaint();
+    });
+    rx
+}
+
+impl YtMp3App {
+    // Lives outside Settings::ui because probing the encoders runs ffmpeg in the background
+    pub(crate) fn show_encoder_settings(&mut self, ui: &mut egui::Ui) {
+        if let EncoderSupport::Checking(rx) = &self.encoder_support {
+            match rx.try_recv() {
+                Ok(Ok(encoders)) => self.encoder_support = EncoderSupport::Available(encoders),
+                Ok(Err(error)) => self.encoder_support = EncoderSupport::Unavailable(error),
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.encoder_support = EncoderSupport::Unavailable("Check did not finish".to_string());
+                }
+            }
+        }
+
+        let reduced_motion = self.settings.reduced_motion;
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🎞 Video re-encoding:", "reencode");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.settings.h264_reencode, "Convert MP4 downloads to H.264/AAC")
+                    .on_hover_text(help::summary("reencode"));
+
+                // Device presets for video re-encode with the same encoder
+                let preset_encodes = self.settings.device_preset.download_format() == Some(DownloadFormat::Mp4);
+                if !self.settings.h264_reencode && !preset_encodes {
+                    return;
+                }
+                if matches!(self.encoder_support, EncoderSupport::Unknown) {
+                    self.encoder_support = EncoderSupport::Checking(detect_encoders(ui.ctx().clone()));
+                }
+
+                let mut recheck = false;
+                match &self.encoder_support {
+                    EncoderSupport::Unknown | EncoderSupport::Checking(_) => {
+                        ui.horizontal(|ui| {
+                            theme::busy_indicator(ui, reduced_motion);
+                            ui.label("Testing which encoders ffmpeg can use...");
+                        });
+                    }
+                    EncoderSupport::Available(encoders) => {
+                        ui.horizontal(|ui| {
+                            ui.label("Encoder:");
+                            egui::ComboBox::from_id_source("video_encoder")
+                                .selected_text(self.settings.video_encoder.label())
+                                .show_ui(ui, |ui| {
+                                    for encoder in VideoEncoder::ALL {
+                                        let usable = encoders.contains(&encoder);
+                                        ui.add_enabled_ui(usable, |ui| {
+                                            ui.selectable_value(&mut self.settings.video_encoder, encoder, encoder.label())
+                                        })
+                                        .response
+                                        .on_disabled_hover_text("Not supported by this ffmpeg or graphics hardware");
+                                    }
+                                });
+                            if ui.small_button("🔄").accessible_name("Test the encoders again").on_hover_text("Test the encoders again").clicked() {
+                                recheck = true;
+                            }
+                        });
+                        if !encoders.contains(&self.settings.video_encoder) {
+                            ui.colored_label(egui::Color32::YELLOW, format!(
+                                "⚠ {} is not available here, downloads will fail to re-encode",
+                                self.settings.video_encoder.label()
+                            ));
+                        }
+                    }
+                    EncoderSupport::Unavailable(reason) => {
+                        ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", reason));
+                        if ui.button("🔄 Check again").clicked() {
+                            recheck = true;
+                        }
+                    }
+                }
+                if recheck {
+                    self.encoder_support = EncoderSupport::Unknown;
+                }
+            });
+        });
+    }
+}
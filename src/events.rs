@@ -0,0 +1,490 @@
+use anyhow::Result;
+use eframe::egui;
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::Message;
+
+use crate::actions::Action;
+use crate::jobs::JobId;
+use crate::pairing::{self, ExtensionRequest};
+use crate::{webui, DownloadFormat, YtMp3App};
+
+// How often client threads look for new events and the listener for new connections
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// What the main window sees of the queue and the downloads, streamed as JSON objects with a
+// "type" field to OBS overlays, dashboards and scripts on the same machine, and to paired
+// instances on the network. The same port takes actions, as WebSocket text messages or as
+// HTTP POST /actions/<name>. GET /status has the queue and the current download
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    // First message on every connection, with the queue as it stands
+    Hello { app: &'static str, version: &'static str, queued: usize, paused: bool, urls: Vec<String>, actions: Vec<&'static str> },
+    VideoInfo { url: String, title: String, uploader: String, duration_secs: Option<u64> },
+    FetchFailed { url: String, error: String },
+    DownloadStarted { job_id: JobId, url: String, title: String, format: &'static str },
+    Progress { job_id: Option<JobId>, percent: f32, status: String },
+    DownloadComplete { job_id: Option<JobId>, url: String, path: String },
+    DownloadFailed { job_id: Option<JobId>, url: String, error: String },
+    // The file failed verification and needs downloading again
+    DownloadSuspicious { job_id: Option<JobId>, path: String, reason: String },
+    Console { job_id: Option<JobId>, line: String },
+    // Downloads waiting after the current one
+    Queue { queued: usize, paused: bool, urls: Vec<String> },
+    // An action was run, from a hotkey or the local API
+    Action { action: &'static str, source: String },
+    // Only sent to the client whose message couldn't be understood
+    Error { message: String },
+}
+
+pub fn format_name(format: DownloadFormat) -> &'static str {
+    match format {
+        DownloadFormat::Mp4 => "mp4",
+        DownloadFormat::Mp3 => "mp3",
+    }
+}
+
+// The last queue state published, for the greeting of clients that connect later
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+fn queued_urls() -> &'static Mutex<Vec<String>> {
+    static QUEUED_URLS: OnceLock<Mutex<Vec<String>>> = OnceLock::new();
+    QUEUED_URLS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+// The download that's running, for GET /status
+#[derive(Clone, Serialize)]
+struct CurrentDownload {
+    title: String,
+    percent: f32,
+    status: String,
+}
+
+fn current_download() -> &'static Mutex<Option<CurrentDownload>> {
+    static CURRENT_DOWNLOAD: OnceLock<Mutex<Option<CurrentDownload>>> = OnceLock::new();
+    CURRENT_DOWNLOAD.get_or_init(|| Mutex::new(None))
+}
+
+fn track_current_download(event: &Event) {
+    let Ok(mut current) = current_download().lock() else {
+        return;
+    };
+    match event {
+        Event::DownloadStarted { title, .. } => {
+            *current = Some(CurrentDownload { title: title.clone(), percent: 0.0, status: String::new() });
+        }
+        Event::Progress { percent, status, .. } => {
+            if let Some(current) = current.as_mut() {
+                current.percent = *percent;
+                current.status.clone_from(status);
+            }
+        }
+        Event::DownloadComplete { .. } | Event::DownloadFailed { .. } => *current = None,
+        _ => {}
+    }
+}
+
+// One sender per connected client
+fn clients() -> &'static Mutex<Vec<mpsc::Sender<String>>> {
+    static CLIENTS: OnceLock<Mutex<Vec<mpsc::Sender<String>>>> = OnceLock::new();
+    CLIENTS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+// Sends the event to every connected client; nothing is serialized while no one listens
+pub fn publish(event: Event) {
+    track_current_download(&event);
+    let Ok(mut clients) = clients().lock() else {
+        return;
+    };
+    if clients.is_empty() {
+        return;
+    }
+    let text = match serde_json::to_string(&event) {
+        Ok(text) => text,
+        Err(e) => {
+            log::warn!("Could not serialize event: {}", e);
+            return;
+        }
+    };
+    clients.retain(|client| client.send(text.clone()).is_ok());
+}
+
+// Web pages could otherwise connect to the loopback port from any site the user visits.
+// Scripts send no Origin, OBS browser sources send http://absolute, and local dashboards are
+// served from localhost. "null" and file:// are refused: any site gets a null origin from a
+// sandboxed iframe
+fn is_allowed_origin(origin: &str) -> bool {
+    let origin = origin.trim().to_ascii_lowercase();
+    if origin == "http://absolute" {
+        return true;
+    }
+    let Some(host) = origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://")) else {
+        return false;
+    };
+    let host = host.trim_end_matches('/');
+    let host = match host.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => host,
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+// The signature is the one tungstenite expects of a handshake callback
+#[allow(clippy::result_large_err)]
+fn check_origin(request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    let origin = request.headers().get("origin").and_then(|origin| origin.to_str().ok());
+    match origin {
+        Some(origin) if !is_allowed_origin(origin) => {
+            log::warn!("Refused an event stream connection from {}", origin);
+            let mut refused = ErrorResponse::new(Some("Origin not allowed".to_string()));
+            *refused.status_mut() = tungstenite::http::StatusCode::FORBIDDEN;
+            Err(refused)
+        }
+        _ => Ok(response),
+    }
+}
+
+// A WebSocket text message is either an action name or {"action": "<name>"}
+fn parse_action(text: &str) -> Option<Action> {
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(value) => value.get("action").and_then(|action| action.as_str()).and_then(Action::from_name),
+        Err(_) => Action::from_name(text),
+    }
+}
+
+// Where the app picks up actions and browser extension requests from the connections
+#[derive(Clone)]
+struct ActionSender {
+    tx: mpsc::Sender<Action>,
+    extension_tx: mpsc::Sender<ExtensionRequest>,
+    ctx: egui::Context,
+}
+
+impl ActionSender {
+    fn send(&self, action: Action) {
+        if self.tx.send(action).is_ok() {
+            self.ctx.request_repaint();
+        }
+    }
+
+    fn send_extension_request(&self, request: ExtensionRequest) {
+        if self.extension_tx.send(request).is_ok() {
+            self.ctx.request_repaint();
+        }
+    }
+}
+
+// The request line and headers, left in the socket so a WebSocket upgrade can still be handed
+// to tungstenite
+fn peek_head(stream: &TcpStream) -> Option<String> {
+    let mut buffer = [0u8; 4096];
+    let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+    loop {
+        let read = stream.peek(&mut buffer).ok()?;
+        if read == 0 {
+            return None;
+        }
+        let head = String::from_utf8_lossy(&buffer[..read]);
+        if let Some(end) = head.find("\r\n\r\n") {
+            return Some(head[..end + 4].to_string());
+        }
+        if read == buffer.len() || Instant::now() > deadline {
+            return None;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+// For controllers that can only make HTTP requests, like the Stream Deck's API plugins.
+// Actions need a POST, which pages on other sites can't send without an Origin header.
+// Browser extensions only get the pairing routes, with CORS headers so they can read the answers.
+// Other computers on the network need a paired token for everything but pairing and the web page
+fn serve_http(mut stream: TcpStream, head: &str, remote: bool, actions: &ActionSender) {
+    // Take the head off the socket so closing it doesn't reset the connection
+    let body_len = header(head, "content-length").and_then(|len| len.parse::<usize>().ok()).unwrap_or(0);
+    let mut consumed = vec![0u8; head.len() + body_len.min(64 * 1024)];
+    stream.read_exact(&mut consumed).ok();
+    let request_body = String::from_utf8_lossy(&consumed[head.len()..]).to_string();
+
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+    let names: Vec<&str> = Action::ALL.iter().map(|action| action.name()).collect();
+    let origin = header(head, "origin");
+    let extension_origin = origin.filter(|origin| pairing::is_extension_origin(origin) && pairing::is_extension_path(path));
+    let authorization = header(head, "authorization");
+    let page_origin = origin.is_some_and(|origin| webui::is_page_origin(origin, header(head, "host")));
+
+    if let Some((status, page)) = webui::serve(method, path) {
+        let body = if method == "HEAD" { "" } else { page };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+            status,
+            page.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).ok();
+        return;
+    }
+
+    let (status, body) = if origin.is_some_and(|origin| !is_allowed_origin(origin)) && extension_origin.is_none() && !page_origin {
+        ("403 Forbidden", serde_json::json!({ "ok": false, "error": "Origin not allowed" }))
+    } else if remote && !pairing::is_pairing_path(path) && !pairing::is_paired(authorization) {
+        ("401 Unauthorized", serde_json::json!({ "ok": false, "error": "Not paired" }))
+    } else if method == "OPTIONS" {
+        ("204 No Content", serde_json::Value::Null)
+    } else if let Some(response) = pairing::serve(method, path, authorization, &request_body, |request| {
+        actions.send_extension_request(request)
+    }) {
+        response
+    } else if let Some(name) = path.strip_prefix("/actions/") {
+        match (method, Action::from_name(name)) {
+            ("POST", Some(action)) => {
+                actions.send(action);
+                ("200 OK", serde_json::json!({ "ok": true, "action": action.name() }))
+            }
+            (_, Some(_)) => ("405 Method Not Allowed", serde_json::json!({ "ok": false, "error": "Use POST" })),
+            (_, None) => ("404 Not Found", serde_json::json!({ "ok": false, "error": "Unknown action", "actions": names })),
+        }
+    } else if path == "/actions" {
+        ("200 OK", serde_json::json!({ "ok": true, "actions": names }))
+    } else if path == "/status" {
+        let urls = queued_urls().lock().map(|urls| urls.clone()).unwrap_or_default();
+        let current = current_download().lock().ok().and_then(|current| current.clone());
+        ("200 OK", serde_json::json!({
+            "ok": true,
+            "queued": urls.len(),
+            "paused": PAUSED.load(Ordering::Relaxed),
+            "urls": urls,
+            "current": current,
+        }))
+    } else {
+        ("404 Not Found", serde_json::json!({ "ok": false, "error": "Not found" }))
+    };
+
+    let body = if body.is_null() { String::new() } else { body.to_string() };
+    let cors = match extension_origin {
+        Some(origin) => format!(
+            "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Methods: GET, POST\r\nAccess-Control-Allow-Headers: Authorization, Content-Type\r\n",
+            origin
+        ),
+        None => String::new(),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        cors,
+        body
+    );
+    stream.write_all(response.as_bytes()).ok();
+}
+
+fn serve_client(stream: TcpStream, stop: Arc<AtomicBool>, actions: ActionSender) {
+    // Accepted sockets inherit non-blocking mode from the listener on some platforms
+    stream.set_nonblocking(false).ok();
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).ok();
+    let remote = stream.peer_addr().map_or(true, |peer| !peer.ip().is_loopback());
+    let Some(head) = peek_head(&stream) else {
+        return;
+    };
+    if !header(&head, "upgrade").is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket")) {
+        serve_http(stream, &head, remote, &actions);
+        return;
+    }
+    if remote && !pairing::is_paired(header(&head, "authorization")) {
+        log::warn!("Refused an event stream connection from {:?}: not paired", stream.peer_addr());
+        let mut stream = stream;
+        stream.write_all(b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").ok();
+        return;
+    }
+
+    let mut socket = match tungstenite::accept_hdr(stream, check_origin) {
+        Ok(socket) => socket,
+        Err(e) => {
+            log::info!("Event stream handshake failed: {}", e);
+            return;
+        }
+    };
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL)).ok();
+
+    let (tx, rx) = mpsc::channel();
+    let hello = Event::Hello {
+        app: crate::APP_TITLE,
+        version: env!("CARGO_PKG_VERSION"),
+        queued: queued_urls().lock().map(|urls| urls.len()).unwrap_or_default(),
+        paused: PAUSED.load(Ordering::Relaxed),
+        urls: queued_urls().lock().map(|urls| urls.clone()).unwrap_or_default(),
+        actions: Action::ALL.iter().map(|action| action.name()).collect(),
+    };
+    if let Ok(text) = serde_json::to_string(&hello) {
+        tx.send(text).ok();
+    }
+    if let Ok(mut clients) = clients().lock() {
+        clients.push(tx);
+    }
+
+    while !stop.load(Ordering::Relaxed) {
+        while let Ok(text) = rx.try_recv() {
+            if socket.send(Message::Text(text)).is_err() {
+                return;
+            }
+        }
+        // Reading also answers pings and notices when the client goes away
+        match socket.read() {
+            Ok(Message::Close(_)) => break,
+            Ok(Message::Text(text)) => match parse_action(&text) {
+                Some(action) => actions.send(action),
+                None => {
+                    let error = Event::Error { message: format!("Unknown action: {}", text.trim()) };
+                    if let Ok(text) = serde_json::to_string(&error) {
+                        socket.send(Message::Text(text)).ok();
+                    }
+                }
+            },
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+            Err(_) => break,
+        }
+    }
+    socket.close(None).ok();
+    socket.flush().ok();
+}
+
+// The WebSocket/HTTP listener; dropping it stops accepting and disconnects the clients
+pub struct EventServer {
+    stop: Arc<AtomicBool>,
+    actions: mpsc::Receiver<Action>,
+    extension_requests: mpsc::Receiver<ExtensionRequest>,
+}
+
+impl EventServer {
+    // Only listens on the loopback interface, unless other computers on the network may connect
+    pub fn start(port: u16, lan: bool, ctx: egui::Context) -> Result<Self> {
+        // A listener that was just stopped lets go of the port within a poll interval
+        let interface = if lan { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
+        let addr = SocketAddr::from((interface, port));
+        let mut attempts = 0;
+        let listener = loop {
+            match TcpListener::bind(addr) {
+                Ok(listener) => break listener,
+                Err(e) if e.kind() == std::io::ErrorKind::AddrInUse && attempts < 5 => {
+                    attempts += 1;
+                    thread::sleep(POLL_INTERVAL);
+                }
+                Err(e) => return Err(anyhow::anyhow!("Could not listen on port {}: {}", port, e)),
+            }
+        };
+        listener.set_nonblocking(true)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let listener_stop = stop.clone();
+        let (tx, actions) = mpsc::channel();
+        let (extension_tx, extension_requests) = mpsc::channel();
+        let action_sender = ActionSender { tx, extension_tx, ctx };
+        thread::spawn(move || {
+            while !listener_stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let client_stop = listener_stop.clone();
+                        let client_actions = action_sender.clone();
+                        thread::spawn(move || serve_client(stream, client_stop, client_actions));
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(e) => {
+                        log::warn!("Event stream: {}", e);
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                }
+            }
+        });
+
+        Ok(Self { stop, actions, extension_requests })
+    }
+
+    // Actions received since the last call
+    pub fn actions(&self) -> Vec<Action> {
+        self.actions.try_iter().collect()
+    }
+
+    pub fn extension_requests(&self) -> Vec<ExtensionRequest> {
+        self.extension_requests.try_iter().collect()
+    }
+}
+
+impl Drop for EventServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl YtMp3App {
+    pub(crate) fn apply_event_stream_settings(&mut self, ctx: &egui::Context) {
+        self.event_server = None;
+        self.event_server_error = None;
+        pairing::set_paired(&self.settings.paired_extensions);
+        webui::set_enabled(self.settings.web_ui_enabled || self.daemon);
+
+        if !self.settings.event_stream_enabled {
+            pairing::cancel();
+            return;
+        }
+        match EventServer::start(self.settings.event_stream_port, self.settings.event_stream_lan, ctx.clone()) {
+            Ok(server) => self.event_server = Some(server),
+            Err(e) => {
+                self.log_console(format!("WARNING: Event stream: {}", e));
+                self.event_server_error = Some(e.to_string());
+            }
+        }
+    }
+
+    // Called every frame, so overlays can show how many downloads are left
+    pub(crate) fn publish_queue_changes(&mut self) {
+        let urls: Vec<String> = self.download_queue
+            .iter()
+            .map(|download| download.url.clone())
+            .chain(self.pending_urls.iter().cloned())
+            .collect();
+        let paused = self.queue_paused;
+        if self.published_queue.as_ref().is_some_and(|(published, was_paused)| *published == urls && *was_paused == paused) {
+            return;
+        }
+        if let Ok(mut queued) = queued_urls().lock() {
+            queued.clone_from(&urls);
+        }
+        PAUSED.store(paused, Ordering::Relaxed);
+        publish(Event::Queue { queued: urls.len(), paused, urls: urls.clone() });
+        self.published_queue = Some((urls, paused));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_local_origins_are_allowed() {
+        for origin in ["http://localhost:8080", "http://127.0.0.1", "https://[::1]:3000/", "http://absolute"] {
+            assert!(is_allowed_origin(origin), "{}", origin);
+        }
+        for origin in ["null", "NULL", "file://", "file:///C:/overlay.html", "https://example.com", "http://localhost.example.com"] {
+            assert!(!is_allowed_origin(origin), "{}", origin);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use std::path::Path;
+
+use crate::commands::{cmd_quote, posix_quote};
+use crate::settings::Settings;
+use crate::{build_download_args, AppState, DownloadFormat, YtMp3App};
+
+// The queue written out as a script of yt-dlp commands, to run on a server or another
+// machine without the app. The commands are the ones the app itself would run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptKind {
+    // .bat for cmd.exe
+    Batch,
+    // .sh for sh/bash
+    Shell,
+}
+
+impl ScriptKind {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_ascii_lowercase()) {
+            Some(ext) if ext == "bat" || ext == "cmd" => ScriptKind::Batch,
+            Some(ext) if ext == "sh" => ScriptKind::Shell,
+            _ if cfg!(target_os = "windows") => ScriptKind::Batch,
+            _ => ScriptKind::Shell,
+        }
+    }
+
+    fn quote(&self, arg: &str) -> String {
+        match self {
+            // A batch file expands %...% even inside quotes, and output templates are full of them
+            ScriptKind::Batch => cmd_quote(arg).replace('%', "%%"),
+            ScriptKind::Shell => posix_quote(arg),
+        }
+    }
+
+    fn comment(&self) -> &'static str {
+        match self {
+            ScriptKind::Batch => "REM",
+            ScriptKind::Shell => "#",
+        }
+    }
+}
+
+pub struct QueuedDownload {
+    pub url: String,
+    pub format: DownloadFormat,
+    pub output_path: String,
+}
+
+pub fn script(kind: ScriptKind, downloads: &[QueuedDownload], settings: &Settings) -> String {
+    let mut lines = Vec::new();
+    match kind {
+        ScriptKind::Batch => {
+            lines.push("@echo off".to_string());
+            // Titles with non-ASCII characters
+            lines.push("chcp 65001 > nul".to_string());
+        }
+        ScriptKind::Shell => lines.push("#!/bin/sh".to_string()),
+    }
+    lines.push(format!(
+        "{} {} download(s) exported by {} on {}. Needs yt-dlp and ffmpeg on the PATH",
+        kind.comment(),
+        downloads.len(),
+        crate::APP_TITLE,
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    ));
+    lines.push(String::new());
+
+    for download in downloads {
+        let args = build_download_args(&download.url, &download.output_path, None, None, download.format, settings, None);
+        let command = std::iter::once("yt-dlp".to_string())
+            .chain(args.iter().map(|arg| kind.quote(arg)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        lines.push(command);
+    }
+
+    let newline = match kind {
+        ScriptKind::Batch => "\r\n",
+        ScriptKind::Shell => "\n",
+    };
+    let mut text = lines.join(newline);
+    text.push_str(newline);
+    text
+}
+
+fn write_script(path: &Path, text: &str) -> Result<()> {
+    std::fs::write(path, text)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut permissions = std::fs::metadata(path)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o111);
+        std::fs::set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
+
+impl YtMp3App {
+    // The video on screen, then the automatic downloads, then the URLs waiting to be loaded
+    fn queued_downloads(&self) -> Vec<QueuedDownload> {
+        let mut downloads = Vec::new();
+        if matches!(self.state, AppState::VideoInfo(_)) && !self.url_input.is_empty() {
+            downloads.push(QueuedDownload {
+                url: self.url_input.clone(),
+                format: self.download_format,
+                output_path: self.output_path.clone(),
+            });
+        }
+        downloads.extend(self.download_queue.iter().map(|download| QueuedDownload {
+            url: download.url.clone(),
+            format: download.format,
+            output_path: download.output_path.clone(),
+        }));
+        downloads.extend(self.pending_urls.iter().map(|url| QueuedDownload {
+            url: url.clone(),
+            format: self.download_format,
+            output_path: self.output_path.clone(),
+        }));
+        downloads
+    }
+
+    pub(crate) fn export_queue_script(&mut self) {
+        let downloads = self.queued_downloads();
+        if downloads.is_empty() {
+            return;
+        }
+
+        let (default_name, filters): (&str, [(&str, &[&str]); 2]) = if cfg!(target_os = "windows") {
+            ("ytmp3-queue.bat", [("Batch file", &["bat", "cmd"]), ("Shell script", &["sh"])])
+        } else {
+            ("ytmp3-queue.sh", [("Shell script", &["sh"]), ("Batch file", &["bat", "cmd"])])
+        };
+        let mut dialog = rfd::FileDialog::new().set_file_name(default_name);
+        for (name, extensions) in filters {
+            dialog = dialog.add_filter(name, extensions);
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+
+        // Per-video choices like burned-in subtitles or picked formats don't carry over to the rest of the queue
+        let mut settings = self.settings
+            .with_cookie_profile(self.cookie_profile.as_deref())
+            .with_proxy(self.proxy.as_deref());
+        settings.burn_subtitles = None;
+        settings.format_selector = None;
+
+        let text = script(ScriptKind::from_path(&path), &downloads, &settings);
+        match write_script(&path, &text) {
+            Ok(()) => self.log_console(format!("Exported {} download(s) to {}", downloads.len(), path.display())),
+            Err(e) => self.log_console(format!("ERROR: Could not write {}: {}", path.display(), e)),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use regex::Regex;
+use std::time::Duration;
+
+use crate::certificates;
+use crate::settings::Settings;
+
+const FEED_TIMEOUT: Duration = Duration::from_secs(30);
+
+// YouTube's own channel and playlist feeds, and any other site's URL; the app only downloads
+// from YouTube otherwise, so a template URL from elsewhere can only be a feed
+pub fn is_feed_url(url: &str) -> bool {
+    let feed_regex = Regex::new(r"^https?://(?:www\.)?youtube\.com/feeds/videos\.xml\?").unwrap();
+    let web_regex = Regex::new(r"^https?://").unwrap();
+    let youtube_regex = Regex::new(r"^https?://(?:[a-z0-9-]+\.)?(?:youtube\.com|youtu\.be)/").unwrap();
+    feed_regex.is_match(url) || (web_regex.is_match(url) && !youtube_regex.is_match(url))
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    pub title: String,
+    // The media file for podcasts, the video page otherwise
+    pub url: String,
+}
+
+fn unescape(text: &str) -> String {
+    let text = text.trim();
+    let text = text
+        .strip_prefix("<![CDATA[")
+        .and_then(|text| text.strip_suffix("]]>"))
+        .unwrap_or(text);
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let attribute_regex = Regex::new(&format!(r#"\b{}\s*=\s*["']([^"']*)["']"#, name)).unwrap();
+    attribute_regex.captures(tag).map(|captures| unescape(&captures[1]))
+}
+
+fn first_tag(item: &str, pattern: &str) -> Option<String> {
+    Regex::new(pattern).unwrap().find(item).map(|found| found.as_str().to_string())
+}
+
+// A YouTube video ID first (its media:content is an old player link), then the enclosure or
+// media file, then the entry's page link
+fn entry_url(item: &str) -> Option<String> {
+    let video_id_regex = Regex::new(r"<yt:videoId>\s*([A-Za-z0-9_-]{11})\s*</yt:videoId>").unwrap();
+    if let Some(captures) = video_id_regex.captures(item) {
+        return Some(format!("https://www.youtube.com/watch?v={}", &captures[1]));
+    }
+    if let Some(url) = first_tag(item, r"<enclosure\b[^>]*>").and_then(|tag| attribute(&tag, "url")) {
+        return Some(url);
+    }
+    if let Some(url) = first_tag(item, r"<media:content\b[^>]*>").and_then(|tag| attribute(&tag, "url")) {
+        return Some(url);
+    }
+
+    // Atom: <link rel="alternate" href="..."/>, RSS: <link>...</link>
+    let link_regex = Regex::new(r"(?s)<link\b([^>]*?)/?>(?:([^<]*)</link>)?").unwrap();
+    for captures in link_regex.captures_iter(item) {
+        let tag = &captures[1];
+        if let Some(href) = attribute(tag, "href") {
+            if attribute(tag, "rel").is_none_or(|rel| rel == "alternate" || rel == "enclosure") {
+                return Some(href);
+            }
+        } else if let Some(text) = captures.get(2).map(|text| unescape(text.as_str())).filter(|text| !text.is_empty()) {
+            return Some(text);
+        }
+    }
+    None
+}
+
+// The items of an RSS feed or the entries of an Atom feed, newest first as feeds list them
+pub fn parse_feed(xml: &str) -> Vec<FeedEntry> {
+    let item_regex = Regex::new(r"(?s)<(item|entry)\b[^>]*>(.*?)</(?:item|entry)>").unwrap();
+    let title_regex = Regex::new(r"(?s)<title\b[^>]*>(.*?)</title>").unwrap();
+    item_regex
+        .captures_iter(xml)
+        .filter_map(|captures| {
+            let item = &captures[2];
+            let url = entry_url(item)?;
+            let title = title_regex
+                .captures(item)
+                .map(|title| unescape(&title[1]))
+                .unwrap_or_else(|| url.clone());
+            Some(FeedEntry { title, url })
+        })
+        .collect()
+}
+
+async fn fetch(url: &str, settings: &Settings) -> Result<Vec<FeedEntry>> {
+    let mut client = certificates::client_builder(settings)
+        .user_agent("ytmp3-downloader")
+        .timeout(FEED_TIMEOUT);
+    if let Some(proxy) = settings.active_proxy() {
+        client = client.proxy(proxy.client_proxy()?);
+    }
+    let response = client.build()?.get(url).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("HTTP {}", response.status()));
+    }
+    let entries = parse_feed(&response.text().await?);
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!("No entries found, this doesn't look like an RSS or Atom feed"));
+    }
+    Ok(entries)
+}
+
+// Each feed's URL with its entries or why it couldn't be read
+pub type FeedResults = Vec<(String, Result<Vec<FeedEntry>, String>)>;
+
+// Blocking
+pub fn fetch_all(urls: &[String], settings: &Settings) -> FeedResults {
+    let rt = match tokio::runtime::Runtime::new() {
+        Ok(rt) => rt,
+        Err(e) => return urls.iter().map(|url| (url.clone(), Err(e.to_string()))).collect(),
+    };
+    urls.iter()
+        .map(|url| (url.clone(), rt.block_on(fetch(url, settings)).map_err(|e| e.to_string())))
+        .collect()
+}
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::YtMp3App;
+
+// How often the files behind the History entries are looked at
+const SCAN_INTERVAL: Duration = Duration::from_secs(30);
+
+// Size and modification time; a renamed file keeps both
+type Fingerprint = (u64, SystemTime);
+
+fn fingerprint(path: &Path) -> Option<Fingerprint> {
+    let metadata = std::fs::metadata(path).ok().filter(|metadata| metadata.is_file())?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+enum Change {
+    Missing { index: usize },
+    Restored { index: usize },
+    Renamed { index: usize, path: String },
+    Modified { index: usize },
+}
+
+struct WatchedFile {
+    index: usize,
+    path: PathBuf,
+    video_id: Option<String>,
+    missing: bool,
+}
+
+struct ScanResult {
+    changes: Vec<Change>,
+    fingerprints: HashMap<PathBuf, Fingerprint>,
+}
+
+// Files in the folder that no History entry points at, where a renamed download could have gone
+fn unclaimed_files(folder: &Path, claimed: &HashSet<PathBuf>) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && !claimed.contains(path))
+        .collect()
+}
+
+// A file with the same size and time, or with the video ID in its name
+fn find_renamed(file: &WatchedFile, known: Option<&Fingerprint>, claimed: &HashSet<PathBuf>) -> Option<PathBuf> {
+    let folder = file.path.parent()?;
+    let candidates = unclaimed_files(folder, claimed);
+    if let Some(known) = known {
+        if let Some(path) = candidates.iter().find(|path| fingerprint(path).as_ref() == Some(known)) {
+            return Some(path.clone());
+        }
+    }
+    let id = file.video_id.as_deref()?;
+    let extension = file.path.extension();
+    candidates
+        .into_iter()
+        .find(|path| path.extension() == extension && path.file_name().is_some_and(|name| name.to_string_lossy().contains(id)))
+}
+
+fn scan(files: Vec<WatchedFile>, mut fingerprints: HashMap<PathBuf, Fingerprint>) -> ScanResult {
+    let mut claimed: HashSet<PathBuf> = files.iter().map(|file| file.path.clone()).collect();
+    let mut changes = Vec::new();
+    for file in &files {
+        match fingerprint(&file.path) {
+            Some(current) => {
+                if file.missing {
+                    changes.push(Change::Restored { index: file.index });
+                } else if fingerprints.get(&file.path).is_some_and(|known| *known != current) {
+                    changes.push(Change::Modified { index: file.index });
+                }
+                fingerprints.insert(file.path.clone(), current);
+            }
+            None if file.missing => {}
+            None => {
+                let known = fingerprints.remove(&file.path);
+                match find_renamed(file, known.as_ref(), &claimed) {
+                    Some(path) => {
+                        claimed.insert(path.clone());
+                        if let Some(current) = fingerprint(&path) {
+                            fingerprints.insert(path.clone(), current);
+                        }
+                        changes.push(Change::Renamed { index: file.index, path: path.display().to_string() });
+                    }
+                    None => changes.push(Change::Missing { index: file.index }),
+                }
+            }
+        }
+    }
+    ScanResult { changes, fingerprints }
+}
+
+// Keeps the History in step with files other programs renamed, changed or deleted
+#[derive(Default)]
+pub struct FileWatch {
+    running: Option<mpsc::Receiver<ScanResult>>,
+    last_scan: Option<Instant>,
+    // Files as they were at the last scan
+    fingerprints: HashMap<PathBuf, Fingerprint>,
+}
+
+impl YtMp3App {
+    // Called every frame
+    pub(crate) fn poll_file_watch(&mut self) {
+        if !self.settings.watch_history_files {
+            return;
+        }
+        if let Some(running) = &self.file_watch.running {
+            match running.try_recv() {
+                Ok(result) => {
+                    self.file_watch.running = None;
+                    self.file_watch.fingerprints = result.fingerprints;
+                    self.apply_file_changes(result.changes);
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.file_watch.running = None,
+            }
+            return;
+        }
+
+        if self.file_watch.last_scan.is_some_and(|last_scan| last_scan.elapsed() < SCAN_INTERVAL) {
+            return;
+        }
+        let files: Vec<WatchedFile> = self.history.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !entry.deleted && !entry.replaced)
+            .map(|(index, entry)| WatchedFile {
+                index,
+                path: PathBuf::from(&entry.path),
+                video_id: entry.video_id.clone(),
+                missing: entry.missing,
+            })
+            .collect();
+        let fingerprints = std::mem::take(&mut self.file_watch.fingerprints);
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            tx.send(scan(files, fingerprints)).ok();
+        });
+        self.file_watch.running = Some(rx);
+        self.file_watch.last_scan = Some(Instant::now());
+    }
+
+    fn apply_file_changes(&mut self, changes: Vec<Change>) {
+        if changes.is_empty() {
+            return;
+        }
+        let mut notes = Vec::new();
+        let mut changed = Vec::new();
+        for change in changes {
+            let index = match &change {
+                Change::Missing { index } | Change::Restored { index } | Change::Renamed { index, .. } | Change::Modified { index } => *index,
+            };
+            let Some(entry) = self.history.entries.get_mut(index) else {
+                continue;
+            };
+            changed.push(index);
+            match change {
+                Change::Missing { .. } => {
+                    entry.missing = true;
+                    notes.push(format!("History: {} was deleted or moved outside the app", entry.path));
+                }
+                Change::Restored { .. } => {
+                    entry.missing = false;
+                    notes.push(format!("History: {} is back", entry.path));
+                }
+                Change::Renamed { path, .. } => {
+                    notes.push(format!("History: {} was renamed to {}", entry.path, path));
+                    entry.path = path;
+                    entry.missing = false;
+                }
+                Change::Modified { .. } => {
+                    // Read again the next time it's needed, the file may have been re-encoded
+                    entry.height = None;
+                }
+            }
+        }
+        for note in notes {
+            self.log_console(note);
+        }
+        if let Err(e) = self.history.save(changed) {
+            self.log_console(format!("WARNING: Could not save history: {}", e));
+        }
+    }
+}
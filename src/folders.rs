@@ -0,0 +1,92 @@
+use eframe::egui;
+
+use crate::a11y::AccessibleName;
+use crate::YtMp3App;
+
+// How many recently used output folders are kept next to the pinned ones
+const MAX_RECENT_FOLDERS: usize = 8;
+
+impl YtMp3App {
+    // Moves the folder to the front of the recent list, pinned folders are listed on their own
+    pub(crate) fn remember_output_folder(&mut self, folder: &str) {
+        let folder = folder.trim();
+        if folder.is_empty() || self.settings.pinned_folders.iter().any(|pinned| pinned == folder) {
+            return;
+        }
+        if self.settings.recent_folders.first().map(String::as_str) == Some(folder) {
+            return;
+        }
+        self.settings.recent_folders.retain(|recent| recent != folder);
+        self.settings.recent_folders.insert(0, folder.to_string());
+        self.settings.recent_folders.truncate(MAX_RECENT_FOLDERS);
+        if let Err(e) = self.settings.save() {
+            self.log_console(format!("WARNING: Could not save settings: {}", e));
+        }
+    }
+
+    // Dropdown next to the Browse button with the pinned and recently used output folders
+    pub(crate) fn output_folder_menu(&mut self, ui: &mut egui::Ui) {
+        let mut chosen = None;
+        let mut pin = None;
+        let mut unpin = None;
+
+        ui.menu_button("⭐ Folders", |ui| {
+            ui.set_min_width(300.0);
+            let current = self.output_path.trim().to_string();
+            let current_pinned = self.settings.pinned_folders.contains(&current);
+            if ui.add_enabled(!current.is_empty() && !current_pinned, egui::Button::new("📌 Pin current folder")).clicked() {
+                pin = Some(current);
+                ui.close_menu();
+            }
+
+            if !self.settings.pinned_folders.is_empty() {
+                ui.separator();
+                ui.label(egui::RichText::new("Pinned").small().color(egui::Color32::GRAY));
+                for folder in &self.settings.pinned_folders {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✖").accessible_name("Unpin").on_hover_text("Unpin").clicked() {
+                            unpin = Some(folder.clone());
+                        }
+                        if ui.selectable_label(*folder == self.output_path, folder).clicked() {
+                            chosen = Some(folder.clone());
+                            ui.close_menu();
+                        }
+                    });
+                }
+            }
+
+            if !self.settings.recent_folders.is_empty() {
+                ui.separator();
+                ui.label(egui::RichText::new("Recent").small().color(egui::Color32::GRAY));
+                for folder in &self.settings.recent_folders {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("📌").accessible_name("Pin").on_hover_text("Pin").clicked() {
+                            pin = Some(folder.clone());
+                        }
+                        if ui.selectable_label(*folder == self.output_path, folder).clicked() {
+                            chosen = Some(folder.clone());
+                            ui.close_menu();
+                        }
+                    });
+                }
+            }
+        });
+
+        if let Some(folder) = chosen {
+            self.output_path = folder;
+        }
+        if pin.is_none() && unpin.is_none() {
+            return;
+        }
+        if let Some(folder) = pin {
+            self.settings.recent_folders.retain(|recent| *recent != folder);
+            self.settings.pinned_folders.push(folder);
+        }
+        if let Some(folder) = unpin {
+            self.settings.pinned_folders.retain(|pinned| *pinned != folder);
+        }
+        if let Err(e) = self.settings.save() {
+            self.log_console(format!("WARNING: Could not save settings: {}", e));
+        }
+    }
+}
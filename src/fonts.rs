@@ -0,0 +1,102 @@
+use eframe::egui;
+use egui::{FontData, FontDefinitions, FontFamily};
+
+use crate::settings::Settings;
+
+// System fonts that cover Chinese, Japanese and Korean, tried in order; the first one found is used
+#[cfg(target_os = "windows")]
+const CJK_FONTS: &[&str] = &[
+    r"C:\Windows\Fonts\msyh.ttc",
+    r"C:\Windows\Fonts\YuGothM.ttc",
+    r"C:\Windows\Fonts\msgothic.ttc",
+    r"C:\Windows\Fonts\malgun.ttf",
+    r"C:\Windows\Fonts\simsun.ttc",
+];
+#[cfg(target_os = "macos")]
+const CJK_FONTS: &[&str] = &[
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/AppleSDGothicNeo.ttc",
+];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const CJK_FONTS: &[&str] = &[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+];
+
+// Fonts with Hebrew and Arabic (including the joined letter forms), for right-to-left titles
+#[cfg(target_os = "windows")]
+const RTL_FONTS: &[&str] = &[r"C:\Windows\Fonts\arial.ttf", r"C:\Windows\Fonts\tahoma.ttf"];
+#[cfg(target_os = "macos")]
+const RTL_FONTS: &[&str] = &["/System/Library/Fonts/Supplemental/Arial.ttf", "/Library/Fonts/Arial.ttf"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const RTL_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+];
+
+// Emoji beyond the small set egui ships with. Only outline fonts work, egui can't draw
+// colour bitmaps (like Apple Color Emoji), so emoji show in a single colour
+#[cfg(target_os = "windows")]
+const EMOJI_FONTS: &[&str] = &[r"C:\Windows\Fonts\seguiemj.ttf", r"C:\Windows\Fonts\seguisym.ttf"];
+#[cfg(target_os = "macos")]
+const EMOJI_FONTS: &[&str] = &[];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const EMOJI_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/truetype/ancient-scripts/Symbola_hint.ttf",
+];
+
+fn load_first(paths: &[&str]) -> Option<(String, Vec<u8>)> {
+    paths
+        .iter()
+        .find_map(|path| std::fs::read(path).ok().map(|bytes| (path.to_string(), bytes)))
+}
+
+fn add_font(fonts: &mut FontDefinitions, name: &str, bytes: Vec<u8>, families: &[FontFamily], first: bool) {
+    fonts.font_data.insert(name.to_string(), FontData::from_owned(bytes));
+    for family in families {
+        let list = fonts.families.entry(family.clone()).or_default();
+        if first {
+            list.insert(0, name.to_string());
+        } else {
+            list.push(name.to_string());
+        }
+    }
+}
+
+// egui's default fonts, the chosen UI font in front of them and the CJK/RTL/emoji fallbacks behind them
+fn font_definitions(settings: &Settings) -> FontDefinitions {
+    let mut fonts = FontDefinitions::default();
+    let all = [FontFamily::Proportional, FontFamily::Monospace];
+
+    // The console keeps its monospace font
+    if !settings.ui_font.is_empty() {
+        match std::fs::read(&settings.ui_font) {
+            Ok(bytes) => add_font(&mut fonts, "ui_font", bytes, &[FontFamily::Proportional], true),
+            Err(e) => log::warn!("Could not load the UI font {}: {}", settings.ui_font, e),
+        }
+    }
+    if let Some((path, bytes)) = load_first(CJK_FONTS) {
+        log::info!("Using {} for CJK text", path);
+        add_font(&mut fonts, "cjk_fallback", bytes, &all, false);
+    }
+    if let Some((path, bytes)) = load_first(RTL_FONTS) {
+        log::info!("Using {} for Hebrew and Arabic text", path);
+        add_font(&mut fonts, "rtl_fallback", bytes, &all, false);
+    }
+    if let Some((path, bytes)) = load_first(EMOJI_FONTS) {
+        log::info!("Using {} for emoji", path);
+        add_font(&mut fonts, "emoji_fallback", bytes, &all, false);
+    }
+    fonts
+}
+
+pub fn apply(ctx: &egui::Context, settings: &Settings) {
+    ctx.set_fonts(font_definitions(settings));
+}
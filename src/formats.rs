@@ -0,0 +1,281 @@
+use eframe::egui;
+use std::cmp::Ordering;
+
+use crate::metadata::Format;
+use crate::{help, locale, DownloadFormat};
+
+// One row of the format table, from the `formats` list of `yt-dlp -J`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOption {
+    pub id: String,
+    pub ext: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    // None for the stream a format doesn't carry
+    pub vcodec: Option<String>,
+    pub acodec: Option<String>,
+    // kbit/s
+    pub bitrate: Option<f64>,
+    // Bytes
+    pub size: Option<f64>,
+    // Worked out from the bitrate and the video's length rather than reported by the site
+    pub size_estimated: bool,
+    pub note: Option<String>,
+}
+
+impl FormatOption {
+    pub fn has_video(&self) -> bool {
+        self.vcodec.is_some()
+    }
+
+    pub fn has_audio(&self) -> bool {
+        self.acodec.is_some()
+    }
+
+    fn resolution(&self) -> String {
+        match (self.width, self.height) {
+            _ if !self.has_video() => "audio only".to_string(),
+            (Some(width), Some(height)) => format!("{}x{}", width, height),
+            (None, Some(height)) => format!("{}p", height),
+            _ => "unknown".to_string(),
+        }
+    }
+
+    // "avc1 + mp4a"; the profile and level after the dot only matter on hover
+    fn codecs(&self) -> String {
+        let short = |codec: &String| codec.split('.').next().unwrap_or(codec).to_string();
+        [self.vcodec.as_ref().map(short), self.acodec.as_ref().map(short)]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    fn size_text(&self) -> String {
+        match self.size {
+            Some(size) if self.size_estimated => format!("~{}", locale::bytes(size)),
+            Some(size) => locale::bytes(size),
+            None => "?".to_string(),
+        }
+    }
+}
+
+// The formats that can be downloaded, leaving out storyboards and other image-only entries
+pub fn options(formats: &[Format], duration: Option<f64>) -> Vec<FormatOption> {
+    formats
+        .iter()
+        .filter(|format| format.has_video() || format.has_audio())
+        .filter(|format| format.protocol.as_deref() != Some("mhtml"))
+        .filter_map(|format| {
+            let codec = |codec: &Option<String>, present: bool| {
+                present.then(|| codec.clone().filter(|codec| codec != "none").unwrap_or_else(|| "unknown".to_string()))
+            };
+            let bitrate = format.tbr.or_else(|| match (format.vbr, format.abr) {
+                (None, None) => None,
+                (video, audio) => Some(video.unwrap_or(0.0) + audio.unwrap_or(0.0)),
+            });
+            let estimate = bitrate.zip(duration).map(|(kbits, seconds)| kbits * 1000.0 / 8.0 * seconds);
+            let reported = format.filesize.or(format.filesize_approx);
+            Some(FormatOption {
+                id: format.format_id.clone()?,
+                ext: format.ext.clone().unwrap_or_default(),
+                width: format.width,
+                height: format.height,
+                fps: format.fps,
+                vcodec: codec(&format.vcodec, format.has_video()),
+                acodec: codec(&format.acodec, format.has_audio()),
+                bitrate,
+                size: reported.or(estimate),
+                size_estimated: reported.is_none() && estimate.is_some(),
+                note: format.format_note.clone(),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SortColumn {
+    #[default]
+    Resolution,
+    Fps,
+    Codec,
+    Bitrate,
+    Size,
+}
+
+impl SortColumn {
+    const ALL: [SortColumn; 5] = [SortColumn::Resolution, SortColumn::Fps, SortColumn::Codec, SortColumn::Bitrate, SortColumn::Size];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SortColumn::Resolution => "Resolution",
+            SortColumn::Fps => "FPS",
+            SortColumn::Codec => "Codec",
+            SortColumn::Bitrate => "Bitrate",
+            SortColumn::Size => "Size",
+        }
+    }
+
+    // Missing values sort below every known one
+    fn compare(&self, a: &FormatOption, b: &FormatOption) -> Ordering {
+        let number = |a: Option<f64>, b: Option<f64>| a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+        match self {
+            SortColumn::Resolution => (a.has_video(), a.height, a.width).cmp(&(b.has_video(), b.height, b.width)),
+            SortColumn::Fps => number(a.fps, b.fps),
+            SortColumn::Codec => a.codecs().cmp(&b.codecs()),
+            SortColumn::Bitrate => number(a.bitrate, b.bitrate),
+            SortColumn::Size => number(a.size, b.size),
+        }
+    }
+}
+
+// The "Choose format" table under the video info. It stays open from one video to the next;
+// what was picked doesn't
+pub struct FormatPicker {
+    pub open: bool,
+    sort: SortColumn,
+    descending: bool,
+    // A format with both streams is kept as the video
+    video: Option<FormatOption>,
+    audio: Option<FormatOption>,
+}
+
+impl Default for FormatPicker {
+    fn default() -> Self {
+        Self { open: false, sort: SortColumn::default(), descending: true, video: None, audio: None }
+    }
+}
+
+impl FormatPicker {
+    pub fn clear(&mut self) {
+        self.video = None;
+        self.audio = None;
+    }
+
+    // The --format selector for what was picked; None leaves it to the MP4/MP3 defaults
+    pub fn selector(&self, format: DownloadFormat) -> Option<String> {
+        match format {
+            DownloadFormat::Mp3 => self.audio.as_ref().map(|audio| audio.id.clone()),
+            DownloadFormat::Mp4 => match (&self.video, &self.audio) {
+                (Some(video), Some(audio)) => Some(format!("{}+{}", video.id, audio.id)),
+                (Some(video), None) if video.has_audio() => Some(video.id.clone()),
+                // Falls back to the video alone when there's no separate audio to add
+                (Some(video), None) => Some(format!("{0}+bestaudio/{0}", video.id)),
+                (None, Some(audio)) => Some(format!("bestvideo+{0}/best", audio.id)),
+                (None, None) => None,
+            },
+        }
+    }
+
+    fn chosen(&self, format: DownloadFormat) -> impl Iterator<Item = &FormatOption> {
+        let video = self.video.as_ref().filter(|_| format == DownloadFormat::Mp4);
+        video.into_iter().chain(self.audio.as_ref())
+    }
+
+    fn is_chosen(&self, option: &FormatOption, format: DownloadFormat) -> bool {
+        self.chosen(format).any(|chosen| chosen.id == option.id)
+    }
+
+    fn choose(&mut self, option: &FormatOption, format: DownloadFormat) {
+        let as_video = format == DownloadFormat::Mp4 && option.has_video();
+        let slot = if as_video { &mut self.video } else { &mut self.audio };
+        // Clicking the chosen one again lets go of it
+        *slot = match slot {
+            Some(chosen) if chosen.id == option.id => None,
+            _ => Some(option.clone()),
+        };
+        // A video that brings its own audio doesn't get a second audio stream, and the other way round
+        if as_video && option.has_audio() {
+            self.audio = None;
+        } else if !as_video && self.video.as_ref().is_some_and(FormatOption::has_audio) {
+            self.video = None;
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, options: &[FormatOption], format: DownloadFormat) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.open, "🎛 Choose format")
+                .on_hover_text("Pick the exact video and audio streams instead of the best ones");
+            help::help_button(ui, "format_picker");
+            if let Some(selector) = self.selector(format) {
+                let size: f64 = self.chosen(format).filter_map(|option| option.size).sum();
+                let text = match size > 0.0 {
+                    true => format!("-f {}  (~{})", selector, locale::bytes(size)),
+                    false => format!("-f {}", selector),
+                };
+                ui.label(egui::RichText::new(text).monospace().color(egui::Color32::GRAY));
+                if ui.small_button("Reset").on_hover_text("Go back to the best formats").clicked() {
+                    self.clear();
+                }
+            }
+        });
+        if !self.open {
+            return;
+        }
+        if options.is_empty() {
+            ui.label(egui::RichText::new("yt-dlp didn't list any formats for this video").color(egui::Color32::GRAY));
+            return;
+        }
+
+        // MP3 only needs the audio, so formats without any are left out
+        let mut rows: Vec<&FormatOption> = options
+            .iter()
+            .filter(|option| format == DownloadFormat::Mp4 || option.has_audio())
+            .collect();
+        rows.sort_by(|a, b| {
+            let ordering = self.sort.compare(a, b);
+            if self.descending { ordering.reverse() } else { ordering }
+        });
+
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .id_source("format_table")
+            .max_height(220.0)
+            .show(ui, |ui| {
+                egui::Grid::new("format_table_grid")
+                    .num_columns(6)
+                    .spacing([12.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("Format").strong());
+                        for column in SortColumn::ALL {
+                            let arrow = match (self.sort == column, self.descending) {
+                                (true, true) => " ⏷",
+                                (true, false) => " ⏶",
+                                (false, _) => "",
+                            };
+                            if ui.selectable_label(self.sort == column, format!("{}{}", column.label(), arrow)).clicked() {
+                                if self.sort == column {
+                                    self.descending = !self.descending;
+                                } else {
+                                    self.sort = column;
+                                    self.descending = true;
+                                }
+                            }
+                        }
+                        ui.end_row();
+
+                        for option in rows {
+                            let mut label = ui.selectable_label(self.is_chosen(option, format), format!("{} {}", option.id, option.ext));
+                            if let Some(note) = &option.note {
+                                label = label.on_hover_text(note);
+                            }
+                            if label.clicked() {
+                                clicked = Some(option);
+                            }
+                            ui.label(option.resolution());
+                            ui.label(option.fps.map(|fps| locale::decimal(fps, 0)).unwrap_or_default());
+                            let codecs = [option.vcodec.as_deref(), option.acodec.as_deref()].into_iter().flatten().collect::<Vec<_>>().join(" + ");
+                            ui.label(option.codecs()).on_hover_text(codecs);
+                            ui.label(option.bitrate.map(|kbits| format!("{} kbit/s", locale::decimal(kbits, 0))).unwrap_or_default());
+                            ui.label(option.size_text());
+                            ui.end_row();
+                        }
+                    });
+            });
+        if let Some(option) = clicked {
+            self.choose(option, format);
+        }
+    }
+}
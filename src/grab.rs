@@ -0,0 +1,131 @@
+use anyhow::Result;
+use std::thread;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::channel::{self, AppSender};
+use crate::commands;
+use crate::longpath::{self, TitleFit};
+use crate::naming;
+use crate::settings::Settings;
+use crate::{get_yt_dlp_path, output, ytdlp_command, AppMessage, AppState, YtMp3App};
+
+// Quick actions that save something about the video without downloading the media
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrabKind {
+    Thumbnail,
+    Metadata,
+}
+
+impl GrabKind {
+    pub fn button_label(&self) -> &'static str {
+        match self {
+            GrabKind::Thumbnail => "🖼 Thumbnail only",
+            GrabKind::Metadata => "📄 Metadata JSON only",
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        match self {
+            GrabKind::Thumbnail => "Saving thumbnail...",
+            GrabKind::Metadata => "Saving metadata...",
+        }
+    }
+
+    // yt-dlp picks the highest resolution thumbnail on its own. Existing files are rewritten,
+    // otherwise yt-dlp only says they are "already present" without naming them
+    fn ytdlp_args(&self) -> &'static [&'static str] {
+        match self {
+            GrabKind::Thumbnail => &["--skip-download", "--force-overwrites", "--write-thumbnail"],
+            GrabKind::Metadata => &["--skip-download", "--force-overwrites", "--write-info-json"],
+        }
+    }
+}
+
+fn grab(
+    url: &str,
+    title: &str,
+    output_path: &str,
+    kind: GrabKind,
+    settings: &Settings,
+    progress_sender: &AppSender,
+) -> Result<String> {
+    let title_field = match longpath::title_fit(&[output_path], title) {
+        TitleFit::Fits => "%(title)s".to_string(),
+        TitleFit::Truncate(limit) => format!("%(title).{}s", limit),
+        TitleFit::FolderTooLong => {
+            return Err(anyhow::anyhow!(
+                "The output folder path is too long to fit a file name under {} characters, choose a shorter folder",
+                longpath::MAX_PATH
+            ));
+        }
+    };
+
+    let yt_dlp_path = get_yt_dlp_path(settings);
+    let mut args = vec![
+        "--no-playlist".to_string(),
+        "--paths".to_string(),
+        format!("home:{}", output_path),
+        "--output".to_string(),
+        format!("{}.%(ext)s", title_field),
+    ];
+    args.extend(kind.ytdlp_args().iter().map(|arg| arg.to_string()));
+    args.extend(settings.ytdlp_common_args());
+    args.extend(naming::title_cleanup_args(&settings.title_cleanup));
+    args.push(url.to_string());
+    progress_sender.send(AppMessage::ConsoleOutput(format!("Running: {} {}", yt_dlp_path.display(), args.join(" ")))).ok();
+
+    let mut command = ytdlp_command(settings);
+    command.args(&args);
+    command.envs(settings.ytdlp_env());
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    let result = commands::output(&mut command)?;
+
+    let stdout = output::clean_output(&result.stdout);
+    for line in stdout.lines() {
+        progress_sender.send(AppMessage::ConsoleOutput(line.to_string())).ok();
+    }
+    if !result.status.success() {
+        return Err(anyhow::anyhow!("yt-dlp failed: {}", output::clean_output(&result.stderr)));
+    }
+
+    // "[info] Writing video thumbnail 41 to: <path>" / "[info] Writing video metadata as JSON to: <path>"
+    stdout
+        .lines()
+        .filter(|line| line.starts_with("[info] ") && line.contains("Writing video"))
+        .find_map(|line| line.rsplit_once(" to: ").map(|(_, path)| path.trim().to_string()))
+        .ok_or_else(|| match kind {
+            GrabKind::Thumbnail => anyhow::anyhow!("This video has no thumbnail"),
+            GrabKind::Metadata => anyhow::anyhow!("yt-dlp did not write the metadata file"),
+        })
+}
+
+impl YtMp3App {
+    pub(crate) fn start_grab(&mut self, kind: GrabKind) {
+        let AppState::VideoInfo(video_info) = &self.state else {
+            return;
+        };
+        let title = video_info.title.clone();
+        let url = self.url_input.clone();
+        let output_path = self.output_path.clone();
+        let settings = self.settings
+            .with_cookie_profile(self.cookie_profile.as_deref())
+            .with_proxy(self.proxy.as_deref());
+
+        // Not a job and not added to the history, the video itself is still to be downloaded
+        let (tx, rx) = channel::app_channel();
+        self.receiver = Some(rx);
+        self.state = AppState::Downloading {
+            job_id: None,
+            progress: 0.0,
+            status: kind.status().to_string(),
+        };
+
+        thread::spawn(move || {
+            let result = grab(&url, &title, &output_path, kind, &settings, &tx);
+            tx.send(AppMessage::DownloadComplete(result)).ok();
+        });
+    }
+}
@@ -0,0 +1,840 @@
+use eframe::egui;
+
+use crate::a11y::AccessibleName;
+use crate::YtMp3App;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HelpCategory {
+    Downloading,
+    Settings,
+    Troubleshooting,
+}
+
+impl HelpCategory {
+    const ALL: [HelpCategory; 3] = [HelpCategory::Downloading, HelpCategory::Settings, HelpCategory::Troubleshooting];
+
+    fn label(&self) -> &'static str {
+        match self {
+            HelpCategory::Downloading => "⬇ Downloading",
+            HelpCategory::Settings => "⚙ Settings",
+            HelpCategory::Troubleshooting => "🛠 Troubleshooting",
+        }
+    }
+}
+
+// One entry of the help registry: `summary` is the tooltip, `details` the text in the Help window
+pub struct HelpTopic {
+    pub id: &'static str,
+    pub category: HelpCategory,
+    pub title: &'static str,
+    pub summary: &'static str,
+    pub details: &'static str,
+}
+
+pub const TOPICS: &[HelpTopic] = &[
+    HelpTopic {
+        id: "getting_started",
+        category: HelpCategory::Downloading,
+        title: "Getting started",
+        summary: "Paste a link, fetch its info, pick a format and download.",
+        details: "1. Paste a YouTube link into the URL box and press 🔍 Fetch Info.\n\
+                  2. Check the title, duration and the file name it will be saved as.\n\
+                  3. Pick MP4 (video) or MP3 (audio only) and the output folder.\n\
+                  4. Press Download. Progress, speed and the time left are shown below and in the window title.\n\n\
+                  📋 Paste & Go does all of this in one click with the link on the clipboard.",
+    },
+    HelpTopic {
+        id: "formats",
+        category: HelpCategory::Downloading,
+        title: "MP4 and MP3",
+        summary: "MP4 keeps the video, MP3 saves only the audio.",
+        details: "MP4 downloads the best video and audio streams and merges them. Very new videos may use VP9 or AV1, \
+                  which some TVs and older players can't play; turn on H.264 re-encoding in Settings for those.\n\n\
+                  MP3 extracts the audio and converts it. Device presets pick settings for a specific player instead.",
+    },
+    HelpTopic {
+        id: "format_picker",
+        category: HelpCategory::Downloading,
+        title: "Choosing the exact format",
+        summary: "Pick the video and audio streams yourself instead of the best ones.",
+        details: "Tick 🎛 Choose format under the video info to see every format yt-dlp found, with its resolution, frame \
+                  rate, codec, bitrate and size. Click a column heading to sort by it, and again to reverse the order. \
+                  Sizes with ~ are worked out from the bitrate.\n\n\
+                  For MP4, click one video and one audio format to have them merged, or a format that has both. Picking \
+                  only one of them adds the best of the other. For MP3, pick the audio format to convert. Click a \
+                  picked format again to let go of it, or Reset to go back to the best formats. The choice applies to \
+                  this video only; device presets pick their own format and hide the table.",
+    },
+    HelpTopic {
+        id: "tags",
+        category: HelpCategory::Downloading,
+        title: "Tags",
+        summary: "Label downloads, filter the history by tag, and optionally save each tag to its own subfolder.",
+        details: "Type tags separated by commas, e.g. \"workout, running\". They are stored in the history, where you \
+                  can filter by tag and edit tags later. When a tag has a folder in Settings → Tag folders, downloads \
+                  with that tag are saved there.",
+    },
+    HelpTopic {
+        id: "insights",
+        category: HelpCategory::Downloading,
+        title: "Insights",
+        summary: "What you download most, how much space it takes and which sites fail often.",
+        details: "Open 📊 Insights from the History window. It lists the channels you download from most, the space \
+                  your MP3 and MP4 files take, and how often fetching or downloading from each site failed. Everything is worked \
+                  out on this computer from the profile's own database and is never uploaded. A site that fails \
+                  often is a hint to look at its errors in the Command History, or to try cookies or another preset.",
+    },
+    HelpTopic {
+        id: "burn_subtitles",
+        category: HelpCategory::Downloading,
+        title: "Burned-in subtitles",
+        summary: "Draw a subtitle track into the video picture, for players that can't show subtitle files.",
+        details: "Pick a track under 💬 Burn in before downloading an MP4. The video is re-encoded with the subtitles \
+                  drawn on the picture, so they can't be turned off afterwards. Tracks marked \"Translated by YouTube\" \
+                  are machine translations.",
+    },
+    HelpTopic {
+        id: "download_queue",
+        category: HelpCategory::Downloading,
+        title: "Downloading several videos",
+        summary: "Paste a list of URLs and they are downloaded one after another.",
+        details: "Click ➕ Add several URLs, paste one URL per line and queue them with the current format and folder. \
+                  Each one shows whether it is waiting, downloading, done or failed. Hover a failed one to see why \
+                  and click ↻ to try it again, or ✖ to take a waiting one out of the queue. ⏸ Pause queue lets the \
+                  current download finish and holds the rest. ⏸ Pause under the progress bar stops the current \
+                  download itself and keeps the partly downloaded file; ▶ Resume carries on from where it stopped.\n\n\
+                  To download several at once, raise Downloads at the same time in ⚙ Settings → Performance. The \
+                  main view follows the newest download; the others keep going and can be paused from Downloads \
+                  this session.",
+    },
+    HelpTopic {
+        id: "playlist_picker",
+        category: HelpCategory::Downloading,
+        title: "Choosing videos from a playlist",
+        summary: "A pasted playlist or channel is listed first, so you can tick the videos you want.",
+        details: "Every video shows its title and length; the ones already in History and members-only or premium \
+                  ones start unticked. Use All, None or Only new to change the selection, then ⬇ Download to queue \
+                  the ticked videos with the current format and folder. Each one shows its own progress in the \
+                  queue. ⏭ Open one by one loads them into the main view instead, to look at each before downloading.\n\n\
+                  The buttons wait until the whole list is read; click Stop to pick from the videos listed so far. \
+                  Paste & Go, queued playlists and queue templates skip the picker and take every video.",
+    },
+    HelpTopic {
+        id: "device_presets",
+        category: HelpCategory::Downloading,
+        title: "Device presets",
+        summary: "Formats that play on a specific kind of device, like old TVs, phones or audiobook players.",
+        details: "A preset replaces the usual format choice with one that suits the device, for example a lower \
+                  resolution H.264 file for old TVs or a small mono Opus file for audiobooks.",
+    },
+    HelpTopic {
+        id: "fetch_timeout",
+        category: HelpCategory::Settings,
+        title: "Fetching video info",
+        summary: "How long Fetch Info waits for a slow site before trying again or giving up.",
+        details: "When yt-dlp hasn't answered within the time limit it's stopped and started again, as many times as \
+                  set under retries, and then the fetch fails with an error instead of loading forever. The network \
+                  timeout is passed to yt-dlp as --socket-timeout and applies to downloads as well.",
+    },
+    HelpTopic {
+        id: "offline",
+        category: HelpCategory::Settings,
+        title: "Offline detection",
+        summary: "Hold fetching and the queue while there is no internet connection.",
+        details: "The app checks every 30 seconds whether YouTube can be reached, or the proxy when downloads go \
+                  through one. While it can't, Fetch Info and Download are disabled, a banner says so, and queued \
+                  downloads wait. The check runs every few seconds until the connection is back, and then the queue \
+                  carries on by itself. Turn it off if a firewall blocks the check but downloads still work.",
+    },
+    HelpTopic {
+        id: "retention",
+        category: HelpCategory::Settings,
+        title: "Automatic cleanup",
+        summary: "Remove old downloads from a folder by age or total size.",
+        details: "Pick a folder and at least one rule. Files older than the set number of days are removed, then the \
+                  oldest files until the folder is under the size limit. With \"only temporary\" ticked, only \
+                  downloads tagged temporary in History are touched. Files go to the trash, not deleted for good. The \
+                  rules run when the app starts and every hour; Preview shows what they would remove without \
+                  removing anything.",
+    },
+    HelpTopic {
+        id: "queue_templates",
+        category: HelpCategory::Settings,
+        title: "Queue templates",
+        summary: "Save a set of URLs with a format and folder, and queue it again by hand or every few days.",
+        details: "Save the current queue as a template, or start a new one and paste the URLs. Channel links are read as \
+                  the channel's uploads and playlists as their videos, and videos that are already in History are held \
+                  back, so a weekly template only downloads what's new. Scheduled templates run while the app is open; \
+                  one that was due while it was closed runs shortly after it starts.\n\n\
+                  Import subscriptions reads the subscriptions.csv from Google Takeout, or an OPML or RSS feed list \
+                  exported from a feed reader, and opens a daily template with one URL per channel. \
+                  \"Newest videos per channel\" keeps each run to the latest uploads instead of the whole channel.\n\n\
+                  A template can also hold RSS or Atom feeds, such as a podcast feed or a YouTube channel's \
+                  feeds/videos.xml link. Each run reads the feed and queues the entries it hasn't queued before: the \
+                  episode's audio file for podcasts, the video for everything else.",
+    },
+    HelpTopic {
+        id: "run_digest",
+        category: HelpCategory::Settings,
+        title: "Template run digests",
+        summary: "A summary of each queue template run: how many new videos it found, downloaded and failed.",
+        details: "When every download of a template run has finished, or the queue goes idle with some left for you, \
+                  the run's digest is written to the console and listed in the digest window, with a link to each \
+                  video. The window opens by itself when a run found something new, unless you turn that off.\n\n\
+                  Enter a webhook URL to have each digest POSTed as JSON. The \"text\" and \"content\" fields hold a \
+                  readable summary that Slack and Discord webhooks post as a message; \"digest\" has the details. \
+                  To get digests by email, point it at a webhook-to-email relay.",
+    },
+    HelpTopic {
+        id: "metered",
+        category: HelpCategory::Settings,
+        title: "Metered connections",
+        summary: "Hold the queue or limit the download speed while on a phone hotspot or another data plan.",
+        details: "The connection is checked every minute. Windows reports whether a network is metered (set it under \
+                  the network's properties); on Linux NetworkManager is asked, and without it a mobile broadband \
+                  connection counts as metered; on macOS a connection through an iPhone or another phone does.\n\n\
+                  \"Hold the queue\" keeps queued downloads waiting until you're on an unmetered network again; \
+                  a download you start yourself still goes ahead, and \"Download anyway\" lets the queue run on this \
+                  connection. \"Limit the download speed\" passes the limit (e.g. 500K or 1M per second) to yt-dlp \
+                  for every download started while the connection is metered. A download already running isn't \
+                  stopped or slowed down.",
+    },
+    HelpTopic {
+        id: "config_file",
+        category: HelpCategory::Settings,
+        title: "Editing config.toml by hand",
+        summary: "Changes saved to config.toml in a text editor are picked up while the app runs.",
+        details: "All settings, including proxies, cookie profiles and the device preset, are kept in config.toml in \
+                  the app's config folder (each profile has its own). The app checks the file every couple of \
+                  seconds and takes in what changed, without a restart. If the file has a syntax error, the console \
+                  says so and the current settings stay in place until it's fixed. Changing a setting in the app \
+                  writes the whole file again, so save your edits before using the Settings window.",
+    },
+    HelpTopic {
+        id: "pre_download",
+        category: HelpCategory::Settings,
+        title: "Pre-download command",
+        summary: "Run your own command before each download, and let it cancel the download.",
+        details: "The command runs through the system shell (cmd on Windows, sh elsewhere) just before yt-dlp starts, \
+                  e.g. to check a URL against an allowlist or to mount the drive the download goes to. {url}, \
+                  {title}, {folder} and {format} are replaced with the download's details, already quoted. The \
+                  values are passed in the YTMP3_URL, YTMP3_TITLE, YTMP3_FOLDER and YTMP3_FORMAT environment \
+                  variables, and the placeholders refer to them, so a title can't change the command. Its output goes to the console. When it exits with a code other than 0, or runs for \
+                  more than 10 minutes, the download is cancelled and its last line of output is shown as the \
+                  reason; turn off \"Cancel the download when the command fails\" to only log a warning.",
+    },
+    HelpTopic {
+        id: "stall_detection",
+        category: HelpCategory::Settings,
+        title: "Stall detection",
+        summary: "Restart or stop a download when it makes no progress for a while.",
+        details: "When a download shows no progress for the timeout, it is either restarted (up to the maximum number \
+                  of restarts) or stopped and marked as stalled in the downloads list, where it can be retried. A timeout \
+                  of 0 turns this off. Restarts continue where the download left off.",
+    },
+    HelpTopic {
+        id: "existing_files",
+        category: HelpCategory::Settings,
+        title: "When the file already exists",
+        summary: "Skip, overwrite or rename when the output folder already has a file with the same name.",
+        details: "Skip keeps the existing file and doesn't download again. Overwrite replaces it. Rename saves the new \
+                  file with a number added, like \"Title (1).mp4\".\n\n\
+                  The upload date and the video ID can be added to file names, which also keeps different videos \
+                  with the same title apart.",
+    },
+    HelpTopic {
+        id: "id_on_collision",
+        category: HelpCategory::Settings,
+        title: "Video ID in file names",
+        summary: "Used when the folder already has a file with this title from a different video.",
+        details: "\"When the title is taken\" only adds the ID (like \"Title [dQw4w9WgXcQ]\") when another video with the \
+                  same title was already saved in the folder. \"Always\" adds it to every file.",
+    },
+    HelpTopic {
+        id: "hash_manifest",
+        category: HelpCategory::Settings,
+        title: "Hash manifest",
+        summary: "Lets you verify and deduplicate a collection later.",
+        details: "After each download a SHA-256 checksum of the file is written to ytmp3-manifest.json in the output \
+                  folder, together with the URL and title.",
+    },
+    HelpTopic {
+        id: "journal",
+        category: HelpCategory::Settings,
+        title: "Download journal",
+        summary: "Adds a record of every finished download to a JSON Lines or CSV file.",
+        details: "Each record has the URL, video ID, title, format, file path, the exact yt-dlp command, start and end \
+                  times, how long yt-dlp took, the average speed, the file size and its SHA-256. Useful for building \
+                  datasets or keeping a compliance log. Without a file chosen it is download-journal.jsonl or .csv \
+                  in the app's data folder.",
+    },
+    HelpTopic {
+        id: "temp_files",
+        category: HelpCategory::Settings,
+        title: "Temporary files",
+        summary: "Partial downloads and intermediate files are kept here and only the finished file is moved to the \
+                  output folder. Useful when the output folder is on a network drive.",
+        details: "Leave it empty to download straight into the output folder. A local folder here speeds up downloads \
+                  to network or USB drives and keeps half-finished files out of your library.",
+    },
+    HelpTopic {
+        id: "default_folders",
+        category: HelpCategory::Settings,
+        title: "Default output folders",
+        summary: "Where MP4 and MP3 downloads go unless you choose another folder.",
+        details: "Left empty, videos go to your system Videos folder and audio to Music. Switching the format switches \
+                  the folder too, unless you picked one yourself. Pin often used folders with ⭐ Folders next to Browse.\n\n\
+                  Each device preset can have a folder of its own, e.g. a USB stick for the old TV. Left empty, it \
+                  uses the folder of its format.",
+    },
+    HelpTopic {
+        id: "tag_folders",
+        category: HelpCategory::Settings,
+        title: "Tag folders",
+        summary: "Send downloads with a tag to their own subfolder.",
+        details: "Relative folders are created inside the output folder, absolute ones are used as they are. When a \
+                  download has several tags, the first one with a folder wins.",
+    },
+    HelpTopic {
+        id: "profiles",
+        category: HelpCategory::Settings,
+        title: "Profiles",
+        summary: "Separate settings, history, presets and folders, e.g. for work and personal downloads.",
+        details: "Add a profile here, then switch with the 👤 menu next to History. Each profile starts with the \
+                  default settings and keeps its own history, saved and retry lists. The app opens the profile used \
+                  last, or asks at startup if you turn that on. Switching waits until the current download is done.",
+    },
+    HelpTopic {
+        id: "simple_mode",
+        category: HelpCategory::Settings,
+        title: "Simple mode",
+        summary: "A stripped-down window for someone else's computer: paste a link, download, done.",
+        details: "History, cookies, proxies, tags, subtitles and the format and folder choices are hidden, and every \
+                  download is saved in the chosen format to the chosen folder. Set a settings password so simple mode \
+                  can't be turned off without it; the settings lock again when their window is closed. A forgotten \
+                  password can be cleared by emptying settings_password_hash in config.toml.\n\n\
+                  Operator mode goes further, for shared lab or studio machines: the window only has a box for URLs \
+                  and the queue, and every URL (including each video of a playlist) is downloaded with the fixed \
+                  folder and format. The 🔑 button opens the settings with the password; without one, start the app \
+                  with --admin to get the full window.",
+    },
+    HelpTopic {
+        id: "hotkey",
+        category: HelpCategory::Settings,
+        title: "Global hotkeys",
+        summary: "Download the link on the clipboard, or start and pause the queue, from any application.",
+        details: "Copy a YouTube link anywhere and press the download hotkey; the app fetches and downloads it with the \
+                  current format. The queue hotkeys are meant for macro pads and Stream Deck buttons; pausing lets the \
+                  current download finish. Leave a hotkey empty to not bind it. Some combinations are taken by the \
+                  system or other apps, in which case an error is shown. On a Mac, write ⌘ as Cmd and ⌥ as Option, \
+                  e.g. Cmd+Shift+Option+D.",
+    },
+    HelpTopic {
+        id: "event_stream",
+        category: HelpCategory::Settings,
+        title: "Local API",
+        summary: "Send queue and progress updates to OBS overlays and dashboards, and take actions from controllers.",
+        details: "Connect a WebSocket client to the address shown. Each message is a JSON object whose type field is \
+                  hello, video_info, fetch_failed, download_started, progress, download_complete, download_failed, \
+                  download_suspicious, console, queue or action. Send start_queue, pause_queue or paste_and_download \
+                  as a text message to run an action, or POST to /actions/<name> on the same port from tools that \
+                  only make HTTP requests. Only programs on this computer can connect, and web pages only from \
+                  localhost or OBS, not local files, unless paired computers on the network are let in.",
+    },
+    HelpTopic {
+        id: "browser_extension",
+        category: HelpCategory::Settings,
+        title: "Pairing a browser extension",
+        summary: "Let a browser extension queue the page you're on with a right-click.",
+        details: "Turn on the local API, click \"Pair a device\" and type the six-digit code into the \
+                  extension within five minutes. The extension then gets its own key, and right-click → \
+                  \"Download with yt-dlp GUI\" queues the page's URL with the format, folder and device preset \
+                  currently chosen in the app. A code works once and stops working after five wrong tries. Click \
+                  Unpair to take an extension's access away. Extension authors: GET /pair/hello finds the app, \
+                  POST {\"code\", \"name\"} to /pair returns a token, and POST {\"url\"} to /enqueue with \
+                  \"Authorization: Bearer <token>\" queues a URL.",
+    },
+    HelpTopic {
+        id: "remote_mode",
+        category: HelpCategory::Settings,
+        title: "Remote control over the network",
+        summary: "Use this app on a laptop to queue downloads on an always-on computer like a home server or NAS.",
+        details: "On the computer that should do the downloading, turn on the local API and \"Let paired computers on \
+                  the network control this app\", then click \"Pair a device\". On the laptop, open 🖧 Remote, enter \
+                  the address shown in the server's settings and the six-digit code, and click Pair. From then on \
+                  the Remote window shows the server's queue and current download, can start and pause its queue, \
+                  and sends URLs to it; \"Send to remote\" next to Fetch Info sends the URL box's link. Downloads \
+                  are saved on the server, in its folder for the chosen format. Only paired computers get in; \
+                  unpair one in the server's settings to shut it out. The connection isn't encrypted, so only use \
+                  this on a network you trust.",
+    },
+    HelpTopic {
+        id: "web_ui",
+        category: HelpCategory::Settings,
+        title: "Web page for phones",
+        summary: "Queue downloads and watch the queue from any browser, without installing anything.",
+        details: "With the local API on, \"Serve a web page\" puts a small page at the API's address, shown under the \
+                  checkbox; it's always served when running with --daemon. Turn on \"Let paired computers on the \
+                  network control this app\" to open it from a phone, using the address with the IP, not the \
+                  computer's name. The first time, the page asks for a pairing code: click \"Pair a device\" here and \
+                  type the code into the page. After that it shows the current download and the queue, pauses and \
+                  starts the queue, and queues URLs in the current format or as MP3 or MP4. The page is listed with \
+                  the paired devices; unpair it to lock it out.",
+    },
+    HelpTopic {
+        id: "daemon",
+        category: HelpCategory::Settings,
+        title: "Running without a window",
+        summary: "Start the app with --daemon to keep a download queue running on a server or in Docker.",
+        details: "\"ytmp3 --daemon\" runs the queue, queue templates and local API without opening a window, until \
+                  it's stopped with Ctrl+C or docker stop. Everything is configured in config.toml, which is read \
+                  again when it changes: set event_stream_enabled = true to queue URLs over HTTP, and \
+                  event_stream_lan = true to reach it from other computers or from outside a container. Downloads \
+                  use the simple mode format and folder, and the web page for phones is served. A pairing code \
+                  for a browser extension, the 🖧 Remote window or the web page is printed at startup while \
+                  nothing is paired, or whenever --pair is given. The console goes to standard output. URLs after \
+                  --daemon are queued right away.",
+    },
+    HelpTopic {
+        id: "startup",
+        category: HelpCategory::Settings,
+        title: "Startup",
+        summary: "Launch the app when you log in, optionally minimized.",
+        details: "Useful together with the global hotkey or Retry later, which need the app running.",
+    },
+    HelpTopic {
+        id: "desktop_entry",
+        category: HelpCategory::Settings,
+        title: "Desktop integration (Linux)",
+        summary: "Add the app to the applications menu and let ytmp3: links and URL lists open in it.",
+        details: "Add to applications menu writes ytmp3.desktop to ~/.local/share/applications (or $XDG_DATA_HOME). \
+                  Links like ytmp3://youtu.be/... or ytmp3:https://... then open in the app, and a text file with one \
+                  URL per line can be opened with it to load every URL. If the app is moved, Update entry points the \
+                  menu at the new place. Settings are kept in $XDG_CONFIG_HOME/ytmp3, the history and yt-dlp in \
+                  $XDG_DATA_HOME/ytmp3 and yt-dlp's cache in $XDG_CACHE_HOME/ytmp3. Deleted files go through \
+                  gio trash, like in the file manager, when it's installed.",
+    },
+    HelpTopic {
+        id: "paste_and_go",
+        category: HelpCategory::Settings,
+        title: "Paste & Go",
+        summary: "Whether 📋 Paste & Go starts the download right away or stops after fetching the info.",
+        details: "With the option off, Paste & Go only fetches the info so you can check it before downloading.",
+    },
+    HelpTopic {
+        id: "cookies",
+        category: HelpCategory::Settings,
+        title: "Cookies",
+        summary: "Let yt-dlp use your signed-in session, for age-restricted, private or members-only videos.",
+        details: "Choose the browser you are signed in with, or a cookies.txt file exported with a browser extension. \
+                  Chromium-based browsers may need to be closed while cookies are read. Account profiles let you keep \
+                  several sign-ins and pick one per download.",
+    },
+    HelpTopic {
+        id: "account_profiles",
+        category: HelpCategory::Settings,
+        title: "Account profiles",
+        summary: "Named sets of cookies to choose from per download.",
+        details: "Each profile uses a browser (with an optional browser profile) or a cookies.txt file. Pick the \
+                  profile under 👥 Account before fetching a video.",
+    },
+    HelpTopic {
+        id: "youtube_extractor",
+        category: HelpCategory::Settings,
+        title: "YouTube extractor options",
+        summary: "Player clients, PO token and metadata language passed to yt-dlp's YouTube extractor.",
+        details: "When YouTube suddenly refuses downloads, a different player client often helps: TV clients usually \
+                  work without a PO token, web clients need one. Leave everything empty to use yt-dlp's defaults.",
+    },
+    HelpTopic {
+        id: "mirror_fallback",
+        category: HelpCategory::Settings,
+        title: "Mirror fallback",
+        summary: "Ask an Invidious or Piped instance for the video info when YouTube blocks the fetch.",
+        details: "When fetching fails with YouTube's \"confirm you're not a bot\" check or a 429 rate limit, the video \
+                  details are requested from the instance instead, and the console says which one answered. Enter the \
+                  instance address for Invidious, or the API address for Piped (often pipedapi.…). The download itself \
+                  still goes through yt-dlp, so a block that lasts may need cookies as well.",
+    },
+    HelpTopic {
+        id: "po_token",
+        category: HelpCategory::Settings,
+        title: "PO token",
+        summary: "Proof-of-origin token as CLIENT.CONTEXT+TOKEN, see the yt-dlp PO Token Guide",
+        details: "Some YouTube clients only hand out working formats together with a PO token. Enter it as \
+                  CLIENT.CONTEXT+TOKEN, for example web.gvs+XXXX. The yt-dlp wiki's PO Token Guide explains how to get one.",
+    },
+    HelpTopic {
+        id: "release_channel",
+        category: HelpCategory::Settings,
+        title: "yt-dlp release channel",
+        summary: "Fixes for broken sites usually land in nightly days before a stable release",
+        details: "Stable is updated every few weeks. Nightly is built every day and gets fixes sooner, with a small \
+                  chance of new bugs. Both are downloaded and kept up to date automatically.",
+    },
+    HelpTopic {
+        id: "retry_later",
+        category: HelpCategory::Settings,
+        title: "Retry later",
+        summary: "How often videos that weren't available yet are checked again.",
+        details: "Premieres, private videos and region-blocked videos can be added to ⏰ Retry later from the error \
+                  screen. They are checked in the background and downloaded as soon as they work.",
+    },
+    HelpTopic {
+        id: "completion_sound",
+        category: HelpCategory::Settings,
+        title: "Completion sound",
+        summary: "A sound when a download or the whole queue is done.",
+        details: "Off until you turn it on in ⚙ Settings, for each download or only for the whole queue. Leave the \
+                  sound file empty for the built-in chime, or choose a WAV, MP3, OGG or FLAC file. The 🔔 button in \
+                  the header mutes it.",
+    },
+    HelpTopic {
+        id: "accessibility",
+        category: HelpCategory::Settings,
+        title: "Accessibility",
+        summary: "High contrast colours, less motion and the interface font.",
+        details: "High contrast draws white text and outlines on black. Reduce motion replaces spinners with a still \
+                  icon and turns off animations. Status changes are announced to screen readers either way.",
+    },
+    HelpTopic {
+        id: "reduced_motion",
+        category: HelpCategory::Settings,
+        title: "Reduce motion",
+        summary: "Replaces spinners with a still icon and turns off animations",
+        details: "Collapsing sections and windows appear at once, the text cursor stops blinking and the progress bar \
+                  doesn't animate.",
+    },
+    HelpTopic {
+        id: "region",
+        category: HelpCategory::Settings,
+        title: "Region",
+        summary: "How view counts, file sizes, speeds and dates are written.",
+        details: "Follows the system's region and language settings by default. Pick a locale to use its digit \
+                  grouping, decimal comma or point and date order instead. File names are not affected, the \
+                  upload date in them has its own format next to Upload date in file names.",
+    },
+    HelpTopic {
+        id: "extra_options",
+        category: HelpCategory::Settings,
+        title: "Extra yt-dlp options",
+        summary: "yt-dlp options added to every download, and importing an existing yt-dlp.conf.",
+        details: "Write them as on the command line, e.g. --limit-rate 2M --embed-metadata. Import yt-dlp.conf \
+                  reads a config file: cookies, proxy, impersonation, folders, overwrite and re-encode options \
+                  become the matching settings, other options are added here. Output name, format and progress \
+                  options are left out because the app sets those itself.",
+    },
+    HelpTopic {
+        id: "system_config",
+        category: HelpCategory::Settings,
+        title: "Your own yt-dlp config",
+        summary: "Whether yt-dlp also reads your own yt-dlp.conf when the app runs it.",
+        details: "Ignore (the default) passes --ignore-config, so yt-dlp behaves the same on every computer. Inherit \
+                  lets options from a yt-dlp.conf you use on the command line apply too, but options like -o or -f \
+                  in it can clash with the app's. Import yt-dlp.conf copies the options over instead.",
+    },
+    HelpTopic {
+        id: "filename_template",
+        category: HelpCategory::Settings,
+        title: "File name template",
+        summary: "Name files with any field yt-dlp knows about, e.g. %(uploader)s - %(title)s.",
+        details: "Uses yt-dlp's output template syntax without the extension, which is added for you. Open \
+                  Available fields and click one to insert it; after fetching a video the list shows all of its \
+                  fields with their values. A / in the template makes subfolders. Leave it empty to build the name \
+                  from the upload date and video ID options.",
+    },
+    HelpTopic {
+        id: "proxies",
+        category: HelpCategory::Settings,
+        title: "Proxies",
+        summary: "Send downloads through a proxy, per download or by default.",
+        details: "Supports http://, https:// and socks5:// proxies. Use 🔌 Test to check one. A proxy in another \
+                  country gets around region blocks.\n\n\
+                  Turn on the system proxy to use the HTTPS_PROXY/HTTP_PROXY environment variables, or the proxy \
+                  set in Windows, macOS or GNOME, when no proxy is chosen. Hosts in NO_PROXY or the system's \
+                  bypass list, and this computer itself, are reached directly. Automatic configuration (PAC) \
+                  scripts are not read; choose a proxy instead. Pick \"Direct connection\" for one download to \
+                  skip the proxy.",
+    },
+    HelpTopic {
+        id: "certificates",
+        category: HelpCategory::Settings,
+        title: "Certificates for TLS-inspecting proxies",
+        summary: "Trust your company proxy's certificate authority, or turn certificate checks off.",
+        details: "Some company networks decrypt HTTPS traffic at the proxy, so YouTube's certificate is replaced by \
+                  one from the proxy and downloads fail with \"certificate verify failed\". Ask your IT department \
+                  for the proxy's CA certificate as a PEM file and choose it as the extra CA bundle. yt-dlp then checks \
+                  certificates against that file instead of its built-in list, so add the usual public CAs to it if \
+                  some traffic doesn't go through the proxy. The app trusts it as well for updates, mirrors, feeds \
+                  and webhooks.\n\n\
+                  Not checking certificates at all also works, but then anyone on the network can read and change \
+                  what is downloaded. Only use it when there's no other way.",
+    },
+    HelpTopic {
+        id: "impersonate",
+        category: HelpCategory::Settings,
+        title: "Client impersonation",
+        summary: "Make yt-dlp look like a regular browser to sites that block it.",
+        details: "Some sites block requests by their TLS fingerprint. Impersonating a browser helps there; it needs a \
+                  yt-dlp build with curl_cffi, which the list of available targets shows.",
+    },
+    HelpTopic {
+        id: "plugins",
+        category: HelpCategory::Settings,
+        title: "yt-dlp plugins",
+        summary: "Load extra extractors and postprocessors from a folder.",
+        details: "Put yt-dlp plugin packages in the folder. The installed plugins are listed once yt-dlp has loaded them.\n\n\
+                  Recent yt-dlp releases get the folder with --plugin-dirs. Older ones don't know that option, so the \
+                  packages are added to PYTHONPATH instead, which only works when yt-dlp was installed with pip.",
+    },
+    HelpTopic {
+        id: "title_cleanup",
+        category: HelpCategory::Settings,
+        title: "Title cleanup",
+        summary: "Remove clutter like \"(Official Video)\" or \"[4K]\" from file names and metadata.",
+        details: "Tick the kinds of clutter to remove or add your own regular expressions. The preview shows what a \
+                  title will look like.",
+    },
+    HelpTopic {
+        id: "reencode",
+        category: HelpCategory::Settings,
+        title: "Video re-encoding",
+        summary: "For TVs and older players that can't play VP9 or AV1. Re-encoding takes a while.",
+        details: "MP4 downloads are converted to H.264 video with AAC audio. A hardware encoder (NVIDIA, Intel, AMD or \
+                  Apple) is much faster than software; only the ones that work on this computer can be chosen.",
+    },
+    HelpTopic {
+        id: "performance",
+        category: HelpCategory::Settings,
+        title: "Keeping the computer responsive",
+        summary: "Runs yt-dlp and ffmpeg at low priority and caps how many CPU threads ffmpeg uses.",
+        details: "Low priority lets other programs go first when the processor is busy, so a long conversion \
+                  doesn't make the rest of the computer sluggish; it only takes longer when something else needs \
+                  the CPU. The thread limit caps how many cores a conversion or re-encode uses at once. \
+                  Hardware encoders are barely affected by either.\n\n\
+                  Conversions at the same time limits how many re-encodes run together; software and hardware \
+                  encoders are counted separately. A re-encoding download starts right away and re-encodes once \
+                  the file is in, waiting for a free slot only then. Pausing it gives up its place in line.\n\n\
+                  Downloads at the same time lets queued downloads start while others are still running, each with \
+                  its own yt-dlp. A few at once help when a site limits the speed of each download; on a slow \
+                  connection they only share it.",
+    },
+    HelpTopic {
+        id: "bot_check",
+        category: HelpCategory::Troubleshooting,
+        title: "\"Sign in to confirm you're not a bot\"",
+        summary: "YouTube wants a signed-in session or a different client.",
+        details: "1. Switch the yt-dlp release channel to Nightly and try again.\n\
+                  2. Under YouTube extractor options, try the TV player client.\n\
+                  3. Use cookies from a browser where you are signed in to YouTube.\n\
+                  4. Avoid downloading many videos quickly from the same connection.",
+    },
+    HelpTopic {
+        id: "http_403",
+        category: HelpCategory::Troubleshooting,
+        title: "HTTP Error 403: Forbidden",
+        summary: "The download link was refused, usually fixed by a newer yt-dlp.",
+        details: "YouTube changes often and old yt-dlp versions stop working. yt-dlp is updated before each fetch; \
+                  if the latest stable release still fails, switch to the Nightly channel. A different player client \
+                  or a PO token can also help.",
+    },
+    HelpTopic {
+        id: "region_blocked",
+        category: HelpCategory::Troubleshooting,
+        title: "Not available in your country",
+        summary: "Use a proxy in a country where the video is available.",
+        details: "Add a proxy under Settings → Proxies and choose it for the download. You can also add the video to \
+                  ⏰ Retry later if it might become available.",
+    },
+    HelpTopic {
+        id: "age_restricted",
+        category: HelpCategory::Troubleshooting,
+        title: "Age-restricted videos",
+        summary: "Needs cookies from an account that is allowed to watch the video.",
+        details: "Sign in to YouTube in your browser, then choose that browser under Settings → Cookies or on the \
+                  age-restriction screen.",
+    },
+    HelpTopic {
+        id: "ffmpeg_missing",
+        category: HelpCategory::Troubleshooting,
+        title: "ffmpeg not found",
+        summary: "Merging, MP3 conversion and re-encoding need ffmpeg installed.",
+        details: "Install ffmpeg and make sure it is on the PATH (winget install ffmpeg on Windows, brew install ffmpeg \
+                  on macOS, your package manager on Linux), then restart the app.",
+    },
+    HelpTopic {
+        id: "slow_or_stalled",
+        category: HelpCategory::Troubleshooting,
+        title: "Slow or stuck downloads",
+        summary: "Stall detection restarts downloads that stop making progress.",
+        details: "Turn on stall detection so stuck downloads restart by themselves. Very slow downloads are often \
+                  throttled; updating yt-dlp or switching the player client usually helps.",
+    },
+    HelpTopic {
+        id: "long_paths",
+        category: HelpCategory::Troubleshooting,
+        title: "File name too long",
+        summary: "Long titles are shortened to fit the path limit; choose a shorter folder if that isn't enough.",
+        details: "Windows limits paths to 260 characters. Titles are shortened automatically, but a deeply nested \
+                  output or temporary folder can leave no room at all.",
+    },
+    HelpTopic {
+        id: "broken_file",
+        category: HelpCategory::Troubleshooting,
+        title: "\"The file looks broken\"",
+        summary: "The download finished but the file didn't pass the check afterwards.",
+        details: "This usually means the connection dropped near the end. Press 🔄 Re-download. If it keeps \
+                  happening, try a temporary folder on a local drive.",
+    },
+    HelpTopic {
+        id: "crash",
+        category: HelpCategory::Troubleshooting,
+        title: "The app closed unexpectedly",
+        summary: "A crash report was saved, and the queue can be restored at the next start.",
+        details: "When the app crashes it writes a report to the crashes folder in its data folder, with the error, \
+                  where it happened, the queue and the last lines of the console. At the next start you can open \
+                  the report and choose whether to restore the downloads that were queued or running. If the app \
+                  crashes again on the same video, start with an empty queue instead. Attaching the report to a \
+                  bug report helps to fix the problem; check it for anything private first.",
+    },
+    HelpTopic {
+        id: "self_test",
+        category: HelpCategory::Troubleshooting,
+        title: "Checking yt-dlp and ffmpeg",
+        summary: "Run ytmp3 --self-test to see whether downloading works on this computer at all.",
+        details: "The self-test serves a short test tone and a test pattern video from the app itself and downloads \
+                  them as MP3 and MP4 with your yt-dlp and ffmpeg, then checks the files and the history. It \
+                  doesn't need YouTube, so if it passes while YouTube downloads fail, the problem is on YouTube's \
+                  side or with your cookies or network. It uses a profile of its own and leaves your history alone.",
+    },
+];
+
+// IDs are only ever written in the code, so a miss is a typo there
+pub fn topic(id: &str) -> Option<&'static HelpTopic> {
+    let topic = TOPICS.iter().find(|topic| topic.id == id);
+    debug_assert!(topic.is_some(), "unknown help topic {:?}", id);
+    topic
+}
+
+pub fn summary(id: &str) -> &'static str {
+    topic(id).map_or("", |topic| topic.summary)
+}
+
+// Where a ❓ click leaves the topic for the Help window; set from places without access to the app
+fn requested_topic_id() -> egui::Id {
+    egui::Id::new("help_requested_topic")
+}
+
+// ❓ showing the topic's summary on hover and opening it in the Help window on click
+pub fn help_button(ui: &mut egui::Ui, id: &'static str) {
+    let Some(topic) = topic(id) else {
+        return;
+    };
+    if ui.small_button("❓")
+        .accessible_name(&format!("Help: {}", topic.title))
+        .on_hover_text(topic.summary)
+        .clicked()
+    {
+        ui.ctx().data_mut(|data| data.insert_temp(requested_topic_id(), id));
+    }
+}
+
+// A section heading followed by its ❓
+pub fn section_label(ui: &mut egui::Ui, text: &str, id: &'static str) {
+    ui.horizontal(|ui| {
+        ui.label(text);
+        help_button(ui, id);
+    });
+}
+
+impl YtMp3App {
+    pub(crate) fn show_help_window(&mut self, ctx: &egui::Context) {
+        if let Some(id) = ctx.data_mut(|data| data.remove_temp::<&'static str>(requested_topic_id())) {
+            self.help_topic = id;
+            self.show_help = true;
+        }
+        if !self.show_help {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new("❓ Help")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([600.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal_top(|ui| {
+                    egui::ScrollArea::vertical()
+                        .id_source("help_topics")
+                        .max_width(200.0)
+                        .show(ui, |ui| {
+                            for category in HelpCategory::ALL {
+                                ui.label(egui::RichText::new(category.label()).strong());
+                                for topic in TOPICS.iter().filter(|topic| topic.category == category) {
+                                    if ui.selectable_label(self.help_topic == topic.id, topic.title).clicked() {
+                                        self.help_topic = topic.id;
+                                    }
+                                }
+                                ui.add_space(8.0);
+                            }
+                        });
+                    ui.separator();
+                    let topic = topic(self.help_topic).unwrap_or(&TOPICS[0]);
+                    egui::ScrollArea::vertical().id_source("help_details").show(ui, |ui| {
+                        ui.vertical(|ui| {
+                            ui.heading(topic.title);
+                            ui.add_space(5.0);
+                            ui.label(egui::RichText::new(topic.summary).italics());
+                            ui.add_space(10.0);
+                            ui.label(topic.details);
+                        });
+                    });
+                });
+            });
+        self.show_help = open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+    use std::collections::HashSet;
+
+    #[test]
+    fn topic_ids_are_unique() {
+        let mut seen = HashSet::new();
+        for topic in TOPICS {
+            assert!(seen.insert(topic.id), "help topic {:?} is listed twice", topic.id);
+        }
+    }
+
+    // Every help_button, section_label and help::summary in the sources names a topic that exists
+    #[test]
+    fn every_referenced_topic_exists() {
+        let calls = Regex::new(
+            r#"(?:help_button\(\s*ui,\s*|section_label\(\s*ui,\s*"(?:[^"\\]|\\.)*",\s*|summary\(\s*)"([a-z0-9_]+)""#,
+        )
+        .unwrap();
+        let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut checked = 0;
+        for entry in std::fs::read_dir(&src).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("rs") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            for captures in calls.captures_iter(&source) {
+                let id = &captures[1];
+                assert!(
+                    TOPICS.iter().any(|topic| topic.id == id),
+                    "{} refers to the unknown help topic {:?}",
+                    path.display(),
+                    id
+                );
+                checked += 1;
+            }
+        }
+        assert!(checked > 50, "only found {} help references, the pattern is probably out of date", checked);
+    }
+}
@@ -0,0 +1,408 @@
+use anyhow::Result;
+use eframe::egui;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+use crate::a11y::AccessibleName;
+use crate::rtl;
+use crate::{database, events, recycle, tags, DownloadFormat, YtMp3App};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    // Row in the history table; None until the entry has been written
+    #[serde(skip)]
+    pub id: Option<i64>,
+    pub video_id: Option<String>,
+    pub url: String,
+    pub title: String,
+    pub uploader: String,
+    #[serde(default)]
+    pub channel_url: Option<String>,
+    pub format: DownloadFormat,
+    pub path: String,
+    // Unix timestamp (seconds) of when the download finished
+    pub completed_at: i64,
+    // The file was moved to the trash from the History window
+    #[serde(default)]
+    pub deleted: bool,
+    // Collections like "workout" or "lectures", editable from the History window
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Video height read from the file, for finding downloads worth upgrading
+    #[serde(default)]
+    pub height: Option<u32>,
+    // A higher quality download took this one's place
+    #[serde(default)]
+    pub replaced: bool,
+    // The file was deleted or moved by another program
+    #[serde(default)]
+    pub missing: bool,
+}
+
+impl HistoryEntry {
+    pub fn completed_at_text(&self) -> String {
+        crate::locale::timestamp(self.completed_at).unwrap_or_else(|| "unknown date".to_string())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    pub entries: Vec<HistoryEntry>,
+}
+
+// Rows in the history table, oldest first; the window works on them in memory
+fn read_entries(connection: &Connection) -> Result<Vec<HistoryEntry>> {
+    let mut select = connection.prepare(
+        "SELECT id, video_id, url, title, uploader, channel_url, format, path, completed_at, deleted, tags, height, replaced, missing
+         FROM history ORDER BY id",
+    )?;
+    let rows = select.query_map([], |row| {
+        let format: String = row.get(6)?;
+        let tags: String = row.get(10)?;
+        Ok(HistoryEntry {
+            id: row.get(0)?,
+            video_id: row.get(1)?,
+            url: row.get(2)?,
+            title: row.get(3)?,
+            uploader: row.get(4)?,
+            channel_url: row.get(5)?,
+            format: database::parse_format(&format).unwrap_or(DownloadFormat::Mp4),
+            path: row.get(7)?,
+            completed_at: row.get(8)?,
+            deleted: row.get(9)?,
+            tags: serde_json::from_str(&tags).unwrap_or_default(),
+            height: row.get(11)?,
+            replaced: row.get(12)?,
+            missing: row.get(13)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+// Returns the new row's id
+fn insert_entry(connection: &Connection, entry: &HistoryEntry) -> Result<i64> {
+    connection.prepare_cached(
+        "INSERT INTO history (video_id, url, title, uploader, channel_url, format, path, completed_at, deleted, tags, height, replaced, missing)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+    )?
+    .execute(params![
+        entry.video_id,
+        entry.url,
+        entry.title,
+        entry.uploader,
+        entry.channel_url,
+        events::format_name(entry.format),
+        entry.path,
+        entry.completed_at,
+        entry.deleted,
+        serde_json::to_string(&entry.tags)?,
+        entry.height,
+        entry.replaced,
+        entry.missing,
+    ])?;
+    Ok(connection.last_insert_rowid())
+}
+
+pub fn insert_entries(connection: &Connection, entries: &[HistoryEntry]) -> Result<()> {
+    for entry in entries {
+        insert_entry(connection, entry)?;
+    }
+    Ok(())
+}
+
+// Only the fields the app changes after a download; the rest never moves
+fn update_entry(connection: &Connection, id: i64, entry: &HistoryEntry) -> Result<()> {
+    connection.prepare_cached(
+        "UPDATE history SET path = ?1, deleted = ?2, tags = ?3, height = ?4, replaced = ?5, missing = ?6 WHERE id = ?7",
+    )?
+    .execute(params![
+        entry.path,
+        entry.deleted,
+        serde_json::to_string(&entry.tags)?,
+        entry.height,
+        entry.replaced,
+        entry.missing,
+        id,
+    ])?;
+    Ok(())
+}
+
+impl History {
+    pub fn load() -> Self {
+        match database::open().and_then(|connection| read_entries(&connection)) {
+            Ok(entries) => Self { entries },
+            Err(e) => {
+                log::warn!("Could not read the history: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    // Writes the rows of the entries at these indices after changes to them. Rows a daemon on
+    // the same profile added in the meantime are left alone. An entry whose first write failed
+    // is added now
+    pub fn save(&mut self, indices: impl IntoIterator<Item = usize>) -> Result<()> {
+        let mut connection = database::open()?;
+        let transaction = connection.transaction()?;
+        for index in indices {
+            let Some(entry) = self.entries.get_mut(index) else {
+                continue;
+            };
+            match entry.id {
+                Some(id) => update_entry(&transaction, id, entry)?,
+                None => entry.id = Some(insert_entry(&transaction, entry)?),
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    // Only adds the new row, however long the history gets
+    pub fn add(&mut self, mut entry: HistoryEntry) -> Result<()> {
+        let result = database::open().and_then(|connection| insert_entry(&connection, &entry));
+        entry.id = result.as_ref().ok().copied();
+        self.entries.push(entry);
+        result.map(|_| ())
+    }
+
+    // Most recent download of a video, matched by ID first and URL as a fallback.
+    // Entries whose file was trashed don't count, so those videos can be downloaded again
+    pub fn find(&self, video_id: Option<&str>, url: &str) -> Option<&HistoryEntry> {
+        let url_id = youtube_video_id(url);
+        let video_id = video_id.or(url_id.as_deref());
+        self.entries.iter().rev().filter(|entry| !entry.deleted && !entry.replaced && !entry.missing).find(|entry| match (video_id, entry.video_id.as_deref()) {
+            (Some(id), Some(entry_id)) => id == entry_id,
+            _ => entry.url == url,
+        })
+    }
+}
+
+// Extracts the video ID from the common YouTube URL shapes without asking yt-dlp
+pub fn youtube_video_id(url: &str) -> Option<String> {
+    let id_regex = Regex::new(
+        r"(?:youtube\.com/(?:watch\?(?:.*&)?v=|shorts/|live/|embed/)|youtu\.be/)([A-Za-z0-9_-]{11})",
+    )
+    .unwrap();
+    id_regex.captures(url).map(|captures| captures[1].to_string())
+}
+
+// Puts the most recently trashed file with this original path back where it was
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+fn restore_from_trash(path: &str) -> Result<()> {
+    let item = trash::os_limited::list()?
+        .into_iter()
+        .filter(|item| item.original_path() == Path::new(path))
+        .max_by_key(|item| item.time_deleted)
+        .ok_or_else(|| anyhow::anyhow!("{} is no longer in the trash", path))?;
+    trash::os_limited::restore_all([item])?;
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+fn restore_from_trash(_path: &str) -> Result<()> {
+    Err(anyhow::anyhow!("Undo is not supported on this platform, restore the file from the Trash instead"))
+}
+
+impl YtMp3App {
+    fn trash_history_file(&mut self, index: usize) {
+        let entry = &self.history.entries[index];
+        if let Err(e) = recycle::move_to_trash(&entry.path) {
+            self.history_notice = Some(format!("Could not move {} to the trash: {}", entry.path, e));
+            return;
+        }
+
+        self.history.entries[index].deleted = true;
+        self.last_trashed = Some(index);
+        self.history_notice = None;
+        if let Err(e) = self.history.save([index]) {
+            self.log_console(format!("WARNING: Could not save history: {}", e));
+        }
+    }
+
+    fn undo_trash(&mut self) {
+        let Some(index) = self.last_trashed.take() else {
+            return;
+        };
+
+        let path = self.history.entries[index].path.clone();
+        if let Err(e) = restore_from_trash(&path) {
+            self.history_notice = Some(format!("Could not restore {}: {}", path, e));
+            return;
+        }
+
+        self.history.entries[index].deleted = false;
+        self.history_notice = None;
+        if let Err(e) = self.history.save([index]) {
+            self.log_console(format!("WARNING: Could not save history: {}", e));
+        }
+    }
+
+    pub(crate) fn show_history_window(&mut self, ctx: &egui::Context) {
+        if !self.show_history {
+            return;
+        }
+
+        let mut open = true;
+        let mut open_location = None;
+        let mut trash_index = None;
+        let mut undo = false;
+        let mut save_tags = None;
+        let mut watch_changed = false;
+
+        egui::Window::new("📚 History")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(550.0)
+            .show(ctx, |ui| {
+                if let Some(index) = self.last_trashed {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("🗑 Moved \"{}\" to the trash", rtl::display(&self.history.entries[index].title)));
+                        if ui.button("↩ Undo").clicked() {
+                            undo = true;
+                        }
+                    });
+                }
+                if let Some(notice) = &self.history_notice {
+                    ui.colored_label(egui::Color32::RED, notice);
+                }
+
+                if self.history.entries.is_empty() {
+                    ui.label("Nothing downloaded yet.");
+                    return;
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("⬆ Upgrade quality...")
+                        .on_hover_text("Download low resolution videos again at a higher quality")
+                        .clicked()
+                    {
+                        self.quality_upgrade.open = true;
+                    }
+                    if ui.button("📊 Insights")
+                        .on_hover_text("Most downloaded channels, storage per format and failures by site")
+                        .clicked()
+                    {
+                        self.open_insights();
+                    }
+                    watch_changed = ui.checkbox(&mut self.settings.watch_history_files, "Follow files renamed or deleted by other programs")
+                        .changed();
+                });
+
+                let mut all_tags: Vec<&String> = Vec::new();
+                for tag in self.history.entries.iter().flat_map(|entry| &entry.tags) {
+                    if !all_tags.iter().any(|known| known.eq_ignore_ascii_case(tag)) {
+                        all_tags.push(tag);
+                    }
+                }
+                if !all_tags.is_empty() {
+                    all_tags.sort_by_key(|tag| tag.to_lowercase());
+                    ui.horizontal(|ui| {
+                        ui.label("🏷 Show:");
+                        egui::ComboBox::from_id_source("history_tag_filter")
+                            .selected_text(self.history_tag_filter.as_deref().unwrap_or("All downloads"))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.history_tag_filter, None, "All downloads");
+                                for tag in all_tags {
+                                    ui.selectable_value(&mut self.history_tag_filter, Some(tag.clone()), tag);
+                                }
+                            });
+                    });
+                }
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("history_grid")
+                        .num_columns(5)
+                        .spacing([10.0, 6.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (index, entry) in self.history.entries.iter().enumerate().rev() {
+                                if let Some(filter) = &self.history_tag_filter {
+                                    if !tags::has_tag(&entry.tags, filter) {
+                                        continue;
+                                    }
+                                }
+                                let title = if entry.deleted || entry.replaced || entry.missing {
+                                    egui::RichText::new(rtl::display(&entry.title)).strikethrough().color(egui::Color32::GRAY)
+                                } else {
+                                    egui::RichText::new(rtl::display(&entry.title))
+                                };
+                                ui.label(title).on_hover_text(&entry.path);
+                                ui.label(entry.format.label());
+                                ui.label(entry.completed_at_text());
+                                match &mut self.history_tag_edit {
+                                    Some((editing, text)) if *editing == index => {
+                                        ui.horizontal(|ui| {
+                                            let response = ui.add(egui::TextEdit::singleline(text)
+                                                .desired_width(140.0)
+                                                .hint_text("tag, tag"));
+                                            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                                            if ui.small_button("✔").accessible_name("Save tags").clicked() || submitted {
+                                                save_tags = Some((index, tags::parse_tags(text)));
+                                            }
+                                        });
+                                    }
+                                    _ => {
+                                        ui.horizontal(|ui| {
+                                            if !entry.tags.is_empty() {
+                                                ui.label(egui::RichText::new(entry.tags.join(", ")).color(egui::Color32::LIGHT_BLUE));
+                                            }
+                                            if ui.small_button("🏷").accessible_name("Edit tags").on_hover_text("Edit tags").clicked() {
+                                                self.history_tag_edit = Some((index, entry.tags.join(", ")));
+                                            }
+                                        });
+                                    }
+                                }
+                                ui.horizontal(|ui| {
+                                    if entry.deleted {
+                                        ui.label(egui::RichText::new("🗑 In trash").color(egui::Color32::GRAY));
+                                    } else if entry.replaced {
+                                        ui.label(egui::RichText::new("⬆ Upgraded").color(egui::Color32::GRAY));
+                                    } else if entry.missing {
+                                        ui.label(egui::RichText::new("❔ File missing").color(egui::Color32::GRAY))
+                                            .on_hover_text("Deleted or moved by another program");
+                                    } else {
+                                        if ui.small_button("📁 Open").clicked() {
+                                            open_location = Some(entry.path.clone());
+                                        }
+                                        if ui.small_button("🗑 Delete file").on_hover_text("Move the file to the trash").clicked() {
+                                            trash_index = Some(index);
+                                        }
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        self.show_history = open;
+
+        if let Some(path) = open_location {
+            self.open_path_location(&path);
+        }
+        if let Some((index, tags)) = save_tags {
+            self.history.entries[index].tags = tags;
+            self.history_tag_edit = None;
+            if let Err(e) = self.history.save([index]) {
+                self.history_notice = Some(format!("Could not save the history: {}", e));
+            }
+        }
+        if let Some(index) = trash_index {
+            self.trash_history_file(index);
+        }
+        if watch_changed {
+            if let Err(e) = self.settings.save() {
+                self.log_console(format!("WARNING: Could not save settings: {}", e));
+            }
+        }
+        if undo {
+            self.undo_trash();
+        }
+    }
+}
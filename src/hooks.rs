@@ -0,0 +1,169 @@
+use anyhow::Result;
+use eframe::egui;
+use std::process::Command;
+use std::time::Duration;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::a11y::AccessibleName;
+use crate::channel::AppSender;
+use crate::settings::Settings;
+use crate::{commands, events, help, output, AppMessage, DownloadFormat, YtMp3App};
+
+// Long enough to mount a network share or ask a slow allowlist service
+const PRE_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+
+// A title or a URL queued over the API can hold anything, so the values never go into the command
+// line: the placeholders become quoted references to the environment variables, which the shell
+// expands only after it has parsed the line. On Windows that takes cmd's delayed expansion, as
+// %VAR% is expanded before parsing
+#[cfg(target_os = "windows")]
+fn variable(name: &str) -> String {
+    format!("\"!{}!\"", name)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn variable(name: &str) -> String {
+    format!("\"${}\"", name)
+}
+
+// The command line with {url}, {title}, {folder} and {format} filled in
+fn expand(template: &str, format: DownloadFormat) -> String {
+    template
+        .replace("{url}", &variable("YTMP3_URL"))
+        .replace("{title}", &variable("YTMP3_TITLE"))
+        .replace("{folder}", &variable("YTMP3_FOLDER"))
+        .replace("{format}", events::format_name(format))
+}
+
+#[cfg(target_os = "windows")]
+fn shell_command(line: &str) -> Command {
+    let mut command = Command::new("cmd");
+    command.args(["/V:ON", "/C"]).raw_arg(line);
+    command.creation_flags(0x08000000);
+    command
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_command(line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(line);
+    command
+}
+
+fn hook_command(line: &str, url: &str, title: &str, folder: &str, format: DownloadFormat) -> Command {
+    let mut command = shell_command(line);
+    command
+        .env("YTMP3_URL", url)
+        .env("YTMP3_TITLE", title)
+        .env("YTMP3_FOLDER", folder)
+        .env("YTMP3_FORMAT", events::format_name(format));
+    command
+}
+
+// Runs the user's pre-download command; an error cancels the download
+pub fn run_pre_download(
+    url: &str,
+    title: &str,
+    folder: &str,
+    format: DownloadFormat,
+    settings: &Settings,
+    progress_sender: &AppSender,
+) -> Result<()> {
+    let template = settings.pre_download_command.trim();
+    if template.is_empty() {
+        return Ok(());
+    }
+
+    progress_sender.send(AppMessage::DownloadProgress(0.0, "Running the pre-download command...".to_string())).ok();
+    let line = expand(template, format);
+    progress_sender.send(AppMessage::ConsoleOutput(format!("Pre-download command: {}", line))).ok();
+
+    let mut command = hook_command(&line, url, title, folder, format);
+    let result = commands::output_with_timeout(&mut command, Some(PRE_DOWNLOAD_TIMEOUT));
+
+    let problem = match result {
+        Ok(Some(result)) => {
+            let text = format!("{}\n{}", output::clean_output(&result.stdout), output::clean_output(&result.stderr));
+            let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+            for line in &lines {
+                progress_sender.send(AppMessage::ConsoleOutput(format!("[pre-download] {}", line))).ok();
+            }
+            if result.status.success() {
+                return Ok(());
+            }
+            let code = result.status.code().map(|code| code.to_string()).unwrap_or_else(|| "killed".to_string());
+            match lines.last() {
+                Some(reason) => format!("exit code {}: {}", code, reason),
+                None => format!("exit code {}", code),
+            }
+        }
+        Ok(None) => format!("no answer after {} minutes", PRE_DOWNLOAD_TIMEOUT.as_secs() / 60),
+        Err(e) => format!("could not run it: {}", e),
+    };
+
+    if settings.pre_download_can_reject {
+        return Err(anyhow::anyhow!("Cancelled by the pre-download command ({})", problem));
+    }
+    progress_sender.send(AppMessage::ConsoleOutput(format!("WARNING: The pre-download command failed ({}), downloading anyway", problem))).ok();
+    Ok(())
+}
+
+impl YtMp3App {
+    pub(crate) fn show_hook_settings(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🪝 Before each download:", "pre_download");
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("Run:");
+                    ui.add(egui::TextEdit::singleline(&mut self.settings.pre_download_command)
+                        .desired_width(350.0)
+                        .hint_text("e.g. check-allowlist {url}"))
+                        .accessible_name("Pre-download command");
+                });
+                ui.label(
+                    egui::RichText::new("{url}, {title}, {folder} and {format} are replaced with the download's details")
+                        .small()
+                        .color(egui::Color32::GRAY),
+                );
+                ui.add_enabled(
+                    !self.settings.pre_download_command.trim().is_empty(),
+                    egui::Checkbox::new(&mut self.settings.pre_download_can_reject, "Cancel the download when the command fails"),
+                );
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOSTILE_TITLE: &str = "a\" & calc & \"b' ; touch pwned ; echo '$(touch pwned) `touch pwned` %PATH% !PATH!";
+
+    #[test]
+    fn values_stay_out_of_the_command_line() {
+        let line = expand("check {url} {title} {folder} {format}", DownloadFormat::Mp3);
+        assert!(!line.contains("calc") && !line.contains("pwned"), "{}", line);
+        assert!(line.ends_with(" mp3"), "{}", line);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hostile_title_reaches_the_command_unchanged() {
+        let folder = std::env::temp_dir().join(format!("ytmp3-hook-test-{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let line = expand("cd {folder} && printf '%s|%s' {title} {url}", DownloadFormat::Mp4);
+        let output = hook_command(&line, "https://example.com/?a=1&b=$(id)", HOSTILE_TITLE, &folder.display().to_string(), DownloadFormat::Mp4)
+            .output()
+            .unwrap();
+        let pwned = folder.join("pwned").exists();
+        std::fs::remove_dir_all(&folder).ok();
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{}|https://example.com/?a=1&b=$(id)", HOSTILE_TITLE));
+        assert!(!pwned);
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+
+use crate::actions::Action;
+use crate::YtMp3App;
+
+pub struct GlobalHotkeys {
+    // Dropping the manager unregisters the hotkeys, so it has to live as long as the app
+    manager: GlobalHotKeyManager,
+    bindings: Vec<(HotKey, Action)>,
+}
+
+impl GlobalHotkeys {
+    pub fn new() -> Result<Self> {
+        let manager = GlobalHotKeyManager::new()
+            .map_err(|e| anyhow::anyhow!("Could not initialize global hotkeys: {}", e))?;
+        Ok(Self { manager, bindings: Vec::new() })
+    }
+
+    pub fn register(&mut self, spec: &str, action: Action) -> Result<()> {
+        let hotkey: HotKey = spec
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid hotkey \"{}\": {}", spec, e))?;
+        self.manager
+            .register(hotkey)
+            .map_err(|e| anyhow::anyhow!("Could not register hotkey \"{}\": {}", spec, e))?;
+        self.bindings.push((hotkey, action));
+        Ok(())
+    }
+
+    // The events come through one process-wide receiver, so all bindings are checked in one pass
+    pub fn pressed(&self) -> Vec<Action> {
+        let mut actions = Vec::new();
+        while let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+            if event.state != HotKeyState::Pressed {
+                continue;
+            }
+            if let Some((_, action)) = self.bindings.iter().find(|(hotkey, _)| hotkey.id() == event.id) {
+                actions.push(*action);
+            }
+        }
+        actions
+    }
+}
+
+impl Drop for GlobalHotkeys {
+    fn drop(&mut self) {
+        for (hotkey, _) in &self.bindings {
+            self.manager.unregister(*hotkey).ok();
+        }
+    }
+}
+
+impl YtMp3App {
+    pub(crate) fn apply_hotkey_settings(&mut self) {
+        // Release the old registrations before grabbing (possibly identical) new ones
+        self.global_hotkeys = None;
+        self.hotkey_error = None;
+
+        // Without a window there's no keyboard to listen to
+        if !self.settings.hotkey_enabled || self.daemon {
+            return;
+        }
+
+        let mut hotkeys = match GlobalHotkeys::new() {
+            Ok(hotkeys) => hotkeys,
+            Err(e) => {
+                self.log_console(format!("WARNING: {}", e));
+                self.hotkey_error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let bindings = [
+            (self.settings.hotkey.clone(), Action::PasteAndDownload),
+            (self.settings.start_queue_hotkey.clone(), Action::StartQueue),
+            (self.settings.pause_queue_hotkey.clone(), Action::PauseQueue),
+        ];
+        let mut errors = Vec::new();
+        for (spec, action) in bindings {
+            if spec.trim().is_empty() {
+                continue;
+            }
+            if let Err(e) = hotkeys.register(spec.trim(), action) {
+                self.log_console(format!("WARNING: {}", e));
+                errors.push(e.to_string());
+            }
+        }
+        if !errors.is_empty() {
+            self.hotkey_error = Some(errors.join("\n"));
+        }
+        self.global_hotkeys = Some(hotkeys);
+    }
+}
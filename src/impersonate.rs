@@ -0,0 +1,155 @@
+use eframe::egui;
+use std::sync::mpsc;
+use std::thread;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::commands;
+use crate::help;
+use crate::settings::Settings;
+use crate::theme;
+use crate::{output, ytdlp_command, YtMp3App};
+
+pub enum ImpersonateSupport {
+    Unknown,
+    Checking(mpsc::Receiver<Result<Vec<String>, String>>),
+    // Targets the installed yt-dlp can actually use (needs curl_cffi)
+    Supported(Vec<String>),
+    Unsupported(String),
+}
+
+// Parses the table printed by --list-impersonate-targets:
+//   Client      OS          Source
+//   ---------------------------------------
+//   Chrome-124  Macos-14    curl_cffi
+//   Edge-101    Windows-10  curl_cffi (unavailable)
+fn parse_targets(listing: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    for line in listing.lines().skip_while(|line| !line.starts_with("---")).skip(1) {
+        if line.contains("unavailable") {
+            continue;
+        }
+        let Some(client) = line.split_whitespace().next() else {
+            continue;
+        };
+        let client = client.to_lowercase();
+        // Offer the browser family too; yt-dlp picks the best matching version for it
+        if let Some((family, _)) = client.split_once('-') {
+            if !targets.iter().any(|target| target == family) {
+                targets.push(family.to_string());
+            }
+        }
+        if !targets.contains(&client) {
+            targets.push(client);
+        }
+    }
+    targets
+}
+
+fn list_targets(settings: Settings, ctx: egui::Context) -> mpsc::Receiver<Result<Vec<String>, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut command = ytdlp_command(&settings);
+        command.arg("--list-impersonate-targets");
+        #[cfg(target_os = "windows")]
+        command.creation_flags(0x08000000);
+
+        let result = match commands::output(&mut command) {
+            Ok(result) if result.status.success() => {
+                let targets = parse_targets(&output::clean_output(&result.stdout));
+                if targets.is_empty() {
+                    Err("yt-dlp was built without curl_cffi, so no impersonation targets are available".to_string())
+                } else {
+                    Ok(targets)
+                }
+            }
+            Ok(_) => Err("This yt-dlp version does not support --impersonate, update it".to_string()),
+            Err(e) => Err(format!("Could not run yt-dlp: {}", e)),
+        };
+        tx.send(result).ok();
+        ctx.request_repaint();
+    });
+    rx
+}
+
+impl YtMp3App {
+    // Lives outside Settings::ui because detecting support runs yt-dlp in the background
+    pub(crate) fn show_impersonate_settings(&mut self, ui: &mut egui::Ui) {
+        if let ImpersonateSupport::Checking(rx) = &self.impersonate_support {
+            match rx.try_recv() {
+                Ok(Ok(targets)) => self.impersonate_support = ImpersonateSupport::Supported(targets),
+                Ok(Err(error)) => self.impersonate_support = ImpersonateSupport::Unsupported(error),
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.impersonate_support = ImpersonateSupport::Unsupported("Check did not finish".to_string());
+                }
+            }
+        }
+        if matches!(self.impersonate_support, ImpersonateSupport::Unknown) {
+            self.impersonate_support = ImpersonateSupport::Checking(list_targets(self.settings.clone(), ui.ctx().clone()));
+        }
+
+        let reduced_motion = self.settings.reduced_motion;
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🎭 Client impersonation (for sites that block yt-dlp's default TLS fingerprint):", "impersonate");
+                ui.add_space(5.0);
+
+                let mut recheck = false;
+                match &self.impersonate_support {
+                    ImpersonateSupport::Unknown | ImpersonateSupport::Checking(_) => {
+                        ui.horizontal(|ui| {
+                            theme::busy_indicator(ui, reduced_motion);
+                            ui.label("Checking what the installed yt-dlp supports...");
+                        });
+                    }
+                    ImpersonateSupport::Supported(targets) => {
+                        ui.horizontal(|ui| {
+                            ui.label("Impersonate:");
+                            let selected = if self.settings.impersonate.is_empty() {
+                                "Off".to_string()
+                            } else {
+                                self.settings.impersonate.clone()
+                            };
+                            egui::ComboBox::from_id_source("impersonate_target")
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.settings.impersonate, String::new(), "Off");
+                                    for target in targets {
+                                        ui.selectable_value(&mut self.settings.impersonate, target.clone(), target);
+                                    }
+                                });
+                        });
+                        if !self.settings.impersonate.is_empty() && !targets.contains(&self.settings.impersonate) {
+                            ui.colored_label(egui::Color32::YELLOW, format!(
+                                "⚠ \"{}\" is not available in the installed yt-dlp",
+                                self.settings.impersonate
+                            ));
+                        }
+                    }
+                    ImpersonateSupport::Unsupported(reason) => {
+                        ui.colored_label(egui::Color32::GRAY, reason);
+                        if !self.settings.impersonate.is_empty() {
+                            ui.horizontal(|ui| {
+                                ui.colored_label(egui::Color32::YELLOW, format!(
+                                    "⚠ Impersonating \"{}\" is on but won't work",
+                                    self.settings.impersonate
+                                ));
+                                if ui.button("Turn off").clicked() {
+                                    self.settings.impersonate.clear();
+                                }
+                            });
+                        }
+                        if ui.button("🔄 Check again").clicked() {
+                            recheck = true;
+                        }
+                    }
+                }
+                if recheck {
+                    self.impersonate_support = ImpersonateSupport::Unknown;
+                }
+            });
+        });
+    }
+}
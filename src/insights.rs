@@ -0,0 +1,254 @@
+use anyhow::Result;
+use eframe::egui;
+use rusqlite::params;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::events::format_name;
+use crate::history::HistoryEntry;
+use crate::{database, help, locale, theme, DownloadFormat, YtMp3App};
+
+// Rows in the channel list
+const TOP_CHANNELS: usize = 10;
+// A site gets a hint once this many downloads from it were tried and this share of them failed
+const HINT_MIN_ATTEMPTS: usize = 5;
+const HINT_FAILURE_RATE: f64 = 0.2;
+
+// Called when a download finishes either way and when a fetch fails; the failure rates are worked
+// out from these rows. They stay in the profile's database like the history and are never sent anywhere
+pub fn record_outcome(url: &str, extractor: &str, format: DownloadFormat, succeeded: bool) -> Result<()> {
+    database::open()?.execute(
+        "INSERT INTO download_outcomes (url, extractor, format, succeeded, finished_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![url, extractor, format_name(format), succeeded, chrono::Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+// yt-dlp's name for the site a video came from, out of the fetched video's fields
+pub fn extractor_name(template_fields: &[(String, String)]) -> String {
+    let field = |name: &str| template_fields.iter().find(|(field, _)| field == name).map(|(_, value)| value.clone());
+    field("extractor_key").or_else(|| field("extractor")).unwrap_or_else(|| "Unknown".to_string())
+}
+
+// For a fetch that failed before yt-dlp reported its extractor: its name for YouTube, which is
+// what extractor_name gives for YouTube videos, and the site's host for anything else
+pub fn extractor_from_url(url: &str) -> String {
+    let host = url::Url::parse(url.trim())
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.trim_start_matches("www.").trim_start_matches("m.").to_string()));
+    match host {
+        Some(host) if host == "youtu.be" || host == "youtube.com" || host.ends_with(".youtube.com") => "Youtube".to_string(),
+        Some(host) => host,
+        None => "Unknown".to_string(),
+    }
+}
+
+struct ChannelCount {
+    uploader: String,
+    downloads: usize,
+    // Unix timestamp of the newest download
+    last: i64,
+}
+
+struct FormatStorage {
+    format: DownloadFormat,
+    files: usize,
+    bytes: u64,
+}
+
+struct ExtractorRate {
+    extractor: String,
+    attempts: usize,
+    failures: usize,
+}
+
+impl ExtractorRate {
+    fn failure_rate(&self) -> f64 {
+        self.failures as f64 / self.attempts.max(1) as f64
+    }
+}
+
+pub struct Report {
+    channels: Vec<ChannelCount>,
+    storage: Vec<FormatStorage>,
+    extractors: Vec<ExtractorRate>,
+    // When the oldest download outcome was recorded
+    outcomes_since: Option<i64>,
+}
+
+// Reads the file sizes, so it runs off the UI thread
+fn compute(entries: Vec<HistoryEntry>) -> Result<Report> {
+    // Upgraded downloads are counted once, by their new entry
+    let counted: Vec<&HistoryEntry> = entries.iter().filter(|entry| !entry.replaced).collect();
+
+    let mut channels: HashMap<&str, ChannelCount> = HashMap::new();
+    for entry in &counted {
+        let key = entry.channel_url.as_deref().unwrap_or(&entry.uploader);
+        let channel = channels.entry(key).or_insert_with(|| ChannelCount { uploader: entry.uploader.clone(), downloads: 0, last: 0 });
+        channel.downloads += 1;
+        channel.last = channel.last.max(entry.completed_at);
+    }
+    let mut channels: Vec<ChannelCount> = channels.into_values().collect();
+    channels.sort_by(|a, b| b.downloads.cmp(&a.downloads).then(b.last.cmp(&a.last)));
+    channels.truncate(TOP_CHANNELS);
+
+    let mut storage: Vec<FormatStorage> = [DownloadFormat::Mp3, DownloadFormat::Mp4]
+        .into_iter()
+        .map(|format| FormatStorage { format, files: 0, bytes: 0 })
+        .collect();
+    for entry in counted.iter().filter(|entry| !entry.deleted && !entry.missing) {
+        let Ok(metadata) = std::fs::metadata(&entry.path) else {
+            continue;
+        };
+        if let Some(format) = storage.iter_mut().find(|storage| storage.format == entry.format) {
+            format.files += 1;
+            format.bytes += metadata.len();
+        }
+    }
+
+    let connection = database::open()?;
+    let mut select = connection.prepare(
+        "SELECT extractor, COUNT(*), SUM(CASE WHEN succeeded THEN 0 ELSE 1 END) FROM download_outcomes \
+         GROUP BY extractor ORDER BY COUNT(*) DESC",
+    )?;
+    let extractors = select
+        .query_map([], |row| Ok(ExtractorRate { extractor: row.get(0)?, attempts: row.get(1)?, failures: row.get(2)? }))?
+        .collect::<Result<Vec<_>, _>>()?;
+    let outcomes_since = connection.query_row("SELECT MIN(finished_at) FROM download_outcomes", [], |row| row.get(0))?;
+
+    Ok(Report { channels, storage, extractors, outcomes_since })
+}
+
+#[derive(Default)]
+pub struct InsightsView {
+    pub open: bool,
+    report: Option<Report>,
+    computing: Option<mpsc::Receiver<Result<Report, String>>>,
+    error: Option<String>,
+}
+
+impl YtMp3App {
+    pub(crate) fn open_insights(&mut self) {
+        self.insights.open = true;
+        if self.insights.report.is_none() && self.insights.computing.is_none() {
+            self.refresh_insights();
+        }
+    }
+
+    fn refresh_insights(&mut self) {
+        let entries = self.history.entries.clone();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            tx.send(compute(entries).map_err(|e| e.to_string())).ok();
+        });
+        self.insights.computing = Some(rx);
+        self.insights.error = None;
+    }
+
+    pub(crate) fn show_insights_window(&mut self, ctx: &egui::Context) {
+        if !self.insights.open {
+            return;
+        }
+        if let Some(result) = self.insights.computing.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.insights.computing = None;
+            match result {
+                Ok(report) => self.insights.report = Some(report),
+                Err(e) => self.insights.error = Some(e),
+            }
+        }
+
+        let mut open = true;
+        let mut refresh = false;
+        egui::Window::new("📊 Insights")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Worked out on this computer from your downloads; nothing is sent anywhere.")
+                        .small()
+                        .color(egui::Color32::GRAY));
+                    help::help_button(ui, "insights");
+                });
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.insights.computing.is_none(), egui::Button::new("🔄 Refresh")).clicked() {
+                        refresh = true;
+                    }
+                    if self.insights.computing.is_some() {
+                        theme::busy_indicator(ui, self.settings.reduced_motion);
+                        ctx.request_repaint_after(std::time::Duration::from_millis(200));
+                    }
+                });
+                if let Some(error) = &self.insights.error {
+                    ui.colored_label(egui::Color32::RED, format!("Could not work out the insights: {}", error));
+                }
+                let Some(report) = &self.insights.report else {
+                    return;
+                };
+
+                ui.separator();
+                ui.strong("Most downloaded channels");
+                if report.channels.is_empty() {
+                    ui.label(egui::RichText::new("Nothing downloaded yet.").color(egui::Color32::GRAY));
+                } else {
+                    egui::Grid::new("insights_channels").striped(true).show(ui, |ui| {
+                        for channel in &report.channels {
+                            ui.label(&channel.uploader);
+                            ui.label(locale::number(channel.downloads as u64));
+                            ui.label(egui::RichText::new(format!("last {}", locale::timestamp(channel.last).unwrap_or_default()))
+                                .small()
+                                .color(egui::Color32::GRAY));
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                ui.separator();
+                ui.strong("Storage used");
+                egui::Grid::new("insights_storage").striped(true).show(ui, |ui| {
+                    for storage in &report.storage {
+                        ui.label(if storage.format == DownloadFormat::Mp3 { "🎵 MP3" } else { "🎥 MP4" });
+                        ui.label(locale::bytes(storage.bytes as f64));
+                        let average = if storage.files > 0 { storage.bytes as f64 / storage.files as f64 } else { 0.0 };
+                        ui.label(egui::RichText::new(format!("{} file(s), {} each on average", locale::number(storage.files as u64), locale::bytes(average)))
+                            .small()
+                            .color(egui::Color32::GRAY));
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                ui.strong("Failures by site");
+                if report.extractors.is_empty() {
+                    ui.label(egui::RichText::new("No downloads have finished since this was added.").color(egui::Color32::GRAY));
+                    return;
+                }
+                egui::Grid::new("insights_extractors").striped(true).show(ui, |ui| {
+                    for rate in &report.extractors {
+                        ui.label(&rate.extractor);
+                        ui.label(format!("{} of {} failed", locale::number(rate.failures as u64), locale::number(rate.attempts as u64)));
+                        let percent = format!("{}%", locale::decimal(rate.failure_rate() * 100.0, 0));
+                        if rate.attempts >= HINT_MIN_ATTEMPTS && rate.failure_rate() >= HINT_FAILURE_RATE {
+                            ui.colored_label(egui::Color32::YELLOW, percent)
+                                .on_hover_text("Many of these fail. The Command History shows the errors; cookies or the \
+                                                mirror fallback in Settings may help");
+                        } else {
+                            ui.label(percent);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(since) = report.outcomes_since.and_then(locale::timestamp) {
+                    ui.label(egui::RichText::new(format!("Since {}", since)).small().color(egui::Color32::GRAY));
+                }
+            });
+
+        if refresh {
+            self.refresh_insights();
+        }
+        if !open {
+            self.insights.open = false;
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use eframe::egui;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// Fixed loopback port used to hand URLs from a second launch to the running window
+const INSTANCE_PORT: u16 = 38517;
+const HANDSHAKE: &str = "ytmp3-instance-v1";
+
+fn instance_addr() -> SocketAddr {
+    SocketAddr::from((Ipv4Addr::LOCALHOST, INSTANCE_PORT))
+}
+
+pub fn forward_to_running_instance(urls: &[String]) -> bool {
+    let Ok(mut stream) = TcpStream::connect_timeout(&instance_addr(), Duration::from_millis(500)) else {
+        return false;
+    };
+
+    let mut message = format!("{}\n", HANDSHAKE);
+    for url in urls {
+        message.push_str(url);
+        message.push('\n');
+    }
+    stream.write_all(message.as_bytes()).is_ok()
+}
+
+pub fn start_listener(ctx: egui::Context) -> Option<mpsc::Receiver<Vec<String>>> {
+    let listener = match TcpListener::bind(instance_addr()) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::warn!("Could not listen for other instances: {}", e);
+            return None;
+        }
+    };
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            stream.set_read_timeout(Some(Duration::from_secs(2))).ok();
+            let mut lines = BufReader::new(stream).lines().map_while(Result::ok);
+            if lines.next().as_deref() != Some(HANDSHAKE) {
+                continue;
+            }
+
+            let urls: Vec<String> = lines
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect();
+            if tx.send(urls).is_err() {
+                break;
+            }
+            ctx.request_repaint();
+        }
+    });
+
+    Some(rx)
+}
@@ -0,0 +1,330 @@
+use eframe::egui;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::a11y::AccessibleName;
+use crate::channel::AppReceiver;
+use crate::output::is_progress_line;
+use crate::rtl;
+use crate::settings::Settings;
+use crate::upgrade::UpgradePlan;
+use crate::ytdlp_config;
+use crate::{format_duration, locale, AppState, DownloadFormat, VideoInfo, YtMp3App};
+
+pub type JobId = u64;
+
+#[derive(Debug, Clone)]
+pub enum JobStatus {
+    Running { progress: f32, status: String },
+    Completed(String),
+    Failed(String),
+    // Gave up after making no progress for the stall timeout
+    Stalled(String),
+    NeedsRedownload { path: String, reason: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: JobId,
+    pub url: String,
+    pub video_info: VideoInfo,
+    pub format: DownloadFormat,
+    pub output_path: String,
+    pub cookie_profile: Option<String>,
+    pub proxy: Option<String>,
+    pub tags: Vec<String>,
+    // Set when the job re-downloads a History entry at a higher quality
+    pub upgrade: Option<UpgradePlan>,
+    // The yt-dlp arguments the download runs with, and the settings it was started with so a
+    // retry downloads the same way
+    pub args: Vec<String>,
+    pub settings: Settings,
+    pub started: Instant,
+    pub finished: Option<Instant>,
+    pub status: JobStatus,
+    pub log: Vec<String>,
+}
+
+// A download whose thread is still running. Each has its own channel, so the progress lines of
+// downloads running side by side don't replace each other
+pub struct RunningDownload {
+    pub job_id: JobId,
+    pub receiver: AppReceiver,
+    // Set to pause the download, cleared to resume it
+    pub paused: Arc<AtomicBool>,
+}
+
+impl RunningDownload {
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+}
+
+// Per-job console logs are kept longer than the global console
+const MAX_JOB_LOG_LINES: usize = 500;
+
+impl Job {
+    pub fn push_log(&mut self, line: String) {
+        if is_progress_line(&line) {
+            if let Some(last) = self.log.last_mut().filter(|last| is_progress_line(last)) {
+                *last = line;
+                return;
+            }
+        }
+
+        self.log.push(line);
+        if self.log.len() > MAX_JOB_LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+
+    pub fn elapsed_seconds(&self) -> f64 {
+        let end = self.finished.unwrap_or_else(Instant::now);
+        end.duration_since(self.started).as_secs_f64()
+    }
+
+    fn status_text(&self) -> String {
+        match &self.status {
+            JobStatus::Running { progress, .. } => format!("⏬ {:.0}%", progress * 100.0),
+            JobStatus::Completed(_) => "✅ Done".to_string(),
+            JobStatus::Failed(_) => "❌ Failed".to_string(),
+            JobStatus::Stalled(_) => "⏸ Stalled".to_string(),
+            JobStatus::NeedsRedownload { .. } => "⚠ Needs re-download".to_string(),
+        }
+    }
+}
+
+pub fn find_job_mut(jobs: &mut [Job], id: Option<JobId>) -> Option<&mut Job> {
+    let id = id?;
+    jobs.iter_mut().find(|job| job.id == id)
+}
+
+impl YtMp3App {
+    // Starts a failed job again with the arguments it ran with, not whatever is selected now
+    pub(crate) fn retry_job(&mut self, id: JobId) {
+        let Some(job) = self.jobs.iter().find(|job| job.id == id) else {
+            return;
+        };
+        let (url, video_info, format, output_path) = (job.url.clone(), job.video_info.clone(), job.format, job.output_path.clone());
+        let (cookie_profile, tags, upgrade, settings) = (job.cookie_profile.clone(), job.tags.clone(), job.upgrade.clone(), job.settings.clone());
+        self.spawn_download(url, video_info, format, output_path, cookie_profile, tags, upgrade, settings);
+    }
+
+    pub(crate) fn show_job_list(&mut self, ui: &mut egui::Ui) {
+        if self.jobs.is_empty() {
+            return;
+        }
+
+        ui.add_space(10.0);
+        egui::CollapsingHeader::new(format!("📋 Downloads this session ({})", self.jobs.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_source("job_list_scroll")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for job in self.jobs.iter().rev() {
+                            let selected = self.selected_job == Some(job.id);
+                            let text = format!("{}  {}  ({})", job.status_text(), rtl::display(&job.video_info.title), job.format.label());
+                            if ui.selectable_label(selected, text).clicked() {
+                                self.selected_job = if selected { None } else { Some(job.id) };
+                            }
+                        }
+                    });
+            });
+    }
+
+    pub(crate) fn show_job_drawer(&mut self, ctx: &egui::Context) {
+        let Some(job) = self.selected_job.and_then(|id| self.jobs.iter().find(|job| job.id == id)) else {
+            return;
+        };
+        let running = self.downloads.iter().find(|download| download.job_id == job.id);
+
+        let mut close = false;
+        let mut remove = false;
+        let mut retry = false;
+        let mut redownload = None;
+        let mut open_path = None;
+
+        egui::SidePanel::right("job_detail_drawer")
+            .resizable(true)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    ui.heading("🔎 Download Details");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("✖").accessible_name("Close").clicked() {
+                            close = true;
+                        }
+                    });
+                });
+                ui.add_space(10.0);
+
+                egui::Grid::new("job_detail_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        ui.label("🎬 Title:");
+                        rtl::title_label(ui, &job.video_info.title);
+                        ui.end_row();
+
+                        ui.label("👤 Uploader:");
+                        ui.label(&job.video_info.uploader);
+                        ui.end_row();
+
+                        ui.label("⏱️ Duration:");
+                        ui.label(&job.video_info.duration);
+                        ui.end_row();
+
+                        if let Some(views) = job.video_info.view_count {
+                            ui.label("👁️ Views:");
+                            ui.label(locale::number(views));
+                            ui.end_row();
+                        }
+
+                        ui.label("🔗 URL:");
+                        ui.label(&job.url);
+                        ui.end_row();
+
+                        ui.label("🎯 Format:");
+                        ui.label(job.format.label());
+                        ui.end_row();
+
+                        ui.label("⚙ Arguments:");
+                        ui.label(egui::RichText::new(ytdlp_config::join_args(&job.args)).monospace());
+                        ui.end_row();
+
+                        ui.label("📁 Output:");
+                        ui.label(&job.output_path);
+                        ui.end_row();
+
+                        if let Some(profile) = &job.cookie_profile {
+                            ui.label("👥 Account:");
+                            ui.label(profile);
+                            ui.end_row();
+                        }
+
+                        if let Some(proxy) = &job.proxy {
+                            ui.label("🌐 Proxy:");
+                            ui.label(proxy);
+                            ui.end_row();
+                        }
+
+                        if !job.tags.is_empty() {
+                            ui.label("🏷 Tags:");
+                            ui.label(job.tags.join(", "));
+                            ui.end_row();
+                        }
+
+                        ui.label("⏲ Time:");
+                        let verb = if job.finished.is_some() { "Took" } else { "Running for" };
+                        ui.label(format!("{} {}", verb, format_duration(job.elapsed_seconds())));
+                        ui.end_row();
+
+                        ui.label("📊 Status:");
+                        match &job.status {
+                            JobStatus::Running { status, .. } => {
+                                ui.label(status);
+                            }
+                            JobStatus::Completed(path) => {
+                                ui.colored_label(egui::Color32::GREEN, format!("Saved to: {}", path));
+                            }
+                            JobStatus::Failed(error) => {
+                                ui.colored_label(egui::Color32::RED, error);
+                            }
+                            JobStatus::Stalled(error) => {
+                                ui.colored_label(egui::Color32::YELLOW, error);
+                            }
+                            JobStatus::NeedsRedownload { path, reason } => {
+                                ui.colored_label(
+                                    egui::Color32::from_rgb(255, 165, 0),
+                                    format!("Needs re-download: {}\nSaved to: {}", reason, path),
+                                );
+                            }
+                        }
+                        ui.end_row();
+                    });
+
+                if let JobStatus::Running { progress, .. } = &job.status {
+                    ui.add_space(5.0);
+                    ui.add(egui::ProgressBar::new(*progress).show_percentage());
+                }
+
+                ui.add_space(10.0);
+                ui.horizontal_wrapped(|ui| {
+                    if let Some(download) = running {
+                        if download.is_paused() {
+                            if ui.button("▶ Resume").clicked() {
+                                download.set_paused(false);
+                            }
+                        } else if ui.button("⏸ Pause").clicked() {
+                            download.set_paused(true);
+                        }
+                    }
+                    if let JobStatus::Completed(path) | JobStatus::NeedsRedownload { path, .. } = &job.status {
+                        if ui.button("📁 Open File Location").clicked() {
+                            open_path = Some(path.clone());
+                        }
+                    }
+                    if let JobStatus::Failed(_) | JobStatus::Stalled(_) = &job.status {
+                        if ui.button("🔄 Retry").clicked() {
+                            retry = true;
+                        }
+                    }
+                    if let JobStatus::NeedsRedownload { path, .. } = &job.status {
+                        if ui.button("🔄 Re-download").on_hover_text("Delete this file and download the video again").clicked() {
+                            redownload = Some((job.url.clone(), path.clone()));
+                        }
+                    }
+                    if ui.button("📋 Copy URL").clicked() {
+                        ui.ctx().copy_text(job.url.clone());
+                    }
+                    if !matches!(job.status, JobStatus::Running { .. }) && ui.button("🗑 Remove").clicked() {
+                        remove = true;
+                    }
+                });
+
+                ui.add_space(10.0);
+                ui.label("📺 Console Output:");
+                ui.add_space(5.0);
+                egui::ScrollArea::vertical()
+                    .id_source("job_detail_console")
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in &job.log {
+                            ui.label(egui::RichText::new(line)
+                                .font(egui::FontId::monospace(12.0))
+                                .color(egui::Color32::LIGHT_GRAY));
+                        }
+                    });
+            });
+
+        let id = job.id;
+        if let Some(path) = open_path {
+            self.open_path_location(&path);
+        }
+        if retry {
+            // Only one download is shown at a time, so don't take over the main view while it's busy
+            if !matches!(self.state, AppState::Loading | AppState::Downloading { .. }) {
+                self.retry_job(id);
+            }
+        }
+        if let Some((url, path)) = redownload {
+            if !matches!(self.state, AppState::Loading | AppState::Downloading { .. }) {
+                self.redownload(url, &path);
+            }
+        }
+        if remove {
+            self.jobs.retain(|job| job.id != id);
+        }
+        if close || remove {
+            self.selected_job = None;
+        }
+    }
+}
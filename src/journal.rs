@@ -0,0 +1,147 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::commands;
+use crate::settings::{get_data_dir, Settings};
+use crate::DownloadFormat;
+
+// A line per finished download in a journal file, for people building datasets or keeping
+// a record of what was downloaded, when and how
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum JournalFormat {
+    Off,
+    // One JSON object per line (.jsonl)
+    JsonLines,
+    Csv,
+}
+
+impl JournalFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            JournalFormat::Off | JournalFormat::JsonLines => "jsonl",
+            JournalFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalRecord {
+    pub url: String,
+    pub video_id: Option<String>,
+    pub title: String,
+    pub format: String,
+    pub path: String,
+    // The yt-dlp command that produced the file
+    pub command: String,
+    // RFC 3339, local time
+    pub started_at: String,
+    pub finished_at: String,
+    // The whole job, including conversions and checks
+    pub total_secs: f64,
+    // yt-dlp's run alone
+    pub download_secs: Option<f64>,
+    pub average_bytes_per_sec: Option<f64>,
+    pub size: u64,
+    pub sha256: Option<String>,
+}
+
+const CSV_HEADER: &str = "url,video_id,title,format,path,command,started_at,finished_at,total_secs,download_secs,average_bytes_per_sec,size,sha256";
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl JournalRecord {
+    // Details that only the finished file and the command log know
+    pub fn new(
+        url: &str,
+        video_id: Option<String>,
+        title: &str,
+        format: DownloadFormat,
+        path: &str,
+        started_at: chrono::DateTime<chrono::Local>,
+        sha256: Option<String>,
+    ) -> Self {
+        let finished_at = chrono::Local::now();
+        let size = std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+        let command = commands::entries()
+            .into_iter()
+            .rev()
+            .find(|record| record.args.iter().any(|arg| arg == url));
+        let download_secs = command.as_ref().and_then(|record| record.duration).map(|duration| duration.as_secs_f64());
+        Self {
+            url: url.to_string(),
+            video_id,
+            title: title.to_string(),
+            format: match format {
+                DownloadFormat::Mp4 => "mp4",
+                DownloadFormat::Mp3 => "mp3",
+            }
+            .to_string(),
+            path: path.to_string(),
+            command: command.map(|record| record.command_line()).unwrap_or_default(),
+            started_at: started_at.to_rfc3339(),
+            finished_at: finished_at.to_rfc3339(),
+            total_secs: (finished_at - started_at).num_milliseconds() as f64 / 1000.0,
+            download_secs,
+            average_bytes_per_sec: download_secs.filter(|secs| *secs > 0.0).map(|secs| size as f64 / secs),
+            size,
+            sha256,
+        }
+    }
+
+    fn csv_line(&self) -> String {
+        let optional = |value: Option<f64>| value.map(|value| format!("{:.3}", value)).unwrap_or_default();
+        [
+            csv_field(&self.url),
+            csv_field(self.video_id.as_deref().unwrap_or_default()),
+            csv_field(&self.title),
+            self.format.clone(),
+            csv_field(&self.path),
+            csv_field(&self.command),
+            self.started_at.clone(),
+            self.finished_at.clone(),
+            format!("{:.3}", self.total_secs),
+            optional(self.download_secs),
+            optional(self.average_bytes_per_sec),
+            self.size.to_string(),
+            self.sha256.clone().unwrap_or_default(),
+        ]
+        .join(",")
+    }
+}
+
+// The journal file; an empty setting means download-journal.jsonl/.csv in the app's data folder
+pub fn journal_path(settings: &Settings) -> Result<PathBuf> {
+    if !settings.journal_path.trim().is_empty() {
+        return Ok(PathBuf::from(settings.journal_path.trim()));
+    }
+    Ok(get_data_dir()?.join(format!("download-journal.{}", settings.journal_format.extension())))
+}
+
+// Appends the record, starting a new CSV file with its header row
+pub fn append(record: &JournalRecord, settings: &Settings) -> Result<PathBuf> {
+    let path = journal_path(settings)?;
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let is_new = !Path::new(&path).exists();
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    match settings.journal_format {
+        JournalFormat::Off => {}
+        JournalFormat::JsonLines => writeln!(file, "{}", serde_json::to_string(record)?)?,
+        JournalFormat::Csv => {
+            if is_new {
+                writeln!(file, "{}", CSV_HEADER)?;
+            }
+            writeln!(file, "{}", record.csv_line())?;
+        }
+    }
+    Ok(path)
+}
@@ -0,0 +1,159 @@
+use eframe::egui;
+
+use crate::{AppState, AutoDownload, YtMp3App};
+
+// Launching with this shows the full window even with operator mode on, for whoever
+// maintains the machine
+pub const ADMIN_ARG: &str = "--admin";
+
+impl YtMp3App {
+    // Operator mode: a window that only takes URLs and shows the queue, for shared machines
+    // where the archival settings must stay as they are. Unlocking the settings with the
+    // password shows the full window until the settings are closed
+    pub(crate) fn is_kiosk(&self) -> bool {
+        self.settings.kiosk_mode && !self.kiosk_admin && !self.settings_lock.unlocked
+    }
+
+    // Every URL is downloaded, so nothing waits on a button only the full window has; the
+    // same goes without a window at all
+    pub(crate) fn run_kiosk_queue(&mut self) {
+        if !self.is_kiosk() && !self.daemon {
+            return;
+        }
+
+        let format = self.settings.simple_format;
+        let output_path = self.simple_folder();
+        while let Some(url) = self.pending_urls.pop_front() {
+            self.download_queue.push_back(AutoDownload { url, format, output_path: output_path.clone() });
+        }
+
+        // Screens that wait for a decision would hold up the rest of the queue
+        let blocked = match &self.state {
+            AppState::AgeRestricted(error) => Some(format!("Age-restricted, needs cookies: {}", error)),
+            AppState::NeedsRedownload { reason, .. } => Some(format!("The file looks broken: {}", reason)),
+            AppState::VideoInfo(_) if !self.auto_download && self.receiver.is_none() => Some(String::new()),
+            _ => None,
+        };
+        if let Some(error) = blocked {
+            self.auto_download = false;
+            self.state = if error.is_empty() { AppState::Input } else { AppState::Error(error) };
+            self.url_input.clear();
+        }
+    }
+
+    // The URL box of the main view belongs to the download that's running, so operators type
+    // into their own
+    fn kiosk_add_url(&mut self) {
+        let url = self.kiosk_input.trim().to_string();
+        if !self.is_valid_youtube_url(&url) {
+            self.kiosk_notice = Some(format!("Not a supported URL: {}", url));
+            return;
+        }
+        self.kiosk_notice = Some(format!("Added {}", url));
+        self.kiosk_input.clear();
+        self.download_queue.push_back(AutoDownload {
+            url,
+            format: self.settings.simple_format,
+            output_path: self.simple_folder(),
+        });
+    }
+
+    pub(crate) fn show_kiosk_ui(&mut self, ctx: &egui::Context) {
+        self.show_unlock_window(ctx);
+        self.show_transfer_footer(ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.heading("🎬 YouTube MP3/MP4 Downloader");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    // Without a password only a launch with --admin gets to the settings
+                    if !self.settings.settings_password_hash.is_empty()
+                        && ui.button("🔑").on_hover_text("Administrator: unlock the settings").clicked()
+                    {
+                        self.settings_lock.show_prompt = true;
+                    }
+                });
+            });
+            self.show_announcement(ui);
+            self.show_offline_banner(ui);
+            self.show_metered_banner(ui);
+            ui.add_space(10.0);
+
+            let mut add = false;
+            ui.group(|ui| {
+                ui.vertical(|ui| {
+                    let url_label = ui.label("📎 YouTube URL:");
+                    ui.add_space(5.0);
+                    ui.horizontal(|ui| {
+                        let response = ui.add(egui::TextEdit::singleline(&mut self.kiosk_input)
+                            .desired_width(400.0)
+                            .hint_text("Paste a video or playlist URL and press Enter..."))
+                            .labelled_by(url_label.id);
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            add = true;
+                        }
+                        if ui.add_enabled(!self.kiosk_input.trim().is_empty(), egui::Button::new("➕ Add to queue")).clicked() {
+                            add = true;
+                        }
+                    });
+                    if let Some(notice) = &self.kiosk_notice {
+                        ui.label(egui::RichText::new(notice).color(egui::Color32::GRAY));
+                    }
+                });
+            });
+            if add {
+                self.kiosk_add_url();
+            }
+
+            ui.add_space(10.0);
+            self.show_simple_destination(ui);
+            ui.add_space(10.0);
+
+            ui.group(|ui| {
+                ui.vertical(|ui| {
+                    ui.label("📋 Queue:");
+                    ui.add_space(5.0);
+                    match &self.state {
+                        AppState::Loading => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label(format!("Fetching {}", self.url_input));
+                            });
+                        }
+                        AppState::Downloading { progress, status, .. } => {
+                            ui.label(status);
+                            ui.add(egui::ProgressBar::new(*progress).show_percentage());
+                        }
+                        AppState::Error(error) => {
+                            ui.colored_label(egui::Color32::RED, format!("Last download failed: {}", error));
+                        }
+                        _ if self.download_queue.is_empty() && self.playlist_fetch.is_none() => {
+                            ui.label("Idle — waiting for URLs");
+                        }
+                        _ => {}
+                    }
+
+                    self.show_playlist_fetch(ui);
+                    if self.queue_paused {
+                        ui.colored_label(egui::Color32::YELLOW, "⏸ The queue is paused");
+                    }
+                    if !self.download_queue.is_empty() {
+                        ui.label(format!("⏳ {} waiting:", self.download_queue.len()));
+                        egui::ScrollArea::vertical()
+                            .id_source("kiosk_queue")
+                            .max_height(150.0)
+                            .show(ui, |ui| {
+                                for download in self.download_queue.iter() {
+                                    ui.label(&download.url);
+                                }
+                            });
+                    }
+                });
+            });
+
+            ui.add_space(10.0);
+            self.show_job_list(ui);
+        });
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{DateTime, Local, TimeZone};
+use std::sync::{OnceLock, RwLock};
+
+// How numbers, sizes, speeds and dates are written. Follows the system's region unless a
+// locale is picked in Settings; the choice is process-wide so any window can format text
+// without carrying the settings around
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Conventions {
+    group_separator: &'static str,
+    decimal_separator: char,
+    date: &'static str,
+    time: &'static str,
+}
+
+const ENGLISH_US: Conventions = Conventions {
+    group_separator: ",",
+    decimal_separator: '.',
+    date: "%m/%d/%Y",
+    time: "%-I:%M %p",
+};
+
+// (locale tag, name shown in Settings, conventions)
+const LOCALES: &[(&str, &str, Conventions)] = &[
+    ("en-US", "English (United States)", ENGLISH_US),
+    ("en-GB", "English (United Kingdom)", Conventions { group_separator: ",", decimal_separator: '.', date: "%d/%m/%Y", time: "%H:%M" }),
+    ("en-IN", "English (India)", Conventions { group_separator: ",", decimal_separator: '.', date: "%d/%m/%Y", time: "%-I:%M %p" }),
+    ("de-DE", "Deutsch", Conventions { group_separator: ".", decimal_separator: ',', date: "%d.%m.%Y", time: "%H:%M" }),
+    ("de-CH", "Deutsch (Schweiz)", Conventions { group_separator: "’", decimal_separator: '.', date: "%d.%m.%Y", time: "%H:%M" }),
+    ("fr-FR", "Français", Conventions { group_separator: "\u{202f}", decimal_separator: ',', date: "%d/%m/%Y", time: "%H:%M" }),
+    ("es-ES", "Español", Conventions { group_separator: ".", decimal_separator: ',', date: "%d/%m/%Y", time: "%H:%M" }),
+    ("it-IT", "Italiano", Conventions { group_separator: ".", decimal_separator: ',', date: "%d/%m/%Y", time: "%H:%M" }),
+    ("nl-NL", "Nederlands", Conventions { group_separator: ".", decimal_separator: ',', date: "%d-%m-%Y", time: "%H:%M" }),
+    ("pt-BR", "Português (Brasil)", Conventions { group_separator: ".", decimal_separator: ',', date: "%d/%m/%Y", time: "%H:%M" }),
+    ("pl-PL", "Polski", Conventions { group_separator: "\u{a0}", decimal_separator: ',', date: "%d.%m.%Y", time: "%H:%M" }),
+    ("sv-SE", "Svenska", Conventions { group_separator: "\u{a0}", decimal_separator: ',', date: "%Y-%m-%d", time: "%H:%M" }),
+    ("ru-RU", "Русский", Conventions { group_separator: "\u{a0}", decimal_separator: ',', date: "%d.%m.%Y", time: "%H:%M" }),
+    ("ja-JP", "日本語", Conventions { group_separator: ",", decimal_separator: '.', date: "%Y/%m/%d", time: "%H:%M" }),
+    ("zh-CN", "中文", Conventions { group_separator: ",", decimal_separator: '.', date: "%Y/%m/%d", time: "%H:%M" }),
+    ("ko-KR", "한국어", Conventions { group_separator: ",", decimal_separator: '.', date: "%Y. %m. %d.", time: "%H:%M" }),
+];
+
+// Locales offered in Settings as (tag, name), besides following the system
+pub fn choices() -> impl Iterator<Item = (&'static str, &'static str)> {
+    LOCALES.iter().map(|(tag, name, _)| (*tag, *name))
+}
+
+pub fn name(tag: &str) -> &'static str {
+    LOCALES
+        .iter()
+        .find(|(known, _, _)| *known == tag)
+        .map(|(_, name, _)| *name)
+        .unwrap_or("System default")
+}
+
+// "de_DE.UTF-8", "de-AT" and "de" all pick German; an unknown region falls back to the
+// language's first entry, an unknown language to US English
+fn conventions_for(tag: &str) -> Conventions {
+    let tag = tag.split(['.', '@']).next().unwrap_or_default().replace('_', "-");
+    let language = tag.split('-').next().unwrap_or_default();
+    LOCALES
+        .iter()
+        .find(|(known, _, _)| known.eq_ignore_ascii_case(&tag))
+        .or_else(|| {
+            LOCALES
+                .iter()
+                .find(|(known, _, _)| known.split('-').next().is_some_and(|known| known.eq_ignore_ascii_case(language)))
+        })
+        .map(|(_, _, conventions)| *conventions)
+        .unwrap_or(ENGLISH_US)
+}
+
+fn current() -> &'static RwLock<Conventions> {
+    static CURRENT: OnceLock<RwLock<Conventions>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(conventions_for(&platform::system_locale().unwrap_or_default())))
+}
+
+fn conventions() -> Conventions {
+    *current().read().unwrap()
+}
+
+// Applies the locale chosen in Settings; empty = follow the system
+pub fn set(tag: &str) {
+    let tag = if tag.is_empty() {
+        platform::system_locale().unwrap_or_default()
+    } else {
+        tag.to_string()
+    };
+    *current().write().unwrap() = conventions_for(&tag);
+}
+
+fn group_digits(digits: &str, separator: &str) -> String {
+    let mut result = String::new();
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push_str(separator);
+        }
+        result.push(ch);
+    }
+    result
+}
+
+// 1234567 -> "1,234,567" / "1.234.567" / "1 234 567"
+pub fn number(value: u64) -> String {
+    group_digits(&value.to_string(), conventions().group_separator)
+}
+
+pub fn decimal(value: f64, places: usize) -> String {
+    let conventions = conventions();
+    let text = format!("{:.*}", places, value.abs());
+    let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+    let mut result = if value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        "-".to_string()
+    } else {
+        String::new()
+    };
+    result.push_str(&group_digits(whole, conventions.group_separator));
+    if !fraction.is_empty() {
+        result.push(conventions.decimal_separator);
+        result.push_str(fraction);
+    }
+    result
+}
+
+pub fn bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let mut value = bytes / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 && value < 1.0 {
+        return format!("{} B", decimal(bytes, 0));
+    }
+    format!("{} {}", decimal(value, 1), UNITS[unit])
+}
+
+pub fn speed(bytes_per_second: f64) -> String {
+    format!("{}/s", bytes(bytes_per_second))
+}
+
+pub fn date_time<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+    let conventions = conventions();
+    time.with_timezone(&Local)
+        .format(&format!("{} {}", conventions.date, conventions.time))
+        .to_string()
+}
+
+// Unix timestamp (seconds) in local time
+pub fn timestamp(seconds: i64) -> Option<String> {
+    DateTime::from_timestamp(seconds, 0).map(|time| date_time(&time))
+}
+
+pub fn time<Tz: TimeZone>(moment: &DateTime<Tz>) -> String {
+    moment.with_timezone(&Local).format(conventions().time).to_string()
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use winapi::um::winnls::GetUserDefaultLocaleName;
+    use winapi::um::winnt::LOCALE_NAME_MAX_LENGTH;
+
+    pub fn system_locale() -> Option<String> {
+        let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH];
+        let length = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+        if length <= 1 {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&buffer[..length as usize - 1]))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    pub fn system_locale() -> Option<String> {
+        // Same order of precedence as the C library
+        let from_env = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty() && value != "C" && value != "POSIX");
+        if from_env.is_some() {
+            return from_env;
+        }
+
+        // Apps started from Finder don't get LANG
+        #[cfg(target_os = "macos")]
+        if let Ok(output) = std::process::Command::new("defaults").args(["read", "-g", "AppleLocale"]).output() {
+            let locale = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !locale.is_empty() {
+                return Some(locale);
+            }
+        }
+
+        None
+    }
+}
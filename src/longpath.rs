@@ -0,0 +1,40 @@
+// Classic Windows path limit, which yt-dlp and ffmpeg still hit unless long paths are enabled system-wide
+pub const MAX_PATH: usize = 260;
+
+// Room kept free for the separator, an upload date, a " [video ID]" tag, a " (99)" rename suffix,
+// the extension and the temporary ".f137.webm.part" style names yt-dlp uses while downloading
+const RESERVED_CHARS: usize = 51;
+
+// Shortest title worth downloading under; below this the output folder itself is the problem
+const MIN_TITLE_CHARS: usize = 16;
+
+// Windows measures paths in UTF-16 code units
+fn path_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TitleFit {
+    Fits,
+    // The title has to be cut to this many characters to stay under MAX_PATH
+    Truncate(usize),
+    // Even a short title would not fit in the output folder
+    FolderTooLong,
+}
+
+// The file lives in each of the folders at some point, so the longest one decides
+pub fn title_fit(folders: &[&str], title: &str) -> TitleFit {
+    if !cfg!(target_os = "windows") {
+        return TitleFit::Fits;
+    }
+
+    let longest = folders.iter().map(|folder| path_len(folder)).max().unwrap_or(0);
+    let budget = MAX_PATH.saturating_sub(longest + RESERVED_CHARS);
+    if budget < MIN_TITLE_CHARS {
+        TitleFit::FolderTooLong
+    } else if path_len(title) > budget {
+        TitleFit::Truncate(budget)
+    } else {
+        TitleFit::Fits
+    }
+}
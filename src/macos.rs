@@ -0,0 +1,56 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Homebrew's bin folders on Apple silicon and on Intel Macs
+const HOMEBREW_BIN_DIRS: &[&str] = &["/opt/homebrew/bin", "/usr/local/bin"];
+
+// Contents/Resources of the .app the program runs from, where a packaged build keeps yt-dlp and ffmpeg.
+// None when it runs as a plain binary, e.g. from cargo
+pub fn bundle_resources_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let macos_dir = exe.parent()?;
+    let contents = macos_dir.parent()?;
+    if macos_dir.file_name()? != "MacOS" || contents.file_name()? != "Contents" {
+        return None;
+    }
+    Some(contents.join("Resources")).filter(|dir| dir.is_dir())
+}
+
+// The bundle's copy of a tool, then Homebrew's
+pub fn find_tool(name: &str) -> Option<PathBuf> {
+    bundle_resources_dir()
+        .into_iter()
+        .chain(HOMEBREW_BIN_DIRS.iter().map(PathBuf::from))
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+// Apps opened from the Finder or the Dock get a PATH of just the system folders, so neither the app
+// nor yt-dlp would find ffmpeg. The bundle goes first and Homebrew last, after what the user set up.
+// Called once at startup, before any thread is started
+pub fn extend_path() {
+    let current = std::env::var_os("PATH").unwrap_or_default();
+    let mut dirs: Vec<PathBuf> = bundle_resources_dir().into_iter().collect();
+    dirs.extend(std::env::split_paths(&current));
+    for homebrew in HOMEBREW_BIN_DIRS.iter().map(PathBuf::from) {
+        if !dirs.contains(&homebrew) {
+            dirs.push(homebrew);
+        }
+    }
+    if let Ok(path) = std::env::join_paths(dirs) {
+        std::env::set_var("PATH", path);
+    }
+}
+
+// Files written by an app that was itself downloaded from the web inherit its quarantine flag, and
+// Gatekeeper then refuses to run them. Only for the binaries the updater downloads; anything the
+// user put in place is left as it is
+pub fn clear_quarantine(path: &Path) -> Result<()> {
+    // Fails when the file isn't quarantined, which is fine
+    Command::new("/usr/bin/xattr")
+        .args(["-d", "com.apple.quarantine"])
+        .arg(path)
+        .output()?;
+    Ok(())
+}
@@ -5,63 +5,347 @@ use serde::{Deserialize, Serialize};
 use std::process::{Command, Stdio};
 use std::sync::mpsc;
 use std::thread;
-use std::io::{BufRead, BufReader};
 use anyhow::Result;
 use regex::Regex;
 use std::sync::{Arc, Mutex};
-use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+mod a11y;
+mod actions;
+mod autostart;
+mod certificates;
+mod channel;
+mod commands;
+mod config_watch;
+mod compact;
+mod console;
+mod conversions;
+mod crash;
+mod daemon;
+mod database;
+#[cfg(target_os = "linux")]
+mod desktop;
+mod digest;
+mod drives;
+mod encoders;
+mod events;
+mod export;
+mod feeds;
+mod file_watch;
+mod fonts;
+mod folders;
+mod formats;
+mod grab;
+mod help;
+mod history;
+mod hooks;
+mod hotkey;
+mod kiosk;
+mod impersonate;
+mod insights;
+mod instance;
+mod jobs;
+mod journal;
+mod locale;
+mod longpath;
+#[cfg(target_os = "macos")]
+mod macos;
+mod manifest;
+mod metadata;
+mod metered;
+mod mirror;
+mod naming;
+mod network;
+mod output;
+mod pairing;
+mod playlist;
+mod plugins;
+mod presets;
+mod process;
+mod profiles;
+mod progress;
+mod proxy;
+mod queue;
+mod recurring;
+mod recycle;
+mod remote;
+mod restricted;
+mod retention;
+mod retry;
+mod rtl;
+mod saved;
+mod selftest;
+mod settings;
+mod shortcuts;
+mod simple;
+mod simulate;
+mod sound;
+mod subtitles;
+mod system_proxy;
+mod suggestions;
+mod tags;
+mod theme;
+mod transfer;
+mod upgrade;
+mod verify;
+mod webui;
+mod ytdlp_config;
+
+use channel::{AppReceiver, AppSender};
+use commands::CommandExit;
+use console::ConsoleLine;
+use drives::DriveKind;
+use history::{History, HistoryEntry};
+use hotkey::GlobalHotkeys;
+use jobs::{Job, JobId, JobStatus};
+use journal::JournalFormat;
+use longpath::TitleFit;
+use metadata::{LiveStatus, VideoMetadata};
+use settings::{IdInFilename, OverwritePolicy, Settings, StallAction, UploadDateInFilename, YtDlpChannel};
+use transfer::TransferStats;
+use a11y::AccessibleName;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
+const APP_TITLE: &str = "YouTube MP3/MP4 Downloader";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct VideoInfo {
+    id: Option<String>,
     title: String,
     duration: String,
+    duration_seconds: Option<f64>,
     uploader: String,
+    #[serde(default)]
+    channel_url: Option<String>,
     view_count: Option<u64>,
     thumbnail: Option<String>,
+    availability: Option<String>,
+    subtitles: Vec<subtitles::SubtitleTrack>,
+    // YYYYMMDD as yt-dlp reports it
+    upload_date: Option<String>,
+    #[serde(default)]
+    live_status: LiveStatus,
+    #[serde(default)]
+    chapters: Vec<metadata::Chapter>,
+    // "1080p60", for videos
+    #[serde(default)]
+    best_quality: Option<String>,
+    // For the "Choose format" table; not kept with the job
+    #[serde(skip)]
+    formats: Vec<formats::FormatOption>,
+    // (name, value) of every simple field yt-dlp reported, for the file name template
+    #[serde(skip)]
+    template_fields: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone)]
 enum AppState {
     Input,
     Loading,
-    VideoInfo(VideoInfo),
-    Downloading { progress: f32, status: String },
+    VideoInfo(Box<VideoInfo>),
+    // The download shown, None for a thumbnail or metadata grab; more can run in the background
+    Downloading { job_id: Option<JobId>, progress: f32, status: String },
     Error(String),
+    AgeRestricted(String),
     Success(String),
+    // Download finished but the file failed verification
+    NeedsRedownload { url: String, path: String, reason: String },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum DownloadFormat {
     Mp4,
     Mp3,
 }
 
+impl DownloadFormat {
+    fn label(&self) -> &'static str {
+        match self {
+            DownloadFormat::Mp4 => "MP4 (Video)",
+            DownloadFormat::Mp3 => "MP3 (Audio Only)",
+        }
+    }
+
+    fn other(&self) -> DownloadFormat {
+        match self {
+            DownloadFormat::Mp4 => DownloadFormat::Mp3,
+            DownloadFormat::Mp3 => DownloadFormat::Mp4,
+        }
+    }
+
+    fn ytdlp_args(&self) -> &'static [&'static str] {
+        match self {
+            DownloadFormat::Mp3 => &["-x", "--audio-format", "mp3"],
+            // Download best video + best audio separately and merge them
+            // This allows getting higher quality than pre-merged formats
+            DownloadFormat::Mp4 => &["--format", "bestvideo[ext=mp4]+bestaudio[ext=m4a]/bestvideo+bestaudio/best"],
+        }
+    }
+}
+
 struct YtMp3App {
     url_input: String,
     state: AppState,
     download_format: DownloadFormat,
     output_path: String,
-    receiver: Option<mpsc::Receiver<AppMessage>>,
-    console_output: Vec<String>,
+    receiver: Option<AppReceiver>,
+    console_output: Vec<ConsoleLine>,
+    console_filter: Option<JobId>,
+    settings: Settings,
+    // Notices config.toml being edited outside the app
+    config_watch: config_watch::ConfigWatch,
+    show_settings: bool,
+    settings_lock: simple::SettingsLock,
+    profiles: profiles::Profiles,
+    // The profile picker is shown instead of the main window at launch
+    choosing_profile: bool,
+    // Picked in the header; switched at the start of the next frame
+    profile_switch: Option<Option<String>>,
+    new_profile_name: String,
+    profile_error: Option<String>,
+    // Launched with --admin: operator mode doesn't apply this session
+    kiosk_admin: bool,
+    // Operator mode's URL box and the result of the last URL typed into it
+    kiosk_input: String,
+    kiosk_notice: Option<String>,
+    on_top: bool,
+    global_hotkeys: Option<GlobalHotkeys>,
+    hotkey_error: Option<String>,
+    // Local WebSocket stream of download events for overlays and dashboards
+    event_server: Option<events::EventServer>,
+    event_server_error: Option<String>,
+    // Queue length and paused state last sent to the event stream
+    published_queue: Option<(Vec<String>, bool)>,
+    // The queue as last written to the database, with each row's position
+    saved_queue: Vec<(i64, database::QueuedUrl)>,
+    // Shown at startup after a crash, before the queue is restored
+    crash_prompt: Option<crash::CrashPrompt>,
+    // Queued downloads wait until the queue is started again
+    queue_paused: bool,
+    // Background check for a working internet connection; None when turned off
+    connectivity: Option<network::Connectivity>,
+    // Whether the last frame saw the connection down, to notice it coming back
+    was_offline: bool,
+    // Watches for metered connections, and whether to download on this one anyway
+    metered: metered::Metered,
+    // Start downloading as soon as the fetched info arrives
+    auto_download: bool,
+    // When the info fetch on the loading screen began
+    fetch_started: Option<Instant>,
+    age_gate_browser: String,
+    // Named cookie profile for the next fetch/download; None = the default cookies
+    cookie_profile: Option<String>,
+    // Proxy for the next fetch/download; None = the default proxy, Some("") = direct connection
+    proxy: Option<String>,
+    proxy_tests: HashMap<String, proxy::ProxyTest>,
+    system_proxy: system_proxy::SystemProxyState,
+    impersonate_support: impersonate::ImpersonateSupport,
+    plugin_dir_support: plugins::PluginDirSupport,
+    // Result of the last yt-dlp config import, shown under the extra options
+    config_import_report: Option<String>,
+    // Where fields picked in the template field list are inserted (character index)
+    template_cursor: Option<usize>,
+    template_field_search: String,
+    // The user's yt-dlp.conf, looked up when the settings are first shown
+    system_config_path: Option<Option<PathBuf>>,
+    // Plugins found in the plugin folder, with the folder they were scanned from
+    detected_plugins: Option<(String, Vec<plugins::DetectedPlugin>)>,
+    // Videos that were temporarily unavailable, re-checked in the background
+    retry_later: retry::RetryList,
+    retry_check: Option<retry::RetryCheck>,
+    show_retry_later: bool,
+    // Comma-separated tags for the next download
+    tags_input: String,
+    history_tag_filter: Option<String>,
+    // History entry whose tags are being edited, with the text being typed
+    history_tag_edit: Option<(usize, String)>,
+    // Subtitle track to burn into the next MP4 download
+    burn_subtitles: Option<subtitles::SubtitleTrack>,
+    // Exact formats picked for the next download
+    format_picker: formats::FormatPicker,
+    encoder_support: encoders::EncoderSupport,
+    // Downloads worked through one after another, with the outcome of finished ones
+    download_queue: queue::DownloadQueue,
+    insights: insights::InsightsView,
+    // Fetched videos parked without downloading
+    saved: saved::SavedList,
+    show_saved: bool,
+    // Named sets of URLs to queue again, by hand or on a schedule
+    queue_templates: recurring::QueueTemplates,
+    recurring: recurring::Recurring,
+    run_digests: digest::RunDigests,
+    // This instance as a remote for another one on the network
+    remote: remote::Remote,
+    // URLs handed over on the command line or by another launch, loaded one at a time
+    pending_urls: VecDeque<String>,
+    // A playlist whose videos are being listed into pending_urls or the picker
+    playlist_fetch: Option<playlist::PlaylistFetch>,
+    // Videos of a pasted playlist to choose from before they're queued
+    playlist_picker: Option<playlist::PlaylistPicker>,
+    // Members-only/premium URLs skipped while working through pending_urls
+    skipped_locked: Vec<(String, restricted::LockKind)>,
+    // URLs held back from pending_urls because they are already in the history
+    skipped_owned: Vec<String>,
+    history: History,
+    // Recently failed downloads and the input screen's suggestions built from them and the history
+    failed_downloads: suggestions::FailedList,
+    suggestions: suggestions::Suggestions,
+    show_history: bool,
+    // History entry whose file was just trashed, offered for undo
+    last_trashed: Option<usize>,
+    history_notice: Option<String>,
+    // History downloads picked to be downloaded again at a higher quality
+    quality_upgrade: upgrade::QualityUpgrade,
+    retention: retention::Retention,
+    file_watch: file_watch::FileWatch,
+    instance_receiver: Option<mpsc::Receiver<Vec<String>>>,
+    jobs: Vec<Job>,
+    // Downloads still running, oldest first
+    downloads: Vec<jobs::RunningDownload>,
+    // Job behind the success screen, offered again in the other format
+    last_completed_job: Option<JobId>,
+    transfer: TransferStats,
+    show_help: bool,
+    show_command_history: bool,
+    help_topic: &'static str,
+    // Status sentence for screen readers
+    announcement: a11y::Announcement,
+    // Last title sent to the OS window
+    window_title: String,
+    selected_job: Option<JobId>,
+    next_job_id: JobId,
+    // Started with --daemon: no window, everything queued is downloaded
+    daemon: bool,
 }
 
 #[derive(Debug)]
 enum AppMessage {
-    VideoInfoReceived(Result<VideoInfo>),
+    VideoInfoReceived(Result<Box<VideoInfo>>),
     DownloadProgress(f32, String),
     DownloadComplete(Result<String>),
+    DownloadSuspicious { path: String, reason: String },
+    // The yt-dlp arguments the download is about to run with
+    DownloadArgs(Vec<String>),
     ConsoleOutput(String),
 }
 
+// A download started without the user at the controls, once the main view is free
+#[derive(Clone)]
+struct AutoDownload {
+    url: String,
+    format: DownloadFormat,
+    output_path: String,
+}
+
 impl Default for YtMp3App {
     fn default() -> Self {
-        let default_path = dirs::download_dir()
-            .unwrap_or_else(|| std::env::current_dir().unwrap())
-            .to_string_lossy()
-            .to_string();
+        let settings = Settings::load();
+        let default_path = settings.default_output_dir(DownloadFormat::Mp4);
 
         Self {
             url_input: String::new(),
@@ -70,12 +354,95 @@ impl Default for YtMp3App {
             output_path: default_path,
             receiver: None,
             console_output: Vec::new(),
+            console_filter: None,
+            settings,
+            config_watch: config_watch::ConfigWatch::default(),
+            show_settings: false,
+            settings_lock: simple::SettingsLock::default(),
+            profiles: profiles::Profiles::load(),
+            choosing_profile: false,
+            profile_switch: None,
+            new_profile_name: String::new(),
+            profile_error: None,
+            kiosk_admin: std::env::args().any(|arg| arg == kiosk::ADMIN_ARG),
+            kiosk_input: String::new(),
+            kiosk_notice: None,
+            on_top: false,
+            global_hotkeys: None,
+            hotkey_error: None,
+            event_server: None,
+            event_server_error: None,
+            published_queue: None,
+            saved_queue: Vec::new(),
+            crash_prompt: None,
+            queue_paused: false,
+            connectivity: None,
+            was_offline: false,
+            metered: metered::Metered::default(),
+            auto_download: false,
+            fetch_started: None,
+            age_gate_browser: String::new(),
+            cookie_profile: None,
+            proxy: None,
+            proxy_tests: HashMap::new(),
+            system_proxy: system_proxy::SystemProxyState::default(),
+            impersonate_support: impersonate::ImpersonateSupport::Unknown,
+            plugin_dir_support: plugins::PluginDirSupport::Unknown,
+            detected_plugins: None,
+            config_import_report: None,
+            template_cursor: None,
+            template_field_search: String::new(),
+            system_config_path: None,
+            retry_later: retry::RetryList::load(),
+            retry_check: None,
+            show_retry_later: false,
+            tags_input: String::new(),
+            history_tag_filter: None,
+            history_tag_edit: None,
+            burn_subtitles: None,
+            format_picker: formats::FormatPicker::default(),
+            encoder_support: encoders::EncoderSupport::Unknown,
+            download_queue: queue::DownloadQueue::default(),
+            insights: insights::InsightsView::default(),
+            saved: saved::SavedList::load(),
+            queue_templates: recurring::QueueTemplates::load(),
+            recurring: recurring::Recurring::default(),
+            run_digests: digest::RunDigests::default(),
+            remote: remote::Remote::default(),
+            show_saved: false,
+            pending_urls: VecDeque::new(),
+            playlist_fetch: None,
+            playlist_picker: None,
+            skipped_locked: Vec::new(),
+            skipped_owned: Vec::new(),
+            history: History::load(),
+            failed_downloads: suggestions::FailedList::load(),
+            suggestions: suggestions::Suggestions::default(),
+            show_history: false,
+            last_trashed: None,
+            history_notice: None,
+            quality_upgrade: upgrade::QualityUpgrade::default(),
+            retention: retention::Retention::default(),
+            file_watch: file_watch::FileWatch::default(),
+            instance_receiver: None,
+            jobs: Vec::new(),
+            downloads: Vec::new(),
+            last_completed_job: None,
+            transfer: TransferStats::default(),
+            announcement: a11y::Announcement::default(),
+            show_help: false,
+            show_command_history: false,
+            help_topic: "getting_started",
+            window_title: APP_TITLE.to_string(),
+            selected_job: None,
+            next_job_id: 1,
+            daemon: false,
         }
     }
 }
 
 impl YtMp3App {
-    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(cc: &eframe::CreationContext<'_>, urls: Vec<String>) -> Self {
         // Set larger UI scaling for better visibility
         cc.egui_ctx.set_pixels_per_point(1.25);
         
@@ -97,8 +464,37 @@ impl YtMp3App {
             egui::FontId::new(20.0, egui::FontFamily::Proportional),
         );
         cc.egui_ctx.set_style(style);
-        
-        Self::default()
+
+        // The profile decides where the settings are loaded from
+        profiles::set_active(profiles::Profiles::load().last_used());
+        let mut app = Self::default();
+        app.choosing_profile = app.profiles.ask_at_startup && app.profiles.has_choices();
+        app.apply_startup_settings(&cc.egui_ctx);
+        // Otherwise the chosen profile's queue is restored when switching to it
+        if !app.choosing_profile {
+            app.restore_queue();
+        }
+        app.instance_receiver = instance::start_listener(cc.egui_ctx.clone());
+        app.receive_urls(urls);
+        if std::env::args().any(|arg| arg == autostart::MINIMIZED_ARG) {
+            cc.egui_ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        }
+        app
+    }
+
+    // Settings that take effect outside the app's own state; again after switching profiles
+    fn apply_startup_settings(&mut self, ctx: &egui::Context) {
+        fonts::apply(ctx, &self.settings);
+        locale::set(&self.settings.locale);
+        self.apply_hotkey_settings();
+        self.apply_event_stream_settings(ctx);
+        self.apply_system_proxy_settings();
+        self.apply_offline_detection_settings(ctx);
+        self.apply_metered_settings(ctx);
+        self.apply_remote_settings(ctx);
+        if self.settings.compact_mode {
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(compact::COMPACT_WINDOW_SIZE.into()));
+        }
     }
 
     fn is_valid_youtube_url(&self, url: &str) -> bool {
@@ -106,29 +502,201 @@ impl YtMp3App {
         youtube_regex.is_match(url)
     }
 
+    fn is_busy(&self) -> bool {
+        matches!(self.state, AppState::Loading | AppState::Downloading { .. })
+    }
+
+    fn receive_urls(&mut self, urls: Vec<String>) {
+        let mut new_count = 0;
+        let mut owned_count = 0;
+        for url in urls {
+            if !self.is_valid_youtube_url(&url) {
+                self.log_console(format!("Ignoring unsupported URL: {}", url));
+            } else if self.history.find(None, &url).is_some() {
+                owned_count += 1;
+                self.skipped_owned.push(url);
+            } else {
+                new_count += 1;
+                self.pending_urls.push_back(url);
+            }
+        }
+
+        if owned_count > 0 {
+            self.log_console(format!(
+                "{} new URL(s), {} already downloaded (held back, see the URL section)",
+                new_count, owned_count
+            ));
+        }
+
+        // Don't replace video info the user is currently looking at
+        if matches!(self.state, AppState::Input | AppState::Error(_) | AppState::Success(_) | AppState::NeedsRedownload { .. }) {
+            self.load_next_pending_url();
+        }
+    }
+
+    // Removes a file that failed verification and downloads the video again
+    fn redownload(&mut self, url: String, path: &str) {
+        if let Err(e) = std::fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                self.state = AppState::Error(format!("Could not delete {}: {}", path, e));
+                return;
+            }
+        }
+        self.url_input = url;
+        self.auto_download = true;
+        self.fetch_video_info();
+    }
+
+    fn cookie_profile_picker(&mut self, ui: &mut egui::Ui) {
+        // Forget a selection whose profile was deleted in the settings
+        if let Some(name) = &self.cookie_profile {
+            if !self.settings.cookie_profiles.iter().any(|profile| &profile.name == name) {
+                self.cookie_profile = None;
+            }
+        }
+        if self.settings.cookie_profiles.is_empty() {
+            return;
+        }
+
+        ui.label("👥 Account:");
+        egui::ComboBox::from_id_source("cookie_profile_picker")
+            .selected_text(self.cookie_profile.as_deref().unwrap_or("Default"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.cookie_profile, None, "Default");
+                for profile in &self.settings.cookie_profiles {
+                    ui.selectable_value(&mut self.cookie_profile, Some(profile.name.clone()), &profile.name);
+                }
+            });
+    }
+
+    fn clipboard_url(&self) -> Result<String, String> {
+        let Some(url) = read_clipboard_text() else {
+            return Err("Clipboard does not contain text".to_string());
+        };
+        if !self.is_valid_youtube_url(&url) {
+            return Err(format!("Clipboard is not a supported URL: {}", url));
+        }
+        Ok(url)
+    }
+
+    fn paste_and_go(&mut self) {
+        if self.is_busy() {
+            return;
+        }
+
+        match self.clipboard_url() {
+            Ok(url) => {
+                self.url_input = url;
+                self.auto_download = self.settings.paste_and_go_downloads;
+                self.fetch_video_info();
+            }
+            Err(e) => self.state = AppState::Error(e),
+        }
+    }
+
+    // Only takes over the main view when it isn't in use, or when it shows a download and
+    // there's room for another one next to it
+    fn start_next_auto_download(&mut self) {
+        if self.queue_paused || self.is_offline() || self.metered_hold() {
+            return;
+        }
+        // A channel or playlist still being listed would be replaced by the next one. Single
+        // videos go ahead, so the queue keeps moving while a long listing waits for it to shrink
+        let next_is_playlist = self.download_queue.front().is_some_and(|download| playlist::is_playlist_url(&download.url));
+        let idle = self.receiver.is_none()
+            && !(self.playlist_fetch.is_some() && next_is_playlist)
+            && self.downloads.len() < self.settings.max_downloads.max(1) as usize
+            && match &self.state {
+                AppState::Input => self.url_input.is_empty(),
+                AppState::Success(_) | AppState::Error(_) | AppState::Downloading { job_id: Some(_), .. } => true,
+                _ => false,
+            };
+        if !idle {
+            return;
+        }
+        if let Some(download) = self.download_queue.pop_front() {
+            self.url_input = download.url;
+            self.download_format = download.format;
+            self.output_path = download.output_path;
+            self.auto_download = true;
+            self.fetch_video_info();
+        }
+    }
+
+    // Switching format moves to that format's default folder, unless a folder was picked by hand
+    fn follow_format_folder(&mut self, previous: DownloadFormat) {
+        if self.output_path == self.settings.default_output_dir(previous) {
+            self.output_path = self.settings.default_output_dir(self.download_format);
+        }
+    }
+
+    fn load_next_pending_url(&mut self) {
+        // Stays queued until the connection is back; without someone at the controls
+        // run_kiosk_queue queues it for download instead
+        if self.is_offline() || self.is_kiosk() || self.daemon {
+            return;
+        }
+        if let Some(url) = self.pending_urls.pop_front() {
+            self.url_input = url;
+            self.fetch_video_info();
+        }
+    }
+
+    fn handle_instance_messages(&mut self, ctx: &egui::Context) {
+        let mut received = Vec::new();
+        if let Some(receiver) = &self.instance_receiver {
+            while let Ok(urls) = receiver.try_recv() {
+                received.push(urls);
+            }
+        }
+
+        for urls in received {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            self.receive_urls(urls);
+        }
+    }
+
     fn fetch_video_info(&mut self) {
-        if !self.is_valid_youtube_url(&self.url_input) {
+        if !self.is_valid_youtube_url(&self.url_input) && !self.is_feed_media(&self.url_input) {
             self.state = AppState::Error("Invalid YouTube URL".to_string());
             return;
         }
+        if playlist::is_playlist_url(&self.url_input) {
+            let url = std::mem::take(&mut self.url_input);
+            let pick = !self.auto_download && !self.is_kiosk() && !self.daemon;
+            self.start_playlist_fetch(url, pick);
+            return;
+        }
+        if self.is_offline() {
+            self.state = AppState::Error("No internet connection. Try again once you're back online".to_string());
+            return;
+        }
 
         let url = self.url_input.clone();
+        let settings = self.settings
+            .with_cookie_profile(self.cookie_profile.as_deref())
+            .with_proxy(self.proxy.as_deref());
 
-        // Clear previous console output
-        self.console_output.clear();
+        // Earlier output stays available through the job filter
+        self.console_filter = None;
+        self.log_console(format!("──── Fetching {} ────", url));
 
-        let (tx, rx) = mpsc::channel();
+        let (tx, rx) = channel::app_channel();
         self.receiver = Some(rx);
         self.state = AppState::Loading;
+        self.fetch_started = Some(Instant::now());
+        self.burn_subtitles = None;
+        self.format_picker.clear();
 
         thread::spawn(move || {
             // First, check and update yt-dlp
             let rt = tokio::runtime::Runtime::new().unwrap();
-            if let Err(e) = rt.block_on(check_and_update_yt_dlp(&tx)) {
+            if let Err(e) = rt.block_on(check_and_update_yt_dlp(settings.ytdlp_channel, &settings, &tx)) {
                 tx.send(AppMessage::ConsoleOutput(format!("Update check failed: {}", e))).ok();
 
                 // Check if yt-dlp exists at all
-                let yt_dlp_path = get_yt_dlp_path();
+                let yt_dlp_path = get_yt_dlp_path(&settings);
                 if !yt_dlp_path.exists() {
                     tx.send(AppMessage::VideoInfoReceived(
                         Err(anyhow::anyhow!("yt-dlp is not installed and could not be downloaded. Error: {}", e))
@@ -142,37 +710,71 @@ impl YtMp3App {
             }
 
             // Then fetch video info
-            let result = get_video_info(&url, &tx);
+            let result = get_video_info(&url, &settings, &tx);
             tx.send(AppMessage::VideoInfoReceived(result)).ok();
         });
     }
 
+    // The format the success screen's video wasn't downloaded in, if it's still known
+    fn other_format_offer(&self) -> Option<DownloadFormat> {
+        let id = self.last_completed_job?;
+        self.jobs.iter().find(|job| job.id == id).map(|job| job.format.other())
+    }
+
+    // Reuses the finished job's info, so the video isn't fetched again
+    fn download_other_format(&mut self) {
+        let Some(job) = self.last_completed_job.and_then(|id| self.jobs.iter().find(|job| job.id == id)) else {
+            return;
+        };
+        let (url, video_info, format, tags) = (job.url.clone(), job.video_info.clone(), job.format, job.tags.join(", "));
+
+        self.url_input = url;
+        self.tags_input = tags;
+        self.burn_subtitles = None;
+        self.format_picker.clear();
+        self.download_format = format.other();
+        self.follow_format_folder(format);
+        self.last_completed_job = None;
+        self.state = AppState::VideoInfo(Box::new(video_info));
+        self.start_download();
+    }
+
     fn start_download(&mut self) {
-        if let AppState::VideoInfo(_) = &self.state {
+        self.apply_simple_mode();
+        if let AppState::VideoInfo(video_info) = &self.state {
+            let video_info = video_info.as_ref().clone();
+            let title = video_info.title.clone();
             let url = self.url_input.clone();
-            let output_path = self.output_path.clone();
+            let upgrade = self.take_upgrade_plan(&url);
+            let tags = tags::parse_tags(&self.tags_input);
+            let output_path = tags::routed_output_path(&self.output_path, &tags, &self.settings.tag_folders);
             let format = self.download_format;
+            self.remember_output_folder(&self.output_path.clone());
+            let burn_subtitles = self.burn_subtitles.as_ref().filter(|_| format == DownloadFormat::Mp4);
+            let format_selector = self.format_picker.selector(format);
+            let title_taken = self.settings.id_in_filename == IdInFilename::OnCollision
+                && naming::title_collides(
+                    &output_path,
+                    &naming::preview_file_name(&video_info, &self.settings).unwrap_or_else(|_| title.clone()),
+                    video_info.id.as_deref(),
+                    &self.history,
+                );
+            let mut settings = self.settings
+                .with_cookie_profile(self.cookie_profile.as_deref())
+                .with_proxy(self.proxy.as_deref())
+                .with_rate_limit(self.metered_rate_limit())
+                .with_burned_subtitles(burn_subtitles)
+                .with_format_selector(format_selector)
+                .with_id_in_filename(title_taken);
+            if let Some(plan) = &upgrade {
+                settings = plan.apply(&settings);
+            }
+            if title_taken {
+                self.log_console("A file with this title from another video exists, adding the video ID to the file name".to_string());
+            }
 
-            // Clear previous console output
-            self.console_output.clear();
-
-            let (tx, rx) = mpsc::channel();
-            self.receiver = Some(rx);
-            
-            // Set state to downloading
-            self.state = AppState::Downloading {
-                progress: 0.0,
-                status: "Starting download...".to_string(),
-            };
-
-            // Add debug message
-            tx.send(AppMessage::ConsoleOutput("DEBUG: start_download() called, spawning thread...".to_string())).ok();
-
-            thread::spawn(move || {
-                tx.send(AppMessage::ConsoleOutput("DEBUG: Thread started, calling download_video()...".to_string())).ok();
-                let result = download_video(&url, &output_path, format, &tx);
-                tx.send(AppMessage::DownloadComplete(result)).ok();
-            });
+            let cookie_profile = self.cookie_profile.clone();
+            self.spawn_download(url, video_info, format, output_path, cookie_profile, tags, upgrade, settings);
         } else {
             // Debug: show what state we're in
             let state_debug = match &self.state {
@@ -181,235 +783,745 @@ impl YtMp3App {
                 AppState::VideoInfo(_) => "VideoInfo",
                 AppState::Downloading { .. } => "Downloading",
                 AppState::Error(_) => "Error",
+                AppState::AgeRestricted(_) => "AgeRestricted",
                 AppState::Success(_) => "Success",
+                AppState::NeedsRedownload { .. } => "NeedsRedownload",
             };
-            self.console_output.push(format!("DEBUG: start_download() called but state is: {}", state_debug));
+            self.log_console(format!("DEBUG: start_download() called but state is: {}", state_debug));
         }
     }
 
+    // Starts the download thread and its job. `settings` are the effective ones, kept with the job
+    // so the drawer shows the arguments it ran with and a retry downloads the same way
+    #[allow(clippy::too_many_arguments)]
+    fn spawn_download(
+        &mut self,
+        url: String,
+        video_info: VideoInfo,
+        format: DownloadFormat,
+        output_path: String,
+        cookie_profile: Option<String>,
+        tags: Vec<String>,
+        upgrade: Option<upgrade::UpgradePlan>,
+        settings: Settings,
+    ) {
+        let duration_seconds = video_info.duration_seconds;
+        let title = video_info.title.clone();
+        let video_id = video_info.id.clone();
+        let job_id = self.next_job_id;
+        self.next_job_id += 1;
+        self.jobs.push(Job {
+            id: job_id,
+            url: url.clone(),
+            video_info,
+            format,
+            output_path: output_path.clone(),
+            cookie_profile,
+            proxy: settings.active_proxy().map(|proxy| proxy.name.clone()),
+            tags,
+            upgrade,
+            // Replaced by the exact list once the download thread builds it
+            args: build_download_args(&url, &output_path, None, None, format, &settings, None),
+            settings: settings.clone(),
+            started: Instant::now(),
+            finished: None,
+            status: JobStatus::Running { progress: 0.0, status: "Starting download...".to_string() },
+            log: Vec::new(),
+        });
+        self.console_filter = Some(job_id);
+        events::publish(events::Event::DownloadStarted {
+            job_id,
+            url: url.clone(),
+            title: title.clone(),
+            format: events::format_name(format),
+        });
+
+        let (tx, rx) = channel::app_channel();
+        let paused = Arc::new(AtomicBool::new(false));
+        self.downloads.push(jobs::RunningDownload { job_id, receiver: rx, paused: paused.clone() });
+        
+        // Set state to downloading
+        self.state = AppState::Downloading {
+            job_id: Some(job_id),
+            progress: 0.0,
+            status: "Starting download...".to_string(),
+        };
+
+        // Add debug message
+        tx.send(AppMessage::ConsoleOutput("DEBUG: start_download() called, spawning thread...".to_string())).ok();
+
+        thread::spawn(move || {
+            tx.send(AppMessage::ConsoleOutput("DEBUG: Thread started, calling download_video()...".to_string())).ok();
+            let started_at = chrono::Local::now();
+            let result = hooks::run_pre_download(&url, &title, &output_path, format, &settings, &tx)
+                .and_then(|_| download_video(&url, &title, &output_path, format, duration_seconds, &settings, &paused, &tx))
+                .and_then(|path| match &settings.burn_subtitles {
+                    Some(track) => subtitles::burn_in(&path, track, &settings, duration_seconds, &paused, &tx),
+                    None => Ok(path),
+                });
+            let message = match result {
+                Ok(path) => match verify::verify_download(&path, duration_seconds, &settings, &tx) {
+                    Some(reason) => AppMessage::DownloadSuspicious { path, reason },
+                    None => {
+                        let mut sha256 = None;
+                        if settings.write_hash_manifest {
+                            match manifest::record_download(&path, &url, &title, &tx) {
+                                Ok(hash) => sha256 = Some(hash),
+                                Err(e) => {
+                                    tx.send(AppMessage::ConsoleOutput(format!("WARNING: Could not update the hash manifest: {}", e))).ok();
+                                }
+                            }
+                        }
+                        if settings.journal_format != JournalFormat::Off {
+                            let sha256 = sha256.or_else(|| manifest::sha256_file(Path::new(&path)).ok());
+                            let record = journal::JournalRecord::new(&url, video_id, &title, format, &path, started_at, sha256);
+                            match journal::append(&record, &settings) {
+                                Ok(journal) => tx.send(AppMessage::ConsoleOutput(format!("Recorded in the journal {}", journal.display()))),
+                                Err(e) => tx.send(AppMessage::ConsoleOutput(format!("WARNING: Could not write the download journal: {}", e))),
+                            }
+                            .ok();
+                        }
+                        AppMessage::DownloadComplete(Ok(path))
+                    }
+                },
+                Err(e) => AppMessage::DownloadComplete(Err(e)),
+            };
+            tx.send(message).ok();
+        });
+    }
+
     fn handle_messages(&mut self) {
         let mut should_clear_receiver = false;
+        let mut should_start_download = false;
+        let mut should_load_next = false;
+        let mut finished_upgrade = None;
         
+        // Fetches and grabs come through self.receiver, each download through its own channel
+        let mut messages = Vec::new();
         if let Some(receiver) = &self.receiver {
             while let Ok(message) = receiver.try_recv() {
-                match message {
-                    AppMessage::VideoInfoReceived(result) => {
-                        match result {
-                            Ok(video_info) => {
-                                self.state = AppState::VideoInfo(video_info);
-                                should_clear_receiver = true;
+                messages.push((None, message));
+            }
+        }
+        for download in &self.downloads {
+            while let Ok(message) = download.receiver.try_recv() {
+                messages.push((Some(download.job_id), message));
+            }
+        }
+
+        for (job_id, message) in messages {
+            // Downloads the main view doesn't show only update their job
+            let shown = matches!(self.state, AppState::Downloading { job_id: shown_job, .. } if shown_job == job_id);
+            match message {
+                AppMessage::VideoInfoReceived(result) => {
+                    match result {
+                        Ok(video_info) => {
+                            events::publish(events::Event::VideoInfo {
+                                url: self.url_input.clone(),
+                                title: video_info.title.clone(),
+                                uploader: video_info.uploader.clone(),
+                                duration_secs: video_info.duration_seconds.map(|secs| secs as u64),
+                            });
+                            self.run_digests.record_title(&self.url_input, &video_info.title);
+                            self.state = AppState::VideoInfo(video_info);
+                            should_clear_receiver = true;
+                            should_start_download = std::mem::take(&mut self.auto_download);
+                        }
+                        Err(e) => {
+                            let error = e.to_string();
+                            should_clear_receiver = true;
+                            events::publish(events::Event::FetchFailed { url: self.url_input.clone(), error: error.clone() });
+                            self.run_digests.record(&self.url_input, digest::Outcome::Failed { error: error.clone() });
+                            // Most failures per site happen here, before there's a job to record them with
+                            let extractor = insights::extractor_from_url(&self.url_input);
+                            if let Err(e) = insights::record_outcome(&self.url_input, &extractor, self.download_format, false) {
+                                log::warn!("Could not record the fetch outcome: {}", e);
                             }
-                            Err(e) => {
-                                self.state = AppState::Error(format!("Failed to fetch video info: {}", e));
-                                should_clear_receiver = true;
+
+                            if let Some(lock) = restricted::lock_from_error(&error) {
+                                if self.settings.skip_locked_content && !self.pending_urls.is_empty() {
+                                    self.skipped_locked.push((self.url_input.clone(), lock));
+                                    should_load_next = true;
+                                } else {
+                                    self.auto_download = false;
+                                    self.state = AppState::Error(format!(
+                                        "{}: this video needs cookies from an account with access (see ⚙ Settings → Cookies).\n\n{}",
+                                        lock.label(),
+                                        error
+                                    ));
+                                }
+                            } else if restricted::is_age_restriction_error(&error) {
+                                // Keep auto_download so a retry from the dialog carries on
+                                self.state = AppState::AgeRestricted(error);
+                            } else {
+                                self.state = AppState::Error(format!("Failed to fetch video info: {}", error));
+                                self.auto_download = false;
                             }
                         }
                     }
-                    AppMessage::DownloadProgress(progress, status) => {
-                        self.state = AppState::Downloading { progress, status };
+                }
+                AppMessage::DownloadProgress(progress, status) => {
+                    events::publish(events::Event::Progress {
+                        job_id,
+                        percent: progress * 100.0,
+                        status: status.clone(),
+                    });
+                    if let Some(job) = jobs::find_job_mut(&mut self.jobs, job_id) {
+                        job.status = JobStatus::Running { progress, status: status.clone() };
+                    }
+                    if shown {
+                        self.state = AppState::Downloading { job_id, progress, status };
+                    }
+                }
+                AppMessage::DownloadArgs(args) => {
+                    if let Some(job) = jobs::find_job_mut(&mut self.jobs, job_id) {
+                        job.args = args;
                     }
-                    AppMessage::DownloadComplete(result) => {
-                        match result {
-                            Ok(path) => {
-                                self.state = AppState::Success(path);
+                }
+                AppMessage::DownloadComplete(result) => {
+                    let job_status = match &result {
+                        Ok(path) => JobStatus::Completed(path.clone()),
+                        Err(e) if e.is::<DownloadStalled>() => JobStatus::Stalled(e.to_string()),
+                        Err(e) => JobStatus::Failed(e.to_string()),
+                    };
+                    if let Some(job) = jobs::find_job_mut(&mut self.jobs, job_id) {
+                        job.status = job_status;
+                        job.finished = Some(Instant::now());
+
+                        if let Ok(path) = &result {
+                            let entry = HistoryEntry {
+                                id: None,
+                                video_id: job.video_info.id.clone(),
+                                url: job.url.clone(),
+                                title: job.video_info.title.clone(),
+                                uploader: job.video_info.uploader.clone(),
+                                channel_url: job.video_info.channel_url.clone(),
+                                format: job.format,
+                                path: path.clone(),
+                                completed_at: chrono::Utc::now().timestamp(),
+                                deleted: false,
+                                tags: job.tags.clone(),
+                                height: None,
+                                replaced: false,
+                                missing: false,
+                            };
+                            if let Err(e) = self.history.add(entry) {
+                                console::push_line(&mut self.console_output, None, format!("WARNING: Could not save history: {}", e));
                             }
-                            Err(e) => {
-                                self.state = AppState::Error(format!("❌ Download failed: {}", e));
+                            if let Some(plan) = job.upgrade.take() {
+                                finished_upgrade = Some((plan, path.clone()));
                             }
                         }
-                        should_clear_receiver = true;
                     }
-                    AppMessage::ConsoleOutput(output) => {
-                        self.console_output.push(output);
-                        // Keep only the last 50 lines to prevent memory issues
-                        if self.console_output.len() > 50 {
-                            self.console_output.remove(0);
+                    let finished = jobs::find_job_mut(&mut self.jobs, job_id)
+                        .map(|job| (job.url.clone(), job.video_info.title.clone(), job.format, job.output_path.clone()));
+                    if let Some(job) = jobs::find_job_mut(&mut self.jobs, job_id) {
+                        let extractor = insights::extractor_name(&job.video_info.template_fields);
+                        if let Err(e) = insights::record_outcome(&job.url, &extractor, job.format, result.is_ok()) {
+                            log::warn!("Could not record the download outcome: {}", e);
+                        }
+                    }
+                    events::publish(match &result {
+                        Ok(path) => events::Event::DownloadComplete {
+                            job_id,
+                            url: finished.as_ref().map(|(url, ..)| url.clone()).unwrap_or_default(),
+                            path: path.clone(),
+                        },
+                        Err(e) => events::Event::DownloadFailed {
+                            job_id,
+                            url: finished.as_ref().map(|(url, ..)| url.clone()).unwrap_or_default(),
+                            error: e.to_string(),
+                        },
+                    });
+                    if let Some((url, ..)) = &finished {
+                        self.run_digests.record(url, match &result {
+                            Ok(path) => digest::Outcome::Downloaded { path: path.clone() },
+                            Err(e) => digest::Outcome::Failed { error: e.to_string() },
+                        });
+                    }
+                    match job_id {
+                        Some(id) => self.downloads.retain(|download| download.job_id != id),
+                        None => should_clear_receiver = true,
+                    }
+                    self.transfer.finish_download(job_id, result.is_ok());
+                    if let Some((url, title, format, output_path)) = finished {
+                        let saved = match &result {
+                            Ok(_) => self.failed_downloads.resolve(&url),
+                            Err(e) => self.failed_downloads.record(suggestions::FailedDownload {
+                                url,
+                                title,
+                                format,
+                                output_path,
+                                error: e.to_string(),
+                                failed_at: chrono::Utc::now().timestamp(),
+                            }),
+                        };
+                        if let Err(e) = saved {
+                            console::push_line(&mut self.console_output, None, format!("WARNING: Could not save the failed downloads list: {}", e));
+                        }
+                    }
+
+                    if let Ok(path) = &result {
+                        self.play_completion_sound(self.queued_download_count() == 0 && self.downloads.is_empty());
+                        if shown {
+                            self.last_completed_job = job_id;
+                            self.state = AppState::Success(path.clone());
                         }
+                    } else if let (Err(e), true) = (&result, shown) {
+                        self.state = AppState::Error(format!("❌ Download failed: {}", e));
+                    }
+                }
+                AppMessage::DownloadSuspicious { path, reason } => {
+                    // Not added to the history, so the video isn't held back as already downloaded
+                    let mut url = self.url_input.clone();
+                    if let Some(job) = jobs::find_job_mut(&mut self.jobs, job_id) {
+                        url = job.url.clone();
+                        job.status = JobStatus::NeedsRedownload { path: path.clone(), reason: reason.clone() };
+                        job.finished = Some(Instant::now());
+                        job.push_log(format!("WARNING: Verification failed: {}", reason));
+                    }
+                    console::push_line(&mut self.console_output, job_id, format!("WARNING: Verification failed: {}", reason));
+                    events::publish(events::Event::DownloadSuspicious {
+                        job_id,
+                        path: path.clone(),
+                        reason: reason.clone(),
+                    });
+                    self.run_digests.record(&url, digest::Outcome::Failed { error: format!("Verification failed: {}", reason) });
+                    match job_id {
+                        Some(id) => self.downloads.retain(|download| download.job_id != id),
+                        None => should_clear_receiver = true,
+                    }
+                    self.transfer.finish_download(job_id, false);
+                    if shown {
+                        self.state = AppState::NeedsRedownload { url, path, reason };
                     }
                 }
+                AppMessage::ConsoleOutput(output) => {
+                    self.transfer.observe(job_id, &output);
+                    events::publish(events::Event::Console { job_id, line: output.clone() });
+                    if let Some(job) = jobs::find_job_mut(&mut self.jobs, job_id) {
+                        job.push_log(output.clone());
+                    }
+                    console::push_line(&mut self.console_output, job_id, output);
+                }
             }
         }
         
         if should_clear_receiver {
             self.receiver = None;
         }
-    }
 
-    fn open_download_folder(&self) {
-        // Open the download folder in the system file explorer
-        #[cfg(target_os = "windows")]
-        {
-            std::process::Command::new("explorer")
-                .arg(&self.output_path)
-                .spawn()
-                .ok();
+        if let Some((plan, path)) = finished_upgrade {
+            self.finish_upgrade(plan, &path);
         }
-        
-        #[cfg(target_os = "macos")]
-        {
-            std::process::Command::new("open")
-                .arg(&self.output_path)
-                .spawn()
-                .ok();
+
+        if should_start_download {
+            self.start_download();
         }
-        
-        #[cfg(target_os = "linux")]
-        {
-            std::process::Command::new("xdg-open")
-                .arg(&self.output_path)
-                .spawn()
-                .ok();
+
+        if should_load_next {
+            self.load_next_pending_url();
         }
     }
 
     fn open_file_location(&self) {
-        if let AppState::Success(ref file_path) = self.state {
-            let path = Path::new(file_path);
+        if let AppState::Success(ref file_path) | AppState::NeedsRedownload { path: ref file_path, .. } = self.state {
+            self.open_path_location(file_path);
+        }
+    }
 
-            #[cfg(target_os = "windows")]
-            {
-                if path.is_file() {
-                    // If it's a file, use /select, to highlight it in Windows Explorer
-                    std::process::Command::new("explorer")
-                        .arg("/select,")
-                        .arg(file_path)
-                        .spawn()
-                        .ok();
-                } else if path.is_dir() {
-                    // If it's a directory, just open it
+    fn open_path_location(&self, file_path: &str) {
+        let path = Path::new(file_path);
+
+        #[cfg(target_os = "windows")]
+        {
+            if path.is_file() {
+                // If it's a file, use /select, to highlight it in Windows Explorer
+                std::process::Command::new("explorer")
+                    .arg("/select,")
+                    .arg(file_path)
+                    .spawn()
+                    .ok();
+            } else if path.is_dir() {
+                // If it's a directory, just open it
+                std::process::Command::new("explorer")
+                    .arg(file_path)
+                    .spawn()
+                    .ok();
+            } else {
+                // If path doesn't exist, try to open the parent directory and select nothing
+                if let Some(parent) = path.parent() {
                     std::process::Command::new("explorer")
-                        .arg(file_path)
+                        .arg(parent.to_string_lossy().as_ref())
                         .spawn()
                         .ok();
-                } else {
-                    // If path doesn't exist, try to open the parent directory and select nothing
-                    if let Some(parent) = path.parent() {
-                        std::process::Command::new("explorer")
-                            .arg(parent.to_string_lossy().as_ref())
-                            .spawn()
-                            .ok();
-                    }
                 }
             }
-            
-            #[cfg(target_os = "macos")]
-            {
-                if path.is_file() {
+        }
+        
+        #[cfg(target_os = "macos")]
+        {
+            if path.is_file() {
+                std::process::Command::new("open")
+                    .arg("-R")
+                    .arg(file_path)
+                    .spawn()
+                    .ok();
+            } else if path.is_dir() {
+                std::process::Command::new("open")
+                    .arg(file_path)
+                    .spawn()
+                    .ok();
+            } else {
+                if let Some(parent) = path.parent() {
                     std::process::Command::new("open")
-                        .arg("-R")
-                        .arg(file_path)
+                        .arg(parent.to_string_lossy().as_ref())
                         .spawn()
                         .ok();
-                } else if path.is_dir() {
-                    std::process::Command::new("open")
-                        .arg(file_path)
+                }
+            }
+        }
+        
+        #[cfg(target_os = "linux")]
+        {
+            if path.is_file() {
+                if let Some(parent) = path.parent() {
+                    std::process::Command::new("xdg-open")
+                        .arg(parent.to_string_lossy().as_ref())
                         .spawn()
                         .ok();
-                } else {
-                    if let Some(parent) = path.parent() {
-                        std::process::Command::new("open")
-                            .arg(parent.to_string_lossy().as_ref())
-                            .spawn()
-                            .ok();
-                    }
                 }
-            }
-            
-            #[cfg(target_os = "linux")]
-            {
-                if path.is_file() {
-                    if let Some(parent) = path.parent() {
-                        std::process::Command::new("xdg-open")
-                            .arg(parent.to_string_lossy().as_ref())
-                            .spawn()
-                            .ok();
-                    }
-                } else if path.is_dir() {
+            } else if path.is_dir() {
+                std::process::Command::new("xdg-open")
+                    .arg(file_path)
+                    .spawn()
+                    .ok();
+            } else {
+                if let Some(parent) = path.parent() {
                     std::process::Command::new("xdg-open")
-                        .arg(file_path)
+                        .arg(parent.to_string_lossy().as_ref())
                         .spawn()
                         .ok();
-                } else {
-                    if let Some(parent) = path.parent() {
-                        std::process::Command::new("xdg-open")
-                            .arg(parent.to_string_lossy().as_ref())
-                            .spawn()
-                            .ok();
-                    }
                 }
             }
         }
     }
 }
 
+impl YtMp3App {
+    // Everything that moves the queue along, every frame or, without a window, every daemon tick
+    fn run_engine(&mut self, ctx: &egui::Context) {
+        self.poll_config_file(ctx);
+        self.handle_messages();
+        self.poll_system_proxy();
+        self.poll_connectivity();
+        self.poll_metered();
+        self.apply_simple_mode();
+        self.run_kiosk_queue();
+        self.poll_retry_later(ctx);
+        self.poll_retention();
+        self.poll_file_watch();
+        self.poll_queue_templates();
+        self.poll_run_digests();
+        self.poll_playlist_fetch();
+        self.poll_suggestions();
+        self.sync_queue_item();
+        self.publish_queue_changes();
+        self.persist_queue();
+        self.start_next_auto_download();
+        self.handle_actions();
+        self.handle_instance_messages(ctx);
+    }
+}
+
 impl eframe::App for YtMp3App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        self.handle_messages();
+        if let Some(profile) = self.profile_switch.take() {
+            self.switch_profile(ctx, profile);
+        }
+        if self.choosing_profile {
+            self.show_profile_chooser(ctx);
+            return;
+        }
+
+        self.run_engine(ctx);
+        self.update_announcement();
+        self.poll_remote(ctx);
+        self.update_window_level(ctx);
+        self.update_window_title(ctx);
+        self.apply_display_settings(ctx);
+        self.show_crash_prompt(ctx);
+
+        if self.is_kiosk() {
+            self.show_kiosk_ui(ctx);
+            ctx.request_repaint();
+            return;
+        }
+
+        if self.is_compact(ctx) {
+            self.show_compact_ui(ctx);
+            ctx.request_repaint();
+            return;
+        }
+        self.handle_shortcuts(ctx);
 
         let mut state_change = None;
         let mut should_start_download = false;
+        let mut grab = None;
         let mut should_open_location = false;
+        let mut enter_compact = false;
+        let mut redownload = None;
+        let mut retry_later = None;
+        let mut save_for_later = false;
+        let mut download_other_format = false;
+        let offline = self.is_offline();
+
+        self.show_settings_window(ctx);
+        self.show_unlock_window(ctx);
+        self.show_history_window(ctx);
+        self.show_insights_window(ctx);
+        self.show_upgrade_window(ctx);
+        self.show_help_window(ctx);
+        self.show_command_history_window(ctx);
+        self.show_retry_later_window(ctx);
+        self.show_saved_window(ctx);
+        self.show_queue_templates_window(ctx);
+        self.show_digest_window(ctx);
+        self.show_remote_window(ctx);
+        self.show_job_drawer(ctx);
+        self.show_transfer_footer(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.add_space(10.0);
-            ui.heading("🎬 YouTube MP3/MP4 Downloader");
-            ui.add_space(15.0);
+            ui.horizontal(|ui| {
+                ui.heading("🎬 YouTube MP3/MP4 Downloader");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let settings_label = if self.settings_locked() { "🔒 Settings" } else { "⚙ Settings" };
+                    if ui.button(settings_label)
+                        .on_hover_text(shortcuts::with_shortcut(ctx, "Settings", &shortcuts::SETTINGS))
+                        .clicked()
+                    {
+                        self.open_settings();
+                    }
+                    if ui.button("❓ Help").clicked() {
+                        self.show_help = !self.show_help;
+                    }
+                    if self.is_simple() {
+                        return;
+                    }
+                    self.profile_picker(ui);
+                    if ui.button("📚 History")
+                        .on_hover_text(shortcuts::with_shortcut(ctx, "Download history", &shortcuts::HISTORY))
+                        .clicked()
+                    {
+                        self.show_history = !self.show_history;
+                    }
+                    if !self.saved.entries.is_empty()
+                        && ui.button(format!("🔖 Saved ({})", self.saved.entries.len())).clicked()
+                    {
+                        self.show_saved = !self.show_saved;
+                    }
+                    if !self.retry_later.entries.is_empty()
+                        && ui.button(format!("⏰ Retry later ({})", self.retry_later.entries.len())).clicked()
+                    {
+                        self.show_retry_later = !self.show_retry_later;
+                    }
+                    if ui.button("🗓 Templates").on_hover_text("Queue templates for recurring downloads").clicked() {
+                        self.recurring.open = !self.recurring.open;
+                    }
+                    if ui.button("🖧 Remote").on_hover_text("Queue downloads on another computer running this app").clicked() {
+                        self.remote.open = !self.remote.open;
+                    }
+                    self.pin_button(ui);
+                    self.mute_button(ui);
+                    if ui.button("🗕 Compact").on_hover_text("Shrink to a small window with just the essentials").clicked() {
+                        enter_compact = true;
+                    }
+                });
+            });
+            self.show_announcement(ui);
+            self.show_offline_banner(ui);
+            self.show_metered_banner(ui);
+            ui.add_space(10.0);
 
             // URL Input Section
             ui.group(|ui| {
                 ui.vertical(|ui| {
-                    ui.label("📎 YouTube URL:");
+                    let url_label = ui.label("📎 YouTube URL:");
                     ui.add_space(5.0);
                     ui.horizontal(|ui| {
                         ui.add(egui::TextEdit::singleline(&mut self.url_input)
-                            .desired_width(500.0)
-                            .hint_text("Paste YouTube URL here..."));
-                        if ui.add_sized([100.0, 25.0], egui::Button::new("🔍 Fetch Info"))
+                            .desired_width(400.0)
+                            .hint_text("Paste YouTube URL here..."))
+                            .labelled_by(url_label.id);
+                        if ui.add_enabled_ui(!offline, |ui| ui.add_sized([100.0, 25.0], egui::Button::new("🔍 Fetch Info"))).inner
                             .clicked() && !self.url_input.is_empty() {
                             self.fetch_video_info();
                         }
+                        if ui.add_enabled(!self.is_busy() && !offline, egui::Button::new("📋 Paste & Go").min_size(egui::vec2(100.0, 25.0)))
+                            .on_hover_text(format!(
+                                "Fetch the URL on the clipboard and download it with the current format ({} outside a text field)",
+                                ctx.format_shortcut(&shortcuts::PASTE_AND_GO)
+                            ))
+                            .clicked() {
+                            self.paste_and_go();
+                        }
+                        if self.remote_connected()
+                            && ui.add_enabled(!self.url_input.trim().is_empty(), egui::Button::new("🖧 Send to remote").min_size(egui::vec2(100.0, 25.0)))
+                                .on_hover_text(format!("Download it on {} with the current format", self.settings.remote_address))
+                                .clicked()
+                        {
+                            self.send_to_remote(self.url_input.trim().to_string(), self.download_format);
+                        }
                     });
+
+                    if !self.is_simple() && (!self.settings.cookie_profiles.is_empty() || !self.settings.proxies.is_empty()) {
+                        ui.horizontal(|ui| {
+                            self.cookie_profile_picker(ui);
+                            self.proxy_picker(ui);
+                        });
+                    }
+
+                    self.show_playlist_fetch(ui);
+                    self.show_playlist_picker(ui);
+
+                    if !self.pending_urls.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("⏭ {} more URL(s) waiting", self.pending_urls.len()));
+                            if ui.add_enabled(!self.is_busy(), egui::Button::new("Load next")).clicked() {
+                                self.load_next_pending_url();
+                            }
+                            if ui.button("Clear").clicked() {
+                                self.pending_urls.clear();
+                            }
+                        });
+                    }
+
+                    if self.queued_download_count() > 0 {
+                        ui.horizontal(|ui| {
+                            if !self.download_queue.is_empty() {
+                                ui.label(format!("⏳ {} download(s) queued", self.download_queue.len()));
+                                if self.queue_paused {
+                                    if ui.button("▶ Start queue").clicked() {
+                                        self.run_action(actions::Action::StartQueue, "Queue");
+                                    }
+                                } else if ui.button("⏸ Pause queue")
+                                    .on_hover_text("Don't start the queued downloads; the current one finishes")
+                                    .clicked()
+                                {
+                                    self.run_action(actions::Action::PauseQueue, "Queue");
+                                }
+                            }
+                            if !self.is_simple() && ui.button("📜 Export as script")
+                                .on_hover_text("Save the queue as a .bat/.sh file of yt-dlp commands, to run without the app")
+                                .clicked()
+                            {
+                                self.export_queue_script();
+                            }
+                        });
+                    }
+
+                    self.show_download_queue(ui);
+
+                    if !self.skipped_locked.is_empty() {
+                        ui.horizontal(|ui| {
+                            let details = self.skipped_locked.iter()
+                                .map(|(url, lock)| format!("{}  {}", lock.label(), url))
+                                .collect::<Vec<_>>()
+                                .join("\n");
+                            ui.label(format!("🔒 Skipped {} members-only/premium item(s)", self.skipped_locked.len()))
+                                .on_hover_text(details);
+                            if ui.button("Dismiss").clicked() {
+                                self.skipped_locked.clear();
+                            }
+                        });
+                    }
+
+                    if !self.skipped_owned.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("📚 {} URL(s) already downloaded were held back", self.skipped_owned.len()))
+                                .on_hover_text(self.skipped_owned.join("\n"));
+                            if ui.button("Add anyway").clicked() {
+                                let owned = std::mem::take(&mut self.skipped_owned);
+                                self.pending_urls.extend(owned);
+                                if matches!(self.state, AppState::Input | AppState::Error(_) | AppState::Success(_) | AppState::NeedsRedownload { .. }) {
+                                    self.load_next_pending_url();
+                                }
+                            }
+                            if ui.button("Dismiss").clicked() {
+                                self.skipped_owned.clear();
+                            }
+                        });
+                    }
                 });
             });
 
             ui.add_space(10.0);
 
-            // Output Path Section
-            ui.group(|ui| {
-                ui.vertical(|ui| {
-                    ui.label("📁 Output Directory:");
-                    ui.add_space(5.0);
-                    ui.horizontal(|ui| {
-                        ui.add(egui::TextEdit::singleline(&mut self.output_path)
-                            .desired_width(500.0));
-                        if ui.add_sized([100.0, 25.0], egui::Button::new("📂 Browse"))
-                            .clicked() {
-                            if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                                self.output_path = path.display().to_string();
+            if matches!(self.state, AppState::Input) && !self.is_simple() {
+                self.show_suggestions(ui);
+            }
+
+            if self.is_simple() {
+                self.show_simple_destination(ui);
+            } else {
+                // Output Path Section
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        let output_label = ui.label("📁 Output Directory:");
+                        ui.add_space(5.0);
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.output_path)
+                                .desired_width(500.0))
+                                .labelled_by(output_label.id);
+                            if ui.add_sized([100.0, 25.0], egui::Button::new("📂 Browse"))
+                                .clicked() {
+                                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                    self.output_path = path.display().to_string();
+                                }
                             }
-                        }
+                            self.output_folder_menu(ui);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("🏷 Tags:");
+                            ui.add(egui::TextEdit::singleline(&mut self.tags_input)
+                                .desired_width(250.0)
+                                .hint_text("e.g. workout, lectures"));
+                            help::help_button(ui, "tags");
+                            let tags = tags::parse_tags(&self.tags_input);
+                            if let Some(route) = tags::routed_folder(&tags, &self.settings.tag_folders) {
+                                ui.label(egui::RichText::new(format!("→ saved in \"{}\"", route.folder)).color(egui::Color32::GRAY));
+                            }
+                        });
                     });
                 });
-            });
 
-            ui.add_space(10.0);
+                ui.add_space(10.0);
 
-            // Format Selection Section
-            ui.group(|ui| {
-                ui.vertical(|ui| {
-                    ui.label("🎯 Download Format:");
-                    ui.add_space(5.0);
-                    ui.horizontal(|ui| {
-                        ui.radio_value(&mut self.download_format, DownloadFormat::Mp4, "🎥 MP4 (Video)");
-                        ui.add_space(20.0);
-                        ui.radio_value(&mut self.download_format, DownloadFormat::Mp3, "🎵 MP3 (Audio Only)");
+                // Format Selection Section
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        help::section_label(ui, "🎯 Download Format:", "formats");
+                        ui.add_space(5.0);
+                        ui.horizontal(|ui| {
+                            let before = self.download_format;
+                            ui.radio_value(&mut self.download_format, DownloadFormat::Mp4, "🎥 MP4 (Video)");
+                            ui.add_space(20.0);
+                            ui.radio_value(&mut self.download_format, DownloadFormat::Mp3, "🎵 MP3 (Audio Only)");
+                            if self.download_format != before {
+                                self.follow_format_folder(before);
+                            }
+                        });
+                        ui.add_space(5.0);
+                        self.device_preset_picker(ui);
                     });
                 });
-            });
+            }
 
             ui.add_space(10.0);
 
             // Main Content Area
+            let other_format = self.other_format_offer().filter(|_| !self.is_simple());
             match &self.state {
                 AppState::Input => {
                     ui.vertical_centered(|ui| {
@@ -422,38 +1534,24 @@ impl eframe::App for YtMp3App {
                 AppState::Loading => {
                     ui.vertical_centered(|ui| {
                         ui.add_space(20.0);
-                        ui.spinner();
+                        theme::busy_indicator(ui, self.settings.reduced_motion);
                         ui.add_space(10.0);
                         ui.label("Fetching video information...");
+                        if let Some(started) = self.fetch_started {
+                            let elapsed = started.elapsed().as_secs();
+                            if elapsed >= 5 {
+                                let limit = match self.settings.fetch_timeout_secs {
+                                    0 => String::new(),
+                                    secs => format!(" (gives up after {} s)", secs),
+                                };
+                                ui.label(egui::RichText::new(format!("{} s{}", elapsed, limit)).color(egui::Color32::GRAY));
+                            }
+                        }
                         
                         ui.add_space(15.0);
                         
                         // Console output section
-                        ui.group(|ui| {
-                            ui.vertical(|ui| {
-                                ui.label("📺 yt-dlp Console Output:");
-                                ui.add_space(5.0);
-                                
-                                egui::ScrollArea::vertical()
-                                    .max_height(200.0)
-                                    .stick_to_bottom(true)
-                                    .show(ui, |ui| {
-                                        ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-                                            for line in &self.console_output {
-                                                ui.label(egui::RichText::new(line)
-                                                    .font(egui::FontId::monospace(12.0))
-                                                    .color(egui::Color32::LIGHT_GRAY));
-                                            }
-                                            
-                                            if self.console_output.is_empty() {
-                                                ui.label(egui::RichText::new("Waiting for yt-dlp output...")
-                                                    .font(egui::FontId::monospace(12.0))
-                                                    .color(egui::Color32::DARK_GRAY));
-                                            }
-                                        });
-                                    });
-                            });
-                        });
+                        self.show_console(ui);
                     });
                 }
                 AppState::VideoInfo(video_info) => {
@@ -468,12 +1566,120 @@ impl eframe::App for YtMp3App {
                                 .spacing([10.0, 5.0])
                                 .show(ui, |ui| {
                                     ui.label("🎬 Title:");
-                                    ui.label(&video_info.title);
+                                    rtl::title_label(ui, &video_info.title);
                                     ui.end_row();
+
+                                    if let Ok(file_name) = naming::preview_file_name(video_info, &self.settings) {
+                                        if file_name != video_info.title {
+                                            ui.label("📝 Saved as:");
+                                            rtl::title_label(ui, &file_name);
+                                            ui.end_row();
+                                        }
+                                    }
+
+                                    if let Some(entry) = self.history.find(video_info.id.as_deref(), &self.url_input) {
+                                        ui.label("📚 History:");
+                                        ui.colored_label(
+                                            egui::Color32::LIGHT_BLUE,
+                                            format!("Already downloaded on {} as {}", entry.completed_at_text(), entry.format.label()),
+                                        ).on_hover_text(&entry.path);
+                                        ui.end_row();
+                                    }
+
+                                    if let Some(lock) = restricted::lock_from_availability(video_info.availability.as_deref()) {
+                                        ui.label("🔐 Access:");
+                                        ui.colored_label(egui::Color32::YELLOW, format!("{} — needs cookies from an account with access", lock.label()));
+                                        ui.end_row();
+                                    }
                                     
+                                    match longpath::title_fit(&[&self.output_path, &self.settings.temp_dir], &video_info.title) {
+                                        TitleFit::Fits => {}
+                                        TitleFit::Truncate(limit) => {
+                                            ui.label("⚠ File name:");
+                                            ui.colored_label(egui::Color32::YELLOW, format!(
+                                                "Path would exceed {} characters, the title will be shortened to {}",
+                                                longpath::MAX_PATH, limit
+                                            ));
+                                            ui.end_row();
+                                        }
+                                        TitleFit::FolderTooLong => {
+                                            ui.label("⚠ File name:");
+                                            ui.colored_label(egui::Color32::RED, "The output folder path is too long, choose a shorter folder");
+                                            ui.end_row();
+                                        }
+                                    }
+
+                                    let drive = drives::drive_kind(&self.output_path);
+                                    if drive != DriveKind::Local {
+                                        ui.label("💾 Output:");
+                                        ui.label(format!(
+                                            "{}: the download pauses if it disconnects and resumes when it's back",
+                                            drive.label()
+                                        ));
+                                        ui.end_row();
+                                    }
+
                                     ui.label("⏱️ Duration:");
                                     ui.label(&video_info.duration);
                                     ui.end_row();
+
+                                    let live_note = match video_info.live_status {
+                                        LiveStatus::IsLive => Some("Live now — the download records until the stream ends"),
+                                        LiveStatus::IsUpcoming => Some("Not started yet — downloading fails until the stream begins"),
+                                        LiveStatus::PostLive => Some("Just ended — the full recording may not be available yet"),
+                                        _ => None,
+                                    };
+                                    if let Some(note) = live_note {
+                                        ui.label("🔴 Live:");
+                                        ui.colored_label(egui::Color32::YELLOW, note);
+                                        ui.end_row();
+                                    }
+
+                                    if let Some(quality) = video_info.best_quality.as_ref().filter(|_| self.download_format == DownloadFormat::Mp4) {
+                                        ui.label("📺 Best quality:");
+                                        ui.label(quality);
+                                        ui.end_row();
+                                    }
+
+                                    if !video_info.chapters.is_empty() {
+                                        let list = video_info.chapters
+                                            .iter()
+                                            .map(|chapter| format!("{}  {}", format_duration(chapter.start_time), chapter.title))
+                                            .collect::<Vec<_>>()
+                                            .join("\n");
+                                        ui.label("📑 Chapters:");
+                                        ui.label(video_info.chapters.len().to_string()).on_hover_text(list);
+                                        ui.end_row();
+                                    }
+
+                                    if self.download_format == DownloadFormat::Mp4 && !video_info.subtitles.is_empty() && !self.settings.simple_mode {
+                                        ui.horizontal(|ui| {
+                                            ui.label("💬 Burn in:");
+                                            help::help_button(ui, "burn_subtitles");
+                                        });
+                                        let selected = self.burn_subtitles
+                                            .as_ref()
+                                            .map(|track| track.label())
+                                            .unwrap_or_else(|| "No subtitles".to_string());
+                                        egui::ComboBox::from_id_source("burn_subtitles")
+                                            .selected_text(selected)
+                                            .height(300.0)
+                                            .show_ui(ui, |ui| {
+                                                ui.selectable_value(&mut self.burn_subtitles, None, "No subtitles");
+                                                let mut translations_started = false;
+                                                for track in &video_info.subtitles {
+                                                    if track.translated && !translations_started {
+                                                        translations_started = true;
+                                                        ui.separator();
+                                                        ui.label(egui::RichText::new("Translated by YouTube").small().color(egui::Color32::GRAY));
+                                                    }
+                                                    ui.selectable_value(&mut self.burn_subtitles, Some(track.clone()), track.label());
+                                                }
+                                            })
+                                            .response
+                                            .on_hover_text("Draws the subtitles onto the picture for players that can't show subtitle tracks. The video is re-encoded.");
+                                        ui.end_row();
+                                    }
                                     
                                     ui.label("👤 Uploader:");
                                     ui.label(&video_info.uploader);
@@ -481,10 +1687,17 @@ impl eframe::App for YtMp3App {
                                     
                                     if let Some(views) = video_info.view_count {
                                         ui.label("👁️ Views:");
-                                        ui.label(format_number_with_commas(views));
+                                        ui.label(locale::number(views));
                                         ui.end_row();
                                     }
                                 });
+
+                            // A device preset brings its own format selection
+                            let preset_applies = self.settings.device_preset.download_format() == Some(self.download_format);
+                            if !self.settings.simple_mode && !preset_applies {
+                                ui.add_space(5.0);
+                                self.format_picker.show(ui, &video_info.formats, self.download_format);
+                            }
                         });
                     });
 
@@ -497,10 +1710,27 @@ impl eframe::App for YtMp3App {
                             DownloadFormat::Mp3 => "🎵 Download MP3",
                         };
                         
-                        if ui.add_sized([200.0, 40.0], egui::Button::new(format_text))
+                        if ui.add_enabled_ui(!offline, |ui| ui.add_sized([200.0, 40.0], egui::Button::new(format_text))).inner
                             .clicked() {
                             should_start_download = true;
                         }
+
+                        ui.add_space(5.0);
+                        ui.add_visible_ui(!self.is_simple(), |ui| ui.horizontal(|ui| {
+                            if self.saved.contains(&self.url_input) {
+                                ui.label("🔖 Saved for later");
+                            } else if ui.button("🔖 Save for later")
+                                .on_hover_text("Keep this video in a list to download later, e.g. in a batch")
+                                .clicked()
+                            {
+                                save_for_later = true;
+                            }
+                            for kind in [grab::GrabKind::Thumbnail, grab::GrabKind::Metadata] {
+                                if ui.button(kind.button_label()).clicked() {
+                                    grab = Some(kind);
+                                }
+                            }
+                        }));
                         
                         ui.add_space(10.0);
                         if ui.add_sized([120.0, 30.0], egui::Button::new("🔙 Back"))
@@ -509,43 +1739,42 @@ impl eframe::App for YtMp3App {
                         }
                     });
                 }
-                AppState::Downloading { progress, status } => {
+                AppState::Downloading { job_id, progress, status } => {
+                    let (job_id, progress, status) = (*job_id, *progress, status.clone());
+                    let pause = self.downloads.iter().find(|download| Some(download.job_id) == job_id).map(|download| download.paused.clone());
+                    let paused = pause.as_ref().is_some_and(|pause| pause.load(Ordering::Relaxed));
+                    let others = self.downloads.iter().filter(|download| Some(download.job_id) != job_id).count();
                     ui.vertical_centered(|ui| {
                         ui.add_space(20.0);
                         ui.label(status);
                         ui.add_space(10.0);
-                        ui.add(egui::ProgressBar::new(*progress)
+                        ui.add(egui::ProgressBar::new(progress)
                             .desired_width(400.0)
-                            .show_percentage());
+                            .show_percentage()
+                            .animate(!self.settings.reduced_motion && !paused));
+                        ui.add_space(5.0);
+                        if let Some(pause) = pause {
+                            if paused {
+                                if ui.button("▶ Resume").on_hover_text("Continue from where the download stopped").clicked() {
+                                    pause.store(false, Ordering::Relaxed);
+                                }
+                            } else if ui.button("⏸ Pause")
+                                .on_hover_text("Stop downloading and keep the partial file; during post-processing the pause waits until it's done")
+                                .clicked()
+                            {
+                                pause.store(true, Ordering::Relaxed);
+                            }
+                        }
+                        if others > 0 {
+                            ui.label(egui::RichText::new(format!("{} more downloading, see Downloads this session", others))
+                                .small()
+                                .color(egui::Color32::GRAY));
+                        }
                         
                         ui.add_space(15.0);
                         
                         // Console output section
-                        ui.group(|ui| {
-                            ui.vertical(|ui| {
-                                ui.label("📺 yt-dlp Console Output:");
-                                ui.add_space(5.0);
-                                
-                                egui::ScrollArea::vertical()
-                                    .max_height(200.0)
-                                    .stick_to_bottom(true)
-                                    .show(ui, |ui| {
-                                        ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
-                                            for line in &self.console_output {
-                                                ui.label(egui::RichText::new(line)
-                                                    .font(egui::FontId::monospace(12.0))
-                                                    .color(egui::Color32::LIGHT_GRAY));
-                                            }
-                                            
-                                            if self.console_output.is_empty() {
-                                                ui.label(egui::RichText::new("Waiting for yt-dlp output...")
-                                                    .font(egui::FontId::monospace(12.0))
-                                                    .color(egui::Color32::DARK_GRAY));
-                                            }
-                                        });
-                                    });
-                            });
-                        });
+                        self.show_console(ui);
                     });
                 }
                 AppState::Error(error) => {
@@ -556,8 +1785,23 @@ impl eframe::App for YtMp3App {
                         if ui.button("🔄 Try Again").clicked() {
                             state_change = Some(AppState::Input);
                         }
+                        if let Some(reason) = retry::temporary_reason(error) {
+                            let queued = self.retry_later.entries.iter().any(|entry| entry.url == self.url_input);
+                            if queued {
+                                ui.label("⏰ Waiting in Retry later");
+                            } else if ui.button("⏰ Retry later")
+                                .on_hover_text(format!("{}: check again on a schedule and download when it works", reason))
+                                .clicked()
+                            {
+                                retry_later = Some(reason);
+                            }
+                        }
                     });
                 }
+                AppState::AgeRestricted(error) => {
+                    let error = error.clone();
+                    self.show_age_restricted(ui, &error);
+                }
                 AppState::Success(path) => {
                     ui.vertical_centered(|ui| {
                         ui.add_space(20.0);
@@ -577,45 +1821,293 @@ impl eframe::App for YtMp3App {
                                 state_change = Some(AppState::Input);
                                 self.url_input.clear();
                             }
+
+                            if let Some(format) = other_format {
+                                ui.add_space(10.0);
+                                let label = match format {
+                                    DownloadFormat::Mp3 => "🎵 Also get as MP3",
+                                    DownloadFormat::Mp4 => "🎥 Also get as MP4",
+                                };
+                                if ui.add_sized([180.0, 40.0], egui::Button::new(label))
+                                    .on_hover_text("Download the same video again in the other format, without fetching it again")
+                                    .clicked() {
+                                    download_other_format = true;
+                                }
+                            }
+                        });
+                    });
+                }
+                AppState::NeedsRedownload { url, path, reason } => {
+                    let (url, path) = (url.clone(), path.clone());
+                    ui.vertical_centered(|ui| {
+                        ui.add_space(20.0);
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 165, 0),
+                            format!("⚠ The download finished but the file looks broken: {}\nSaved to: {}", reason, path),
+                        );
+                        ui.add_space(15.0);
+
+                        ui.horizontal(|ui| {
+                            if ui.add_sized([180.0, 40.0], egui::Button::new("🔄 Re-download"))
+                                .on_hover_text("Delete this file and download the video again")
+                                .clicked() {
+                                redownload = Some((url, path));
+                            }
+
+                            ui.add_space(10.0);
+
+                            if ui.add_sized([180.0, 40.0], egui::Button::new("📁 Open File Location"))
+                                .clicked() {
+                                should_open_location = true;
+                            }
+
+                            ui.add_space(10.0);
+
+                            if ui.add_sized([180.0, 40.0], egui::Button::new("📥 Download Another"))
+                                .clicked() {
+                                state_change = Some(AppState::Input);
+                                self.url_input.clear();
+                            }
                         });
                     });
                 }
             }
+
+            self.show_job_list(ui);
         });
 
         // Handle state changes after the UI update
         if let Some(new_state) = state_change {
             self.state = new_state;
         }
+
+        if let Some((url, path)) = redownload {
+            self.redownload(url, &path);
+        }
         
         // Handle download start separately
         if should_start_download {
             self.start_download();
         }
         
+        if let Some(reason) = retry_later {
+            self.add_to_retry_later(reason);
+        }
+
+        if save_for_later {
+            self.save_for_later();
+        }
+
+        if download_other_format {
+            self.download_other_format();
+        }
+
+        if let Some(kind) = grab {
+            self.start_grab(kind);
+        }
+
         // Handle folder opening separately
         if should_open_location {
             self.open_file_location();
         }
 
+        if enter_compact {
+            self.set_compact_mode(ctx, true);
+        }
+
         // Request repaint to handle async updates
         ctx.request_repaint();
     }
-}
+}
+
+impl YtMp3App {
+    fn pin_button(&mut self, ui: &mut egui::Ui) {
+        let pinned = self.settings.pin_while_downloading;
+        if ui.selectable_label(pinned, "📌")
+            .accessible_name("Keep on top while downloading")
+            .on_hover_text("Keep the window on top while downloading")
+            .clicked()
+        {
+            self.settings.pin_while_downloading = !pinned;
+            if let Err(e) = self.settings.save() {
+                self.log_console(format!("WARNING: Could not save settings: {}", e));
+            }
+        }
+    }
+
+    fn update_window_level(&mut self, ctx: &egui::Context) {
+        let downloading = matches!(self.state, AppState::Downloading { .. }) || !self.downloads.is_empty();
+        let on_top = self.settings.pin_while_downloading && downloading;
+        if on_top != self.on_top {
+            self.on_top = on_top;
+            let level = if on_top {
+                egui::WindowLevel::AlwaysOnTop
+            } else {
+                egui::WindowLevel::Normal
+            };
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+        }
+    }
+
+    fn show_settings_window(&mut self, ctx: &egui::Context) {
+        if !self.show_settings {
+            return;
+        }
+
+        let before = self.settings.clone();
+        let mut open = true;
+        egui::Window::new("⚙ Settings")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.show_simple_mode_settings(ui);
+                    ui.add_space(10.0);
+                    self.show_profile_settings(ui);
+                    ui.add_space(10.0);
+                    self.settings.ui(ui);
+                    ui.add_space(10.0);
+                    self.show_proxy_settings(ui);
+                    ui.add_space(10.0);
+                    self.show_impersonate_settings(ui);
+                    ui.add_space(10.0);
+                    self.show_plugin_settings(ui);
+                    ui.add_space(10.0);
+                    self.show_ytdlp_options_settings(ui);
+                    ui.add_space(10.0);
+                    self.show_title_cleanup_settings(ui);
+                    ui.add_space(10.0);
+                    self.show_filename_template_settings(ui);
+                    ui.add_space(10.0);
+                    self.show_encoder_settings(ui);
+                    ui.add_space(10.0);
+                    self.show_retention_settings(ui);
+                    ui.add_space(10.0);
+                    self.show_hook_settings(ui);
+                    #[cfg(target_os = "linux")]
+                    {
+                        ui.add_space(10.0);
+                        self.show_desktop_integration_settings(ui);
+                    }
+
+                    if let Some(error) = &self.hotkey_error {
+                        ui.add_space(5.0);
+                        ui.colored_label(egui::Color32::RED, format!("⌨ {}", error));
+                    }
+                    if let Some(error) = &self.event_server_error {
+                        ui.add_space(5.0);
+                        ui.colored_label(egui::Color32::RED, format!("📡 {}", error));
+                    }
+                });
+            });
+        if !open {
+            self.close_settings();
+        }
+
+        if self.settings != before {
+            if let Err(e) = self.settings.save() {
+                self.log_console(format!("WARNING: Could not save settings: {}", e));
+            }
+            self.apply_changed_settings(ctx, &before);
+        }
+    }
+
+    // Settings that need more than the next frame to take effect, after the settings window or
+    // an outside edit of config.toml changed them
+    pub(crate) fn apply_changed_settings(&mut self, ctx: &egui::Context, before: &Settings) {
+        if self.settings.ui_font != before.ui_font {
+            fonts::apply(ctx, &self.settings);
+        }
+        if self.settings.locale != before.locale {
+            locale::set(&self.settings.locale);
+        }
+        if self.settings.hotkey_enabled != before.hotkey_enabled
+            || self.settings.hotkey != before.hotkey
+            || self.settings.start_queue_hotkey != before.start_queue_hotkey
+            || self.settings.pause_queue_hotkey != before.pause_queue_hotkey
+        {
+            self.apply_hotkey_settings();
+        }
+        if self.settings.event_stream_enabled != before.event_stream_enabled
+            || self.settings.event_stream_port != before.event_stream_port
+            || self.settings.event_stream_lan != before.event_stream_lan
+        {
+            self.apply_event_stream_settings(ctx);
+        } else {
+            if self.settings.paired_extensions != before.paired_extensions {
+                pairing::set_paired(&self.settings.paired_extensions);
+            }
+            if self.settings.web_ui_enabled != before.web_ui_enabled {
+                webui::set_enabled(self.settings.web_ui_enabled || self.daemon);
+            }
+        }
+        if self.settings.offline_detection != before.offline_detection {
+            self.apply_offline_detection_settings(ctx);
+        }
+        if self.settings.use_system_proxy != before.use_system_proxy {
+            self.apply_system_proxy_settings();
+        }
+        if self.settings.remote_address != before.remote_address || self.settings.remote_token != before.remote_token {
+            self.apply_remote_settings(ctx);
+        }
+        if self.settings.metered_action != before.metered_action {
+            self.apply_metered_settings(ctx);
+        }
+        if self.settings.ytdlp_channel != before.ytdlp_channel {
+            // The other build may support different impersonation targets and options
+            self.impersonate_support = impersonate::ImpersonateSupport::Unknown;
+            self.plugin_dir_support = plugins::PluginDirSupport::Unknown;
+        }
+        if (self.settings.launch_at_login != before.launch_at_login
+            || self.settings.start_minimized != before.start_minimized)
+            && !self.daemon
+        {
+            if let Err(e) = autostart::set_launch_at_login(self.settings.launch_at_login, self.settings.start_minimized) {
+                self.log_console(format!("WARNING: Could not update launch at login: {}", e));
+            }
+        }
+    }
+}
+
+fn get_yt_dlp_path(settings: &Settings) -> std::path::PathBuf {
+    if settings.simulation.is_some() {
+        return simulate::fake_ytdlp_path();
+    }
+    let channel = settings.ytdlp_channel;
+    // Nightly lives next to stable; until it has been downloaded, stable is used
+    if channel == YtDlpChannel::Nightly {
+        if let Ok(nightly) = get_managed_yt_dlp_path(channel) {
+            if nightly.exists() {
+                return nightly;
+            }
+        }
+    }
+
+    // Check in codecs.bin folder first
+    if let Ok(stable) = get_managed_yt_dlp_path(YtDlpChannel::Stable) {
+        if stable.exists() {
+            return stable;
+        }
+    }
+
+    // Shipped in the app bundle or installed with Homebrew
+    #[cfg(target_os = "macos")]
+    if let Some(yt_dlp) = macos::find_tool("yt-dlp") {
+        return yt_dlp;
+    }
 
-impl YtMp3App {}
+    // Installed with the distribution's package manager or pip
+    #[cfg(target_os = "linux")]
+    if let Some(yt_dlp) = std::env::var_os("PATH")
+        .and_then(|paths| std::env::split_paths(&paths).map(|dir| dir.join("yt-dlp")).find(|path| path.is_file()))
+    {
+        return yt_dlp;
+    }
 
-fn get_yt_dlp_path() -> std::path::PathBuf {
     // Get the directory where the current executable is located
     if let Ok(exe_path) = std::env::current_exe() {
         if let Some(exe_dir) = exe_path.parent() {
-            // Check in codecs.bin folder first
-            let codecs_dir = exe_dir.join("codecs.bin");
-            let yt_dlp_in_codecs = codecs_dir.join("yt-dlp.exe");
-            if yt_dlp_in_codecs.exists() {
-                return yt_dlp_in_codecs;
-            }
-
             // Legacy: check for yt-dlp.exe in root
             let yt_dlp_exe = exe_dir.join("yt-dlp.exe");
             if yt_dlp_exe.exists() {
@@ -628,15 +2120,108 @@ fn get_yt_dlp_path() -> std::path::PathBuf {
     std::path::PathBuf::from("yt-dlp")
 }
 
-fn get_codecs_dir() -> Result<std::path::PathBuf> {
+// Every yt-dlp process starts here, so a simulated one knows which scenario to play
+fn ytdlp_command(settings: &Settings) -> Command {
+    let mut command = Command::new(get_yt_dlp_path(settings));
+    if let Some(scenario) = settings.simulation {
+        simulate::mark_child(&mut command, scenario);
+    }
+    command
+}
+
+// Next to the executable, where the updater kept yt-dlp on every platform before
+fn get_exe_codecs_dir() -> Result<std::path::PathBuf> {
     let exe_path = std::env::current_exe()?;
     let exe_dir = exe_path.parent()
         .ok_or_else(|| anyhow::anyhow!("Could not determine executable directory"))?;
     Ok(exe_dir.join("codecs.bin"))
 }
 
-async fn get_current_yt_dlp_version() -> Option<String> {
-    let yt_dlp_path = get_yt_dlp_path();
+// Where the updater keeps yt-dlp. An app bundle or a system-wide install isn't writable, so on a
+// Mac and on Linux it's kept with the app's data: ~/Library/Application Support or
+// $XDG_DATA_HOME (~/.local/share)
+fn get_codecs_dir() -> Result<std::path::PathBuf> {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        let data_dir = dirs::data_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+        Ok(data_dir.join("ytmp3").join("codecs.bin"))
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    get_exe_codecs_dir()
+}
+
+// Moves a codecs.bin left next to the executable to the data folder, so an update doesn't lose
+// the managed yt-dlp. Called once at startup; when the folder can't be renamed, e.g. to another
+// file system, it's copied and the original left behind if it can't be removed
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn move_exe_codecs_dir() {
+    let (Ok(old_dir), Ok(new_dir)) = (get_exe_codecs_dir(), get_codecs_dir()) else {
+        return;
+    };
+    if !old_dir.is_dir() || new_dir.exists() {
+        return;
+    }
+    let moved: std::io::Result<()> = new_dir.parent().map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::rename(&old_dir, &new_dir))
+        .or_else(|_| {
+            copy_dir(&old_dir, &new_dir).inspect_err(|_| {
+                // Tried again at the next start
+                std::fs::remove_dir_all(&new_dir).ok();
+            })?;
+            std::fs::remove_dir_all(&old_dir).ok();
+            Ok(())
+        });
+    match moved {
+        Ok(()) => log::info!("Moved yt-dlp from {} to {}", old_dir.display(), new_dir.display()),
+        Err(e) => log::warn!("Could not move {} to {}: {}", old_dir.display(), new_dir.display(), e),
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn copy_dir(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            // Keeps the executable bit
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+// yt-dlp's release asset for this platform, and the name the updater saves it under
+#[cfg(target_os = "windows")]
+const YT_DLP_ASSET: &str = "yt-dlp.exe";
+#[cfg(target_os = "macos")]
+const YT_DLP_ASSET: &str = "yt-dlp_macos";
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+const YT_DLP_ASSET: &str = "yt-dlp_linux";
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+const YT_DLP_ASSET: &str = "yt-dlp_linux_aarch64";
+// The zipapp, which needs python3
+#[cfg(not(any(target_os = "windows", target_os = "macos", all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))))]
+const YT_DLP_ASSET: &str = "yt-dlp";
+#[cfg(target_os = "windows")]
+const YT_DLP_FILE: &str = "yt-dlp.exe";
+#[cfg(not(target_os = "windows"))]
+const YT_DLP_FILE: &str = "yt-dlp";
+
+// Where the updater keeps each channel's build
+fn get_managed_yt_dlp_path(channel: YtDlpChannel) -> Result<std::path::PathBuf> {
+    let codecs_dir = get_codecs_dir()?;
+    Ok(match channel {
+        YtDlpChannel::Stable => codecs_dir.join(YT_DLP_FILE),
+        YtDlpChannel::Nightly => codecs_dir.join("nightly").join(YT_DLP_FILE),
+    })
+}
+
+async fn get_current_yt_dlp_version(channel: YtDlpChannel) -> Option<String> {
+    let yt_dlp_path = get_managed_yt_dlp_path(channel).ok()?;
 
     if !yt_dlp_path.exists() {
         return None;
@@ -647,9 +2232,9 @@ async fn get_current_yt_dlp_version() -> Option<String> {
     #[cfg(target_os = "windows")]
     command.creation_flags(0x08000000);
 
-    match command.output() {
+    match commands::output(&mut command) {
         Ok(output) if output.status.success() => {
-            String::from_utf8(output.stdout).ok().map(|s| s.trim().to_string())
+            Some(output::clean_output(&output.stdout).trim().to_string())
         }
         _ => None,
     }
@@ -667,13 +2252,13 @@ struct GitHubAsset {
     browser_download_url: String,
 }
 
-async fn get_latest_yt_dlp_release() -> Result<GitHubRelease> {
-    let client = reqwest::Client::builder()
+async fn get_latest_yt_dlp_release(channel: YtDlpChannel, settings: &Settings) -> Result<GitHubRelease> {
+    let client = certificates::client_builder(settings)
         .user_agent("ytmp3-downloader")
         .build()?;
 
     let response = client
-        .get("https://api.github.com/repos/yt-dlp/yt-dlp/releases/latest")
+        .get(channel.latest_release_url())
         .send()
         .await?;
 
@@ -681,11 +2266,11 @@ async fn get_latest_yt_dlp_release() -> Result<GitHubRelease> {
     Ok(release)
 }
 
-async fn download_yt_dlp(url: &str, dest_path: &std::path::Path, progress_sender: &mpsc::Sender<AppMessage>) -> Result<()> {
+async fn download_yt_dlp(url: &str, dest_path: &std::path::Path, settings: &Settings, progress_sender: &AppSender) -> Result<()> {
     progress_sender.send(AppMessage::ConsoleOutput(format!("Download URL: {}", url))).ok();
     progress_sender.send(AppMessage::ConsoleOutput(format!("Destination: {}", dest_path.display()))).ok();
 
-    let client = reqwest::Client::builder()
+    let client = certificates::client_builder(settings)
         .user_agent("ytmp3-downloader")
         .timeout(std::time::Duration::from_secs(300)) // 5 minute timeout
         .build()?;
@@ -706,6 +2291,7 @@ async fn download_yt_dlp(url: &str, dest_path: &std::path::Path, progress_sender
 
     progress_sender.send(AppMessage::ConsoleOutput("Writing file...".to_string())).ok();
     std::fs::write(dest_path, bytes)?;
+    prepare_downloaded_binary(dest_path)?;
 
     // Verify the file was written successfully
     if dest_path.exists() {
@@ -720,17 +2306,36 @@ async fn download_yt_dlp(url: &str, dest_path: &std::path::Path, progress_sender
     Ok(())
 }
 
-async fn check_and_update_yt_dlp(progress_sender: &mpsc::Sender<AppMessage>) -> Result<()> {
-    progress_sender.send(AppMessage::ConsoleOutput("Checking for yt-dlp updates...".to_string())).ok();
+// Makes the downloaded yt-dlp runnable: executable, and on a Mac without the quarantine flag
+fn prepare_downloaded_binary(path: &std::path::Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    #[cfg(target_os = "macos")]
+    macos::clear_quarantine(path)?;
+    Ok(())
+}
+
+async fn check_and_update_yt_dlp(channel: YtDlpChannel, settings: &Settings, progress_sender: &AppSender) -> Result<()> {
+    if settings.simulation.is_some() {
+        progress_sender.send(AppMessage::ConsoleOutput("Simulation mode: yt-dlp is faked, skipping the update check".to_string())).ok();
+        return Ok(());
+    }
+
+    progress_sender.send(AppMessage::ConsoleOutput(
+        format!("Checking for yt-dlp updates ({} channel)...", channel.label())
+    )).ok();
 
     // Get current version
-    let current_version = get_current_yt_dlp_version().await;
+    let current_version = get_current_yt_dlp_version(channel).await;
     progress_sender.send(AppMessage::ConsoleOutput(
         format!("Current version: {}", current_version.as_deref().unwrap_or("not installed"))
     )).ok();
 
     // Get latest release info
-    let release = match get_latest_yt_dlp_release().await {
+    let release = match get_latest_yt_dlp_release(channel, settings).await {
         Ok(r) => r,
         Err(e) => {
             progress_sender.send(AppMessage::ConsoleOutput(
@@ -754,20 +2359,19 @@ async fn check_and_update_yt_dlp(progress_sender: &mpsc::Sender<AppMessage>) ->
         return Ok(());
     }
 
-    // Find the Windows executable in the assets
+    // Find this platform's executable in the assets
     let yt_dlp_asset = release.assets.iter()
-        .find(|asset| asset.name == "yt-dlp.exe")
-        .ok_or_else(|| anyhow::anyhow!("Could not find yt-dlp.exe in latest release"))?;
+        .find(|asset| asset.name == YT_DLP_ASSET)
+        .ok_or_else(|| anyhow::anyhow!("Could not find {} in latest release", YT_DLP_ASSET))?;
 
     progress_sender.send(AppMessage::ConsoleOutput(
         format!("Downloading yt-dlp {}...", latest_version)
     )).ok();
 
     // Download to codecs.bin folder
-    let codecs_dir = get_codecs_dir()?;
-    let dest_path = codecs_dir.join("yt-dlp.exe");
+    let dest_path = get_managed_yt_dlp_path(channel)?;
 
-    download_yt_dlp(&yt_dlp_asset.browser_download_url, &dest_path, progress_sender).await?;
+    download_yt_dlp(&yt_dlp_asset.browser_download_url, &dest_path, settings, progress_sender).await?;
 
     progress_sender.send(AppMessage::ConsoleOutput(
         format!("Successfully downloaded yt-dlp {} to {}", latest_version, dest_path.display())
@@ -775,7 +2379,7 @@ async fn check_and_update_yt_dlp(progress_sender: &mpsc::Sender<AppMessage>) ->
 
     // Verify yt-dlp works by checking version
     progress_sender.send(AppMessage::ConsoleOutput("Verifying yt-dlp installation...".to_string())).ok();
-    match get_current_yt_dlp_version().await {
+    match get_current_yt_dlp_version(channel).await {
         Some(version) => {
             progress_sender.send(AppMessage::ConsoleOutput(
                 format!("Verification successful! yt-dlp version: {}", version)
@@ -791,43 +2395,101 @@ async fn check_and_update_yt_dlp(progress_sender: &mpsc::Sender<AppMessage>) ->
     Ok(())
 }
 
-fn get_video_info(url: &str, progress_sender: &mpsc::Sender<AppMessage>) -> Result<VideoInfo> {
-    let yt_dlp_path = get_yt_dlp_path();
+fn get_video_info(url: &str, settings: &Settings, progress_sender: &AppSender) -> Result<Box<VideoInfo>> {
+    let yt_dlp_path = get_yt_dlp_path(settings);
+    let mut args = settings.ytdlp_common_args();
+    args.extend(["--dump-json".to_string(), "--no-playlist".to_string(), url.to_string()]);
     
-    progress_sender.send(AppMessage::ConsoleOutput(format!("Running: {} --dump-json --no-playlist {}", yt_dlp_path.display(), url))).ok();
+    progress_sender.send(AppMessage::ConsoleOutput(format!("Running: {} {}", yt_dlp_path.display(), args.join(" ")))).ok();
     
-    let mut command = Command::new(&yt_dlp_path);
-    command.args(&["--dump-json", "--no-playlist", url]);
+    let mut command = ytdlp_command(settings);
+    command.args(&args);
+    command.envs(settings.ytdlp_env());
     #[cfg(target_os = "windows")]
     command.creation_flags(0x08000000);
-    let output = command.output()?;
+
+    // Slow extractors can hang without ever failing
+    let timeout = (settings.fetch_timeout_secs > 0).then(|| Duration::from_secs(settings.fetch_timeout_secs as u64));
+    let mut attempt = 0;
+    let output = loop {
+        match commands::output_with_timeout(&mut command, timeout)? {
+            Some(output) => break output,
+            None if attempt < settings.fetch_retries => {
+                attempt += 1;
+                progress_sender.send(AppMessage::ConsoleOutput(format!(
+                    "No answer after {} seconds, retrying ({}/{})",
+                    settings.fetch_timeout_secs, attempt, settings.fetch_retries
+                ))).ok();
+            }
+            None => {
+                return Err(anyhow::anyhow!(
+                    "yt-dlp gave no answer within {} seconds ({} attempt(s)). The site may be slow or unreachable; \
+                     the fetch timeout can be raised in Settings",
+                    settings.fetch_timeout_secs,
+                    attempt + 1
+                ));
+            }
+        }
+    };
 
     if !output.status.success() {
-        let error_msg = String::from_utf8_lossy(&output.stderr);
+        let error_msg = output::clean_output(&output.stderr);
         progress_sender.send(AppMessage::ConsoleOutput(format!("ERROR: {}", error_msg))).ok();
         if error_msg.is_empty() {
             return Err(anyhow::anyhow!("yt-dlp not found. Please place yt-dlp.exe or yt-dlp.bin in the same folder as this application."));
         }
+        if let Some(info) = mirror::fallback(url, settings, &error_msg, progress_sender) {
+            return Ok(info);
+        }
         return Err(anyhow::anyhow!("yt-dlp failed: {}", error_msg));
     }
 
-    let json_str = String::from_utf8(output.stdout)?;
-    progress_sender.send(AppMessage::ConsoleOutput("Successfully fetched video information".to_string())).ok();
-    let json_value: serde_json::Value = serde_json::from_str(&json_str)?;
-
-    let title = json_value["title"].as_str().unwrap_or("Unknown").to_string();
-    let duration = format_duration(json_value["duration"].as_f64().unwrap_or(0.0));
-    let uploader = json_value["uploader"].as_str().unwrap_or("Unknown").to_string();
-    let view_count = json_value["view_count"].as_u64();
-    let thumbnail = json_value["thumbnail"].as_str().map(|s| s.to_string());
+    let json_str = String::from_utf8_lossy(&output.stdout);
+    progress_sender.send(AppMessage::ConsoleOutput("Successfully fetched video information from YouTube".to_string())).ok();
+    let metadata = VideoMetadata::parse(&json_str)?;
+    for error in metadata.field_errors() {
+        progress_sender.send(AppMessage::ConsoleOutput(format!("WARNING: Could not read {}", error))).ok();
+    }
 
-    Ok(VideoInfo {
-        title,
-        duration,
-        uploader,
-        view_count,
+    let live_status = metadata.live_status();
+    let thumbnail = metadata.best_thumbnail();
+    let best_quality = metadata.best_quality();
+    let duration_seconds = metadata.duration.get().copied();
+    let formats = formats::options(metadata.formats.get().map(Vec::as_slice).unwrap_or_default(), duration_seconds);
+    let subtitles = subtitles::parse_tracks(
+        metadata.subtitles.get().unwrap_or(&Default::default()),
+        metadata.automatic_captions.get().unwrap_or(&Default::default()),
+    );
+
+    Ok(Box::new(VideoInfo {
+        id: metadata.id.value(),
+        title: metadata.title.value().unwrap_or_else(|| "Unknown".to_string()),
+        duration: format_duration(duration_seconds.unwrap_or(0.0)),
+        duration_seconds,
+        uploader: metadata.uploader.value().unwrap_or_else(|| "Unknown".to_string()),
+        channel_url: metadata.channel_url.value(),
+        view_count: metadata.view_count.value(),
         thumbnail,
-    })
+        availability: metadata.availability.value(),
+        subtitles,
+        upload_date: metadata.upload_date.value(),
+        live_status,
+        chapters: metadata.chapters.value_or_default(),
+        best_quality,
+        formats,
+        template_fields: metadata::template_fields(&json_str),
+    }))
+}
+
+fn read_clipboard_text() -> Option<String> {
+    let mut clipboard = arboard::Clipboard::new().ok()?;
+    let text = clipboard.get_text().ok()?;
+    let text = text.trim();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.to_string())
+    }
 }
 
 fn format_duration(seconds: f64) -> String {
@@ -843,26 +2505,16 @@ fn format_duration(seconds: f64) -> String {
     }
 }
 
-fn format_number_with_commas(num: u64) -> String {
-    let num_str = num.to_string();
-    let mut result = String::new();
-    let chars: Vec<char> = num_str.chars().collect();
-    
-    for (i, ch) in chars.iter().enumerate() {
-        if i > 0 && (chars.len() - i) % 3 == 0 {
-            result.push(',');
-        }
-        result.push(*ch);
-    }
-    
-    result
-}
-
+#[allow(clippy::too_many_arguments)]
 fn download_video(
     url: &str,
+    title: &str,
     output_path: &str,
     format: DownloadFormat,
-    progress_sender: &mpsc::Sender<AppMessage>,
+    duration_seconds: Option<f64>,
+    settings: &Settings,
+    paused: &AtomicBool,
+    progress_sender: &AppSender,
 ) -> Result<String> {
     progress_sender.send(AppMessage::ConsoleOutput("DEBUG: download_video() function called".to_string())).ok();
     progress_sender.send(AppMessage::DownloadProgress(
@@ -870,205 +2522,649 @@ fn download_video(
         "Starting download...".to_string(),
     )).ok();
 
-    let downloaded_file = Arc::new(Mutex::new(None::<String>));
-
-    let output_template = format!("{}\\%(title)s.%(ext)s", output_path);
-    let mut args = vec![
-        "--newline",
-        "--no-warnings",
-        "--output", &output_template,
-        url,
-    ];
-
-    // Add format-specific arguments
-    match format {
-        DownloadFormat::Mp3 => {
-            args.extend_from_slice(&["-x", "--audio-format", "mp3"]);
+    let title_limit = match longpath::title_fit(&[output_path, &settings.temp_dir], title) {
+        TitleFit::Fits => None,
+        TitleFit::Truncate(limit) => {
+            progress_sender.send(AppMessage::ConsoleOutput(format!(
+                "WARNING: The full file path would exceed {} characters, shortening the title to {} characters",
+                longpath::MAX_PATH, limit
+            ))).ok();
+            Some(limit)
         }
-        DownloadFormat::Mp4 => {
-            // Download best video + best audio separately and merge them
-            // This allows getting higher quality than pre-merged formats
-            args.extend_from_slice(&["--format", "bestvideo[ext=mp4]+bestaudio[ext=m4a]/bestvideo+bestaudio/best"]);
+        TitleFit::FolderTooLong => {
+            return Err(anyhow::anyhow!(
+                "The output folder path is too long to fit a file name under {} characters, choose a shorter folder",
+                longpath::MAX_PATH
+            ));
         }
-    }
+    };
 
-    let yt_dlp_path = get_yt_dlp_path();
+    let tracker = Arc::new(DownloadTracker {
+        file: Mutex::new(None),
+        already_downloaded: AtomicBool::new(false),
+    });
+
+    // Have ffmpeg write machine-readable progress to a file we can poll, since yt-dlp
+    // swallows ffmpeg's own output while post-processing
+    let conversion_progress = duration_seconds
+        .filter(|d| *d > 0.0)
+        .map(|duration| ConversionProgress {
+            path: get_ffmpeg_progress_path(),
+            duration,
+            label: match format {
+                _ if settings.device_preset.download_format() == Some(format) => settings.device_preset.conversion_label(),
+                DownloadFormat::Mp3 => "Converting to MP3",
+                DownloadFormat::Mp4 => "Post-processing",
+            },
+        });
+
+    // Runs once the download is done, so waiting for a conversion slot doesn't hold up the transfer
+    let reencode = reencode_plan(format, settings);
+
+    let yt_dlp_path = get_yt_dlp_path(settings);
+    let mut rename_suffix = None;
+    let mut args = build_download_args(url, output_path, title_limit, rename_suffix, format, settings, conversion_progress.as_ref());
     
+    progress_sender.send(AppMessage::DownloadArgs(args.clone())).ok();
+
     // Log the exact command being run
     let command_str = format!("{} {}", yt_dlp_path.display(), args.join(" "));
     progress_sender.send(AppMessage::ConsoleOutput(format!("Running: {}", command_str))).ok();
+
+    // Folders on network or removable drives are watched so the download can pause if they disappear
+    let watched_folders: Vec<String> = [output_path, settings.temp_dir.as_str()]
+        .into_iter()
+        .filter(|folder| !folder.is_empty() && drives::drive_kind(folder) != DriveKind::Local)
+        .map(String::from)
+        .collect();
+    for folder in &watched_folders {
+        progress_sender.send(AppMessage::ConsoleOutput(format!(
+            "{} is on a {}, the download will pause if it becomes unreachable",
+            folder, drives::drive_kind(folder).label()
+        ))).ok();
+    }
+
+    let mut restarts = 0;
+    let mut skipped = false;
+    let (status, error_lines) = loop {
+        match run_download_attempt(&args, settings, conversion_progress.as_ref(), &tracker, &watched_folders, paused, progress_sender)? {
+            AttemptOutcome::Finished(status, error_lines) => {
+                let already_downloaded = tracker.already_downloaded.swap(false, Ordering::SeqCst);
+                if status.success() && already_downloaded {
+                    match settings.overwrite_policy {
+                        OverwritePolicy::Rename => {
+                            let next = rename_suffix.unwrap_or(0) + 1;
+                            if next > MAX_RENAME_SUFFIX {
+                                return Err(anyhow::anyhow!("Could not find a free file name after {} attempts", MAX_RENAME_SUFFIX));
+                            }
+                            rename_suffix = Some(next);
+                            args = build_download_args(url, output_path, title_limit, rename_suffix, format, settings, conversion_progress.as_ref());
+                            progress_sender.send(AppMessage::DownloadArgs(args.clone())).ok();
+                            progress_sender.send(AppMessage::ConsoleOutput(
+                                format!("File already exists, saving a copy with suffix ({}) instead", next)
+                            )).ok();
+                            progress_sender.send(AppMessage::ConsoleOutput(
+                                format!("Running: {} {}", yt_dlp_path.display(), args.join(" "))
+                            )).ok();
+                            continue;
+                        }
+                        OverwritePolicy::Skip => {
+                            progress_sender.send(AppMessage::ConsoleOutput(
+                                "File already exists, skipped (overwrite policy: skip)".to_string()
+                            )).ok();
+                            skipped = true;
+                        }
+                        OverwritePolicy::Overwrite => {}
+                    }
+                }
+                break (status, error_lines);
+            }
+            AttemptOutcome::Stalled => {
+                let message = format!(
+                    "Download stalled: no progress for {} seconds",
+                    settings.stall_timeout_secs
+                );
+                progress_sender.send(AppMessage::ConsoleOutput(format!("WARNING: {}", message))).ok();
+
+                if settings.stall_action == StallAction::Restart && restarts < settings.stall_max_restarts {
+                    restarts += 1;
+                    let status = format!(
+                        "Stalled, restarting download (attempt {}/{})...",
+                        restarts, settings.stall_max_restarts
+                    );
+                    progress_sender.send(AppMessage::ConsoleOutput(status.clone())).ok();
+                    progress_sender.send(AppMessage::DownloadProgress(0.0, status)).ok();
+                    continue;
+                }
+
+                return Err(DownloadStalled(message).into());
+            }
+            AttemptOutcome::FolderUnavailable(folder) => {
+                let status = format!("⏸ Paused: {} is unreachable, waiting for it to come back...", folder);
+                progress_sender.send(AppMessage::ConsoleOutput(format!("WARNING: {}", status))).ok();
+                progress_sender.send(AppMessage::DownloadProgress(0.0, status.clone())).ok();
+
+                // Pausing stops the clock, the user knows the download is waiting then
+                let mut deadline = Instant::now() + FOLDER_WAIT_LIMIT;
+                while !drives::is_reachable(&folder) {
+                    if paused.load(Ordering::Relaxed) {
+                        progress_sender.send(AppMessage::DownloadProgress(0.0, "⏸ Paused".to_string())).ok();
+                        while paused.load(Ordering::Relaxed) {
+                            thread::sleep(PAUSE_POLL_INTERVAL);
+                        }
+                        progress_sender.send(AppMessage::DownloadProgress(0.0, status.clone())).ok();
+                        deadline = Instant::now() + FOLDER_WAIT_LIMIT;
+                    }
+                    if Instant::now() >= deadline {
+                        return Err(anyhow::anyhow!(
+                            "⏸ {} was unreachable for {} minutes, the partial download is kept for a retry",
+                            folder,
+                            FOLDER_WAIT_LIMIT.as_secs() / 60
+                        ));
+                    }
+                    thread::sleep(FOLDER_RETRY_INTERVAL);
+                }
+
+                // yt-dlp picks up the .part file where it left off
+                progress_sender.send(AppMessage::ConsoleOutput(format!("{} is reachable again, resuming download", folder))).ok();
+                progress_sender.send(AppMessage::DownloadProgress(0.0, "Resuming download...".to_string())).ok();
+                continue;
+            }
+            AttemptOutcome::Paused(progress) => {
+                progress_sender.send(AppMessage::ConsoleOutput("Download paused, the partial file is kept".to_string())).ok();
+                progress_sender.send(AppMessage::DownloadProgress(progress, "⏸ Paused".to_string())).ok();
+
+                while paused.load(Ordering::Relaxed) {
+                    thread::sleep(PAUSE_POLL_INTERVAL);
+                }
+
+                // Same arguments; yt-dlp continues the .part file and reports progress of the whole file
+                progress_sender.send(AppMessage::ConsoleOutput("Resuming download".to_string())).ok();
+                progress_sender.send(AppMessage::DownloadProgress(progress, "Resuming download...".to_string())).ok();
+                continue;
+            }
+        }
+    };
     
-    let mut command = Command::new(&yt_dlp_path);
-    command.args(&args);
+    if let Some(cp) = &conversion_progress {
+        std::fs::remove_file(&cp.path).ok();
+    }
+
+    let final_path = tracker.file.lock().unwrap().clone().unwrap_or_else(|| {
+        // If we couldn't parse the destination, log it for debugging
+        progress_sender.send(AppMessage::ConsoleOutput("WARNING: Could not determine exact file path from yt-dlp output".to_string())).ok();
+        output_path.to_string()
+    });
+    
+    if !status.success() {
+        if error_lines.is_empty() {
+            return Err(anyhow::anyhow!("Download failed with {}", status));
+        }
+        return Err(anyhow::anyhow!("Download failed: {}", error_lines.join("\n")));
+    }
+
+    // A skipped file is the one converted last time
+    let final_path = match &reencode {
+        Some(plan) if !skipped => conversions::reencode(&final_path, plan, duration_seconds, settings, paused, progress_sender)?,
+        _ => final_path,
+    };
+
+    progress_sender.send(AppMessage::DownloadProgress(
+        1.0,
+        "Download completed!".to_string(),
+    )).ok();
+
+    // Small delay to ensure the final progress message is processed
+    thread::sleep(std::time::Duration::from_millis(100));
+
+    Ok(final_path)
+}
+
+// Highest " (n)" suffix tried before giving up on finding a free file name
+const MAX_RENAME_SUFFIX: u32 = 99;
+
+// How a download's video is re-encoded once it's in, if it is. Extracting audio is cheap
+// enough to stay in yt-dlp's run and not count as a conversion
+fn reencode_plan(format: DownloadFormat, settings: &Settings) -> Option<conversions::Reencode> {
+    if format != DownloadFormat::Mp4 {
+        return None;
+    }
+    let encoder = settings.video_encoder;
+    let (args, label) = match settings.device_preset.reencode_args(encoder) {
+        Some(args) => (args, settings.device_preset.conversion_label()),
+        None if settings.h264_reencode => (encoder.ffmpeg_args(), "Re-encoding to H.264"),
+        None => return None,
+    };
+    Some(conversions::Reencode { encoder, args, label })
+}
+
+fn build_download_args(
+    url: &str,
+    output_path: &str,
+    title_limit: Option<usize>,
+    rename_suffix: Option<u32>,
+    format: DownloadFormat,
+    settings: &Settings,
+    conversion_progress: Option<&ConversionProgress>,
+) -> Vec<String> {
+    // "%(title).Ns" makes yt-dlp cut the title to N characters
+    let limited_title = match title_limit {
+        Some(limit) => format!("%(title).{}s", limit),
+        None => "%(title)s".to_string(),
+    };
+    let title_field = if let Some(template) = naming::custom_template(settings) {
+        template.replace("%(title)s", &limited_title)
+    } else {
+        let title_field = match settings.id_in_filename {
+            IdInFilename::Always => format!("{} [%(id)s]", limited_title),
+            IdInFilename::Never | IdInFilename::OnCollision => limited_title,
+        };
+        let date_field = format!("%(upload_date>{})s", settings.upload_date_format);
+        match settings.upload_date_in_filename {
+            UploadDateInFilename::Off => title_field,
+            UploadDateInFilename::Before => format!("{} - {}", date_field, title_field),
+            UploadDateInFilename::After => format!("{} ({})", title_field, date_field),
+        }
+    };
+    let file_name = match rename_suffix {
+        Some(suffix) => format!("{} ({}).%(ext)s", title_field, suffix),
+        None => format!("{}.%(ext)s", title_field),
+    };
+    // The template has to stay relative, yt-dlp ignores --paths for absolute ones
+    let mut args = vec![
+        "--newline".to_string(),
+        "--no-warnings".to_string(),
+        "--paths".to_string(),
+        format!("home:{}", output_path),
+        "--output".to_string(),
+        file_name,
+        url.to_string(),
+    ];
+    if !settings.temp_dir.is_empty() {
+        args.push("--paths".to_string());
+        args.push(format!("temp:{}", settings.temp_dir));
+    }
+
+    // Add format-specific arguments; a device preset brings its own format selection
+    let preset = Some(settings.device_preset).filter(|preset| preset.download_format() == Some(format));
+    match (preset, &settings.format_selector) {
+        (Some(preset), _) => args.extend(preset.ytdlp_args().iter().map(|arg| arg.to_string())),
+        (None, Some(selector)) => {
+            args.extend(["--format".to_string(), selector.clone()]);
+            // Whatever was picked still ends up as the file type the app promised
+            match format {
+                DownloadFormat::Mp3 => args.extend(["-x", "--audio-format", "mp3"].map(String::from)),
+                DownloadFormat::Mp4 => {
+                    args.extend(["--merge-output-format", "mp4"].map(String::from));
+                    // A single WebM file isn't merged; H.264 re-encoding below converts it anyway
+                    if !settings.h264_reencode {
+                        args.extend(["--remux-video", "mp4"].map(String::from));
+                    }
+                }
+            }
+        }
+        (None, None) => args.extend(format.ytdlp_args().iter().map(|arg| arg.to_string())),
+    }
+    args.extend(settings.ytdlp_common_args());
+    args.extend(settings.overwrite_policy.ytdlp_args().iter().map(|arg| arg.to_string()));
+    args.extend(naming::title_cleanup_args(&settings.title_cleanup));
+
+    let mut ffmpeg_args = settings.ffmpeg_thread_args();
+    if let Some(cp) = conversion_progress {
+        ffmpeg_args.push(format!("-progress \"{}\" -nostats", cp.path.display().to_string().replace('\\', "/")));
+    }
+    let ffmpeg_args = Some(ffmpeg_args.join(" ")).filter(|ffmpeg_args| !ffmpeg_args.is_empty());
+    if let Some(ffmpeg_args) = &ffmpeg_args {
+        args.push("--postprocessor-args".to_string());
+        args.push(format!("ffmpeg:{}", ffmpeg_args));
+    }
+
+    if format == DownloadFormat::Mp4 {
+        if let Some(track) = &settings.burn_subtitles {
+            args.extend(track.ytdlp_args());
+        }
+    }
+
+    // Video re-encodes run after the download (see reencode_plan), only the audio preset's
+    // options go to yt-dlp's extraction
+    if let Some((preset_args, postprocessor)) = preset.and_then(|preset| preset.postprocessor_args()) {
+        // Args under a postprocessor's own key replace the generic ffmpeg: ones, hence the progress args again
+        let preset_args = match &ffmpeg_args {
+            Some(ffmpeg_args) => format!("{} {}", preset_args, ffmpeg_args),
+            None => preset_args.to_string(),
+        };
+        args.push("--postprocessor-args".to_string());
+        args.push(format!("{}:{}", postprocessor, preset_args));
+    }
+    args.extend(ytdlp_config::split_args(&settings.extra_args));
+
+    args
+}
+
+// What the output reader learned about the file yt-dlp produced
+struct DownloadTracker {
+    file: Mutex<Option<String>>,
+    already_downloaded: AtomicBool,
+}
+
+// How often watched output folders on network/removable drives are checked
+const FOLDER_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const FOLDER_RETRY_INTERVAL: Duration = Duration::from_secs(3);
+// How long a download waits for its folder to come back before it fails
+const FOLDER_WAIT_LIMIT: Duration = Duration::from_secs(15 * 60);
+// How often a paused download checks whether it was resumed
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// Returned when a download gave up after stalling, so its job is marked stalled rather than failed
+#[derive(Debug)]
+struct DownloadStalled(String);
+
+impl std::fmt::Display for DownloadStalled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DownloadStalled {}
+
+enum AttemptOutcome {
+    Finished(std::process::ExitStatus, Vec<String>),
+    Stalled,
+    // A watched folder disappeared and the download was stopped
+    FolderUnavailable(String),
+    // The user paused the download and it was stopped at this progress
+    Paused(f32),
+}
+
+// Tracks when the download last made progress so a stalled transfer can be detected
+struct StallWatch {
+    last_change: Instant,
+    last_progress: f32,
+    // Only armed while yt-dlp is in the download phase; post-processing can be silent for minutes
+    armed: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_download_attempt(
+    args: &[String],
+    settings: &Settings,
+    conversion_progress: Option<&ConversionProgress>,
+    tracker: &Arc<DownloadTracker>,
+    watched_folders: &[String],
+    paused: &AtomicBool,
+    progress_sender: &AppSender,
+) -> Result<AttemptOutcome> {
+    let mut command = ytdlp_command(settings);
+    command.args(args);
+    command.envs(settings.ytdlp_env());
     command.stdout(Stdio::piped());
     command.stderr(Stdio::piped());
     #[cfg(target_os = "windows")]
     command.creation_flags(0x08000000);
-    let mut child = command.spawn()?;
+    if settings.low_priority {
+        process::lower_priority(&mut command);
+    }
+    let command_id = commands::started(&command);
+    let mut tree = match process::ProcessTree::spawn(&mut command) {
+        Ok(tree) => tree,
+        Err(e) => {
+            commands::finished(command_id, CommandExit::FailedToStart(e.to_string()), "");
+            return Err(e.into());
+        }
+    };
 
     // Read stdout in a separate thread to parse progress
-    let stdout = child.stdout.take().unwrap();
-    let stderr = child.stderr.take().unwrap();
+    let stdout = tree.child.stdout.take().unwrap();
+    let stderr = tree.child.stderr.take().unwrap();
+
+    let stall_watch = Arc::new(Mutex::new(StallWatch {
+        last_change: Instant::now(),
+        last_progress: -1.0,
+        armed: false,
+    }));
 
     let progress_thread = thread::spawn({
         let progress_tx = progress_sender.clone();
         let console_tx = progress_sender.clone();
-        let df_clone = downloaded_file.clone();
+        let tracker_clone = tracker.clone();
+        let watch_clone = stall_watch.clone();
+        let tracks_conversion = conversion_progress.is_some();
         move || {
-            let reader = BufReader::new(stdout);
-            
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    console_tx.send(AppMessage::ConsoleOutput(line.clone())).ok();
-                    
-                    // Try to parse the destination file path from various yt-dlp output patterns
-                    if line.contains("Destination:") {
-                        if let Some(pos) = line.find("Destination:") {
-                            let path = line[pos + 12..].trim().to_string();
+            output::for_each_line(stdout, |line| {
+                console_tx.send(AppMessage::ConsoleOutput(line.clone())).ok();
+                
+                // Try to parse the destination file path from various yt-dlp output patterns
+                if line.contains("Destination:") {
+                    if let Some(pos) = line.find("Destination:") {
+                        let path = line[pos + 12..].trim().to_string();
+                        if !subtitles::is_subtitle_file(&path) {
                             console_tx.send(AppMessage::ConsoleOutput(format!("DEBUG: Found destination: {}", path))).ok();
-                            *df_clone.lock().unwrap() = Some(path);
+                            *tracker_clone.file.lock().unwrap() = Some(path);
+                        }
+                    }
+                } else if line.contains("[download]") && line.contains("has already been downloaded") {
+                    // Handle case where file was already downloaded
+                    if let Some(start) = line.find("] ") {
+                        if let Some(end) = line.find(" has already been downloaded") {
+                            let path = line[start + 2..end].trim().to_string();
+                            console_tx.send(AppMessage::ConsoleOutput(format!("DEBUG: Found existing file: {}", path))).ok();
+                            *tracker_clone.file.lock().unwrap() = Some(path);
+                            tracker_clone.already_downloaded.store(true, Ordering::SeqCst);
+                        }
+                    }
+                } else if line.starts_with("[MoveFiles]") && line.contains("Moving file") {
+                    // With a separate temp folder the finished file is moved to the output folder last
+                    if let Some((_, to)) = line.rsplit_once("\" to \"") {
+                        let path = to.trim_end_matches('"').to_string();
+                        if !subtitles::is_subtitle_file(&path) {
+                            console_tx.send(AppMessage::ConsoleOutput(format!("DEBUG: Found moved file: {}", path))).ok();
+                            *tracker_clone.file.lock().unwrap() = Some(path);
                         }
-                    } else if line.contains("[download]") && line.contains("has already been downloaded") {
-                        // Handle case where file was already downloaded
-                        if let Some(start) = line.find("] ") {
-                            if let Some(end) = line.find(" has already been downloaded") {
-                                let path = line[start + 2..end].trim().to_string();
-                                console_tx.send(AppMessage::ConsoleOutput(format!("DEBUG: Found existing file: {}", path))).ok();
-                                *df_clone.lock().unwrap() = Some(path);
+                    }
+                } else if line.contains("[Merger]") && line.contains("Merging formats into") {
+                    // Handle merged file output
+                    if let Some(start) = line.find("into \"") {
+                        if let Some(end) = line.rfind("\"") {
+                            if end > start + 6 {
+                                let path = line[start + 6..end].to_string();
+                                console_tx.send(AppMessage::ConsoleOutput(format!("DEBUG: Found merged file: {}", path))).ok();
+                                *tracker_clone.file.lock().unwrap() = Some(path);
                             }
                         }
-                    } else if line.contains("[Merger]") && line.contains("Merging formats into") {
-                        // Handle merged file output
-                        if let Some(start) = line.find("into \"") {
-                            if let Some(end) = line.rfind("\"") {
-                                if end > start + 6 {
-                                    let path = line[start + 6..end].to_string();
-                                    console_tx.send(AppMessage::ConsoleOutput(format!("DEBUG: Found merged file: {}", path))).ok();
-                                    *df_clone.lock().unwrap() = Some(path);
-                                }
+                    }
+                }
+                
+                let parsed = progress::parse_progress_line(&line);
+
+                {
+                    let mut watch = watch_clone.lock().unwrap();
+                    if line.starts_with("[download]") {
+                        watch.armed = true;
+                        if let Some((progress, _)) = &parsed {
+                            if *progress != watch.last_progress {
+                                watch.last_progress = *progress;
+                                watch.last_change = Instant::now();
                             }
                         }
+                    } else if line.starts_with('[') {
+                        watch.armed = false;
+                        watch.last_change = Instant::now();
                     }
-                    
-                    if let Some((progress, status)) = parse_progress_line(&line) {
+                }
+
+                if let Some((progress, status)) = parsed {
+                    // Real conversion progress is polled from ffmpeg, so skip the estimated values
+                    if !tracks_conversion || line.starts_with("[download]") {
                         progress_tx.send(AppMessage::DownloadProgress(progress, status)).ok();
                     }
                 }
-            }
+            });
         }
     });
     
     // Read stderr in a separate thread for error messages
     let error_tx = progress_sender.clone();
     let error_thread = thread::spawn(move || {
-        let reader = BufReader::new(stderr);
+        let mut error_lines = Vec::new();
         
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                // Send error output to console as well
-                error_tx.send(AppMessage::ConsoleOutput(format!("ERROR: {}", line))).ok();
+        output::for_each_line(stderr, |line| {
+            // Send error output to console as well
+            error_tx.send(AppMessage::ConsoleOutput(format!("ERROR: {}", line))).ok();
+            error_lines.push(line);
+        });
+
+        error_lines
+    });
+
+    // Wait for the process to complete, killing it if it stops making progress
+    let stall_timeout = Duration::from_secs(settings.stall_timeout_secs);
+    let mut last_conversion_fraction = None;
+    let mut last_folder_check = Instant::now();
+    let outcome = loop {
+        if let Some(status) = tree.child.try_wait()? {
+            break AttemptOutcome::Finished(status, Vec::new());
+        }
+
+        if settings.stall_timeout_secs > 0 {
+            let watch = stall_watch.lock().unwrap();
+            if watch.armed && watch.last_change.elapsed() >= stall_timeout {
+                drop(watch);
+                // Take ffmpeg down too, otherwise it keeps the pipes and the partial file open
+                tree.kill();
+                break AttemptOutcome::Stalled;
+            }
+        }
+
+        // Only while downloading; a file stopped during post-processing would count as already downloaded
+        if paused.load(Ordering::Relaxed) {
+            let watch = stall_watch.lock().unwrap();
+            if watch.armed {
+                let progress = watch.last_progress.max(0.0);
+                drop(watch);
+                tree.kill();
+                break AttemptOutcome::Paused(progress);
+            }
+        }
+
+        if !watched_folders.is_empty() && last_folder_check.elapsed() >= FOLDER_CHECK_INTERVAL {
+            last_folder_check = Instant::now();
+            if let Some(folder) = watched_folders.iter().find(|folder| !drives::is_reachable(folder)) {
+                // Writing into a vanished share only produces errors, stop and wait for it instead
+                tree.kill();
+                break AttemptOutcome::FolderUnavailable(folder.clone());
+            }
+        }
+
+        if let Some(cp) = conversion_progress {
+            if let Some(fraction) = read_ffmpeg_progress(cp) {
+                if last_conversion_fraction != Some(fraction) {
+                    last_conversion_fraction = Some(fraction);
+                    progress_sender.send(AppMessage::DownloadProgress(
+                        fraction,
+                        format!("{}... {:.1}%", cp.label, fraction * 100.0),
+                    )).ok();
+                }
             }
         }
-    });
 
-    // Wait for the process to complete
-    let output = child.wait_with_output()?;
+        thread::sleep(Duration::from_millis(250));
+    };
     
     // Wait for both threads to finish
     progress_thread.join().ok();
-    error_thread.join().ok();
-    
-    let final_path = downloaded_file.lock().unwrap().clone().unwrap_or_else(|| {
-        // If we couldn't parse the destination, log it for debugging
-        progress_sender.send(AppMessage::ConsoleOutput("WARNING: Could not determine exact file path from yt-dlp output".to_string())).ok();
-        output_path.to_string()
-    });
-    
-    if !output.status.success() {
-        let error_msg = String::from_utf8_lossy(&output.stderr);
-        if error_msg.is_empty() {
-            return Err(anyhow::anyhow!("yt-dlp not found. Please place yt-dlp.exe or yt-dlp.bin in the same folder as this application."));
-        }
-        return Err(anyhow::anyhow!("Download failed: {}", error_msg));
-    }
+    let error_lines = error_thread.join().unwrap_or_default();
+    let exit = match &outcome {
+        AttemptOutcome::Finished(status, _) => commands::exit_of(*status),
+        _ => CommandExit::Killed,
+    };
+    commands::finished(command_id, exit, &error_lines.join("\n"));
 
-    progress_sender.send(AppMessage::DownloadProgress(
-        1.0,
-        "Download completed!".to_string(),
-    )).ok();
+    Ok(match outcome {
+        AttemptOutcome::Finished(status, _) => AttemptOutcome::Finished(status, error_lines),
+        other => other,
+    })
+}
 
-    // Small delay to ensure the final progress message is processed
-    thread::sleep(std::time::Duration::from_millis(100));
+struct ConversionProgress {
+    path: std::path::PathBuf,
+    duration: f64,
+    label: &'static str,
+}
 
-    Ok(final_path)
+fn get_ffmpeg_progress_path() -> std::path::PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    std::env::temp_dir().join(format!("ytmp3-ffmpeg-progress-{}-{}.txt", std::process::id(), nanos))
 }
 
-fn parse_progress_line(line: &str) -> Option<(f32, String)> {
-    // yt-dlp progress format: [download] 45.2% of 123.45MiB at 1.23MiB/s ETA 00:30
-    if line.contains("[download]") && line.contains("%") {
-        // Extract percentage
-        if let Some(percent_start) = line.find("] ") {
-            let percent_part = &line[percent_start + 2..];
-            if let Some(percent_end) = percent_part.find('%') {
-                let percent_str = &percent_part[..percent_end];
-                if let Ok(percent) = percent_str.parse::<f32>() {
-                    let progress = percent / 100.0;
-                    
-                    // Extract additional info for status
-                    let status = if line.contains(" at ") && line.contains(" ETA ") {
-                        // Extract speed and ETA
-                        let speed_start = line.find(" at ").unwrap() + 4;
-                        let speed_end = line.find(" ETA ").unwrap();
-                        let speed = &line[speed_start..speed_end];
-                        
-                        let eta_start = line.find(" ETA ").unwrap() + 5;
-                        let eta = &line[eta_start..].trim();
-                        
-                        format!("Downloading... {:.1}% at {} (ETA: {})", percent, speed, eta)
-                    } else {
-                        format!("Downloading... {:.1}%", percent)
-                    };
-                    
-                    return Some((progress, status));
-                }
-            }
+fn read_ffmpeg_progress(conversion: &ConversionProgress) -> Option<f32> {
+    // ffmpeg appends blocks of key=value lines; the last out_time_us is the current position
+    let contents = std::fs::read_to_string(&conversion.path).ok()?;
+    let mut out_time_us = None;
+    let mut ended = false;
+    for line in contents.lines() {
+        if let Some(value) = line.strip_prefix("out_time_us=") {
+            out_time_us = value.trim().parse::<i64>().ok().or(out_time_us);
+        } else if line.trim() == "progress=end" {
+            ended = true;
         }
     }
-    
-    // Handle other status messages
-    if line.contains("[download] Destination:") {
-        return Some((0.0, "Preparing download...".to_string()));
-    }
-    
-    if line.contains("[download] 100%") {
-        return Some((1.0, "Download completed!".to_string()));
-    }
-    
-    if line.contains("[ExtractAudio]") {
-        return Some((0.9, "Extracting audio...".to_string()));
-    }
-    
-    if line.contains("[ffmpeg]") {
-        return Some((0.95, "Converting to MP3...".to_string()));
+
+    if ended {
+        return Some(1.0);
     }
-    
-    None
+
+    let seconds = out_time_us? as f64 / 1_000_000.0;
+    Some((seconds / conversion.duration).clamp(0.0, 1.0) as f32)
 }
 
 fn main() -> Result<(), eframe::Error> {
     env_logger::init();
+    crash::install_hook();
+    #[cfg(target_os = "macos")]
+    macos::extend_path();
+    simulate::run_fake_ytdlp_if_child();
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    move_exe_codecs_dir();
+
+    let urls: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+    #[cfg(target_os = "linux")]
+    let urls = desktop::expand_args(urls);
+
+    if std::env::args().any(|arg| arg == selftest::SELF_TEST_ARG) {
+        std::process::exit(selftest::run());
+    }
+
+    if std::env::args().any(|arg| arg == daemon::DAEMON_ARG) {
+        daemon::run(urls);
+        return Ok(());
+    }
+
+    // Hand the URLs to an already running window instead of opening a second one
+    if !urls.is_empty() && instance::forward_to_running_instance(&urls) {
+        return Ok(());
+    }
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([900.0, 800.0])
-            .with_min_inner_size([800.0, 700.0])
+            .with_inner_size(compact::NORMAL_WINDOW_SIZE)
+            .with_min_inner_size(compact::MIN_WINDOW_SIZE)
             .with_resizable(true),
         ..Default::default()
     };
 
-    eframe::run_native(
-        "YouTube MP3/MP4 Downloader",
+    let result = eframe::run_native(
+        APP_TITLE,
         options,
-        Box::new(|cc| Ok(Box::new(YtMp3App::new(cc)))),
-    )
+        Box::new(|cc| Ok(Box::new(YtMp3App::new(cc, urls)))),
+    );
+
+    // Downloads run in background threads that die with the app; don't leave yt-dlp behind
+    process::kill_all();
+    result
 }
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::channel::AppSender;
+use crate::AppMessage;
+
+// Written next to the downloads so a folder can be verified on its own
+const MANIFEST_FILE_NAME: &str = "ytmp3-manifest.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    // File name relative to the manifest's folder
+    pub file: String,
+    pub sha256: String,
+    pub size: u64,
+    pub url: String,
+    pub title: String,
+    // Unix timestamp (seconds) of when the hash was recorded
+    pub recorded_at: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+}
+
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Downloads finishing together into one folder take turns updating its manifest, or one's
+// read-modify-write would drop the other's entry
+fn folder_lock(folder: &Path) -> Arc<Mutex<()>> {
+    static LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+    let mut locks = LOCKS.get_or_init(Default::default).lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    locks.entry(folder.to_path_buf()).or_default().clone()
+}
+
+// Hashes a finished download and records it in the manifest of its folder. Returns the hash
+pub fn record_download(path: &str, url: &str, title: &str, progress_sender: &AppSender) -> Result<String> {
+    let path = Path::new(path);
+    let folder = path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("{} has no parent folder", path.display()))?;
+    let file = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("{} is not a file", path.display()))?
+        .to_string_lossy()
+        .to_string();
+
+    progress_sender.send(AppMessage::ConsoleOutput(format!("Computing SHA-256 of {}...", file))).ok();
+    let sha256 = sha256_file(path)?;
+    let size = std::fs::metadata(path)?.len();
+
+    let lock = folder_lock(folder);
+    let _updating = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let manifest_path = folder.join(MANIFEST_FILE_NAME);
+    let mut manifest: Manifest = match std::fs::read_to_string(&manifest_path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Could not parse {}: {}", manifest_path.display(), e))?,
+        Err(_) => Manifest::default(),
+    };
+
+    if let Some(duplicate) = manifest.entries.iter().find(|entry| entry.sha256 == sha256 && entry.file != file) {
+        progress_sender.send(AppMessage::ConsoleOutput(
+            format!("NOTE: {} is identical to {} already in this folder", file, duplicate.file)
+        )).ok();
+    }
+
+    // A re-download of the same file name replaces its old record
+    manifest.entries.retain(|entry| entry.file != file);
+    manifest.entries.push(ManifestEntry {
+        file,
+        sha256: sha256.clone(),
+        size,
+        url: url.to_string(),
+        title: title.to_string(),
+        recorded_at: chrono::Utc::now().timestamp(),
+    });
+
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+    progress_sender.send(AppMessage::ConsoleOutput(
+        format!("SHA-256 {} recorded in {}", sha256, manifest_path.display())
+    )).ok();
+    Ok(sha256)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::app_channel;
+
+    #[test]
+    fn downloads_finishing_together_are_all_recorded() {
+        let folder = tempfile::tempdir().unwrap();
+        let (sender, _receiver) = app_channel();
+        let threads: Vec<_> = (0..16)
+            .map(|index| {
+                let path = folder.path().join(format!("video {}.mp4", index));
+                std::fs::write(&path, format!("video {}", index)).unwrap();
+                let sender = sender.clone();
+                std::thread::spawn(move || {
+                    record_download(&path.display().to_string(), "https://example.com", "Video", &sender).unwrap();
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let manifest: Manifest = serde_json::from_str(&std::fs::read_to_string(folder.path().join(MANIFEST_FILE_NAME)).unwrap()).unwrap();
+        let mut files: Vec<String> = manifest.entries.into_iter().map(|entry| entry.file).collect();
+        files.sort();
+        let mut expected: Vec<String> = (0..16).map(|index| format!("video {}.mp4", index)).collect();
+        expected.sort();
+        assert_eq!(files, expected);
+    }
+}
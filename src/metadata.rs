@@ -0,0 +1,247 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+// A field of yt-dlp's JSON. yt-dlp writes null for a lot of fields and extractors sometimes
+// put unexpected types in others, so a bad field is kept as an error instead of failing the video
+#[derive(Debug, Clone, Default)]
+pub enum Field<T> {
+    #[default]
+    Missing,
+    Parsed(T),
+    Invalid(String),
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Field<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if value.is_null() {
+            return Ok(Field::Missing);
+        }
+        Ok(match serde_json::from_value(value) {
+            Ok(parsed) => Field::Parsed(parsed),
+            Err(e) => Field::Invalid(e.to_string()),
+        })
+    }
+}
+
+impl<T> Field<T> {
+    pub fn value(self) -> Option<T> {
+        match self {
+            Field::Parsed(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        match self {
+            Field::Parsed(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn error(&self) -> Option<&str> {
+        match self {
+            Field::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Default> Field<T> {
+    pub fn value_or_default(self) -> T {
+        self.value().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveStatus {
+    #[default]
+    NotLive,
+    IsLive,
+    IsUpcoming,
+    WasLive,
+    PostLive,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Format {
+    pub format_id: Option<String>,
+    pub ext: Option<String>,
+    pub vcodec: Option<String>,
+    pub acodec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    // Total, video and audio bitrate in kbit/s
+    pub tbr: Option<f64>,
+    pub vbr: Option<f64>,
+    pub abr: Option<f64>,
+    // Bytes, written as floats by some extractors
+    pub filesize: Option<f64>,
+    pub filesize_approx: Option<f64>,
+    pub format_note: Option<String>,
+    pub protocol: Option<String>,
+}
+
+impl Format {
+    // yt-dlp reports "none" for the codec a format doesn't have
+    pub fn has_video(&self) -> bool {
+        self.vcodec.as_deref().is_some_and(|codec| codec != "none") || self.height.is_some_and(|height| height > 0)
+    }
+
+    // Formats whose codecs aren't known at all are usually a single file with both
+    pub fn has_audio(&self) -> bool {
+        match self.acodec.as_deref() {
+            Some(codec) => codec != "none",
+            None => self.vcodec.is_none(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Thumbnail {
+    pub url: String,
+    #[serde(default)]
+    pub preference: Option<i64>,
+    #[serde(default)]
+    pub width: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub start_time: f64,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubtitleFormat {
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+// Subtitle language key -> the formats it's offered in
+pub type SubtitleLanguages = BTreeMap<String, Vec<SubtitleFormat>>;
+
+// The parts of `yt-dlp --dump-json` the app uses
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct VideoMetadata {
+    pub id: Field<String>,
+    pub title: Field<String>,
+    pub duration: Field<f64>,
+    pub uploader: Field<String>,
+    pub channel_url: Field<String>,
+    pub view_count: Field<u64>,
+    pub thumbnail: Field<String>,
+    pub thumbnails: Field<Vec<Thumbnail>>,
+    pub formats: Field<Vec<Format>>,
+    pub chapters: Field<Vec<Chapter>>,
+    pub subtitles: Field<SubtitleLanguages>,
+    pub automatic_captions: Field<SubtitleLanguages>,
+    pub availability: Field<String>,
+    pub live_status: Field<LiveStatus>,
+    pub is_live: Field<bool>,
+    // YYYYMMDD
+    pub upload_date: Field<String>,
+}
+
+impl VideoMetadata {
+    pub fn parse(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    // Fields that were present but couldn't be read, as "name: error"
+    pub fn field_errors(&self) -> Vec<String> {
+        [
+            ("id", self.id.error()),
+            ("title", self.title.error()),
+            ("duration", self.duration.error()),
+            ("uploader", self.uploader.error()),
+            ("channel_url", self.channel_url.error()),
+            ("view_count", self.view_count.error()),
+            ("thumbnail", self.thumbnail.error()),
+            ("thumbnails", self.thumbnails.error()),
+            ("formats", self.formats.error()),
+            ("chapters", self.chapters.error()),
+            ("subtitles", self.subtitles.error()),
+            ("automatic_captions", self.automatic_captions.error()),
+            ("availability", self.availability.error()),
+            ("live_status", self.live_status.error()),
+            ("is_live", self.is_live.error()),
+            ("upload_date", self.upload_date.error()),
+        ]
+        .into_iter()
+        .filter_map(|(name, error)| error.map(|error| format!("{}: {}", name, error)))
+        .collect()
+    }
+
+    // Older extractors only set is_live
+    pub fn live_status(&self) -> LiveStatus {
+        match (self.live_status.get(), self.is_live.get()) {
+            (Some(status), _) => *status,
+            (None, Some(true)) => LiveStatus::IsLive,
+            _ => LiveStatus::NotLive,
+        }
+    }
+
+    // The main thumbnail, or the best entry of the thumbnail list when it's missing
+    pub fn best_thumbnail(&self) -> Option<String> {
+        if let Some(url) = self.thumbnail.get() {
+            return Some(url.clone());
+        }
+        self.thumbnails
+            .get()?
+            .iter()
+            .max_by_key(|thumbnail| (thumbnail.preference.unwrap_or(i64::MIN), thumbnail.width.unwrap_or(0)))
+            .map(|thumbnail| thumbnail.url.clone())
+    }
+
+    // "1080p60" for the best video format on offer
+    pub fn best_quality(&self) -> Option<String> {
+        let best = self
+            .formats
+            .get()?
+            .iter()
+            .filter(|format| format.has_video())
+            .filter_map(|format| format.height.map(|height| (height, format.fps.unwrap_or(0.0).round() as u32)))
+            .max()?;
+        Some(match best {
+            (height, fps) if fps > 30 => format!("{}p{}", height, fps),
+            (height, _) => format!("{}p", height),
+        })
+    }
+}
+
+// Longer values are cut in the template field list
+const MAX_SAMPLE_CHARS: usize = 60;
+
+// Every top-level text, number or yes/no field of `yt-dlp --dump-json` with its value, sorted
+// by name. These are what an output template can use as %(name)s
+pub fn template_fields(json: &str) -> Vec<(String, String)> {
+    let Ok(serde_json::Value::Object(fields)) = serde_json::from_str::<serde_json::Value>(json) else {
+        return Vec::new();
+    };
+    let mut fields: Vec<(String, String)> = fields
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let text = match value {
+                serde_json::Value::String(text) => text,
+                serde_json::Value::Number(number) => number.to_string(),
+                serde_json::Value::Bool(flag) => flag.to_string(),
+                _ => return None,
+            };
+            let sample = match text.char_indices().nth(MAX_SAMPLE_CHARS) {
+                Some((end, _)) => format!("{}…", &text[..end]),
+                None => text,
+            };
+            Some((name, sample))
+        })
+        .collect();
+    fields.sort();
+    fields
+}
@@ -0,0 +1,236 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::help;
+use crate::YtMp3App;
+
+// Switching networks is rare, and the checks start a process on Windows and macOS
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const TICK: Duration = Duration::from_millis(250);
+
+// What the queue does while the connection is metered
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MeteredAction {
+    Ignore,
+    // Queued downloads wait for an unmetered network
+    Pause,
+    // Downloads go on at the metered rate limit
+    LimitRate,
+}
+
+impl MeteredAction {
+    pub const ALL: [MeteredAction; 3] = [MeteredAction::Ignore, MeteredAction::Pause, MeteredAction::LimitRate];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MeteredAction::Ignore => "Download as usual",
+            MeteredAction::Pause => "Hold the queue",
+            MeteredAction::LimitRate => "Limit the download speed",
+        }
+    }
+}
+
+fn command_output(command: &mut Command) -> Option<String> {
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    let output = command.output().ok().filter(|output| output.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// Windows rates every connection's cost: Fixed and Variable are data plans
+#[cfg(target_os = "windows")]
+fn detect() -> Option<bool> {
+    let script = "$connection = [Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile(); \
+                  if ($connection) { $connection.GetConnectionCost().NetworkCostType }";
+    let output = command_output(Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-Command", script]))?;
+    match output.trim() {
+        "Unrestricted" => Some(false),
+        "Fixed" | "Variable" => Some(true),
+        _ => None,
+    }
+}
+
+// NetworkManager knows, or guesses from the device type and DHCP hints; without it, only a mobile
+// broadband interface carrying the default route tells. USB tethering looks like any USB Ethernet
+// adapter, so anything else is unknown
+#[cfg(target_os = "linux")]
+fn detect() -> Option<bool> {
+    let network_manager = command_output(Command::new("busctl").args([
+        "get-property",
+        "org.freedesktop.NetworkManager",
+        "/org/freedesktop/NetworkManager",
+        "org.freedesktop.NetworkManager",
+        "Metered",
+    ]));
+    // "u 1": 1 = yes, 2 = no, 3 = guessed yes, 4 = guessed no, 0 = unknown
+    match network_manager.as_deref().map(str::trim) {
+        Some("u 1") | Some("u 3") => return Some(true),
+        Some("u 2") | Some("u 4") => return Some(false),
+        _ => {}
+    }
+
+    let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+    let interface = routes
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|fields| fields.get(1) == Some(&"00000000"))
+        .and_then(|fields| fields.first().map(|name| name.to_string()))?;
+    ["wwan", "ppp", "rmnet"].iter().any(|prefix| interface.starts_with(prefix)).then_some(true)
+}
+
+// A default route through a phone's hotspot over USB or Bluetooth
+#[cfg(target_os = "macos")]
+fn detect() -> Option<bool> {
+    let route = command_output(Command::new("route").args(["-n", "get", "default"]))?;
+    let interface = route.lines().find_map(|line| line.trim().strip_prefix("interface:"))?.trim().to_string();
+    let ports = command_output(Command::new("networksetup").arg("-listallhardwareports"))?;
+    let mut port_name = "";
+    for line in ports.lines() {
+        if let Some(name) = line.strip_prefix("Hardware Port:") {
+            port_name = name.trim();
+        } else if line.strip_prefix("Device:").map(str::trim) == Some(interface.as_str()) {
+            return Some(["iPhone", "iPad", "Android", "Bluetooth PAN"].iter().any(|phone| port_name.contains(phone)));
+        }
+    }
+    Some(false)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn detect() -> Option<bool> {
+    None
+}
+
+// A background thread that keeps checking whether the connection is metered; dropping it
+// stops the checks
+pub struct MeteredWatch {
+    // None until the first check, or when the system can't tell
+    metered: Arc<Mutex<Option<bool>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl MeteredWatch {
+    pub fn start(ctx: egui::Context) -> Self {
+        let metered = Arc::new(Mutex::new(None));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_metered, thread_stop) = (metered.clone(), stop.clone());
+        thread::spawn(move || {
+            let mut waited = CHECK_INTERVAL;
+            while !thread_stop.load(Ordering::Relaxed) {
+                if waited >= CHECK_INTERVAL {
+                    let now = detect();
+                    if std::mem::replace(&mut *thread_metered.lock().unwrap(), now) != now {
+                        ctx.request_repaint();
+                    }
+                    waited = Duration::ZERO;
+                }
+                thread::sleep(TICK);
+                waited += TICK;
+            }
+        });
+
+        Self { metered, stop }
+    }
+
+    pub fn is_metered(&self) -> bool {
+        *self.metered.lock().unwrap() == Some(true)
+    }
+}
+
+impl Drop for MeteredWatch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+pub struct Metered {
+    watch: Option<MeteredWatch>,
+    was_metered: bool,
+    // "Download anyway" for this metered connection
+    allowed: bool,
+}
+
+impl YtMp3App {
+    fn is_metered(&self) -> bool {
+        self.metered.watch.as_ref().is_some_and(|watch| watch.is_metered())
+    }
+
+    // Queued downloads wait while this is true
+    pub(crate) fn metered_hold(&self) -> bool {
+        self.settings.metered_action == MeteredAction::Pause && !self.metered.allowed && self.is_metered()
+    }
+
+    // yt-dlp's --limit-rate for the next download
+    pub(crate) fn metered_rate_limit(&self) -> Option<&str> {
+        let limit = self.settings.metered_rate_limit.trim();
+        (self.settings.metered_action == MeteredAction::LimitRate && !limit.is_empty() && self.is_metered()).then_some(limit)
+    }
+
+    pub(crate) fn apply_metered_settings(&mut self, ctx: &egui::Context) {
+        self.metered.watch = (self.settings.metered_action != MeteredAction::Ignore).then(|| MeteredWatch::start(ctx.clone()));
+        self.metered.was_metered = false;
+    }
+
+    // Called every frame
+    pub(crate) fn poll_metered(&mut self) {
+        let metered = self.is_metered();
+        if metered == self.metered.was_metered {
+            return;
+        }
+        self.metered.was_metered = metered;
+        if !metered {
+            self.metered.allowed = false;
+            self.log_console("On an unmetered network again".to_string());
+            return;
+        }
+        match self.settings.metered_action {
+            MeteredAction::Ignore => {}
+            MeteredAction::Pause => self.log_console("Metered connection, holding queued downloads until you're on an unmetered network".to_string()),
+            MeteredAction::LimitRate => self.log_console(format!(
+                "Metered connection, limiting new downloads to {}/s",
+                self.settings.metered_rate_limit.trim()
+            )),
+        }
+    }
+
+    pub(crate) fn show_metered_banner(&mut self, ui: &mut egui::Ui) {
+        if !self.metered_hold() || self.download_queue.is_empty() {
+            return;
+        }
+        let mut allow = false;
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgb(90, 60, 0))
+            .rounding(4.0)
+            .inner_margin(8.0)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        egui::Color32::WHITE,
+                        format!(
+                            "📶 Metered connection. {} queued download(s) wait for an unmetered network.",
+                            self.download_queue.len()
+                        ),
+                    );
+                    if ui.button("Download anyway").clicked() {
+                        allow = true;
+                    }
+                    help::help_button(ui, "metered");
+                });
+            });
+        ui.add_space(5.0);
+        if allow {
+            self.metered.allowed = true;
+            self.log_console("Downloading on the metered connection".to_string());
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::certificates;
+use crate::channel::AppSender;
+use crate::history::youtube_video_id;
+use crate::metadata::LiveStatus;
+use crate::settings::Settings;
+use crate::{format_duration, AppMessage, VideoInfo};
+
+const MIRROR_TIMEOUT: Duration = Duration::from_secs(20);
+
+// Errors YouTube gives when it's refusing this machine rather than the video being unavailable
+const BLOCKED_MARKERS: &[&str] = &[
+    "not a bot",
+    "http error 429",
+    "too many requests",
+    "rate-limited",
+    "rate limited",
+];
+
+pub fn is_blocked_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    BLOCKED_MARKERS.iter().any(|marker| error.contains(marker))
+}
+
+// Alternate YouTube frontends with a JSON API for video details
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MirrorKind {
+    Invidious,
+    Piped,
+}
+
+impl MirrorKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MirrorKind::Invidious => "Invidious",
+            MirrorKind::Piped => "Piped",
+        }
+    }
+
+    pub fn url_hint(&self) -> &'static str {
+        match self {
+            MirrorKind::Invidious => "https://invidious.example.org",
+            MirrorKind::Piped => "https://pipedapi.example.org",
+        }
+    }
+
+    fn api_url(&self, instance: &str, id: &str) -> String {
+        let instance = instance.trim().trim_end_matches('/');
+        match self {
+            MirrorKind::Invidious => format!("{}/api/v1/videos/{}", instance, id),
+            MirrorKind::Piped => format!("{}/streams/{}", instance, id),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvidiousThumbnail {
+    url: String,
+    #[serde(default)]
+    width: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvidiousVideo {
+    title: String,
+    #[serde(default)]
+    author: String,
+    author_url: Option<String>,
+    length_seconds: Option<f64>,
+    view_count: Option<u64>,
+    #[serde(default)]
+    video_thumbnails: Vec<InvidiousThumbnail>,
+    // Unix time
+    published: Option<i64>,
+    #[serde(default)]
+    live_now: bool,
+    #[serde(default)]
+    is_upcoming: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PipedVideo {
+    title: String,
+    #[serde(default)]
+    uploader: String,
+    uploader_url: Option<String>,
+    duration: Option<f64>,
+    views: Option<u64>,
+    thumbnail_url: Option<String>,
+    // "2024-01-31"
+    upload_date: Option<String>,
+    #[serde(default)]
+    livestream: bool,
+}
+
+// The frontends give channel links relative to themselves
+fn youtube_channel_url(path: Option<String>) -> Option<String> {
+    path.filter(|path| path.starts_with('/')).map(|path| format!("https://www.youtube.com{}", path))
+}
+
+// Thumbnail links can be relative to the instance as well
+fn absolute_url(instance: &str, url: String) -> String {
+    if url.starts_with("//") {
+        format!("https:{}", url)
+    } else if url.starts_with('/') {
+        format!("{}{}", instance.trim().trim_end_matches('/'), url)
+    } else {
+        url
+    }
+}
+
+// What both frontends report, in the app's terms
+struct MirrorVideo {
+    title: String,
+    uploader: String,
+    channel_url: Option<String>,
+    duration_seconds: Option<f64>,
+    view_count: Option<u64>,
+    thumbnail: Option<String>,
+    upload_date: Option<String>,
+    live_status: LiveStatus,
+}
+
+impl MirrorVideo {
+    fn into_video_info(self, id: &str) -> Box<VideoInfo> {
+        let mut template_fields = vec![
+            ("id".to_string(), id.to_string()),
+            ("title".to_string(), self.title.clone()),
+            ("uploader".to_string(), self.uploader.clone()),
+        ];
+        if let Some(date) = &self.upload_date {
+            template_fields.push(("upload_date".to_string(), date.clone()));
+        }
+
+        Box::new(VideoInfo {
+            id: Some(id.to_string()),
+            title: self.title,
+            duration: format_duration(self.duration_seconds.unwrap_or(0.0)),
+            duration_seconds: self.duration_seconds,
+            uploader: self.uploader,
+            channel_url: self.channel_url,
+            view_count: self.view_count,
+            thumbnail: self.thumbnail,
+            availability: None,
+            // The frontends' caption lists don't say which ones are automatic
+            subtitles: Vec::new(),
+            upload_date: self.upload_date,
+            live_status: self.live_status,
+            chapters: Vec::new(),
+            best_quality: None,
+            formats: Vec::new(),
+            template_fields,
+        })
+    }
+}
+
+async fn fetch(kind: MirrorKind, instance: &str, id: &str, settings: &Settings) -> Result<Box<VideoInfo>> {
+    let mut client = certificates::client_builder(settings)
+        .user_agent("ytmp3-downloader")
+        .timeout(MIRROR_TIMEOUT);
+    if let Some(proxy) = settings.active_proxy() {
+        client = client.proxy(proxy.client_proxy()?);
+    }
+    let response = client.build()?.get(kind.api_url(instance, id)).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!("{} answered with HTTP {}", instance, response.status()));
+    }
+
+    let video = match kind {
+        MirrorKind::Invidious => {
+            let video: InvidiousVideo = response.json().await?;
+            let thumbnail = video.video_thumbnails.into_iter().max_by_key(|thumbnail| thumbnail.width);
+            let live_status = if video.live_now {
+                LiveStatus::IsLive
+            } else if video.is_upcoming {
+                LiveStatus::IsUpcoming
+            } else {
+                LiveStatus::NotLive
+            };
+            MirrorVideo {
+                title: video.title,
+                uploader: video.author,
+                channel_url: youtube_channel_url(video.author_url),
+                duration_seconds: video.length_seconds,
+                view_count: video.view_count,
+                thumbnail: thumbnail.map(|thumbnail| absolute_url(instance, thumbnail.url)),
+                upload_date: video.published
+                    .and_then(|time| chrono::DateTime::from_timestamp(time, 0))
+                    .map(|time| time.format("%Y%m%d").to_string()),
+                live_status,
+            }
+        }
+        MirrorKind::Piped => {
+            let video: PipedVideo = response.json().await?;
+            let live_status = if video.livestream { LiveStatus::IsLive } else { LiveStatus::NotLive };
+            MirrorVideo {
+                title: video.title,
+                uploader: video.uploader,
+                channel_url: youtube_channel_url(video.uploader_url),
+                // Piped reports -1 for live streams
+                duration_seconds: video.duration.filter(|duration| *duration > 0.0),
+                view_count: video.views,
+                thumbnail: video.thumbnail_url.map(|url| absolute_url(instance, url)),
+                upload_date: video.upload_date.map(|date| date.replace('-', "")).filter(|date| date.len() == 8),
+                live_status,
+            }
+        }
+    };
+    Ok(video.into_video_info(id))
+}
+
+// Tried when YouTube turns the fetch down with its bot check or rate limit; None keeps yt-dlp's error
+pub fn fallback(url: &str, settings: &Settings, error: &str, progress_sender: &AppSender) -> Option<Box<VideoInfo>> {
+    let instance = settings.mirror_url.trim();
+    if instance.is_empty() || !is_blocked_error(error) {
+        return None;
+    }
+    let id = youtube_video_id(url)?;
+
+    let kind = settings.mirror_kind;
+    progress_sender.send(AppMessage::ConsoleOutput(format!(
+        "YouTube refused the request, trying the {} instance at {}",
+        kind.label(),
+        instance
+    ))).ok();
+
+    let rt = tokio::runtime::Runtime::new().ok()?;
+    match rt.block_on(fetch(kind, instance, &id, settings)) {
+        Ok(info) => {
+            progress_sender.send(AppMessage::ConsoleOutput(format!(
+                "Fetched video information through {} ({}) instead of YouTube",
+                kind.label(),
+                instance
+            ))).ok();
+            Some(info)
+        }
+        Err(e) => {
+            progress_sender.send(AppMessage::ConsoleOutput(format!("The {} instance failed too: {}", kind.label(), e))).ok();
+            None
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDate;
+use eframe::egui;
+use regex::Regex;
+use std::path::Path;
+
+use crate::a11y::AccessibleName;
+use crate::help;
+use crate::history::History;
+use crate::settings::{IdInFilename, Settings, TitleCleanup, UploadDateInFilename};
+use crate::{AppState, VideoInfo, YtMp3App};
+
+// The patterns are written so Python's re (which yt-dlp uses) and the regex crate (for the
+// preview) read them the same way
+const OFFICIAL_TAGS: &str = r"(?i)\s*[(\[](official\s*)?(music\s*|lyrics?\s*)?(video|audio|visualizer|lyrics?)[)\]]";
+const QUALITY_TAGS: &str =
+    r"(?i)\s*([(\[]\s*(4k|8k|uhd|hd|hq|hdr|2160p|1440p|1080p|720p|60\s?fps)\s*[)\]]|\b(4k|8k|uhd|2160p|1440p|1080p|720p|60\s?fps)\b)";
+const ALL_BRACKETS: &str = r"\s*(\([^)]*\)|\[[^\]]*\])";
+const EMOJI: &str = r"[\U0001F000-\U0001FAFF\u2600-\u27BF\uFE0F\u200D]";
+// Run last: separators and spaces left dangling by the removals above
+const TIDY_UP: &[(&str, &str)] = &[(r"\s{2,}", " "), (r"^[\s\-|–—]+|[\s\-|–—]+$", "")];
+
+// (pattern, replacement) pairs in the order they are applied
+fn cleanup_rules(cleanup: &TitleCleanup) -> Vec<(String, &'static str)> {
+    let mut rules = Vec::new();
+    if cleanup.official_tags {
+        rules.push((OFFICIAL_TAGS.to_string(), ""));
+    }
+    if cleanup.quality_tags {
+        rules.push((QUALITY_TAGS.to_string(), ""));
+    }
+    if cleanup.all_brackets {
+        rules.push((ALL_BRACKETS.to_string(), ""));
+    }
+    if cleanup.emoji {
+        rules.push((EMOJI.to_string(), ""));
+    }
+    rules.extend(
+        cleanup.custom_patterns.iter()
+            .filter(|pattern| !pattern.trim().is_empty())
+            .map(|pattern| (pattern.clone(), "")),
+    );
+    if !rules.is_empty() {
+        rules.extend(TIDY_UP.iter().map(|(pattern, replacement)| (pattern.to_string(), *replacement)));
+    }
+    rules
+}
+
+// --replace-in-metadata runs before the file name is made, so the cleaned title also ends up in embedded metadata
+pub fn title_cleanup_args(cleanup: &TitleCleanup) -> Vec<String> {
+    cleanup_rules(cleanup)
+        .into_iter()
+        .flat_map(|(pattern, replacement)| {
+            ["--replace-in-metadata".to_string(), "title".to_string(), pattern, replacement.to_string()]
+        })
+        .collect()
+}
+
+// What yt-dlp will make of the title; Err names a custom pattern the preview can't run
+pub fn clean_title(title: &str, cleanup: &TitleCleanup) -> Result<String, String> {
+    let mut cleaned = title.to_string();
+    for (pattern, replacement) in cleanup_rules(cleanup) {
+        let regex = Regex::new(&pattern).map_err(|_| pattern.clone())?;
+        cleaned = regex.replace_all(&cleaned, replacement).to_string();
+    }
+    Ok(cleaned)
+}
+
+// How yt-dlp's default (non --restrict-filenames) sanitizing turns a title into a file name,
+// close enough to find an existing file with the same title
+fn approx_filename(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| match c {
+            '/' => '⧸',
+            '\\' if cfg!(target_os = "windows") => '⧹',
+            ':' if cfg!(target_os = "windows") => '：',
+            '*' if cfg!(target_os = "windows") => '＊',
+            '?' if cfg!(target_os = "windows") => '？',
+            '"' if cfg!(target_os = "windows") => '＂',
+            '<' if cfg!(target_os = "windows") => '＜',
+            '>' if cfg!(target_os = "windows") => '＞',
+            '|' if cfg!(target_os = "windows") => '｜',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+// Fields offered in the template field list before a video is fetched: (name, description)
+const COMMON_TEMPLATE_FIELDS: &[(&str, &str)] = &[
+    ("title", "Video title"),
+    ("id", "Video ID"),
+    ("uploader", "Name of the uploader"),
+    ("channel", "Channel name"),
+    ("upload_date", "Upload date as YYYYMMDD, or %(upload_date>%Y-%m-%d)s"),
+    ("duration_string", "Length, like 3:32"),
+    ("view_count", "Number of views"),
+    ("playlist_title", "Playlist the video was downloaded from"),
+    ("playlist_index", "Position in the playlist"),
+    ("resolution", "Video size, like 1920x1080"),
+    ("fps", "Frame rate"),
+    ("language", "Language of the video"),
+    ("extractor", "Site, like youtube"),
+];
+
+// A strftime format from the settings or a template. chrono panics when it prints a date with
+// an invalid one, so it's checked first
+pub fn is_valid_date_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+// yt-dlp's YYYYMMDD upload date in another format; None when either can't be read
+fn format_upload_date(date: &str, format: &str) -> Option<String> {
+    let date = NaiveDate::parse_from_str(date, "%Y%m%d").ok()?;
+    is_valid_date_format(format).then(|| date.format(format).to_string())
+}
+
+// The custom file name template without the extension, which the app adds itself
+pub fn custom_template(settings: &Settings) -> Option<String> {
+    let template = settings.filename_template.trim();
+    let template = template.strip_suffix(".%(ext)s").unwrap_or(template);
+    (!template.is_empty()).then(|| template.to_string())
+}
+
+// Fills in a template the way yt-dlp will, as far as the fetched info allows: %(name)s,
+// %(name).Ns cut to N characters and %(upload_date>FORMAT)s. Unknown fields become "NA"
+fn render_template(template: &str, video_info: &VideoInfo, title: &str) -> String {
+    let field_regex = Regex::new(r"%\(([A-Za-z0-9_]+)(?:>([^)]*))?\)[-#0+ ]*\d*(?:\.(\d+))?[sdf]|%%").unwrap();
+    field_regex.replace_all(template, |captures: &regex::Captures| {
+        let Some(name) = captures.get(1) else {
+            return "%".to_string();
+        };
+        let value = match name.as_str() {
+            "title" => Some(title.to_string()),
+            "id" => video_info.id.clone(),
+            "uploader" => Some(video_info.uploader.clone()),
+            "upload_date" => video_info.upload_date.clone(),
+            name => video_info.template_fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.clone()),
+        };
+        let value = match (value, captures.get(2)) {
+            (Some(date), Some(format)) => format_upload_date(&date, format.as_str()).unwrap_or(date),
+            (value, _) => value.unwrap_or_else(|| "NA".to_string()),
+        };
+        match captures.get(3).and_then(|limit| limit.as_str().parse::<usize>().ok()) {
+            Some(limit) => value.chars().take(limit).collect(),
+            None => value,
+        }
+    })
+    .to_string()
+}
+
+// The file name (without extension) the download template will produce, as far as it can be known up front
+pub fn preview_file_name(video_info: &VideoInfo, settings: &Settings) -> Result<String, String> {
+    let mut name = clean_title(&video_info.title, &settings.title_cleanup)?;
+    if let Some(template) = custom_template(settings) {
+        return Ok(render_template(&template, video_info, &name));
+    }
+    if settings.id_in_filename == IdInFilename::Always {
+        name = format!("{} [{}]", name, video_info.id.as_deref().unwrap_or("NA"));
+    }
+    if settings.upload_date_in_filename != UploadDateInFilename::Off && !is_valid_date_format(&settings.upload_date_format) {
+        return Err(format!("\"{}\" is not a valid upload date format", settings.upload_date_format));
+    }
+    let date = video_info
+        .upload_date
+        .as_deref()
+        .and_then(|date| format_upload_date(date, &settings.upload_date_format))
+        .unwrap_or_else(|| "NA".to_string());
+    Ok(match settings.upload_date_in_filename {
+        UploadDateInFilename::Off => name,
+        UploadDateInFilename::Before => format!("{} - {}", date, name),
+        UploadDateInFilename::After => format!("{} ({})", name, date),
+    })
+}
+
+// Whether the folder has a file named after this title that belongs to a different video.
+// A file the history knows as this very video is not a collision, the overwrite policy handles that
+pub fn title_collides(folder: &str, title: &str, video_id: Option<&str>, history: &History) -> bool {
+    let name = approx_filename(title);
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return false;
+    };
+    let existing: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.file_stem().is_some_and(|stem| stem.to_string_lossy() == name))
+        .collect();
+    if existing.is_empty() {
+        return false;
+    }
+
+    let Some(video_id) = video_id else {
+        return true;
+    };
+    !existing.iter().all(|path| {
+        history.entries.iter().any(|entry| {
+            entry.video_id.as_deref() == Some(video_id) && Path::new(&entry.path) == path.as_path()
+        })
+    })
+}
+
+impl YtMp3App {
+    // Lives outside Settings::ui so the preview can use the fetched video's title
+    pub(crate) fn show_title_cleanup_settings(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🧹 Title cleanup (file names and metadata):", "title_cleanup");
+                ui.add_space(5.0);
+                let cleanup = &mut self.settings.title_cleanup;
+                ui.checkbox(&mut cleanup.official_tags, "Remove \"(Official Video)\", \"[Lyrics]\", \"(Audio)\"");
+                ui.checkbox(&mut cleanup.quality_tags, "Remove quality tags like \"[4K]\", \"(HD)\", \"1080p\"");
+                ui.checkbox(&mut cleanup.all_brackets, "Remove everything in brackets");
+                ui.checkbox(&mut cleanup.emoji, "Remove emoji");
+
+                ui.label("Extra patterns to remove (regular expressions):");
+                let mut remove = None;
+                for (index, pattern) in cleanup.custom_patterns.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(pattern)
+                            .desired_width(260.0)
+                            .font(egui::TextStyle::Monospace)
+                            .hint_text(r"e.g. \s*\| Topic$"));
+                        if ui.button("🗑").accessible_name("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = remove {
+                    cleanup.custom_patterns.remove(index);
+                }
+                if ui.button("➕ Add pattern").clicked() {
+                    cleanup.custom_patterns.push(String::new());
+                }
+
+                ui.add_space(5.0);
+                let sample = match &self.state {
+                    AppState::VideoInfo(video_info) => video_info.title.clone(),
+                    _ => "Artist - Song (Official Music Video) [4K] 🔥".to_string(),
+                };
+                ui.label(egui::RichText::new(format!("Before: {}", sample)).color(egui::Color32::GRAY));
+                match clean_title(&sample, &self.settings.title_cleanup) {
+                    Ok(cleaned) if cleaned.is_empty() => {
+                        ui.colored_label(egui::Color32::YELLOW, "⚠ After: nothing is left of the title");
+                    }
+                    Ok(cleaned) => {
+                        ui.label(format!("After:  {}", cleaned));
+                    }
+                    Err(pattern) => {
+                        ui.colored_label(egui::Color32::YELLOW, format!("⚠ Can't preview the pattern {}", pattern));
+                    }
+                }
+            });
+        });
+    }
+
+    pub(crate) fn show_filename_template_settings(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "📝 File name template:", "filename_template");
+                ui.add_space(5.0);
+                let output = egui::TextEdit::singleline(&mut self.settings.filename_template)
+                    .desired_width(400.0)
+                    .font(egui::TextStyle::Monospace)
+                    .hint_text("Built from the options above, e.g. %(uploader)s - %(title)s")
+                    .show(ui);
+                if let Some(range) = output.cursor_range {
+                    self.template_cursor = Some(range.primary.ccursor.index);
+                }
+
+                let template = self.settings.filename_template.trim();
+                if Path::new(template).is_absolute() {
+                    ui.colored_label(egui::Color32::YELLOW, "⚠ Use a name relative to the output folder, pick the folder on the main screen");
+                }
+                if !template.is_empty() {
+                    ui.label(egui::RichText::new("The upload date and video ID options above don't apply to a custom template")
+                        .small()
+                        .color(egui::Color32::GRAY));
+                    if let AppState::VideoInfo(video_info) = &self.state {
+                        if let Ok(name) = preview_file_name(video_info, &self.settings) {
+                            ui.label(format!("Preview: {}", name));
+                        }
+                    }
+                }
+
+                ui.collapsing("🔎 Available fields", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Search:");
+                        ui.add(egui::TextEdit::singleline(&mut self.template_field_search).desired_width(200.0));
+                    });
+
+                    // The fetched video's own fields, with their values, or the common ones
+                    let fields: Vec<(String, String)> = match &self.state {
+                        AppState::VideoInfo(video_info) if !video_info.template_fields.is_empty() => {
+                            video_info.template_fields.clone()
+                        }
+                        _ => COMMON_TEMPLATE_FIELDS
+                            .iter()
+                            .map(|(name, description)| (name.to_string(), description.to_string()))
+                            .collect(),
+                    };
+                    if !matches!(self.state, AppState::VideoInfo(_)) {
+                        ui.label(egui::RichText::new("Fetch a video to see every field it has, with its values")
+                            .small()
+                            .color(egui::Color32::GRAY));
+                    }
+
+                    let search = self.template_field_search.to_lowercase();
+                    let mut insert = None;
+                    egui::ScrollArea::vertical().max_height(200.0).id_source("template_fields").show(ui, |ui| {
+                        egui::Grid::new("template_fields_grid").num_columns(2).spacing([10.0, 2.0]).show(ui, |ui| {
+                            for (name, value) in fields.iter().filter(|(name, value)| {
+                                search.is_empty()
+                                    || name.to_lowercase().contains(&search)
+                                    || value.to_lowercase().contains(&search)
+                            }) {
+                                let field = format!("%({})s", name);
+                                if ui.button(egui::RichText::new(&field).monospace())
+                                    .on_hover_text("Insert into the template")
+                                    .clicked()
+                                {
+                                    insert = Some(field);
+                                }
+                                ui.label(egui::RichText::new(value).color(egui::Color32::GRAY));
+                                ui.end_row();
+                            }
+                        });
+                    });
+
+                    if let Some(field) = insert {
+                        let template = &mut self.settings.filename_template;
+                        let position = self.template_cursor
+                            .and_then(|index| template.char_indices().nth(index).map(|(byte, _)| byte))
+                            .unwrap_or(template.len());
+                        template.insert_str(position, &field);
+                        self.template_cursor = Some(template[..position + field.len()].chars().count());
+                    }
+                });
+            });
+        });
+    }
+}
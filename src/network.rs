@@ -0,0 +1,184 @@
+use eframe::egui;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::help;
+use crate::{AppState, YtMp3App};
+
+// Without a proxy, being able to open a connection to YouTube counts as online
+const CHECK_HOST: &str = "www.youtube.com";
+const CHECK_PORT: u16 = 443;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// Checked less often while things work, and often while offline so the queue resumes quickly
+const ONLINE_INTERVAL: Duration = Duration::from_secs(30);
+const OFFLINE_INTERVAL: Duration = Duration::from_secs(5);
+const TICK: Duration = Duration::from_millis(250);
+
+// The host to try: the proxy when downloads go through one, since a network that only allows
+// the proxy out would otherwise look offline
+fn check_target(proxy_url: Option<&str>) -> (String, u16) {
+    let Some(url) = proxy_url.and_then(|url| reqwest::Url::parse(url).ok()) else {
+        return (CHECK_HOST.to_string(), CHECK_PORT);
+    };
+    let Some(host) = url.host_str() else {
+        return (CHECK_HOST.to_string(), CHECK_PORT);
+    };
+    let port = url.port_or_known_default().unwrap_or(if url.scheme().starts_with("socks") { 1080 } else { 8080 });
+    (host.trim_matches(|c| c == '[' || c == ']').to_string(), port)
+}
+
+// DNS failing counts as offline as well
+fn can_connect(host: &str, port: u16) -> bool {
+    let Ok(addrs) = (host, port).to_socket_addrs() else {
+        return false;
+    };
+    addrs.take(3).any(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok())
+}
+
+#[derive(Default)]
+struct Status {
+    // None until the first check finishes
+    online: Option<bool>,
+    proxy_url: Option<String>,
+}
+
+// A background thread that keeps checking whether the internet can be reached;
+// dropping it stops the checks
+pub struct Connectivity {
+    status: Arc<Mutex<Status>>,
+    recheck: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Connectivity {
+    pub fn start(ctx: egui::Context, proxy_url: Option<String>) -> Self {
+        let status = Arc::new(Mutex::new(Status { online: None, proxy_url }));
+        let recheck = Arc::new(AtomicBool::new(true));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_status, thread_recheck, thread_stop) = (status.clone(), recheck.clone(), stop.clone());
+        thread::spawn(move || {
+            let mut waited = Duration::ZERO;
+            let mut interval = Duration::ZERO;
+            while !thread_stop.load(Ordering::Relaxed) {
+                if thread_recheck.swap(false, Ordering::Relaxed) || waited >= interval {
+                    let proxy_url = thread_status.lock().unwrap().proxy_url.clone();
+                    let (host, port) = check_target(proxy_url.as_deref());
+                    let online = can_connect(&host, port);
+                    let changed = thread_status.lock().unwrap().online.replace(online) != Some(online);
+                    if changed {
+                        ctx.request_repaint();
+                    }
+                    interval = if online { ONLINE_INTERVAL } else { OFFLINE_INTERVAL };
+                    waited = Duration::ZERO;
+                }
+                thread::sleep(TICK);
+                waited += TICK;
+            }
+        });
+
+        Self { status, recheck, stop }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.status.lock().unwrap().online == Some(false)
+    }
+
+    // Checks again right away when downloads start going through another proxy
+    pub fn set_proxy(&self, proxy_url: Option<&str>) {
+        let mut status = self.status.lock().unwrap();
+        if status.proxy_url.as_deref() != proxy_url {
+            status.proxy_url = proxy_url.map(str::to_string);
+            self.recheck.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn check_now(&self) {
+        self.recheck.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Connectivity {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl YtMp3App {
+    pub(crate) fn is_offline(&self) -> bool {
+        self.connectivity.as_ref().is_some_and(|connectivity| connectivity.is_offline())
+    }
+
+    // The proxy the next fetch would use, without building its settings
+    fn current_proxy_url(&self) -> Option<&str> {
+        let proxy = match self.proxy.as_deref() {
+            Some("") => None,
+            Some(name) => self.settings.proxies.iter().find(|proxy| proxy.name == name),
+            None => self.settings.active_proxy(),
+        };
+        proxy.map(|proxy| proxy.url.as_str())
+    }
+
+    pub(crate) fn apply_offline_detection_settings(&mut self, ctx: &egui::Context) {
+        self.connectivity = self.settings.offline_detection
+            .then(|| Connectivity::start(ctx.clone(), self.current_proxy_url().map(str::to_string)));
+        self.was_offline = false;
+    }
+
+    // Called every frame; picks the queue up again when the connection comes back
+    pub(crate) fn poll_connectivity(&mut self) {
+        let Some(connectivity) = &self.connectivity else {
+            return;
+        };
+        connectivity.set_proxy(self.current_proxy_url());
+
+        let offline = self.is_offline();
+        if offline == self.was_offline {
+            return;
+        }
+        self.was_offline = offline;
+        if offline {
+            self.log_console("No internet connection, holding the queue until it's back".to_string());
+            return;
+        }
+
+        self.log_console("Back online".to_string());
+        let idle = self.receiver.is_none()
+            && matches!(self.state, AppState::Input | AppState::Error(_) | AppState::Success(_));
+        if idle && self.download_queue.is_empty() && !self.pending_urls.is_empty() {
+            self.load_next_pending_url();
+        }
+    }
+
+    pub(crate) fn show_offline_banner(&mut self, ui: &mut egui::Ui) {
+        if !self.is_offline() {
+            return;
+        }
+        let mut check_now = false;
+        egui::Frame::none()
+            .fill(egui::Color32::from_rgb(90, 60, 0))
+            .rounding(4.0)
+            .inner_margin(8.0)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        egui::Color32::WHITE,
+                        "📡 No internet connection. Fetching and downloading are paused and queued downloads continue when the connection is back.",
+                    );
+                    if ui.button("Check again").clicked() {
+                        check_now = true;
+                    }
+                    help::help_button(ui, "offline");
+                });
+            });
+        ui.add_space(5.0);
+        if check_now {
+            if let Some(connectivity) = &self.connectivity {
+                connectivity.check_now();
+            }
+        }
+    }
+}
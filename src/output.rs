@@ -0,0 +1,69 @@
+use regex::Regex;
+use std::io::{BufRead, BufReader, ErrorKind, Read};
+use std::sync::OnceLock;
+
+// CSI sequences (colors, cursor movement) and OSC sequences (window titles) that
+// yt-dlp and ffmpeg emit when they think they are talking to a terminal
+fn ansi_regex() -> &'static Regex {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    ANSI.get_or_init(|| {
+        Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]").unwrap()
+    })
+}
+
+pub fn strip_ansi(text: &str) -> String {
+    ansi_regex().replace_all(text, "").to_string()
+}
+
+// Turns raw process output into displayable text, whatever encoding it arrived in
+pub fn clean_output(bytes: &[u8]) -> String {
+    strip_ansi(&String::from_utf8_lossy(bytes))
+}
+
+// Calls on_line for every non-empty line of the stream. Both "\n" and a bare "\r" end a
+// line, so in-place progress updates arrive as they happen instead of when the line ends.
+// Unlike BufRead::lines this never stops early on invalid UTF-8
+pub fn for_each_line<R: Read>(reader: R, mut on_line: impl FnMut(String)) {
+    let mut reader = BufReader::new(reader);
+    let mut pending = Vec::new();
+
+    loop {
+        let available = match reader.fill_buf() {
+            Ok([]) => break,
+            Ok(available) => available,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        };
+
+        let (consumed, line_ended) = match available.iter().position(|byte| *byte == b'\n' || *byte == b'\r') {
+            Some(pos) => {
+                pending.extend_from_slice(&available[..pos]);
+                (pos + 1, true)
+            }
+            None => {
+                pending.extend_from_slice(available);
+                (available.len(), false)
+            }
+        };
+        reader.consume(consumed);
+
+        if line_ended {
+            emit_line(&mut pending, &mut on_line);
+        }
+    }
+
+    emit_line(&mut pending, &mut on_line);
+}
+
+fn emit_line(pending: &mut Vec<u8>, on_line: &mut impl FnMut(String)) {
+    let line = clean_output(pending).trim_end().to_string();
+    pending.clear();
+    if !line.trim().is_empty() {
+        on_line(line);
+    }
+}
+
+// Progress updates replace each other in the console instead of stacking up
+pub fn is_progress_line(line: &str) -> bool {
+    crate::progress::parse_download_line(line).is_some()
+}
@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::events;
+use crate::{AutoDownload, DownloadFormat, YtMp3App};
+
+// A pairing code shown in Settings is good for this long, and for this many wrong guesses
+const CODE_LIFETIME: Duration = Duration::from_secs(300);
+const MAX_CODE_ATTEMPTS: u32 = 5;
+const MAX_NAME_LEN: usize = 60;
+
+// Routes browser extensions and remote instances use; the only ones open to extension origins
+const HELLO_PATH: &str = "/pair/hello";
+const PAIR_PATH: &str = "/pair";
+const ENQUEUE_PATH: &str = "/enqueue";
+
+// A browser extension or another instance that traded a pairing code for a token. Only the
+// token's hash is saved, so it can't be read from config.toml
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairedExtension {
+    pub name: String,
+    pub token_hash: String,
+    // Unix timestamp (seconds)
+    pub paired_at: i64,
+}
+
+// What the listener hands to the app
+pub enum ExtensionRequest {
+    Paired(PairedExtension),
+    // No format = the one chosen in the app
+    Enqueue { url: String, format: Option<DownloadFormat>, extension: String },
+}
+
+struct PairingCode {
+    code: String,
+    expires: Instant,
+    attempts: u32,
+}
+
+#[derive(Default)]
+struct PairingState {
+    code: Option<PairingCode>,
+    // Token hash and extension name
+    paired: Vec<(String, String)>,
+}
+
+// Shared by the listener's threads and the settings
+fn state() -> &'static Mutex<PairingState> {
+    static STATE: OnceLock<Mutex<PairingState>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(PairingState::default()))
+}
+
+fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(format!("ytmp3-extension:{}", token).as_bytes()))
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).expect("The system's random number generator is not available");
+    bytes
+}
+
+// Tokens the app accepts; called at startup and whenever the saved list changes
+pub fn set_paired(extensions: &[PairedExtension]) {
+    state().lock().unwrap().paired = extensions
+        .iter()
+        .map(|extension| (extension.token_hash.clone(), extension.name.clone()))
+        .collect();
+}
+
+// A new six-digit code for the user to type into the extension or remote; replaces any earlier one
+pub fn start() {
+    let number = u32::from_le_bytes(random_bytes()) % 1_000_000;
+    state().lock().unwrap().code = Some(PairingCode {
+        code: format!("{:06}", number),
+        expires: Instant::now() + CODE_LIFETIME,
+        attempts: 0,
+    });
+}
+
+pub fn cancel() {
+    state().lock().unwrap().code = None;
+}
+
+// The code and the time it has left, while pairing
+pub fn active_code() -> Option<(String, Duration)> {
+    let mut state = state().lock().unwrap();
+    let left = state.code.as_ref()?.expires.checked_duration_since(Instant::now());
+    match left {
+        Some(left) => state.code.as_ref().map(|code| (code.code.clone(), left)),
+        None => {
+            state.code = None;
+            None
+        }
+    }
+}
+
+// Trades the code for a new token; the code works once
+fn redeem(code: &str, name: &str) -> Result<(String, PairedExtension), &'static str> {
+    let mut state = state().lock().unwrap();
+    let Some(pairing) = state.code.as_mut().filter(|pairing| pairing.expires > Instant::now()) else {
+        state.code = None;
+        return Err("Not pairing. Click \"Pair a device\" in the app's settings first");
+    };
+    if pairing.code != code.trim() {
+        pairing.attempts += 1;
+        if pairing.attempts >= MAX_CODE_ATTEMPTS {
+            state.code = None;
+            return Err("Wrong code too many times. Start pairing again in the app");
+        }
+        return Err("Wrong code");
+    }
+
+    state.code = None;
+    let token: String = random_bytes::<32>().iter().map(|byte| format!("{:02x}", byte)).collect();
+    let extension = PairedExtension {
+        name: name.to_string(),
+        token_hash: token_hash(&token),
+        paired_at: chrono::Utc::now().timestamp(),
+    };
+    state.paired.push((extension.token_hash.clone(), extension.name.clone()));
+    Ok((token, extension))
+}
+
+// The name of the extension the token was given to
+fn extension_for(authorization: Option<&str>) -> Option<String> {
+    let token = authorization?.trim().strip_prefix("Bearer ")?.trim();
+    let hash = token_hash(token);
+    let state = state().lock().unwrap();
+    state.paired.iter().find(|(paired, _)| *paired == hash).map(|(_, name)| name.clone())
+}
+
+pub fn is_extension_origin(origin: &str) -> bool {
+    let origin = origin.trim().to_ascii_lowercase();
+    ["chrome-extension://", "moz-extension://", "safari-web-extension://"]
+        .iter()
+        .any(|scheme| origin.starts_with(scheme))
+}
+
+pub fn is_extension_path(path: &str) -> bool {
+    matches!(path, HELLO_PATH | PAIR_PATH | ENQUEUE_PATH)
+}
+
+// Open to everyone who can connect, so new devices can pair
+pub fn is_pairing_path(path: &str) -> bool {
+    matches!(path, HELLO_PATH | PAIR_PATH)
+}
+
+pub fn is_paired(authorization: Option<&str>) -> bool {
+    extension_for(authorization).is_some()
+}
+
+fn field<'a>(body: &'a serde_json::Value, name: &str) -> &'a str {
+    body.get(name).and_then(|value| value.as_str()).unwrap_or_default().trim()
+}
+
+// The extension's routes on the local API. The extension finds the app with GET /pair/hello,
+// POSTs {"code", "name"} to /pair for a token, then POSTs {"url"} to /enqueue with
+// "Authorization: Bearer <token>"; "format" may be "mp3" or "mp4". None for paths that aren't
+// the extension's
+pub fn serve(
+    method: &str,
+    path: &str,
+    authorization: Option<&str>,
+    body: &str,
+    send: impl Fn(ExtensionRequest),
+) -> Option<(&'static str, serde_json::Value)> {
+    if !is_extension_path(path) {
+        return None;
+    }
+    let expected = if path == HELLO_PATH { "GET" } else { "POST" };
+    if method != expected {
+        return Some(("405 Method Not Allowed", serde_json::json!({ "ok": false, "error": format!("Use {}", expected) })));
+    }
+    let body: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+
+    Some(match path {
+        HELLO_PATH => (
+            "200 OK",
+            serde_json::json!({
+                "ok": true,
+                "app": crate::APP_TITLE,
+                "version": env!("CARGO_PKG_VERSION"),
+                "pairing": active_code().is_some(),
+            }),
+        ),
+        PAIR_PATH => {
+            let name: String = match field(&body, "name") {
+                "" => "Browser extension".to_string(),
+                name => name.chars().take(MAX_NAME_LEN).collect(),
+            };
+            match redeem(field(&body, "code"), &name) {
+                Ok((token, extension)) => {
+                    send(ExtensionRequest::Paired(extension));
+                    ("200 OK", serde_json::json!({ "ok": true, "token": token }))
+                }
+                Err(error) => ("403 Forbidden", serde_json::json!({ "ok": false, "error": error })),
+            }
+        }
+        _ => match extension_for(authorization) {
+            None => ("401 Unauthorized", serde_json::json!({ "ok": false, "error": "Not paired. Pair the extension again" })),
+            Some(extension) => match field(&body, "url") {
+                "" => ("400 Bad Request", serde_json::json!({ "ok": false, "error": "No url" })),
+                url => {
+                    let format = [DownloadFormat::Mp3, DownloadFormat::Mp4]
+                        .into_iter()
+                        .find(|format| events::format_name(*format) == field(&body, "format"));
+                    send(ExtensionRequest::Enqueue { url: url.to_string(), format, extension });
+                    ("202 Accepted", serde_json::json!({ "ok": true, "url": url }))
+                }
+            },
+        },
+    })
+}
+
+impl YtMp3App {
+    pub(crate) fn handle_extension_request(&mut self, request: ExtensionRequest) {
+        match request {
+            ExtensionRequest::Paired(extension) => {
+                self.log_console(format!("Paired with {}", extension.name));
+                self.settings.paired_extensions.push(extension);
+                if let Err(e) = self.settings.save() {
+                    self.log_console(format!("WARNING: Could not save settings: {}", e));
+                }
+            }
+            ExtensionRequest::Enqueue { url, format, extension } => {
+                if !self.is_valid_youtube_url(&url) {
+                    self.log_console(format!("{}: not a supported URL: {}", extension, url));
+                    return;
+                }
+                // Queued like a typed URL: the current format, folder and device preset. Another
+                // format goes to that format's default folder. Without a window the fixed folder and
+                // format of simple mode stand in for the current ones
+                let (current_format, current_folder) = if self.daemon {
+                    (self.settings.simple_format, self.simple_folder())
+                } else {
+                    (self.download_format, self.output_path.clone())
+                };
+                let (format, output_path) = match format {
+                    _ if self.is_kiosk() => (self.settings.simple_format, self.simple_folder()),
+                    Some(format) if format != current_format => (format, self.settings.default_output_dir(format)),
+                    _ => (current_format, current_folder),
+                };
+                self.log_console(format!("{}: queued {}", extension, url));
+                self.queue_auto_downloads(vec![AutoDownload { url, format, output_path }]);
+            }
+        }
+    }
+}
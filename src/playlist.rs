@@ -0,0 +1,439 @@
+use eframe::egui;
+use serde::Deserialize;
+use regex::Regex;
+use std::process::Stdio;
+use std::sync::mpsc;
+use std::thread;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::commands::{self, CommandExit};
+use crate::settings::Settings;
+use crate::theme;
+use crate::{format_duration, help, output, process, restricted, ytdlp_command, AppState, AutoDownload, DownloadFormat, YtMp3App};
+
+// Entries waiting for the UI. When it's full the reader stops taking yt-dlp's output, and yt-dlp
+// waits until the pipe is read again
+const CHANNEL_CAPACITY: usize = 64;
+// Entries taken from the channel per frame, so a fast yt-dlp can't stall the UI either
+const ENTRIES_PER_FRAME: usize = 50;
+// URLs waiting in the app before no more are taken from the channel. Together with the channel
+// this keeps a playlist with thousands of videos from being held in memory all at once; the
+// rest is read as the queue gets shorter
+const MAX_WAITING: usize = 200;
+// The picker lists this many videos at most; the listing stops there
+const MAX_PICKER_ENTRIES: usize = 1000;
+
+// One line of `--flat-playlist --dump-json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaylistEntry {
+    pub id: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub availability: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    // Seconds; missing for live streams and some channel tabs
+    #[serde(default)]
+    pub duration: Option<f64>,
+}
+
+impl PlaylistEntry {
+    // Flat entries sometimes carry only the ID
+    pub fn watch_url(&self) -> String {
+        match &self.url {
+            Some(url) if url.starts_with("http") => url.clone(),
+            _ => format!("https://www.youtube.com/watch?v={}", self.id),
+        }
+    }
+}
+
+pub enum PlaylistEvent {
+    Entry(PlaylistEntry),
+    // Lines that weren't a video, e.g. an extractor warning
+    Skipped(String),
+    Finished(Result<(), String>),
+}
+
+pub struct PlaylistFetch {
+    pub url: String,
+    receiver: mpsc::Receiver<PlaylistEvent>,
+    pub received: usize,
+    // The queue template run whose digest the videos are counted in
+    digest: Option<String>,
+    // The videos go to the picker instead of straight to pending_urls
+    pick: bool,
+}
+
+// A video of the playlist being picked from
+pub struct PickerEntry {
+    pub entry: PlaylistEntry,
+    pub selected: bool,
+    // Already in the history
+    pub owned: bool,
+    pub lock: Option<restricted::LockKind>,
+}
+
+// The videos of a playlist pasted into the main view, to choose which of them to download
+pub struct PlaylistPicker {
+    pub url: String,
+    pub entries: Vec<PickerEntry>,
+    // The playlist has more videos than MAX_PICKER_ENTRIES
+    pub truncated: bool,
+}
+
+// Only pure playlist links and channel pages are expanded; a video opened from a playlist
+// stays a single video
+pub fn is_playlist_url(url: &str) -> bool {
+    (url.contains("youtube.com/playlist?") && url.contains("list=")) || channel_tab(url).is_some()
+}
+
+// (channel page, tab) of a channel link such as youtube.com/@name or youtube.com/channel/UC…/streams
+fn channel_tab(url: &str) -> Option<(String, Option<String>)> {
+    let channel_regex = Regex::new(
+        r"^((?:https?://)?(?:www\.|m\.)?youtube\.com/(?:@[^/?#]+|channel/[^/?#]+|c/[^/?#]+|user/[^/?#]+))(?:/([a-z]+))?/?(?:[?#].*)?$",
+    )
+    .unwrap();
+    let captures = channel_regex.captures(url.trim())?;
+    Some((captures[1].to_string(), captures.get(2).map(|tab| tab.as_str().to_string())))
+}
+
+// A channel's front page lists its tabs rather than videos, so it's read as its uploads
+fn listing_url(url: String) -> String {
+    match channel_tab(&url) {
+        Some((channel, None)) => format!("{}/videos", channel),
+        _ => url,
+    }
+}
+
+// `limit` reads only the first entries, e.g. a channel's latest uploads
+pub fn stream_entries(url: String, settings: Settings, limit: Option<usize>) -> mpsc::Receiver<PlaylistEvent> {
+    let (tx, rx) = mpsc::sync_channel(CHANNEL_CAPACITY);
+    thread::spawn(move || {
+        let mut args = settings.ytdlp_common_args();
+        args.extend(["--flat-playlist".to_string(), "--dump-json".to_string()]);
+        if let Some(limit) = limit {
+            args.extend(["--playlist-end".to_string(), limit.to_string()]);
+        }
+        args.push(url);
+        let mut command = ytdlp_command(&settings);
+        command.args(&args);
+        command.envs(settings.ytdlp_env());
+        command.stdout(Stdio::piped());
+        command.stderr(Stdio::piped());
+        #[cfg(target_os = "windows")]
+        command.creation_flags(0x08000000);
+
+        let command_id = commands::started(&command);
+        let mut tree = match process::ProcessTree::spawn(&mut command) {
+            Ok(tree) => tree,
+            Err(e) => {
+                commands::finished(command_id, CommandExit::FailedToStart(e.to_string()), "");
+                tx.send(PlaylistEvent::Finished(Err(format!("Could not run yt-dlp: {}", e)))).ok();
+                return;
+            }
+        };
+
+        let stderr = tree.child.stderr.take().unwrap();
+        let error_thread = thread::spawn(move || {
+            let mut error_lines = Vec::new();
+            output::for_each_line(stderr, |line| error_lines.push(line));
+            error_lines
+        });
+
+        // Each line is parsed and handed over as soon as it arrives; send blocks while the UI catches up
+        let mut cancelled = false;
+        let stdout = tree.child.stdout.take().unwrap();
+        output::for_each_line(stdout, |line| {
+            if cancelled {
+                return;
+            }
+            let event = match serde_json::from_str::<PlaylistEntry>(&line) {
+                Ok(entry) => PlaylistEvent::Entry(entry),
+                Err(_) => PlaylistEvent::Skipped(line),
+            };
+            // The receiver is gone when the user stopped reading the playlist
+            if tx.send(event).is_err() {
+                cancelled = true;
+                tree.kill();
+            }
+        });
+
+        let status = tree.child.wait();
+        let error_lines = error_thread.join().unwrap_or_default();
+        let exit = match &status {
+            Ok(status) if !cancelled => commands::exit_of(*status),
+            Ok(_) => CommandExit::Killed,
+            Err(e) => CommandExit::FailedToStart(e.to_string()),
+        };
+        commands::finished(command_id, exit, &error_lines.join("\n"));
+
+        let result = match status {
+            Ok(status) if status.success() => Ok(()),
+            _ => Err(error_lines
+                .iter()
+                .rev()
+                .find(|line| line.starts_with("ERROR"))
+                .cloned()
+                .unwrap_or_else(|| "yt-dlp could not read the playlist".to_string())),
+        };
+        tx.send(PlaylistEvent::Finished(result)).ok();
+    });
+    rx
+}
+
+impl YtMp3App {
+    // `pick` lists the videos in the picker; queue templates and the unattended modes never wait on it
+    pub(crate) fn start_playlist_fetch(&mut self, url: String, pick: bool) {
+        let settings = self.settings
+            .with_cookie_profile(self.cookie_profile.as_deref())
+            .with_proxy(self.proxy.as_deref());
+        let limit = self.take_playlist_limit(&url);
+        let digest = self.run_digests.take_source(&url);
+        let pick = pick && digest.is_none();
+        let url = listing_url(url);
+        self.log_console(format!("──── Reading playlist {} ────", url));
+        let receiver = stream_entries(url.clone(), settings, limit);
+        self.playlist_picker = pick.then(|| PlaylistPicker { url: url.clone(), entries: Vec::new(), truncated: false });
+        self.playlist_fetch = Some(PlaylistFetch { url, receiver, received: 0, digest, pick });
+    }
+
+    // Videos already taken from the playlist that haven't been dealt with yet
+    fn playlist_backlog(&self) -> usize {
+        match &self.playlist_picker {
+            Some(picker) => picker.entries.len(),
+            None => self.pending_urls.len() + self.download_queue.len(),
+        }
+    }
+
+    fn playlist_backlog_limit(&self) -> usize {
+        if self.playlist_picker.is_some() { MAX_PICKER_ENTRIES } else { MAX_WAITING }
+    }
+
+    // Called every frame: queues the playlist's videos as yt-dlp lists them
+    pub(crate) fn poll_playlist_fetch(&mut self) {
+        let room = self.playlist_backlog_limit().saturating_sub(self.playlist_backlog());
+        let Some(fetch) = &mut self.playlist_fetch else {
+            return;
+        };
+
+        let mut entries = Vec::new();
+        let mut messages = Vec::new();
+        let mut finished = None;
+        // Nothing is taken while the backlog is full, which holds up the reader and yt-dlp too
+        for _ in 0..ENTRIES_PER_FRAME.min(room) {
+            match fetch.receiver.try_recv() {
+                Ok(PlaylistEvent::Entry(entry)) => {
+                    fetch.received += 1;
+                    entries.push(entry);
+                }
+                Ok(PlaylistEvent::Skipped(line)) => messages.push(line),
+                Ok(PlaylistEvent::Finished(result)) => {
+                    finished = Some(result);
+                    break;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = Some(Err("Reading the playlist stopped unexpectedly".to_string()));
+                    break;
+                }
+            }
+        }
+        let received = fetch.received;
+        let digest = fetch.digest.clone();
+        let pick = fetch.pick;
+
+        for line in messages {
+            self.log_console(line);
+        }
+
+        if pick {
+            let picked: Vec<PickerEntry> = std::mem::take(&mut entries)
+                .into_iter()
+                .map(|entry| {
+                    let owned = self.history.find(None, &entry.watch_url()).is_some();
+                    let lock = restricted::lock_from_availability(entry.availability.as_deref());
+                    let skipped = owned || (lock.is_some() && self.settings.skip_locked_content);
+                    PickerEntry { entry, selected: !skipped, owned, lock }
+                })
+                .collect();
+            if let Some(picker) = &mut self.playlist_picker {
+                picker.entries.extend(picked);
+                // Stopped rather than held, the buttons wait for the listing to end
+                if picker.entries.len() >= MAX_PICKER_ENTRIES && finished.is_none() {
+                    picker.truncated = true;
+                    self.playlist_fetch = None;
+                    self.log_console(format!("Listed the first {} videos of the playlist", MAX_PICKER_ENTRIES));
+                    return;
+                }
+            }
+        }
+
+        let mut urls = Vec::new();
+        for entry in entries {
+            let url = entry.watch_url();
+            match restricted::lock_from_availability(entry.availability.as_deref()) {
+                Some(lock) if self.settings.skip_locked_content => self.skipped_locked.push((url, lock)),
+                _ => urls.push(url),
+            }
+        }
+        if let Some(template) = &digest {
+            let new = urls.iter().filter(|url| self.history.find(None, url).is_none()).map(|url| (url.clone(), None)).collect();
+            self.run_digests.add_items(template, new);
+        }
+        if !urls.is_empty() {
+            self.receive_urls(urls);
+        }
+
+        if let Some(result) = finished {
+            self.playlist_fetch = None;
+            if let Some(template) = &digest {
+                self.run_digests.source_done(template);
+            }
+            match result {
+                Ok(()) => self.log_console(format!("Playlist read: {} video(s)", received)),
+                Err(error) => {
+                    self.log_console(format!("ERROR: {}", error));
+                    if received == 0 {
+                        self.state = AppState::Error(format!("Could not read the playlist: {}", error));
+                        if pick {
+                            self.playlist_picker = None;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn show_playlist_fetch(&mut self, ui: &mut egui::Ui) {
+        let Some(fetch) = &self.playlist_fetch else {
+            return;
+        };
+        let mut cancel = false;
+        ui.horizontal(|ui| {
+            theme::busy_indicator(ui, self.settings.reduced_motion);
+            ui.label(format!("📃 Reading playlist... {} video(s) so far", fetch.received))
+                .on_hover_text(&fetch.url);
+            if self.playlist_backlog() >= self.playlist_backlog_limit() {
+                ui.label(egui::RichText::new("waiting for the queue to get shorter").small().color(egui::Color32::GRAY));
+            }
+            cancel = ui.button("Stop").clicked();
+        });
+        if cancel {
+            // Dropping the receiver makes the reader stop yt-dlp; what was listed so far stays queued
+            if let Some(template) = self.playlist_fetch.take().and_then(|fetch| fetch.digest) {
+                self.run_digests.source_done(&template);
+            }
+            self.log_console("Stopped reading the playlist".to_string());
+        }
+    }
+
+    pub(crate) fn show_playlist_picker(&mut self, ui: &mut egui::Ui) {
+        let Some(picker) = &mut self.playlist_picker else {
+            return;
+        };
+        let format = if self.download_format == DownloadFormat::Mp3 { "MP3" } else { "MP4" };
+        // Picking from a list that's still growing would leave out the rest of it
+        let listing = self.playlist_fetch.is_some();
+        let mut download = false;
+        let mut open = false;
+        let mut close = false;
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                let mut count = if picker.truncated {
+                    format!("📃 The first {} videos of the playlist", picker.entries.len())
+                } else {
+                    format!("📃 {} video(s) in the playlist", picker.entries.len())
+                };
+                let owned = picker.entries.iter().filter(|item| item.owned).count();
+                if owned > 0 {
+                    count.push_str(&format!(" — {} new, {} already in History", picker.entries.len() - owned, owned));
+                }
+                ui.label(egui::RichText::new(count).strong()).on_hover_text(&picker.url);
+                if ui.small_button("All").clicked() {
+                    picker.entries.iter_mut().for_each(|item| item.selected = true);
+                }
+                if ui.small_button("None").clicked() {
+                    picker.entries.iter_mut().for_each(|item| item.selected = false);
+                }
+                if picker.entries.iter().any(|item| item.owned)
+                    && ui.small_button("Only new").on_hover_text("Leave out the videos already in History").clicked()
+                {
+                    picker.entries.iter_mut().for_each(|item| item.selected = !item.owned);
+                }
+                help::help_button(ui, "playlist_picker");
+            });
+
+            egui::ScrollArea::vertical()
+                .id_source("playlist_picker")
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    for (index, item) in picker.entries.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            let title = item.entry.title.clone().unwrap_or_else(|| item.entry.id.clone());
+                            ui.checkbox(&mut item.selected, format!("{}. {}", index + 1, title))
+                                .on_hover_text(item.entry.watch_url());
+                            if let Some(duration) = item.entry.duration {
+                                ui.label(egui::RichText::new(format_duration(duration)).small().color(egui::Color32::GRAY));
+                            }
+                            if item.owned {
+                                ui.label(egui::RichText::new("📚 Already downloaded").small().color(egui::Color32::GRAY));
+                            }
+                            if let Some(lock) = item.lock {
+                                ui.label(egui::RichText::new(lock.label()).small().color(egui::Color32::GRAY));
+                            }
+                        });
+                    }
+                });
+
+            let selected: Vec<&PickerEntry> = picker.entries.iter().filter(|item| item.selected).collect();
+            let total: f64 = selected.iter().filter_map(|item| item.entry.duration).sum();
+            ui.horizontal(|ui| {
+                let ready = !selected.is_empty() && !listing;
+                let hint = |response: egui::Response| match listing {
+                    true => response.on_disabled_hover_text("Still listing the videos; Stop to pick from the ones listed so far"),
+                    false => response,
+                };
+                download = hint(ui.add_enabled(ready, egui::Button::new(format!("⬇ Download {} as {}", selected.len(), format))))
+                    .on_hover_text(format!("Queue them to {}", self.output_path))
+                    .clicked();
+                open = hint(ui.add_enabled(ready, egui::Button::new("⏭ Open one by one")))
+                    .on_hover_text("Load each video into the main view to look at it before downloading")
+                    .clicked();
+                close = ui.button("Close").clicked();
+                if total > 0.0 {
+                    ui.label(egui::RichText::new(format!("{} in total", format_duration(total))).small().color(egui::Color32::GRAY));
+                }
+            });
+        });
+
+        if !(download || open || close) {
+            return;
+        }
+        let Some(picker) = self.playlist_picker.take() else {
+            return;
+        };
+        if close && listing {
+            // Dropping the receiver makes the reader stop yt-dlp
+            self.playlist_fetch = None;
+            self.log_console("Stopped reading the playlist".to_string());
+        }
+        let chosen: Vec<PlaylistEntry> = picker.entries.into_iter().filter(|item| item.selected).map(|item| item.entry).collect();
+        if download {
+            self.log_console(format!("Queued {} video(s) from {}", chosen.len(), picker.url));
+            for entry in chosen {
+                let download = AutoDownload {
+                    url: entry.watch_url(),
+                    format: self.download_format,
+                    output_path: self.output_path.clone(),
+                };
+                self.download_queue.push_titled(download, entry.title);
+            }
+        } else if open {
+            self.receive_urls(chosen.iter().map(PlaylistEntry::watch_url).collect());
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::SystemTime;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::a11y::AccessibleName;
+use crate::commands;
+use crate::help;
+use crate::settings::Settings;
+use crate::{get_yt_dlp_path, output, ytdlp_command, YtMp3App};
+
+pub enum PluginDirSupport {
+    Unknown,
+    Checking(mpsc::Receiver<bool>),
+    Known(bool),
+}
+
+#[derive(Debug, Clone)]
+pub struct DetectedPlugin {
+    pub package: String,
+    // "extractor" or "postprocessor"
+    pub kind: String,
+    pub module: String,
+}
+
+// A yt-dlp build by path and modification time, so an updated one is asked again
+type Build = (PathBuf, Option<SystemTime>);
+
+// yt-dlp builds already asked about --plugin-dirs
+static PLUGIN_DIRS_SUPPORT: Mutex<Vec<(Build, bool)>> = Mutex::new(Vec::new());
+
+// yt-dlp plugins are namespace packages: <dir>/<package>/yt_dlp_plugins/{extractor,postprocessor}/*.py.
+// The chosen folder may also be a single package or the yt_dlp_plugins folder itself. These are the
+// package folders, the ones that go on Python's path
+pub fn package_dirs(dir: &str) -> Vec<PathBuf> {
+    let root = Path::new(dir);
+    if root.file_name().is_some_and(|name| name == "yt_dlp_plugins") {
+        return root.parent().map(Path::to_path_buf).into_iter().collect();
+    }
+    if root.join("yt_dlp_plugins").is_dir() {
+        return vec![root.to_path_buf()];
+    }
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|package| package.join("yt_dlp_plugins").is_dir())
+        .collect()
+}
+
+pub fn detect_plugins(dir: &str) -> Vec<DetectedPlugin> {
+    let namespaces = package_dirs(dir).into_iter().map(|package| package.join("yt_dlp_plugins"));
+
+    let mut plugins = Vec::new();
+    for namespace in namespaces {
+        let package = namespace
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for kind in ["extractor", "postprocessor"] {
+            let Ok(entries) = std::fs::read_dir(namespace.join(kind)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Some(module) = path.file_stem().map(|stem| stem.to_string_lossy().to_string()) else {
+                    continue;
+                };
+                if path.extension().is_some_and(|ext| ext == "py") && !module.starts_with('_') {
+                    plugins.push(DetectedPlugin { package: package.clone(), kind: kind.to_string(), module });
+                }
+            }
+        }
+    }
+    plugins.sort_by(|a, b| (&a.package, &a.kind, &a.module).cmp(&(&b.package, &b.kind, &b.module)));
+    plugins
+}
+
+// --plugin-dirs only exists in recent yt-dlp releases. Runs `yt-dlp --help` the first time for each
+// build, so it's only called with a plugin folder set
+pub fn supports_plugin_dirs(settings: &Settings) -> bool {
+    let path = get_yt_dlp_path(settings);
+    let key = (path.clone(), std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok());
+    if let Some((_, supported)) = PLUGIN_DIRS_SUPPORT.lock().unwrap().iter().find(|(known, _)| *known == key) {
+        return *supported;
+    }
+
+    let mut command = ytdlp_command(settings);
+    command.arg("--help");
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    let Ok(result) = commands::output(&mut command) else {
+        // Asked again once yt-dlp is there, e.g. after it was downloaded
+        return false;
+    };
+    let supported = output::clean_output(&result.stdout).contains("--plugin-dirs");
+    PLUGIN_DIRS_SUPPORT.lock().unwrap().push((key, supported));
+    supported
+}
+
+// PYTHONPATH with the plugin packages in front, for yt-dlp releases without --plugin-dirs. Only
+// yt-dlp running from a Python install reads it; the standalone builds ignore it
+pub fn python_path(dir: &str) -> Option<String> {
+    let mut paths = package_dirs(dir);
+    if paths.is_empty() {
+        return None;
+    }
+    if let Some(existing) = std::env::var_os("PYTHONPATH") {
+        paths.extend(std::env::split_paths(&existing));
+    }
+    std::env::join_paths(paths).ok().map(|joined| joined.to_string_lossy().to_string())
+}
+
+fn check_plugin_dirs_support(settings: Settings, ctx: egui::Context) -> mpsc::Receiver<bool> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        tx.send(supports_plugin_dirs(&settings)).ok();
+        ctx.request_repaint();
+    });
+    rx
+}
+
+impl YtMp3App {
+    // Lives outside Settings::ui because checking for --plugin-dirs runs yt-dlp in the background
+    pub(crate) fn show_plugin_settings(&mut self, ui: &mut egui::Ui) {
+        if let PluginDirSupport::Checking(rx) = &self.plugin_dir_support {
+            match rx.try_recv() {
+                Ok(supported) => self.plugin_dir_support = PluginDirSupport::Known(supported),
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.plugin_dir_support = PluginDirSupport::Known(false),
+            }
+        }
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🧩 yt-dlp plugins:", "plugins");
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("Plugin folder:");
+                    ui.add(egui::TextEdit::singleline(&mut self.settings.plugin_dir)
+                        .desired_width(220.0)
+                        .hint_text("None"));
+                    if ui.button("📂").accessible_name("Choose folder").clicked() {
+                        if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                            self.settings.plugin_dir = path.display().to_string();
+                        }
+                    }
+                });
+
+                if self.settings.plugin_dir.is_empty() {
+                    return;
+                }
+
+                if matches!(self.plugin_dir_support, PluginDirSupport::Unknown) {
+                    self.plugin_dir_support = PluginDirSupport::Checking(
+                        check_plugin_dirs_support(self.settings.clone(), ui.ctx().clone())
+                    );
+                }
+                match self.plugin_dir_support {
+                    PluginDirSupport::Known(false) => {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(egui::Color32::YELLOW,
+                                "⚠ The installed yt-dlp has no --plugin-dirs; the folder is passed through PYTHONPATH, \
+                                 which only yt-dlp installed with pip reads. Try the nightly channel");
+                            if ui.button("Clear").clicked() {
+                                self.settings.plugin_dir.clear();
+                            }
+                        });
+                    }
+                    PluginDirSupport::Unknown | PluginDirSupport::Checking(_) | PluginDirSupport::Known(true) => {}
+                }
+
+                let stale = self.detected_plugins.as_ref().is_none_or(|(dir, _)| *dir != self.settings.plugin_dir);
+                if stale {
+                    let plugins = detect_plugins(&self.settings.plugin_dir);
+                    self.detected_plugins = Some((self.settings.plugin_dir.clone(), plugins));
+                }
+                let Some((_, plugins)) = &self.detected_plugins else {
+                    return;
+                };
+
+                if plugins.is_empty() {
+                    ui.colored_label(egui::Color32::GRAY, "No plugins found (expected <package>/yt_dlp_plugins/extractor/*.py)");
+                } else {
+                    for plugin in plugins {
+                        ui.label(format!("• {}  {}/{}", plugin.package, plugin.kind, plugin.module));
+                    }
+                }
+                if ui.small_button("🔄 Rescan").clicked() {
+                    self.detected_plugins = None;
+                }
+            });
+        });
+    }
+}
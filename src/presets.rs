@@ -0,0 +1,139 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::help;
+use crate::settings::VideoEncoder;
+use crate::{DownloadFormat, YtMp3App};
+
+// Format selection plus an ffmpeg re-encode for players that can't handle modern codecs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DevicePreset {
+    Off,
+    OldTv,
+    Phone,
+    Audiobook,
+}
+
+impl DevicePreset {
+    pub const ALL: [DevicePreset; 4] = [
+        DevicePreset::Off,
+        DevicePreset::OldTv,
+        DevicePreset::Phone,
+        DevicePreset::Audiobook,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DevicePreset::Off => "None",
+            DevicePreset::OldTv => "📺 Old TV: H.264 1080p, AAC",
+            DevicePreset::Phone => "📱 Phone: H.264 720p, AAC",
+            DevicePreset::Audiobook => "📖 Audiobook: mono 64k Opus",
+        }
+    }
+
+    // The format radio button the preset belongs to
+    pub fn download_format(&self) -> Option<DownloadFormat> {
+        match self {
+            DevicePreset::Off => None,
+            DevicePreset::OldTv | DevicePreset::Phone => Some(DownloadFormat::Mp4),
+            DevicePreset::Audiobook => Some(DownloadFormat::Mp3),
+        }
+    }
+
+    pub fn conversion_label(&self) -> &'static str {
+        match self {
+            DevicePreset::Off => "Post-processing",
+            DevicePreset::OldTv => "Re-encoding for TV",
+            DevicePreset::Phone => "Re-encoding for phone",
+            DevicePreset::Audiobook => "Converting to Opus",
+        }
+    }
+
+    // Replaces DownloadFormat::ytdlp_args; the smallest good-enough source keeps the re-encode short
+    pub fn ytdlp_args(&self) -> &'static [&'static str] {
+        match self {
+            DevicePreset::Off => &[],
+            DevicePreset::OldTv => &[
+                "--format",
+                "bestvideo[height<=1080][vcodec^=avc1]+bestaudio[ext=m4a]/bestvideo[height<=1080]+bestaudio/best[height<=1080]/best",
+                "--merge-output-format", "mp4",
+            ],
+            DevicePreset::Phone => &[
+                "--format",
+                "bestvideo[height<=720][vcodec^=avc1]+bestaudio[ext=m4a]/bestvideo[height<=720]+bestaudio/best[height<=720]/best",
+                "--merge-output-format", "mp4",
+            ],
+            DevicePreset::Audiobook => &[
+                "--format", "bestaudio/best",
+                "-x", "--audio-format", "opus",
+                "--audio-quality", "64K",
+            ],
+        }
+    }
+
+    // ffmpeg output options of the video presets, for the re-encode after the download
+    pub fn reencode_args(&self, encoder: VideoEncoder) -> Option<String> {
+        // Single quotes keep the escaped comma in one argument
+        let video = |height: u32, audio_bitrate: &str| {
+            format!(
+                "{} -vf 'scale=-2:min(ih\\,{})' -profile:v high -level 4.1 -c:a aac -b:a {} -ac 2",
+                encoder.video_args(), height, audio_bitrate
+            )
+        };
+        match self {
+            DevicePreset::Off | DevicePreset::Audiobook => None,
+            DevicePreset::OldTv => Some(video(1080, "192k")),
+            DevicePreset::Phone => Some(video(720, "128k")),
+        }
+    }
+
+    // ffmpeg options of the audio preset, and the yt-dlp postprocessor that runs them
+    pub fn postprocessor_args(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            DevicePreset::Audiobook => Some(("-ac 1", "ExtractAudio")),
+            _ => None,
+        }
+    }
+}
+
+impl YtMp3App {
+    pub(crate) fn device_preset_picker(&mut self, ui: &mut egui::Ui) {
+        let before = self.settings.device_preset;
+        let before_folder = self.settings.default_output_dir(self.download_format);
+        // Presets for the other format stay remembered but don't apply
+        let selected = if before.download_format() == Some(self.download_format) {
+            before
+        } else {
+            DevicePreset::Off
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Device preset:");
+            egui::ComboBox::from_id_source("device_preset")
+                .selected_text(selected.label())
+                .width(220.0)
+                .show_ui(ui, |ui| {
+                    for preset in DevicePreset::ALL {
+                        let fits = preset == DevicePreset::Off || preset.download_format() == Some(self.download_format);
+                        if fits {
+                            ui.selectable_value(&mut self.settings.device_preset, preset, preset.label());
+                        }
+                    }
+                });
+            help::help_button(ui, "device_presets");
+        });
+        if selected == DevicePreset::Audiobook {
+            ui.label(egui::RichText::new("Saves an .opus file instead of MP3").small().color(egui::Color32::GRAY));
+        }
+
+        if self.settings.device_preset != before {
+            // Moves to the preset's folder, unless a folder was picked by hand
+            if self.output_path == before_folder {
+                self.output_path = self.settings.default_output_dir(self.download_format);
+            }
+            if let Err(e) = self.settings.save() {
+                self.log_console(format!("WARNING: Could not save settings: {}", e));
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::io;
+use std::process::{Child, Command};
+use std::sync::{Mutex, OnceLock};
+
+// yt-dlp starts ffmpeg (and the Windows build re-launches itself), so killing just the
+// yt-dlp process leaves children behind that keep output files locked. Every spawned
+// process gets its own group: a job object on Windows, a process group on Unix.
+pub struct ProcessTree {
+    pub child: Child,
+    group: platform::Group,
+}
+
+// Groups that are still running, keyed by the yt-dlp process ID, so they can be killed on exit
+fn running_groups() -> &'static Mutex<HashMap<u32, platform::RawGroup>> {
+    static RUNNING: OnceLock<Mutex<HashMap<u32, platform::RawGroup>>> = OnceLock::new();
+    RUNNING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+impl ProcessTree {
+    pub fn spawn(command: &mut Command) -> io::Result<Self> {
+        platform::prepare(command);
+        let mut child = command.spawn()?;
+        let group = match platform::Group::attach(&child) {
+            Ok(group) => group,
+            Err(e) => {
+                child.kill().ok();
+                child.wait().ok();
+                return Err(e);
+            }
+        };
+        running_groups().lock().unwrap().insert(child.id(), group.raw());
+        Ok(Self { child, group })
+    }
+
+    // Kills yt-dlp together with everything it started
+    pub fn kill(&mut self) {
+        self.group.kill();
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        running_groups().lock().unwrap().remove(&self.child.id());
+    }
+}
+
+// Children inherit the priority, so this covers the ffmpeg processes yt-dlp starts as well.
+// On Windows call it after setting the other creation flags
+pub fn lower_priority(command: &mut Command) {
+    platform::lower_priority(command);
+}
+
+// Called when the window closes so no download keeps running in the background
+pub fn kill_all() {
+    for (_, group) in running_groups().lock().unwrap().drain() {
+        platform::kill_raw(group);
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use std::os::windows::io::AsRawHandle;
+    use std::os::windows::process::CommandExt;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::jobapi2::{AssignProcessToJobObject, CreateJobObjectW, SetInformationJobObject, TerminateJobObject};
+    use winapi::um::winnt::{
+        JobObjectExtendedLimitInformation, HANDLE, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    // HANDLE is a raw pointer, which can't be kept in a static
+    pub type RawGroup = isize;
+
+    pub fn prepare(_command: &mut Command) {}
+
+    pub fn lower_priority(command: &mut Command) {
+        // CREATE_NO_WINDOW | BELOW_NORMAL_PRIORITY_CLASS; the flags replace the earlier ones
+        command.creation_flags(0x08000000 | 0x00004000);
+    }
+
+    pub struct Group(HANDLE);
+
+    impl Group {
+        pub fn attach(child: &Child) -> io::Result<Self> {
+            unsafe {
+                let job = CreateJobObjectW(std::ptr::null_mut(), std::ptr::null());
+                if job.is_null() {
+                    return Err(io::Error::last_os_error());
+                }
+                let group = Group(job);
+
+                // Closing the last handle (including when this app exits or crashes) kills the whole tree
+                let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+                limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+                if SetInformationJobObject(
+                    job,
+                    JobObjectExtendedLimitInformation,
+                    &mut limits as *mut _ as *mut _,
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                ) == 0
+                {
+                    return Err(io::Error::last_os_error());
+                }
+
+                // Children started from here on inherit the job
+                if AssignProcessToJobObject(job, child.as_raw_handle() as HANDLE) == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(group)
+            }
+        }
+
+        pub fn raw(&self) -> RawGroup {
+            self.0 as RawGroup
+        }
+
+        pub fn kill(&self) {
+            kill_raw(self.raw());
+        }
+    }
+
+    impl Drop for Group {
+        fn drop(&mut self) {
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+
+    pub fn kill_raw(group: RawGroup) {
+        unsafe {
+            TerminateJobObject(group as HANDLE, 1);
+        }
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use super::*;
+    use std::os::unix::process::CommandExt;
+
+    // The process group ID, which is the yt-dlp process ID
+    pub type RawGroup = i32;
+
+    pub fn prepare(command: &mut Command) {
+        // Start a new process group led by yt-dlp; ffmpeg and friends join it
+        command.process_group(0);
+    }
+
+    pub fn lower_priority(command: &mut Command) {
+        // Runs in the child between fork and exec, where only async-signal-safe calls are allowed
+        unsafe {
+            command.pre_exec(|| {
+                libc::nice(10);
+                Ok(())
+            });
+        }
+    }
+
+    pub struct Group(i32);
+
+    impl Group {
+        pub fn attach(child: &Child) -> io::Result<Self> {
+            Ok(Group(child.id() as i32))
+        }
+
+        pub fn raw(&self) -> RawGroup {
+            self.0
+        }
+
+        pub fn kill(&self) {
+            kill_raw(self.0);
+        }
+    }
+
+    pub fn kill_raw(group: RawGroup) {
+        unsafe {
+            libc::kill(-group, libc::SIGKILL);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", unix)))]
+mod platform {
+    use super::*;
+
+    pub type RawGroup = ();
+
+    pub fn prepare(_command: &mut Command) {}
+
+    pub fn lower_priority(_command: &mut Command) {}
+
+    pub struct Group;
+
+    impl Group {
+        pub fn attach(_child: &Child) -> io::Result<Self> {
+            Ok(Group)
+        }
+
+        pub fn raw(&self) -> RawGroup {}
+
+        pub fn kill(&self) {}
+    }
+
+    pub fn kill_raw(_group: RawGroup) {}
+}
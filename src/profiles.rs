@@ -0,0 +1,259 @@
+use anyhow::Result;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use crate::a11y::AccessibleName;
+use crate::help;
+use crate::settings::get_root_config_dir;
+use crate::YtMp3App;
+
+pub const DEFAULT_PROFILE: &str = "Default";
+
+// The profile whose settings and data are in use; None = the default profile, which keeps
+// the folders from before profiles existed
+fn active_profile() -> &'static RwLock<Option<String>> {
+    static ACTIVE: OnceLock<RwLock<Option<String>>> = OnceLock::new();
+    ACTIVE.get_or_init(|| RwLock::new(None))
+}
+
+pub fn active() -> Option<String> {
+    active_profile().read().ok().and_then(|active| active.clone())
+}
+
+pub fn set_active(profile: Option<&str>) {
+    if let Ok(mut active) = active_profile().write() {
+        *active = profile.map(str::to_string);
+    }
+}
+
+pub fn label(profile: Option<&str>) -> &str {
+    profile.unwrap_or(DEFAULT_PROFILE)
+}
+
+// Profile names may hold anything, folder names can't
+fn folder_name(profile: &str) -> String {
+    profile
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' { c } else { '_' })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+// The config or data folder of the active profile
+pub fn scoped(base: PathBuf) -> PathBuf {
+    match active() {
+        Some(profile) => base.join("profiles").join(folder_name(&profile)),
+        None => base,
+    }
+}
+
+// The profiles besides the default one, kept next to the default profile's settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    pub names: Vec<String>,
+    // Profile opened last, opened again at the next launch; empty = the default profile
+    pub last_used: String,
+    // Show the profile picker when the app starts
+    pub ask_at_startup: bool,
+}
+
+fn get_profiles_path() -> Result<PathBuf> {
+    Ok(get_root_config_dir()?.join("profiles.toml"))
+}
+
+impl Profiles {
+    pub fn load() -> Self {
+        let Ok(path) = get_profiles_path() else {
+            return Self::default();
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                log::warn!("Could not parse {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = get_profiles_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn last_used(&self) -> Option<&str> {
+        Some(self.last_used.as_str()).filter(|name| self.names.iter().any(|existing| existing == name))
+    }
+
+    // Whether the picker has anything to pick from
+    pub fn has_choices(&self) -> bool {
+        !self.names.is_empty()
+    }
+
+    pub fn add(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Enter a name for the profile".to_string());
+        }
+        if folder_name(name).is_empty() {
+            return Err("The name needs at least one letter or digit".to_string());
+        }
+        let taken = name.eq_ignore_ascii_case(DEFAULT_PROFILE)
+            || self.names.iter().any(|existing| folder_name(existing).eq_ignore_ascii_case(&folder_name(name)));
+        if taken {
+            return Err(format!("There is already a profile called {}", name));
+        }
+        self.names.push(name.to_string());
+        Ok(())
+    }
+}
+
+// Where a profile keeps its files, shown when it's removed from the list
+fn profile_folder(base: &Path, profile: &str) -> PathBuf {
+    base.join("profiles").join(folder_name(profile))
+}
+
+impl YtMp3App {
+    // Reloads everything from the other profile's folders. Only when nothing is running, since
+    // the downloads report back to the app that's replaced
+    pub(crate) fn switch_profile(&mut self, ctx: &egui::Context, profile: Option<String>) {
+        if self.is_busy() || self.receiver.is_some() || !self.downloads.is_empty() {
+            self.log_console("Finish the running downloads before switching profiles".to_string());
+            return;
+        }
+
+        // The waiting URLs move to the other profile and leave this one's saved queue
+        let urls: Vec<String> = std::mem::take(&mut self.pending_urls).into_iter().collect();
+        self.persist_queue();
+
+        set_active(profile.as_deref());
+        self.profiles.last_used = profile.clone().unwrap_or_default();
+        if let Err(e) = self.profiles.save() {
+            log::warn!("Could not save profiles: {}", e);
+        }
+
+        let instance_receiver = self.instance_receiver.take();
+        // Let go of the hotkeys and the local API port before the new profile takes them
+        self.global_hotkeys = None;
+        self.event_server = None;
+
+        *self = Self::default();
+        self.instance_receiver = instance_receiver;
+        self.apply_startup_settings(ctx);
+        self.log_console(format!("Switched to the {} profile", label(profile.as_deref())));
+        self.restore_queue();
+        self.receive_urls(urls);
+    }
+
+    pub(crate) fn profile_picker(&mut self, ui: &mut egui::Ui) {
+        if !self.profiles.has_choices() {
+            return;
+        }
+        let active = active();
+        let mut selected = active.clone();
+        egui::ComboBox::from_id_source("profile_picker")
+            .selected_text(format!("👤 {}", label(active.as_deref())))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected, None, DEFAULT_PROFILE);
+                for name in &self.profiles.names {
+                    ui.selectable_value(&mut selected, Some(name.clone()), name);
+                }
+            })
+            .response
+            .on_hover_text("Profiles have their own settings, history and folders");
+        if selected != active {
+            self.profile_switch = Some(selected);
+        }
+    }
+
+    // Shown instead of the main window at launch
+    pub(crate) fn show_profile_chooser(&mut self, ctx: &egui::Context) {
+        let mut chosen = None;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(40.0);
+                ui.heading("👤 Which profile?");
+                ui.add_space(20.0);
+                let names = std::iter::once(None).chain(self.profiles.names.iter().cloned().map(Some));
+                for profile in names {
+                    if ui.add_sized([220.0, 36.0], egui::Button::new(label(profile.as_deref()))).clicked() {
+                        chosen = Some(profile);
+                    }
+                }
+                ui.add_space(20.0);
+                ui.checkbox(&mut self.profiles.ask_at_startup, "Ask every time the app starts");
+            });
+        });
+
+        if let Some(profile) = chosen {
+            self.choosing_profile = false;
+            self.switch_profile(ctx, profile);
+        }
+    }
+
+    pub(crate) fn show_profile_settings(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        let mut remove = None;
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "👤 Profiles:", "profiles");
+                ui.add_space(5.0);
+                ui.label(format!("Using: {}", label(active().as_deref())));
+                for (index, name) in self.profiles.names.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        if active().as_deref() != Some(name.as_str()) && ui.button("🗑").accessible_name("Remove profile").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.new_profile_name)
+                        .desired_width(150.0)
+                        .hint_text("e.g. Work"));
+                    if ui.button("➕ Add profile").clicked() {
+                        match self.profiles.add(&self.new_profile_name) {
+                            Ok(()) => {
+                                self.new_profile_name.clear();
+                                self.profile_error = None;
+                                changed = true;
+                            }
+                            Err(e) => self.profile_error = Some(e),
+                        }
+                    }
+                });
+                if let Some(error) = &self.profile_error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                if self.profiles.has_choices() {
+                    changed |= ui.checkbox(&mut self.profiles.ask_at_startup, "Ask which profile to use when the app starts").changed();
+                }
+            });
+        });
+
+        if let Some(index) = remove {
+            let name = self.profiles.names.remove(index);
+            changed = true;
+            if let Ok(base) = get_root_config_dir() {
+                self.log_console(format!(
+                    "Removed the {} profile; its settings stay in {}",
+                    name,
+                    profile_folder(&base, &name).display()
+                ));
+            }
+        }
+        if changed {
+            if let Err(e) = self.profiles.save() {
+                self.log_console(format!("WARNING: Could not save profiles: {}", e));
+            }
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::output::strip_ansi;
+
+// What a "[download]" progress line says. yt-dlp leaves out or writes "Unknown"/"N/A" for
+// anything it doesn't know yet, so every part is optional
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DownloadProgress {
+    pub percent: Option<f32>,
+    // Bytes; estimated when yt-dlp prefixes the size with "~" (fragmented downloads)
+    pub total_size: Option<f64>,
+    pub size_estimated: bool,
+    // Bytes per second
+    pub speed: Option<f64>,
+    // As yt-dlp wrote them, e.g. "1.23MiB/s" and "00:30"
+    pub speed_text: Option<String>,
+    pub eta: Option<String>,
+    // (done, total) for HLS/DASH downloads
+    pub fragment: Option<(u32, u32)>,
+}
+
+// Colour codes whose escape character got lost on the way, e.g. "[0;94m"
+fn ansi_remnant_regex() -> &'static Regex {
+    static REMNANT: OnceLock<Regex> = OnceLock::new();
+    REMNANT.get_or_init(|| Regex::new(r"\[[0-9;]*m").unwrap())
+}
+
+fn clean(line: &str) -> String {
+    ansi_remnant_regex().replace_all(&strip_ansi(line), "").to_string()
+}
+
+// "123.45MiB", "1.2 KB", "512B" -> bytes
+pub fn parse_size(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let unit_start = text.find(|c: char| c.is_ascii_alphabetic())?;
+    let value: f64 = text[..unit_start].trim().parse().ok()?;
+    let multiplier = match &text[unit_start..] {
+        "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        "KB" | "kB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return None,
+    };
+    Some(value * multiplier).filter(|bytes| bytes.is_finite() && *bytes >= 0.0)
+}
+
+fn is_unknown(token: &str) -> bool {
+    matches!(token, "Unknown" | "N/A" | "NA" | "--:--")
+}
+
+// "3/120)" -> (3, 120)
+fn parse_fragment(token: &str) -> Option<(u32, u32)> {
+    let (done, total) = token.trim_end_matches(')').split_once('/')?;
+    Some((done.parse().ok()?, total.parse().ok()?))
+}
+
+// Reads one line of yt-dlp's download output, e.g.
+//   [download]  45.2% of ~ 123.45MiB at    1.23MiB/s ETA 00:30 (frag 3/120)
+//   [download]   0.0% of   10.00MiB at  Unknown B/s ETA Unknown
+//   [download] 100% of  123.45MiB in 00:00:30 at 4.10MiB/s
+//   [download]    1.23MiB at  500.00KiB/s (00:00:03)
+// Returns None for lines that aren't progress, like "[download] Destination: ..."
+pub fn parse_download_line(line: &str) -> Option<DownloadProgress> {
+    let line = clean(line);
+    let rest = line.trim_start().strip_prefix("[download]")?;
+    let tokens: Vec<&str> = rest.split_whitespace().collect();
+    let mut progress = DownloadProgress::default();
+
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens[index];
+        match token {
+            "of" => {
+                let mut size_index = index + 1;
+                if tokens.get(size_index) == Some(&"~") {
+                    progress.size_estimated = true;
+                    size_index += 1;
+                }
+                if let Some(size) = tokens.get(size_index) {
+                    // "~123.45MiB" without a space
+                    if let Some(size) = size.strip_prefix('~') {
+                        progress.size_estimated = true;
+                        progress.total_size = parse_size(size);
+                    } else {
+                        progress.total_size = parse_size(size);
+                    }
+                }
+                index = size_index;
+            }
+            "at" => {
+                if let Some(speed) = tokens.get(index + 1) {
+                    if is_unknown(speed) {
+                        // "Unknown B/s"
+                        index += 1;
+                        if tokens.get(index + 1).is_some_and(|unit| unit.ends_with("/s")) {
+                            index += 1;
+                        }
+                    } else if let Some(value) = speed.strip_suffix("/s") {
+                        progress.speed = parse_size(value);
+                        progress.speed_text = progress.speed.map(|_| speed.to_string());
+                        index += 1;
+                    }
+                }
+            }
+            "ETA" => {
+                if let Some(eta) = tokens.get(index + 1) {
+                    if !is_unknown(eta) {
+                        progress.eta = Some(eta.to_string());
+                    }
+                    index += 1;
+                }
+            }
+            "(frag" => {
+                progress.fragment = tokens.get(index + 1).and_then(|token| parse_fragment(token));
+                index += 1;
+            }
+            _ if token.ends_with('%') && progress.percent.is_none() => {
+                progress.percent = token
+                    .trim_end_matches('%')
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|percent| percent.is_finite())
+                    .map(|percent| percent.clamp(0.0, 100.0));
+                // Anything else starting with a percentage isn't a progress line
+                progress.percent?;
+            }
+            // A size on its own is the downloaded amount of a stream of unknown length
+            _ if index == 0 && parse_size(token).is_some() => {}
+            _ if index == 0 => return None,
+            _ => {}
+        }
+        index += 1;
+    }
+
+    // Fragmented downloads sometimes only report the fragment count
+    if progress.percent.is_none() {
+        if let Some((done, total)) = progress.fragment.filter(|(_, total)| *total > 0) {
+            progress.percent = Some((done as f32 / total as f32 * 100.0).min(100.0));
+        }
+    }
+
+    let has_any = progress.percent.is_some() || progress.speed.is_some() || progress.total_size.is_some();
+    has_any.then_some(progress)
+}
+
+// Turns a line of yt-dlp output into the progress bar's fraction and status text
+pub fn parse_progress_line(line: &str) -> Option<(f32, String)> {
+    if let Some(progress) = parse_download_line(line) {
+        if let Some(percent) = progress.percent {
+            let mut status = format!("Downloading... {:.1}%", percent);
+            if let Some(speed) = &progress.speed_text {
+                status.push_str(&format!(" at {}", speed));
+            }
+            if let Some(eta) = &progress.eta {
+                status.push_str(&format!(" (ETA: {})", eta));
+            }
+            if let Some((done, total)) = progress.fragment {
+                status.push_str(&format!(" [fragment {}/{}]", done, total));
+            }
+            return Some((percent / 100.0, status));
+        }
+    }
+
+    let line = clean(line);
+    if line.contains("[download] Destination:") {
+        return Some((0.0, "Preparing download...".to_string()));
+    }
+
+    if line.contains("[ExtractAudio]") {
+        return Some((0.9, "Extracting audio...".to_string()));
+    }
+
+    if line.contains("[ffmpeg]") {
+        return Some((0.95, "Converting to MP3...".to_string()));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const MIB: f64 = 1024.0 * 1024.0;
+
+    fn approx(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("no value");
+        assert!((actual - expected).abs() < 1.0, "{} != {}", actual, expected);
+    }
+
+    // Lines copied from yt-dlp 2024.x runs
+    #[test]
+    fn reads_plain_progress() {
+        let progress = parse_download_line("[download]  45.2% of  123.45MiB at    1.23MiB/s ETA 00:30").unwrap();
+        assert_eq!(progress.percent, Some(45.2));
+        approx(progress.total_size, 123.45 * MIB);
+        assert!(!progress.size_estimated);
+        approx(progress.speed, 1.23 * MIB);
+        assert_eq!(progress.speed_text.as_deref(), Some("1.23MiB/s"));
+        assert_eq!(progress.eta.as_deref(), Some("00:30"));
+        assert_eq!(progress.fragment, None);
+    }
+
+    #[test]
+    fn reads_fragmented_progress() {
+        let progress = parse_download_line("[download]  12.0% of ~ 250.10MiB at    3.02MiB/s ETA 01:12 (frag 14/117)").unwrap();
+        assert_eq!(progress.percent, Some(12.0));
+        assert!(progress.size_estimated);
+        approx(progress.total_size, 250.10 * MIB);
+        assert_eq!(progress.fragment, Some((14, 117)));
+
+        let progress = parse_download_line("[download]   3.4% of ~250.10MiB at  2.00MiB/s ETA 02:10 (frag 4/117)").unwrap();
+        assert!(progress.size_estimated);
+        approx(progress.total_size, 250.10 * MIB);
+    }
+
+    #[test]
+    fn reads_unknown_parts() {
+        let progress = parse_download_line("[download]   0.0% of   10.00MiB at  Unknown B/s ETA Unknown").unwrap();
+        assert_eq!(progress.percent, Some(0.0));
+        assert_eq!(progress.speed, None);
+        assert_eq!(progress.speed_text, None);
+        assert_eq!(progress.eta, None);
+
+        let progress = parse_download_line("[download]   1.0% of   10.00MiB at    N/A ETA --:--").unwrap();
+        assert_eq!(progress.speed, None);
+        assert_eq!(progress.eta, None);
+    }
+
+    #[test]
+    fn reads_finished_and_open_ended_lines() {
+        let progress = parse_download_line("[download] 100% of  123.45MiB in 00:00:30 at 4.10MiB/s").unwrap();
+        assert_eq!(progress.percent, Some(100.0));
+        approx(progress.speed, 4.10 * MIB);
+
+        // Live streams and unknown lengths only report what's been downloaded so far
+        let progress = parse_download_line("[download]    1.23MiB at  500.00KiB/s (00:00:03)").unwrap();
+        assert_eq!(progress.percent, None);
+        approx(progress.speed, 500.0 * 1024.0);
+    }
+
+    #[test]
+    fn percent_from_fragments_alone() {
+        let progress = parse_download_line("[download]    5.12MiB at    1.00MiB/s ETA Unknown (frag 30/120)").unwrap();
+        assert_eq!(progress.percent, Some(25.0));
+        assert_eq!(progress.fragment, Some((30, 120)));
+    }
+
+    #[test]
+    fn strips_colours() {
+        let line = "\u{1b}[0;94m[download]\u{1b}[0m \u{1b}[0;94m  7.5%\u{1b}[0m of   50.00MiB at \u{1b}[0;32m 900.00KiB/s\u{1b}[0m ETA \u{1b}[0;33m00:52\u{1b}[0m";
+        let progress = parse_download_line(line).unwrap();
+        assert_eq!(progress.percent, Some(7.5));
+        assert_eq!(progress.eta.as_deref(), Some("00:52"));
+
+        // The escape character itself sometimes gets lost on Windows consoles
+        let progress = parse_download_line("[0;94m[download][0m  60.0% of 5.00MiB at 1.00MiB/s ETA 00:02").unwrap();
+        assert_eq!(progress.percent, Some(60.0));
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        for line in [
+            "[download] Destination: /home/user/Music/Some Song.webm",
+            "[download] Some Song.mp3 has already been downloaded",
+            "[download] Downloading item 3 of 25",
+            "[youtube] dQw4w9WgXcQ: Downloading webpage",
+            "[ExtractAudio] Destination: /home/user/Music/Some Song.mp3",
+            "WARNING: [youtube] Falling back to generic n function search",
+            "",
+        ] {
+            assert_eq!(parse_download_line(line), None, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn progress_line_status() {
+        let (fraction, status) = parse_progress_line("[download]  50.0% of 10.00MiB at 2.00MiB/s ETA 00:03 (frag 5/10)").unwrap();
+        assert_eq!(fraction, 0.5);
+        assert_eq!(status, "Downloading... 50.0% at 2.00MiB/s (ETA: 00:03) [fragment 5/10]");
+        assert_eq!(
+            parse_progress_line("[download] Destination: song.webm"),
+            Some((0.0, "Preparing download...".to_string()))
+        );
+        assert_eq!(
+            parse_progress_line("[ExtractAudio] Destination: song.mp3"),
+            Some((0.9, "Extracting audio...".to_string()))
+        );
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("512B"), Some(512.0));
+        assert_eq!(parse_size("1.5KiB"), Some(1536.0));
+        assert_eq!(parse_size("2 MB"), Some(2e6));
+        assert_eq!(parse_size("1.00GiB"), Some(1024.0 * MIB));
+        assert_eq!(parse_size("12"), None);
+        assert_eq!(parse_size("3 parsecs"), None);
+        assert_eq!(parse_size("-1MiB"), None);
+    }
+
+    fn token() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("of".to_string()),
+            Just("at".to_string()),
+            Just("ETA".to_string()),
+            Just("(frag".to_string()),
+            Just("~".to_string()),
+            Just("Unknown".to_string()),
+            Just("B/s".to_string()),
+            any::<f64>().prop_map(|value| format!("{}%", value)),
+            any::<f32>().prop_map(|value| format!("{}MiB", value)),
+            any::<f64>().prop_map(|value| format!("{}KiB/s", value)),
+            (any::<u32>(), any::<u32>()).prop_map(|(done, total)| format!("{}/{})", done, total)),
+            Just("NaN%".to_string()),
+            Just("inf%".to_string()),
+            "\\PC{0,12}",
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn never_panics_on_any_text(line in "\\PC*") {
+            let _ = parse_download_line(&line);
+            let _ = parse_progress_line(&line);
+        }
+
+        #[test]
+        fn percent_stays_in_range(tokens in prop::collection::vec(token(), 0..12)) {
+            let line = format!("[download] {}", tokens.join(" "));
+            if let Some(progress) = parse_download_line(&line) {
+                if let Some(percent) = progress.percent {
+                    prop_assert!((0.0..=100.0).contains(&percent), "{} from {:?}", percent, line);
+                }
+            }
+            if let Some((fraction, _)) = parse_progress_line(&line) {
+                prop_assert!((0.0..=1.0).contains(&fraction), "{} from {:?}", fraction, line);
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use eframe::egui;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::a11y::AccessibleName;
+use crate::certificates;
+use crate::help;
+use crate::settings::{ProxyProfile, Settings};
+use crate::system_proxy::SYSTEM_PROXY_NAME;
+use crate::theme;
+use crate::YtMp3App;
+
+// Tor's SOCKS port; socks5h makes DNS lookups go through Tor as well
+const TOR_PROXY_URL: &str = "socks5h://127.0.0.1:9050";
+const TEST_URL: &str = "https://www.youtube.com/generate_204";
+const TEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub enum ProxyTest {
+    Running(mpsc::Receiver<Result<Duration, String>>),
+    Passed(Duration),
+    Failed(String),
+}
+
+// Requests YouTube through the proxy and reports how long the round trip took
+fn test_proxy(url: String, settings: Settings, ctx: egui::Context) -> mpsc::Receiver<Result<Duration, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt.block_on(async {
+            let proxy = reqwest::Proxy::all(&url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+            let client = certificates::client_builder(&settings)
+                .proxy(proxy)
+                .timeout(TEST_TIMEOUT)
+                .build()
+                .map_err(|e| e.to_string())?;
+
+            let started = Instant::now();
+            let response = client.get(TEST_URL).send().await.map_err(|e| e.to_string())?;
+            if response.status().is_success() {
+                Ok(started.elapsed())
+            } else {
+                Err(format!("YouTube answered with HTTP {}", response.status()))
+            }
+        });
+        tx.send(result).ok();
+        ctx.request_repaint();
+    });
+    rx
+}
+
+impl YtMp3App {
+    // Lives outside Settings::ui because the connectivity tests keep state in the app
+    pub(crate) fn show_proxy_settings(&mut self, ui: &mut egui::Ui) {
+        for test in self.proxy_tests.values_mut() {
+            if let ProxyTest::Running(rx) = test {
+                match rx.try_recv() {
+                    Ok(Ok(elapsed)) => *test = ProxyTest::Passed(elapsed),
+                    Ok(Err(error)) => *test = ProxyTest::Failed(error),
+                    Err(mpsc::TryRecvError::Empty) => {}
+                    Err(mpsc::TryRecvError::Disconnected) => *test = ProxyTest::Failed("Test did not finish".to_string()),
+                }
+            }
+        }
+
+        let reduced_motion = self.settings.reduced_motion;
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🌐 Proxies:", "proxies");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.settings.use_system_proxy, "Use the system proxy when no proxy is chosen");
+                if self.settings.use_system_proxy {
+                    let status = if self.system_proxy.is_detecting() {
+                        "Looking for the system proxy...".to_string()
+                    } else {
+                        match &self.system_proxy.found {
+                            Some(proxy) => format!("Found {} in {}", proxy.url, proxy.source),
+                            None => "No system proxy is set, connecting directly (auto-config scripts are not read)".to_string(),
+                        }
+                    };
+                    ui.label(egui::RichText::new(status).small().color(egui::Color32::GRAY));
+                }
+                ui.add_space(5.0);
+
+                let mut remove = None;
+                let mut start_test = None;
+                for (index, proxy) in self.settings.proxies.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut proxy.name)
+                            .desired_width(90.0)
+                            .hint_text("Name"));
+                        ui.add(egui::TextEdit::singleline(&mut proxy.url)
+                            .desired_width(200.0)
+                            .hint_text("socks5://host:port"));
+                        let running = matches!(self.proxy_tests.get(&proxy.url), Some(ProxyTest::Running(_)));
+                        if ui.add_enabled(!running && !proxy.url.is_empty(), egui::Button::new("🔌 Test")).clicked() {
+                            start_test = Some(proxy.url.clone());
+                        }
+                        if ui.button("🗑").accessible_name("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+
+                    match self.proxy_tests.get(&proxy.url) {
+                        Some(ProxyTest::Running(_)) => {
+                            ui.horizontal(|ui| {
+                                theme::busy_indicator(ui, reduced_motion);
+                                ui.label("Testing...");
+                            });
+                        }
+                        Some(ProxyTest::Passed(elapsed)) => {
+                            ui.colored_label(egui::Color32::GREEN, format!("✅ Reached YouTube in {} ms", elapsed.as_millis()));
+                        }
+                        Some(ProxyTest::Failed(error)) => {
+                            ui.colored_label(egui::Color32::RED, format!("❌ {}", error));
+                        }
+                        None => {}
+                    }
+                }
+
+                if let Some(index) = remove {
+                    let removed = self.settings.proxies.remove(index);
+                    if self.settings.default_proxy == removed.name {
+                        self.settings.default_proxy.clear();
+                    }
+                }
+                if let Some(url) = start_test {
+                    let rx = test_proxy(url.clone(), self.settings.clone(), ui.ctx().clone());
+                    self.proxy_tests.insert(url, ProxyTest::Running(rx));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("➕ Add proxy").clicked() {
+                        self.settings.proxies.push(ProxyProfile {
+                            name: format!("Proxy {}", self.settings.proxies.len() + 1),
+                            ..Default::default()
+                        });
+                    }
+                    let has_tor = self.settings.proxies.iter().any(|proxy| proxy.url == TOR_PROXY_URL);
+                    if ui.add_enabled(!has_tor, egui::Button::new("🧅 Add Tor"))
+                        .on_hover_text("Tor Browser or the tor service must be running")
+                        .clicked()
+                    {
+                        self.settings.proxies.push(ProxyProfile {
+                            name: "Tor".to_string(),
+                            url: TOR_PROXY_URL.to_string(),
+                            ..Default::default()
+                        });
+                    }
+                });
+
+                if !self.settings.proxies.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.label("Use by default:");
+                        let no_choice = if self.settings.system_proxy.is_some() { SYSTEM_PROXY_NAME } else { "Direct connection" };
+                        let selected = if self.settings.default_proxy.is_empty() {
+                            no_choice.to_string()
+                        } else {
+                            self.settings.default_proxy.clone()
+                        };
+                        egui::ComboBox::from_id_source("default_proxy")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.settings.default_proxy, String::new(), no_choice);
+                                for proxy in &self.settings.proxies {
+                                    ui.selectable_value(&mut self.settings.default_proxy, proxy.name.clone(), &proxy.name);
+                                }
+                            });
+                    });
+                }
+                ui.add_space(5.0);
+                self.show_certificate_settings(ui);
+            });
+        });
+    }
+
+    pub(crate) fn proxy_picker(&mut self, ui: &mut egui::Ui) {
+        // Forget a selection whose proxy was deleted in the settings
+        if let Some(name) = &self.proxy {
+            if !name.is_empty() && !self.settings.proxies.iter().any(|proxy| &proxy.name == name) {
+                self.proxy = None;
+            }
+        }
+        if self.settings.proxies.is_empty() {
+            return;
+        }
+
+        let default_label = if self.settings.default_proxy.is_empty() {
+            if self.settings.system_proxy.is_some() { "Default (system proxy)" } else { "Default (direct)" }.to_string()
+        } else {
+            format!("Default ({})", self.settings.default_proxy)
+        };
+        let selected = match self.proxy.as_deref() {
+            None => default_label.clone(),
+            Some("") => "Direct connection".to_string(),
+            Some(name) => name.to_string(),
+        };
+
+        ui.label("🌐 Proxy:");
+        egui::ComboBox::from_id_source("proxy_picker")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.proxy, None, default_label);
+                ui.selectable_value(&mut self.proxy, Some(String::new()), "Direct connection");
+                for proxy in &self.settings.proxies {
+                    ui.selectable_value(&mut self.proxy, Some(proxy.name.clone()), &proxy.name);
+                }
+            });
+    }
+}
@@ -0,0 +1,358 @@
+use eframe::egui;
+
+use crate::a11y::AccessibleName;
+use crate::jobs::{JobId, JobStatus};
+use crate::{help, AppState, AutoDownload, DownloadFormat, YtMp3App};
+
+// Finished items shown before the oldest are dropped
+const MAX_FINISHED: usize = 100;
+
+pub type QueueItemId = u64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemStatus {
+    Waiting,
+    // Being fetched by the main view, or downloading
+    Active { progress: f32, status: String },
+    // The saved file; None for channels and playlists, whose videos were queued instead
+    Done(Option<String>),
+    Failed(String),
+}
+
+pub struct QueueItem {
+    pub id: QueueItemId,
+    pub download: AutoDownload,
+    // Known once the video info is fetched
+    pub title: Option<String>,
+    pub status: ItemStatus,
+    // The download's job once it started
+    job: Option<JobId>,
+}
+
+// Downloads worked through in order, whatever the main view shows; Settings decide how many run
+// at once. Items stay listed with their outcome after they finish; the VecDeque-like methods
+// only see the waiting ones
+#[derive(Default)]
+pub struct DownloadQueue {
+    items: Vec<QueueItem>,
+    next_id: QueueItemId,
+    // The item the main view is fetching. Once its download starts it follows its job instead
+    active: Option<QueueItemId>,
+    // Text of the "Add several URLs" box while it's open
+    adding: Option<String>,
+    notice: Option<String>,
+}
+
+impl DownloadQueue {
+    pub fn push_back(&mut self, download: AutoDownload) {
+        self.push_titled(download, None);
+    }
+
+    // With the title already known, e.g. from a playlist listing
+    pub fn push_titled(&mut self, download: AutoDownload, title: Option<String>) {
+        self.next_id += 1;
+        self.items.push(QueueItem { id: self.next_id, download, title, status: ItemStatus::Waiting, job: None });
+    }
+
+    pub fn extend(&mut self, downloads: impl IntoIterator<Item = AutoDownload>) {
+        for download in downloads {
+            self.push_back(download);
+        }
+    }
+
+    // Downloads that haven't started
+    pub fn iter(&self) -> impl Iterator<Item = &AutoDownload> {
+        self.items.iter().filter(|item| item.status == ItemStatus::Waiting).map(|item| &item.download)
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    pub fn front(&self) -> Option<&AutoDownload> {
+        self.iter().next()
+    }
+
+    // The next waiting download, which becomes the active item
+    pub fn pop_front(&mut self) -> Option<AutoDownload> {
+        let next = self.items.iter().find(|item| item.status == ItemStatus::Waiting)?.id;
+        // Still active only when it's a playlist whose listing carries on in the background; its
+        // videos are queued items of their own
+        if let Some(id) = self.active.take() {
+            self.finish_item(id, ItemStatus::Done(None));
+        }
+        let item = self.items.iter_mut().find(|item| item.id == next)?;
+        item.status = ItemStatus::Active { progress: 0.0, status: "Starting...".to_string() };
+        self.active = Some(item.id);
+        Some(item.download.clone())
+    }
+
+    // Waiting, active and finished items, in order
+    pub fn all(&self) -> &[QueueItem] {
+        &self.items
+    }
+
+    fn active(&self) -> Option<&QueueItem> {
+        self.items.iter().find(|item| Some(item.id) == self.active)
+    }
+
+    // An item is being fetched or downloaded
+    pub fn is_running(&self) -> bool {
+        self.items.iter().any(|item| matches!(item.status, ItemStatus::Active { .. }))
+    }
+
+    fn active_mut(&mut self) -> Option<&mut QueueItem> {
+        self.items.iter_mut().find(|item| Some(item.id) == self.active)
+    }
+
+    fn finish(&mut self, status: ItemStatus) {
+        if let Some(id) = self.active.take() {
+            self.finish_item(id, status);
+        }
+    }
+
+    fn finish_item(&mut self, id: QueueItemId, status: ItemStatus) {
+        if let Some(item) = self.items.iter_mut().find(|item| item.id == id) {
+            item.status = status;
+        }
+
+        let finished = self.items.iter().filter(|item| matches!(item.status, ItemStatus::Done(_) | ItemStatus::Failed(_))).count();
+        if finished > MAX_FINISHED {
+            if let Some(oldest) = self.items.iter().position(|item| matches!(item.status, ItemStatus::Done(_) | ItemStatus::Failed(_))) {
+                self.items.remove(oldest);
+            }
+        }
+    }
+
+    fn has_finished(&self) -> bool {
+        self.items.iter().any(|item| matches!(item.status, ItemStatus::Done(_) | ItemStatus::Failed(_)))
+    }
+}
+
+// One URL per line; blank lines and duplicates are dropped
+fn parse_url_list(text: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for url in text.split_whitespace() {
+        if !urls.iter().any(|existing| existing == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+impl YtMp3App {
+    // Called every frame; the active item follows the main view until its download starts,
+    // downloading items follow their job
+    pub(crate) fn sync_queue_item(&mut self) {
+        self.sync_downloading_items();
+        let Some(item) = self.download_queue.active() else {
+            return;
+        };
+        let url = item.download.url.clone();
+        let status = match &self.state {
+            AppState::Success(path) => {
+                self.download_queue.finish(ItemStatus::Done(Some(path.clone())));
+                return;
+            }
+            AppState::Error(error) => {
+                self.download_queue.finish(ItemStatus::Failed(error.clone()));
+                return;
+            }
+            AppState::NeedsRedownload { reason, .. } => {
+                self.download_queue.finish(ItemStatus::Failed(format!("The file looks broken: {}", reason)));
+                return;
+            }
+            _ if self.playlist_fetch.is_some() && self.url_input.is_empty() => "Listing the videos...".to_string(),
+            AppState::Input => {
+                self.download_queue.finish(ItemStatus::Done(None));
+                return;
+            }
+            // Another URL took over the main view, e.g. a members-only video was skipped
+            _ if self.url_input.trim() != url => {
+                self.download_queue.finish(ItemStatus::Failed("Skipped".to_string()));
+                return;
+            }
+            AppState::Loading => "Fetching video info...".to_string(),
+            AppState::VideoInfo(info) => {
+                let title = info.title.clone();
+                if let Some(item) = self.download_queue.active_mut() {
+                    item.title = Some(title);
+                }
+                if self.auto_download || self.receiver.is_some() {
+                    "Starting download...".to_string()
+                } else {
+                    "Waiting for you to start it".to_string()
+                }
+            }
+            AppState::AgeRestricted(_) => "Waiting for you to confirm your age".to_string(),
+            AppState::Downloading { job_id: Some(job_id), .. } => {
+                let job_id = *job_id;
+                if let Some(item) = self.download_queue.active_mut() {
+                    item.job = Some(job_id);
+                }
+                // The main view is free for the next item, which may start next to this one
+                self.download_queue.active = None;
+                self.sync_downloading_items();
+                return;
+            }
+            AppState::Downloading { job_id: None, progress, status } => {
+                let progress = *progress;
+                let status = status.clone();
+                if let Some(item) = self.download_queue.active_mut() {
+                    item.status = ItemStatus::Active { progress, status };
+                }
+                return;
+            }
+        };
+        if let Some(item) = self.download_queue.active_mut() {
+            item.status = ItemStatus::Active { progress: 0.0, status };
+        }
+    }
+
+    fn sync_downloading_items(&mut self) {
+        let mut finished = Vec::new();
+        for item in &mut self.download_queue.items {
+            if !matches!(item.status, ItemStatus::Active { .. }) {
+                continue;
+            }
+            let Some(job) = item.job.and_then(|id| self.jobs.iter().find(|job| job.id == id)) else {
+                continue;
+            };
+            match &job.status {
+                JobStatus::Running { progress, status } => {
+                    item.status = ItemStatus::Active { progress: *progress, status: status.clone() };
+                }
+                JobStatus::Completed(path) => finished.push((item.id, ItemStatus::Done(Some(path.clone())))),
+                JobStatus::Failed(error) | JobStatus::Stalled(error) => finished.push((item.id, ItemStatus::Failed(error.clone()))),
+                JobStatus::NeedsRedownload { reason, .. } => {
+                    finished.push((item.id, ItemStatus::Failed(format!("The file looks broken: {}", reason))));
+                }
+            }
+        }
+        for (id, status) in finished {
+            self.download_queue.finish_item(id, status);
+        }
+    }
+
+    fn add_url_list(&mut self, text: &str) {
+        let mut added = 0;
+        let mut invalid = Vec::new();
+        for url in parse_url_list(text) {
+            if self.is_valid_youtube_url(&url) {
+                self.download_queue.push_back(AutoDownload {
+                    url,
+                    format: self.download_format,
+                    output_path: self.output_path.clone(),
+                });
+                added += 1;
+            } else {
+                invalid.push(url);
+            }
+        }
+        self.download_queue.notice = Some(match invalid.len() {
+            0 => format!("Added {} URL(s)", added),
+            _ => format!("Added {} URL(s), skipped {} that aren't supported: {}", added, invalid.len(), invalid.join(" ")),
+        });
+    }
+
+    pub(crate) fn show_download_queue(&mut self, ui: &mut egui::Ui) {
+        let format = if self.download_format == DownloadFormat::Mp3 { "MP3" } else { "MP4" };
+        let mut add = false;
+        let mut remove = None;
+        let mut retry = None;
+
+        ui.horizontal(|ui| {
+            let label = if self.download_queue.adding.is_some() { "➖ Add several URLs" } else { "➕ Add several URLs" };
+            if ui.small_button(label).on_hover_text("Paste a list of URLs to download one after another").clicked() {
+                self.download_queue.adding = match self.download_queue.adding {
+                    Some(_) => None,
+                    None => Some(String::new()),
+                };
+            }
+            if self.download_queue.has_finished() && ui.small_button("🧹 Clear finished").clicked() {
+                self.download_queue.items.retain(|item| !matches!(item.status, ItemStatus::Done(_) | ItemStatus::Failed(_)));
+            }
+            help::help_button(ui, "download_queue");
+        });
+
+        if let Some(text) = &mut self.download_queue.adding {
+            ui.add(egui::TextEdit::multiline(text)
+                .desired_rows(4)
+                .desired_width(f32::INFINITY)
+                .hint_text("One URL per line"))
+                .accessible_name("URLs to queue");
+            ui.horizontal(|ui| {
+                let count = parse_url_list(text).len();
+                if ui.add_enabled(count > 0, egui::Button::new(format!("Queue {} as {}", count, format))).clicked() {
+                    add = true;
+                }
+                ui.label(egui::RichText::new(format!("to {}", self.output_path)).small().color(egui::Color32::GRAY));
+            });
+        }
+        if add {
+            let text = self.download_queue.adding.take().unwrap_or_default();
+            self.add_url_list(&text);
+        }
+        if let Some(notice) = &self.download_queue.notice {
+            ui.label(egui::RichText::new(notice).small());
+        }
+
+        if self.download_queue.items.is_empty() {
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_source("download_queue")
+            .max_height(180.0)
+            .show(ui, |ui| {
+                for item in &self.download_queue.items {
+                    ui.horizontal(|ui| {
+                        let name = item.title.as_deref().unwrap_or(&item.download.url);
+                        match &item.status {
+                            ItemStatus::Waiting => {
+                                ui.label("⏳");
+                                ui.label(name).on_hover_text(&item.download.output_path);
+                                if ui.small_button("✖").accessible_name(&format!("Remove {}", name)).clicked() {
+                                    remove = Some(item.id);
+                                }
+                            }
+                            ItemStatus::Active { progress, status } => {
+                                ui.label("⬇");
+                                ui.label(egui::RichText::new(name).strong());
+                                ui.add(egui::ProgressBar::new(*progress).desired_width(120.0).show_percentage());
+                                ui.label(egui::RichText::new(status).small().color(egui::Color32::GRAY));
+                            }
+                            ItemStatus::Done(path) => {
+                                ui.colored_label(egui::Color32::GREEN, "✔");
+                                let label = ui.label(name);
+                                if let Some(path) = path {
+                                    label.on_hover_text(path);
+                                }
+                            }
+                            ItemStatus::Failed(error) => {
+                                ui.colored_label(egui::Color32::RED, "✖");
+                                ui.label(name).on_hover_text(error);
+                                if ui.small_button("↻").accessible_name(&format!("Retry {}", name)).clicked() {
+                                    retry = Some(item.id);
+                                }
+                            }
+                        }
+                    });
+                }
+            });
+
+        if let Some(id) = remove {
+            self.download_queue.items.retain(|item| item.id != id);
+        }
+        if let Some(id) = retry {
+            if let Some(index) = self.download_queue.items.iter().position(|item| item.id == id) {
+                let item = self.download_queue.items.remove(index);
+                self.download_queue.push_back(item.download);
+            }
+        }
+    }
+}
+
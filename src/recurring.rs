@@ -0,0 +1,562 @@
+use anyhow::Result;
+use eframe::egui;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::a11y::AccessibleName;
+use crate::feeds::{self, FeedEntry, FeedResults};
+use crate::help;
+use crate::{database, locale, playlist, AutoDownload, DownloadFormat, YtMp3App};
+
+// How often scheduled templates are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+// Newest videos read from each channel of imported subscriptions, so the first run doesn't
+// fetch every channel's whole back catalog
+const IMPORTED_LATEST: u32 = 3;
+// Feed links remembered per template; feeds only list their recent entries anyway
+const SEEN_LIMIT: usize = 1000;
+
+// A named set of URLs with the format and folder to download them in, run again on demand
+// or every few days
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueTemplate {
+    pub name: String,
+    pub urls: Vec<String>,
+    pub format: DownloadFormat,
+    pub output_path: String,
+    // 0 = only when run by hand
+    #[serde(default)]
+    pub every_days: u32,
+    // Unix timestamp (seconds)
+    #[serde(default)]
+    pub last_run: Option<i64>,
+    // Only the newest videos of each channel, playlist or feed; 0 = all of them
+    #[serde(default)]
+    pub latest: u32,
+    // Entries already queued from this template's RSS/Atom feeds, so a run only takes new ones
+    #[serde(default)]
+    pub seen: Vec<String>,
+}
+
+impl QueueTemplate {
+    fn is_due(&self, now: i64) -> bool {
+        self.every_days > 0
+            && self.last_run.is_none_or(|last_run| now - last_run >= self.every_days as i64 * SECONDS_PER_DAY)
+    }
+
+    fn schedule_text(&self) -> String {
+        match self.every_days {
+            0 => "On demand".to_string(),
+            1 => "Daily".to_string(),
+            7 => "Weekly".to_string(),
+            days => format!("Every {} days", days),
+        }
+    }
+}
+
+// Channel IDs in a Google Takeout subscriptions.csv, an OPML export or a list of YouTube feed
+// URLs, in the order they appear
+pub fn parse_subscriptions(text: &str) -> Vec<String> {
+    let id_regex = Regex::new(r"(?:^|[^A-Za-z0-9_-])(UC[A-Za-z0-9_-]{22})(?:$|[^A-Za-z0-9_-])").unwrap();
+    let mut ids: Vec<String> = Vec::new();
+    for line in text.lines() {
+        for captures in id_regex.captures_iter(line) {
+            let id = captures[1].to_string();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids.into_iter()
+        .map(|id| format!("https://www.youtube.com/channel/{}/videos", id))
+        .collect()
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QueueTemplates {
+    pub templates: Vec<QueueTemplate>,
+}
+
+impl QueueTemplates {
+    pub fn load() -> Self {
+        match database::load_list(database::QUEUE_TEMPLATES) {
+            Ok(templates) => Self { templates },
+            Err(e) => {
+                log::warn!("Could not read the queue templates: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        database::save_list(database::QUEUE_TEMPLATES, &self.templates)
+    }
+}
+
+// A template being written or changed in the templates window
+pub struct TemplateEditor {
+    // None = a new template
+    index: Option<usize>,
+    name: String,
+    urls: String,
+    format: DownloadFormat,
+    output_path: String,
+    every_days: u32,
+    latest: u32,
+    error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct Recurring {
+    pub open: bool,
+    editor: Option<TemplateEditor>,
+    last_check: Option<Instant>,
+    import_error: Option<String>,
+    // How many of the newest videos to read, for channels and playlists queued by a template
+    playlist_limits: HashMap<String, usize>,
+    // Feeds being read, by template name
+    feed_runs: Vec<(String, mpsc::Receiver<FeedResults>)>,
+    // Media links from feeds that aren't YouTube videos but may be fetched all the same
+    feed_media: HashSet<String>,
+}
+
+impl YtMp3App {
+    fn save_queue_templates(&mut self) {
+        if let Err(e) = self.queue_templates.save() {
+            self.log_console(format!("WARNING: Could not save the queue templates: {}", e));
+        }
+    }
+
+    fn run_queue_template(&mut self, index: usize) {
+        let template = &mut self.queue_templates.templates[index];
+        template.last_run = Some(chrono::Utc::now().timestamp());
+        let (feed_urls, urls): (Vec<String>, Vec<String>) = template.urls.iter().cloned().partition(|url| feeds::is_feed_url(url));
+        let downloads: Vec<AutoDownload> = urls
+            .iter()
+            .map(|url| AutoDownload { url: url.clone(), format: template.format, output_path: template.output_path.clone() })
+            .collect();
+        if template.latest > 0 {
+            for url in urls.iter().filter(|url| playlist::is_playlist_url(url)) {
+                self.recurring.playlist_limits.insert(url.clone(), template.latest as usize);
+            }
+        }
+        let name = template.name.clone();
+        self.save_queue_templates();
+
+        self.run_digests.start(&name);
+        let (playlists, videos): (Vec<&String>, Vec<&String>) = urls.iter().partition(|url| playlist::is_playlist_url(url));
+        self.run_digests.add_items(&name, videos.into_iter().map(|url| (url.clone(), None)).collect());
+        for url in playlists {
+            self.run_digests.add_source(&name, Some(url));
+        }
+        self.log_console(format!("Running the queue template \"{}\": {} URL(s)", name, downloads.len()));
+        self.queue_auto_downloads(downloads);
+
+        if !feed_urls.is_empty() {
+            self.run_digests.add_source(&name, None);
+            self.log_console(format!("Reading {} feed(s) of \"{}\"", feed_urls.len(), name));
+            let settings = self.settings.with_proxy(self.proxy.as_deref());
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                tx.send(feeds::fetch_all(&feed_urls, &settings)).ok();
+            });
+            self.recurring.feed_runs.push((name, rx));
+        }
+    }
+
+    // Queues the entries of a template's feeds it hasn't queued before
+    fn finish_feed_run(&mut self, name: &str, results: FeedResults) {
+        self.run_digests.source_done(name);
+        let Some(template) = self.queue_templates.templates.iter_mut().find(|template| template.name == name) else {
+            return;
+        };
+        let mut notes = Vec::new();
+        let mut downloads = Vec::new();
+        for (url, result) in results {
+            let entries = match result {
+                Ok(entries) => entries,
+                Err(e) => {
+                    notes.push(format!("WARNING: Could not read the feed {}: {}", url, e));
+                    continue;
+                }
+            };
+            let limit = if template.latest > 0 { template.latest as usize } else { entries.len() };
+            let new: Vec<FeedEntry> = entries
+                .into_iter()
+                .take(limit)
+                .filter(|entry| !template.seen.contains(&entry.url))
+                .collect();
+            notes.push(format!("Feed {}: {} new entr{}", url, new.len(), if new.len() == 1 { "y" } else { "ies" }));
+            for entry in new {
+                notes.push(format!("    {}", entry.title));
+                self.run_digests.add_items(name, vec![(entry.url.clone(), Some(entry.title))]);
+                template.seen.push(entry.url.clone());
+                downloads.push(AutoDownload { url: entry.url, format: template.format, output_path: template.output_path.clone() });
+            }
+        }
+        let overflow = template.seen.len().saturating_sub(SEEN_LIMIT);
+        template.seen.drain(..overflow);
+
+        for note in notes {
+            self.log_console(note);
+        }
+        for download in &downloads {
+            if !self.is_valid_youtube_url(&download.url) {
+                self.recurring.feed_media.insert(download.url.clone());
+            }
+        }
+        self.save_queue_templates();
+        self.queue_auto_downloads(downloads);
+    }
+
+    pub(crate) fn reading_feeds(&self) -> bool {
+        !self.recurring.feed_runs.is_empty()
+    }
+
+    // Podcast episodes and other media that came from a feed, which yt-dlp can fetch directly
+    pub(crate) fn is_feed_media(&self, url: &str) -> bool {
+        self.recurring.feed_media.contains(url)
+    }
+
+    // Lets a link that isn't a YouTube video through to yt-dlp, like the feeds' media links
+    pub(crate) fn allow_direct_media(&mut self, url: &str) {
+        self.recurring.feed_media.insert(url.to_string());
+    }
+
+    pub(crate) fn take_playlist_limit(&mut self, url: &str) -> Option<usize> {
+        self.recurring.playlist_limits.remove(url)
+    }
+
+    // Called every frame; queues the templates whose time has come
+    pub(crate) fn poll_queue_templates(&mut self) {
+        let mut finished = Vec::new();
+        self.recurring.feed_runs.retain(|(name, receiver)| match receiver.try_recv() {
+            Ok(results) => {
+                finished.push((name.clone(), results));
+                false
+            }
+            Err(mpsc::TryRecvError::Empty) => true,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        });
+        for (name, results) in finished {
+            self.finish_feed_run(&name, results);
+        }
+
+        if self.recurring.last_check.is_some_and(|last_check| last_check.elapsed() < CHECK_INTERVAL) {
+            return;
+        }
+        self.recurring.last_check = Some(Instant::now());
+
+        let now = chrono::Utc::now().timestamp();
+        let due: Vec<usize> = self.queue_templates.templates
+            .iter()
+            .enumerate()
+            .filter(|(_, template)| template.is_due(now))
+            .map(|(index, _)| index)
+            .collect();
+        for index in due {
+            self.run_queue_template(index);
+        }
+    }
+
+    // The queue as it stands, in the format and folder of its first download
+    fn editor_from_queue(&self) -> TemplateEditor {
+        let urls: Vec<&str> = self.download_queue
+            .iter()
+            .map(|download| download.url.as_str())
+            .chain(self.pending_urls.iter().map(String::as_str))
+            .collect();
+        TemplateEditor {
+            index: None,
+            name: String::new(),
+            urls: urls.join("\n"),
+            format: self.download_queue.front().map_or(self.download_format, |download| download.format),
+            output_path: self.download_queue.front().map_or_else(|| self.output_path.clone(), |download| download.output_path.clone()),
+            every_days: 0,
+            latest: 0,
+            error: None,
+        }
+    }
+
+    fn new_editor(&self, name: &str, urls: Vec<String>, every_days: u32, latest: u32) -> TemplateEditor {
+        TemplateEditor {
+            index: None,
+            name: name.to_string(),
+            urls: urls.join("\n"),
+            format: self.download_format,
+            output_path: self.output_path.clone(),
+            every_days,
+            latest,
+            error: None,
+        }
+    }
+
+    // Opens the editor with the channels of a subscriptions export, to be looked over and saved
+    fn import_subscriptions(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Subscriptions", &["csv", "opml", "xml", "txt"])
+            .pick_file()
+        else {
+            return;
+        };
+        let urls = match std::fs::read_to_string(&path) {
+            Ok(text) => parse_subscriptions(&text),
+            Err(e) => {
+                self.recurring.import_error = Some(format!("Could not read {}: {}", path.display(), e));
+                return;
+            }
+        };
+        if urls.is_empty() {
+            self.recurring.import_error = Some(format!("No YouTube channels found in {}", file_name(&path)));
+            return;
+        }
+
+        self.recurring.import_error = None;
+        self.log_console(format!("Imported {} channel(s) from {}", urls.len(), file_name(&path)));
+        self.recurring.editor = Some(self.new_editor("YouTube subscriptions", urls, 1, IMPORTED_LATEST));
+    }
+
+    fn editor_for(&self, index: usize) -> TemplateEditor {
+        let template = &self.queue_templates.templates[index];
+        TemplateEditor {
+            index: Some(index),
+            name: template.name.clone(),
+            urls: template.urls.join("\n"),
+            format: template.format,
+            output_path: template.output_path.clone(),
+            every_days: template.every_days,
+            latest: template.latest,
+            error: None,
+        }
+    }
+
+    fn save_template_editor(&mut self) {
+        let Some(mut editor) = self.recurring.editor.take() else {
+            return;
+        };
+        let name = editor.name.trim().to_string();
+        let urls: Vec<String> = editor.urls
+            .lines()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect();
+        let invalid: Vec<&String> = urls.iter().filter(|url| !self.is_valid_youtube_url(url) && !feeds::is_feed_url(url)).collect();
+
+        let error = if name.is_empty() {
+            Some("Enter a name for the template".to_string())
+        } else if urls.is_empty() {
+            Some("Add at least one URL".to_string())
+        } else if let Some(url) = invalid.first() {
+            Some(format!("Not a supported URL: {}", url))
+        } else if self.queue_templates.templates
+            .iter()
+            .enumerate()
+            .any(|(index, template)| Some(index) != editor.index && template.name.eq_ignore_ascii_case(&name))
+        {
+            Some(format!("There is already a template called {}", name))
+        } else {
+            None
+        };
+        if error.is_some() {
+            editor.error = error;
+            self.recurring.editor = Some(editor);
+            return;
+        }
+
+        let template = QueueTemplate {
+            name,
+            urls,
+            format: editor.format,
+            output_path: editor.output_path.trim().to_string(),
+            every_days: editor.every_days,
+            latest: editor.latest,
+            last_run: editor.index.and_then(|index| self.queue_templates.templates[index].last_run),
+            seen: editor.index.map(|index| self.queue_templates.templates[index].seen.clone()).unwrap_or_default(),
+        };
+        match editor.index {
+            Some(index) => self.queue_templates.templates[index] = template,
+            None => self.queue_templates.templates.push(template),
+        }
+        self.save_queue_templates();
+    }
+
+    pub(crate) fn show_queue_templates_window(&mut self, ctx: &egui::Context) {
+        if !self.recurring.open {
+            return;
+        }
+
+        let mut open = true;
+        let mut run = None;
+        let mut edit = None;
+        let mut remove = None;
+        let mut from_queue = false;
+        let mut new_template = false;
+        let mut import = false;
+        let mut save = false;
+        let mut cancel = false;
+
+        egui::Window::new("🗓 Queue templates")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Sets of URLs to download again on demand or on a schedule.");
+                    help::help_button(ui, "queue_templates");
+                    if ui.button("📬 Digests").on_hover_text("What the last runs found and downloaded").clicked() {
+                        self.run_digests.open = true;
+                    }
+                });
+                ui.add_space(5.0);
+
+                if self.queue_templates.templates.is_empty() {
+                    ui.label(egui::RichText::new("No templates yet.").color(egui::Color32::GRAY));
+                } else {
+                    egui::Grid::new("queue_templates_grid")
+                        .num_columns(4)
+                        .spacing([10.0, 6.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (index, template) in self.queue_templates.templates.iter().enumerate() {
+                                ui.label(egui::RichText::new(&template.name).strong())
+                                    .on_hover_text(template.urls.join("\n"));
+                                ui.label(format!("{} URL(s), {}", template.urls.len(), template.format.label()));
+                                let last_run = template.last_run
+                                    .and_then(locale::timestamp)
+                                    .map(|time| format!(", last run {}", time))
+                                    .unwrap_or_default();
+                                ui.label(format!("{}{}", template.schedule_text(), last_run));
+                                ui.horizontal(|ui| {
+                                    if ui.small_button("▶ Run").clicked() {
+                                        run = Some(index);
+                                    }
+                                    if ui.small_button("✏").accessible_name("Edit template").clicked() {
+                                        edit = Some(index);
+                                    }
+                                    if ui.small_button("🗑").accessible_name("Remove template").clicked() {
+                                        remove = Some(index);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                }
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    let queued = self.download_queue.len() + self.pending_urls.len();
+                    if ui.add_enabled(queued > 0, egui::Button::new(format!("💾 Save the queue ({})", queued))).clicked() {
+                        from_queue = true;
+                    }
+                    if ui.button("➕ New template").clicked() {
+                        new_template = true;
+                    }
+                    if ui.button("📥 Import subscriptions...")
+                        .on_hover_text("Google Takeout subscriptions.csv, or an OPML or RSS feed list")
+                        .clicked()
+                    {
+                        import = true;
+                    }
+                });
+                if let Some(error) = &self.recurring.import_error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+
+                let Some(editor) = &mut self.recurring.editor else {
+                    return;
+                };
+                ui.separator();
+                egui::Grid::new("queue_template_editor")
+                    .num_columns(2)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        ui.label("Name:");
+                        ui.add(egui::TextEdit::singleline(&mut editor.name)
+                            .desired_width(250.0)
+                            .hint_text("e.g. Weekly radio shows"));
+                        ui.end_row();
+
+                        ui.label("Format:");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut editor.format, DownloadFormat::Mp4, "🎥 MP4");
+                            ui.radio_value(&mut editor.format, DownloadFormat::Mp3, "🎵 MP3");
+                        });
+                        ui.end_row();
+
+                        ui.label("Folder:");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut editor.output_path).desired_width(220.0));
+                            if ui.button("📂").accessible_name("Choose template folder").clicked() {
+                                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                    editor.output_path = path.display().to_string();
+                                }
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Run every (days, 0 = by hand):");
+                        ui.add(egui::DragValue::new(&mut editor.every_days).range(0..=365));
+                        ui.end_row();
+
+                        ui.label("Newest videos per channel or feed (0 = all):");
+                        ui.add(egui::DragValue::new(&mut editor.latest).range(0..=500));
+                        ui.end_row();
+                    });
+                ui.label("URLs, one per line (videos, playlists, channels or RSS/Atom feeds):");
+                ui.add(egui::TextEdit::multiline(&mut editor.urls)
+                    .desired_width(f32::INFINITY)
+                    .desired_rows(5));
+                if let Some(error) = &editor.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("💾 Save template").clicked() {
+                        save = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        self.recurring.open = open;
+
+        if let Some(index) = run {
+            self.run_queue_template(index);
+        }
+        if let Some(index) = edit {
+            self.recurring.editor = Some(self.editor_for(index));
+        }
+        if from_queue {
+            self.recurring.editor = Some(self.editor_from_queue());
+        }
+        if new_template {
+            self.recurring.editor = Some(self.new_editor("", Vec::new(), 7, 0));
+        }
+        if import {
+            self.import_subscriptions();
+        }
+        if save {
+            self.save_template_editor();
+        }
+        if cancel {
+            self.recurring.editor = None;
+        }
+        if let Some(index) = remove {
+            self.queue_templates.templates.remove(index);
+            // The editor's index would point at another template now
+            self.recurring.editor = None;
+            self.save_queue_templates();
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
@@ -0,0 +1,17 @@
+use anyhow::Result;
+use std::path::Path;
+
+// Moves a file to the trash. On Linux `gio trash` does it the way the file manager does, including
+// the trash folders of other drives; the trash crate takes over where gio isn't installed
+pub fn move_to_trash(path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    #[cfg(target_os = "linux")]
+    match std::process::Command::new("gio").args(["trash", "--"]).arg(path).output() {
+        Ok(output) if output.status.success() => return Ok(()),
+        Ok(output) => return Err(anyhow::anyhow!("{}", String::from_utf8_lossy(&output.stderr).trim())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    trash::delete(path)?;
+    Ok(())
+}
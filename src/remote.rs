@@ -0,0 +1,464 @@
+use anyhow::Result;
+use eframe::egui;
+use std::collections::VecDeque;
+use std::net::{IpAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::{HandshakeError, Message, WebSocket};
+
+use crate::a11y::AccessibleName;
+use crate::help;
+use crate::{events, rtl, DownloadFormat, YtMp3App};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+// Finished downloads listed in the remote window
+const KEEP_RESULTS: usize = 20;
+// Queued URLs listed before "and N more"
+const SHOW_QUEUED: usize = 10;
+
+// The address other computers on the network reach this one at. Connecting a UDP socket only
+// picks the route, nothing is sent
+pub fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+fn computer_name() -> String {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "another computer".to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteResult {
+    pub title: String,
+    pub error: Option<String>,
+}
+
+// The other instance as its event stream describes it
+#[derive(Debug, Clone, Default)]
+pub struct RemoteStatus {
+    pub connected: bool,
+    pub error: Option<String>,
+    pub version: String,
+    pub paused: bool,
+    pub queue: Vec<String>,
+    // Title, percent and status of the download running there
+    pub current: Option<(String, f32, String)>,
+    pub results: VecDeque<RemoteResult>,
+}
+
+fn text<'a>(event: &'a serde_json::Value, name: &str) -> &'a str {
+    event.get(name).and_then(|value| value.as_str()).unwrap_or_default()
+}
+
+fn urls(event: &serde_json::Value) -> Vec<String> {
+    event
+        .get("urls")
+        .and_then(|urls| urls.as_array())
+        .map(|urls| urls.iter().filter_map(|url| url.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+impl RemoteStatus {
+    fn apply_event(&mut self, event: &serde_json::Value) {
+        match text(event, "type") {
+            "hello" => {
+                self.version = text(event, "version").to_string();
+                self.paused = event.get("paused").and_then(|paused| paused.as_bool()).unwrap_or_default();
+                self.queue = urls(event);
+            }
+            "queue" => {
+                self.paused = event.get("paused").and_then(|paused| paused.as_bool()).unwrap_or_default();
+                self.queue = urls(event);
+            }
+            "download_started" => {
+                self.current = Some((text(event, "title").to_string(), 0.0, "Starting download...".to_string()));
+            }
+            "progress" => {
+                if let Some((_, percent, status)) = &mut self.current {
+                    *percent = event.get("percent").and_then(|percent| percent.as_f64()).unwrap_or_default() as f32;
+                    *status = text(event, "status").to_string();
+                }
+            }
+            "download_complete" | "download_failed" => {
+                let title = self.current.take().map(|(title, ..)| title).unwrap_or_else(|| text(event, "url").to_string());
+                let error = (text(event, "type") == "download_failed").then(|| text(event, "error").to_string());
+                self.results.push_front(RemoteResult { title, error });
+                self.results.truncate(KEEP_RESULTS);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn connect(address: &str, token: &str) -> Result<WebSocket<TcpStream>> {
+    let addr = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Could not find {}", address))?;
+    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    let mut request = format!("ws://{}/", address).into_client_request()?;
+    request.headers_mut().insert("Authorization", format!("Bearer {}", token).parse()?);
+    let socket = match tungstenite::client(request, stream) {
+        Ok((socket, _)) => socket,
+        Err(HandshakeError::Failure(tungstenite::Error::Http(response))) if response.status() == 401 => {
+            return Err(anyhow::anyhow!("This computer isn't paired with it anymore, pair again"));
+        }
+        Err(e) => return Err(anyhow::anyhow!("{}", e)),
+    };
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    Ok(socket)
+}
+
+// Reads the other instance's event stream on a background thread that reconnects when the
+// connection drops; dropping it disconnects
+pub struct RemoteConnection {
+    status: Arc<Mutex<RemoteStatus>>,
+    // Action names sent over the stream
+    actions: mpsc::Sender<String>,
+    stop: Arc<AtomicBool>,
+}
+
+impl RemoteConnection {
+    pub fn start(address: String, token: String, ctx: egui::Context) -> Self {
+        let status = Arc::new(Mutex::new(RemoteStatus::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (actions, action_rx) = mpsc::channel::<String>();
+
+        let (thread_status, thread_stop) = (status.clone(), stop.clone());
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                let result = connect(&address, &token).and_then(|mut socket| {
+                    {
+                        let mut status = thread_status.lock().unwrap();
+                        status.connected = true;
+                        status.error = None;
+                    }
+                    ctx.request_repaint();
+                    while !thread_stop.load(Ordering::Relaxed) {
+                        while let Ok(action) = action_rx.try_recv() {
+                            socket.send(Message::Text(action))?;
+                        }
+                        match socket.read() {
+                            Ok(Message::Text(text)) => {
+                                if let Ok(event) = serde_json::from_str::<serde_json::Value>(&text) {
+                                    thread_status.lock().unwrap().apply_event(&event);
+                                    ctx.request_repaint();
+                                }
+                            }
+                            Ok(Message::Close(_)) => return Err(anyhow::anyhow!("The other computer closed the connection")),
+                            Ok(_) => {}
+                            Err(tungstenite::Error::Io(e))
+                                if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+                            Err(e) => return Err(e.into()),
+                        }
+                    }
+                    socket.close(None).ok();
+                    Ok(())
+                });
+
+                {
+                    let mut status = thread_status.lock().unwrap();
+                    status.connected = false;
+                    status.current = None;
+                    status.error = result.err().map(|e| e.to_string());
+                }
+                ctx.request_repaint();
+
+                let mut waited = Duration::ZERO;
+                while waited < RECONNECT_DELAY && !thread_stop.load(Ordering::Relaxed) {
+                    thread::sleep(POLL_INTERVAL);
+                    waited += POLL_INTERVAL;
+                }
+            }
+        });
+
+        Self { status, actions, stop }
+    }
+
+    pub fn status(&self) -> RemoteStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    fn send_action(&self, action: &str) {
+        self.actions.send(action.to_string()).ok();
+    }
+}
+
+impl Drop for RemoteConnection {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// POSTs JSON to the other instance on a background thread; the answer's JSON comes back
+fn post(address: &str, path: &str, token: Option<&str>, body: serde_json::Value) -> mpsc::Receiver<Result<serde_json::Value>> {
+    let url = format!("http://{}{}", address, path);
+    let token = token.map(String::from);
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = tokio::runtime::Runtime::new().map_err(anyhow::Error::from).and_then(|rt| {
+            rt.block_on(async {
+                // Straight to the other computer, never through a proxy
+                let client = reqwest::Client::builder().no_proxy().timeout(REQUEST_TIMEOUT).build()?;
+                let mut request = client.post(&url).json(&body);
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+                let answer: serde_json::Value = request.send().await?.json().await?;
+                if answer.get("ok").and_then(|ok| ok.as_bool()) != Some(true) {
+                    return Err(anyhow::anyhow!("{}", text(&answer, "error")));
+                }
+                Ok(answer)
+            })
+        });
+        tx.send(result).ok();
+    });
+    rx
+}
+
+#[derive(Default)]
+pub struct Remote {
+    pub open: bool,
+    connection: Option<RemoteConnection>,
+    address_input: String,
+    code_input: String,
+    url_input: String,
+    format: Option<DownloadFormat>,
+    pairing: Option<mpsc::Receiver<Result<serde_json::Value>>>,
+    sending: Vec<(String, mpsc::Receiver<Result<serde_json::Value>>)>,
+    notice: Option<String>,
+}
+
+impl YtMp3App {
+    pub(crate) fn apply_remote_settings(&mut self, ctx: &egui::Context) {
+        let address = self.settings.remote_address.trim().to_string();
+        let token = self.settings.remote_token.trim().to_string();
+        self.remote.connection = (!address.is_empty() && !token.is_empty())
+            .then(|| RemoteConnection::start(address, token, ctx.clone()));
+    }
+
+    pub(crate) fn remote_connected(&self) -> bool {
+        self.remote.connection.as_ref().is_some_and(|connection| connection.status().connected)
+    }
+
+    // Queues the URL on the other instance, in its folder for the format
+    pub(crate) fn send_to_remote(&mut self, url: String, format: DownloadFormat) {
+        let body = serde_json::json!({ "url": url, "format": events::format_name(format) });
+        let rx = post(&self.settings.remote_address, "/enqueue", Some(&self.settings.remote_token), body);
+        self.remote.sending.push((url, rx));
+    }
+
+    // Called every frame for the answers to pairing and sent URLs
+    pub(crate) fn poll_remote(&mut self, ctx: &egui::Context) {
+        if let Some(pairing) = &self.remote.pairing {
+            match pairing.try_recv() {
+                Ok(Ok(answer)) => {
+                    self.remote.pairing = None;
+                    self.settings.remote_address = self.remote.address_input.trim().to_string();
+                    self.settings.remote_token = text(&answer, "token").to_string();
+                    self.remote.code_input.clear();
+                    self.remote.notice = None;
+                    if let Err(e) = self.settings.save() {
+                        self.log_console(format!("WARNING: Could not save settings: {}", e));
+                    }
+                    self.log_console(format!("Paired with {} as a remote", self.settings.remote_address));
+                    self.apply_remote_settings(ctx);
+                }
+                Ok(Err(e)) => {
+                    self.remote.pairing = None;
+                    self.remote.notice = Some(format!("Pairing failed: {}", e));
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.remote.pairing = None,
+            }
+        }
+
+        let mut answers = Vec::new();
+        self.remote.sending.retain(|(url, rx)| match rx.try_recv() {
+            Ok(answer) => {
+                answers.push((url.clone(), answer));
+                false
+            }
+            Err(mpsc::TryRecvError::Empty) => true,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        });
+        for (url, answer) in answers {
+            let notice = match answer {
+                Ok(_) => format!("Sent to {}: {}", self.settings.remote_address, url),
+                Err(e) => format!("Could not send {} to {}: {}", url, self.settings.remote_address, e),
+            };
+            self.log_console(notice.clone());
+            self.remote.notice = Some(notice);
+        }
+    }
+
+    pub(crate) fn show_remote_window(&mut self, ctx: &egui::Context) {
+        if !self.remote.open {
+            return;
+        }
+
+        let mut open = true;
+        let mut forget = false;
+        let mut send = None;
+        egui::Window::new("🖧 Remote control")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Queue downloads on another computer running this app, like an always-on home server.");
+                    help::help_button(ui, "remote_mode");
+                });
+                ui.separator();
+
+                let Some(connection) = &self.remote.connection else {
+                    ui.label("On the other computer, turn on the local API and \"Let paired computers on the network control \
+                              this app\" in Settings, then click \"Pair a device\" there.");
+                    ui.horizontal(|ui| {
+                        ui.label("Address:");
+                        ui.add(egui::TextEdit::singleline(&mut self.remote.address_input)
+                            .desired_width(180.0)
+                            .hint_text("192.168.1.20:38518"))
+                            .accessible_name("Remote address");
+                        ui.label("Code:");
+                        ui.add(egui::TextEdit::singleline(&mut self.remote.code_input)
+                            .desired_width(70.0)
+                            .hint_text("123456"))
+                            .accessible_name("Pairing code");
+                        let ready = !self.remote.address_input.trim().is_empty()
+                            && !self.remote.code_input.trim().is_empty()
+                            && self.remote.pairing.is_none();
+                        if ui.add_enabled(ready, egui::Button::new("Pair")).clicked() {
+                            let body = serde_json::json!({
+                                "code": self.remote.code_input.trim(),
+                                "name": format!("{} on {}", crate::APP_TITLE, computer_name()),
+                            });
+                            self.remote.pairing = Some(post(self.remote.address_input.trim(), "/pair", None, body));
+                            self.remote.notice = None;
+                        }
+                    });
+                    if self.remote.pairing.is_some() {
+                        ui.label("Pairing...");
+                    }
+                    if let Some(notice) = &self.remote.notice {
+                        ui.colored_label(egui::Color32::RED, notice);
+                    }
+                    return;
+                };
+
+                let status = connection.status();
+                ui.horizontal(|ui| {
+                    if status.connected {
+                        ui.colored_label(egui::Color32::GREEN, format!("● Connected to {}", self.settings.remote_address));
+                        if !status.version.is_empty() {
+                            ui.label(egui::RichText::new(format!("v{}", status.version)).small().color(egui::Color32::GRAY));
+                        }
+                    } else {
+                        ui.colored_label(egui::Color32::GRAY, format!("○ Not connected to {}", self.settings.remote_address));
+                    }
+                    if ui.small_button("Forget").on_hover_text("Stop being a remote for this computer").clicked() {
+                        forget = true;
+                    }
+                });
+                if let Some(error) = &status.error {
+                    ui.colored_label(egui::Color32::from_rgb(230, 150, 0), format!("{}, trying again...", error));
+                }
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.remote.url_input)
+                        .desired_width(260.0)
+                        .hint_text("Paste a URL to download there..."))
+                        .accessible_name("URL to send");
+                    let format = self.remote.format.get_or_insert(self.download_format);
+                    ui.radio_value(format, DownloadFormat::Mp3, "MP3");
+                    ui.radio_value(format, DownloadFormat::Mp4, "MP4");
+                    let url = self.remote.url_input.trim();
+                    if ui.add_enabled(status.connected && !url.is_empty(), egui::Button::new("📤 Send")).clicked() {
+                        send = Some((url.to_string(), *format));
+                    }
+                });
+                if let Some(notice) = &self.remote.notice {
+                    ui.label(egui::RichText::new(notice).small());
+                }
+                ui.separator();
+
+                ui.add_enabled_ui(status.connected, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "{} queued{}",
+                            status.queue.len(),
+                            if status.paused { ", paused" } else { "" }
+                        ));
+                        if status.paused {
+                            if ui.button("▶ Start queue").clicked() {
+                                connection.send_action("start_queue");
+                            }
+                        } else if ui.button("⏸ Pause queue").clicked() {
+                            connection.send_action("pause_queue");
+                        }
+                    });
+                    if let Some((title, percent, status)) = &status.current {
+                        ui.label(format!("⬇ {}", rtl::display(title)));
+                        ui.add(egui::ProgressBar::new(percent / 100.0).text(status.as_str()));
+                    }
+                    for url in status.queue.iter().take(SHOW_QUEUED) {
+                        ui.label(egui::RichText::new(url).small());
+                    }
+                    if status.queue.len() > SHOW_QUEUED {
+                        ui.label(egui::RichText::new(format!("and {} more", status.queue.len() - SHOW_QUEUED)).small());
+                    }
+                });
+
+                if !status.results.is_empty() {
+                    ui.separator();
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for result in &status.results {
+                            ui.horizontal(|ui| {
+                                match &result.error {
+                                    Some(error) => {
+                                        ui.colored_label(egui::Color32::RED, "✖").on_hover_text(error);
+                                    }
+                                    None => {
+                                        ui.colored_label(egui::Color32::GREEN, "✔");
+                                    }
+                                }
+                                ui.label(rtl::display(&result.title).to_string());
+                            });
+                        }
+                    });
+                }
+            });
+        self.remote.open = open;
+
+        if let Some((url, format)) = send {
+            self.remote.url_input.clear();
+            self.send_to_remote(url, format);
+        }
+        if forget {
+            self.remote.address_input = self.settings.remote_address.clone();
+            self.settings.remote_address.clear();
+            self.settings.remote_token.clear();
+            if let Err(e) = self.settings.save() {
+                self.log_console(format!("WARNING: Could not save settings: {}", e));
+            }
+            self.remote.connection = None;
+            self.remote.notice = None;
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use eframe::egui;
+
+use crate::{AppState, YtMp3App};
+
+// Browsers yt-dlp can read cookies from with --cookies-from-browser
+pub const COOKIE_BROWSERS: &[&str] = &[
+    "chrome", "firefox", "edge", "brave", "opera", "vivaldi", "chromium", "safari", "whale",
+];
+
+const AGE_RESTRICTION_MARKERS: &[&str] = &[
+    "sign in to confirm your age",
+    "age-restricted",
+    "age restricted",
+    "inappropriate for some users",
+];
+
+pub fn is_age_restriction_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    AGE_RESTRICTION_MARKERS.iter().any(|marker| error.contains(marker))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LockKind {
+    MembersOnly,
+    Premium,
+}
+
+impl LockKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LockKind::MembersOnly => "🔒 Members only",
+            LockKind::Premium => "🔒 Premium only",
+        }
+    }
+}
+
+const MEMBERS_ONLY_MARKERS: &[&str] = &[
+    "members-only",
+    "members only",
+    "join this channel to get access",
+    "available to this channel's members",
+];
+
+const PREMIUM_MARKERS: &[&str] = &[
+    "premium members",
+    "youtube premium",
+    "music premium",
+    "premium_only",
+];
+
+pub fn lock_from_error(error: &str) -> Option<LockKind> {
+    let error = error.to_lowercase();
+    if MEMBERS_ONLY_MARKERS.iter().any(|marker| error.contains(marker)) {
+        Some(LockKind::MembersOnly)
+    } else if PREMIUM_MARKERS.iter().any(|marker| error.contains(marker)) {
+        Some(LockKind::Premium)
+    } else {
+        None
+    }
+}
+
+// yt-dlp's "availability" field: public, unlisted, private, needs_auth, subscriber_only, premium_only
+pub fn lock_from_availability(availability: Option<&str>) -> Option<LockKind> {
+    match availability? {
+        "subscriber_only" => Some(LockKind::MembersOnly),
+        "premium_only" => Some(LockKind::Premium),
+        _ => None,
+    }
+}
+
+impl YtMp3App {
+    pub(crate) fn show_age_restricted(&mut self, ui: &mut egui::Ui, error: &str) {
+        let mut retry = false;
+        let mut cancel = false;
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.heading("🔞 This video is age-restricted");
+                ui.add_space(5.0);
+                ui.label("YouTube only shows this video to signed-in adult accounts. yt-dlp can use the login \
+                          of a browser where you are signed in, or a cookies.txt file exported from one.");
+                ui.add_space(5.0);
+                ui.label(egui::RichText::new(error).small().color(egui::Color32::GRAY));
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("🌐 Use cookies from browser:");
+                    let selected = if self.age_gate_browser.is_empty() {
+                        "Choose...".to_string()
+                    } else {
+                        self.age_gate_browser.clone()
+                    };
+                    egui::ComboBox::from_id_source("age_gate_browser")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            for browser in COOKIE_BROWSERS {
+                                ui.selectable_value(&mut self.age_gate_browser, browser.to_string(), *browser);
+                            }
+                        });
+                    if ui.add_enabled(!self.age_gate_browser.is_empty(), egui::Button::new("✅ Enable & Retry"))
+                        .clicked()
+                    {
+                        self.settings.cookies_browser = self.age_gate_browser.clone();
+                        self.settings.cookies_file.clear();
+                        retry = true;
+                    }
+                });
+
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("📄 Use a cookies.txt file:");
+                    if ui.button("📂 Choose File & Retry").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Cookies", &["txt"])
+                            .pick_file()
+                        {
+                            self.settings.cookies_file = path.display().to_string();
+                            self.settings.cookies_browser.clear();
+                            retry = true;
+                        }
+                    }
+                });
+
+                ui.add_space(10.0);
+                if ui.button("🔙 Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+        if retry {
+            if let Err(e) = self.settings.save() {
+                self.log_console(format!("WARNING: Could not save settings: {}", e));
+            }
+            self.fetch_video_info();
+        } else if cancel {
+            self.auto_download = false;
+            self.state = AppState::Input;
+        }
+    }
+}
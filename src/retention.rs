@@ -0,0 +1,331 @@
+use anyhow::Result;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::a11y::AccessibleName;
+use crate::help;
+use crate::{locale, recycle, tags, YtMp3App};
+
+// History tag for downloads that may be cleaned up when only temporary items are
+pub const TEMPORARY_TAG: &str = "temporary";
+// The rules run at startup and then this often
+const RUN_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// Files yt-dlp is still writing
+const PARTIAL_EXTENSIONS: &[&str] = &["part", "ytdl", "temp"];
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+// Rules for a folder that's cleaned up automatically
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    // Subfolders included; empty = no cleanup
+    pub folder: String,
+    // Remove files older than this; 0 = no age limit
+    pub max_age_days: u32,
+    // Remove the oldest files while the folder is bigger than this; 0 = no size limit
+    pub max_size_gb: f64,
+    // Only remove downloads tagged "temporary" in History
+    pub temporary_only: bool,
+}
+
+impl RetentionPolicy {
+    pub fn is_active(&self) -> bool {
+        !self.folder.trim().is_empty() && (self.max_age_days > 0 || self.max_size_gb > 0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reason {
+    TooOld,
+    OverSize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Removal {
+    pub path: PathBuf,
+    pub size: u64,
+    pub reason: Reason,
+}
+
+// What the rules would remove right now
+#[derive(Debug, Clone, Default)]
+pub struct CleanupPlan {
+    pub removals: Vec<Removal>,
+    pub folder_size: u64,
+}
+
+impl CleanupPlan {
+    pub fn freed(&self) -> u64 {
+        self.removals.iter().map(|removal| removal.size).sum()
+    }
+}
+
+struct FileInfo {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn list_files(folder: &Path, files: &mut Vec<FileInfo>) -> Result<()> {
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let path = entry.path();
+        if metadata.is_dir() {
+            list_files(&path, files)?;
+            continue;
+        }
+        let partial = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| PARTIAL_EXTENSIONS.contains(&extension));
+        if metadata.is_file() && !partial {
+            files.push(FileInfo { path, size: metadata.len(), modified: metadata.modified()? });
+        }
+    }
+    Ok(())
+}
+
+// Too old first, then the oldest until the folder fits; `temporary` limits it to those files
+pub fn plan(policy: &RetentionPolicy, temporary: Option<&HashSet<PathBuf>>) -> Result<CleanupPlan> {
+    let mut files = Vec::new();
+    list_files(Path::new(policy.folder.trim()), &mut files)?;
+    files.sort_by_key(|file| file.modified);
+    let folder_size: u64 = files.iter().map(|file| file.size).sum();
+
+    let eligible = |file: &FileInfo| temporary.is_none_or(|temporary| temporary.contains(&file.path));
+    let max_age = Duration::from_secs(policy.max_age_days as u64 * 24 * 60 * 60);
+    let now = SystemTime::now();
+
+    let mut removals = Vec::new();
+    let mut removed = HashSet::new();
+    if policy.max_age_days > 0 {
+        for (index, file) in files.iter().enumerate() {
+            let age = now.duration_since(file.modified).unwrap_or_default();
+            if age > max_age && eligible(file) {
+                removals.push(Removal { path: file.path.clone(), size: file.size, reason: Reason::TooOld });
+                removed.insert(index);
+            }
+        }
+    }
+
+    if policy.max_size_gb > 0.0 {
+        let cap = (policy.max_size_gb * BYTES_PER_GB) as u64;
+        let mut remaining = folder_size - removals.iter().map(|removal| removal.size).sum::<u64>();
+        for (index, file) in files.iter().enumerate() {
+            if remaining <= cap {
+                break;
+            }
+            if removed.contains(&index) || !eligible(file) {
+                continue;
+            }
+            removals.push(Removal { path: file.path.clone(), size: file.size, reason: Reason::OverSize });
+            remaining -= file.size;
+        }
+    }
+
+    Ok(CleanupPlan { removals, folder_size })
+}
+
+pub struct CleanupReport {
+    pub removed: Vec<Removal>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct Retention {
+    preview: Option<Result<CleanupPlan, String>>,
+    running: Option<mpsc::Receiver<Result<CleanupReport, String>>>,
+    last_run: Option<Instant>,
+}
+
+impl YtMp3App {
+    // Downloads tagged temporary, when the rules are limited to them
+    fn temporary_files(&self) -> Option<HashSet<PathBuf>> {
+        self.settings.retention.temporary_only.then(|| {
+            self.history.entries
+                .iter()
+                .filter(|entry| !entry.deleted && !entry.missing && tags::has_tag(&entry.tags, TEMPORARY_TAG))
+                .map(|entry| PathBuf::from(&entry.path))
+                .collect()
+        })
+    }
+
+    fn start_cleanup(&mut self) {
+        let policy = self.settings.retention.clone();
+        let temporary = self.temporary_files();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = plan(&policy, temporary.as_ref()).map_err(|e| e.to_string()).map(|plan| {
+                let mut report = CleanupReport { removed: Vec::new(), errors: Vec::new() };
+                for removal in plan.removals {
+                    match recycle::move_to_trash(&removal.path) {
+                        Ok(()) => report.removed.push(removal),
+                        Err(e) => report.errors.push(format!("{}: {}", removal.path.display(), e)),
+                    }
+                }
+                report
+            });
+            tx.send(result).ok();
+        });
+        self.retention.running = Some(rx);
+        self.retention.last_run = Some(Instant::now());
+    }
+
+    // Called every frame; runs the rules at startup and every hour, not while a download is
+    // writing into the folder
+    pub(crate) fn poll_retention(&mut self) {
+        if let Some(running) = &self.retention.running {
+            let result = match running.try_recv() {
+                Ok(result) => result,
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => Err("The cleanup stopped unexpectedly".to_string()),
+            };
+            self.retention.running = None;
+            self.finish_cleanup(result);
+            return;
+        }
+
+        let due = self.retention.last_run.is_none_or(|last_run| last_run.elapsed() >= RUN_INTERVAL);
+        if due && self.settings.retention.is_active() && !self.is_busy() && self.receiver.is_none() && self.downloads.is_empty() {
+            self.start_cleanup();
+        }
+    }
+
+    fn finish_cleanup(&mut self, result: Result<CleanupReport, String>) {
+        let report = match result {
+            Ok(report) => report,
+            Err(e) => {
+                self.log_console(format!("WARNING: Cleanup of {} failed: {}", self.settings.retention.folder, e));
+                return;
+            }
+        };
+
+        for error in &report.errors {
+            self.log_console(format!("WARNING: Cleanup could not remove {}", error));
+        }
+        if report.removed.is_empty() {
+            return;
+        }
+
+        let removed: HashSet<&Path> = report.removed.iter().map(|removal| removal.path.as_path()).collect();
+        let mut changed = Vec::new();
+        for (index, entry) in self.history.entries.iter_mut().enumerate() {
+            if !entry.deleted && removed.contains(Path::new(&entry.path)) {
+                entry.deleted = true;
+                changed.push(index);
+            }
+        }
+        if !changed.is_empty() {
+            if let Err(e) = self.history.save(changed) {
+                self.log_console(format!("WARNING: Could not save history: {}", e));
+            }
+        }
+
+        let freed: u64 = report.removed.iter().map(|removal| removal.size).sum();
+        self.log_console(format!(
+            "Cleanup moved {} file(s) to the trash, freeing {}",
+            report.removed.len(),
+            locale::bytes(freed as f64)
+        ));
+        self.retention.preview = None;
+    }
+
+    pub(crate) fn show_retention_settings(&mut self, ui: &mut egui::Ui) {
+        let mut preview = false;
+        let mut run_now = false;
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🧹 Automatic cleanup:", "retention");
+                ui.add_space(5.0);
+                let policy = &mut self.settings.retention;
+                ui.horizontal(|ui| {
+                    ui.label("Folder:");
+                    ui.add(egui::TextEdit::singleline(&mut policy.folder)
+                        .desired_width(250.0)
+                        .hint_text("None"));
+                    if ui.button("📂").accessible_name("Choose cleanup folder").clicked() {
+                        if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                            policy.folder = path.display().to_string();
+                        }
+                    }
+                });
+                egui::Grid::new("retention_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        ui.label("Remove files older than (days, 0 = keep):");
+                        ui.add(egui::DragValue::new(&mut policy.max_age_days).range(0..=3650));
+                        ui.end_row();
+
+                        ui.label("Keep the folder under (GB, 0 = no limit):");
+                        ui.add(egui::DragValue::new(&mut policy.max_size_gb).range(0.0..=10000.0).speed(0.5));
+                        ui.end_row();
+                    });
+                ui.checkbox(
+                    &mut policy.temporary_only,
+                    format!("Only remove downloads tagged \"{}\" in History", TEMPORARY_TAG),
+                );
+
+                let active = policy.is_active();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(active, egui::Button::new("👁 Preview")).on_hover_text("Show what would be removed, without removing anything").clicked() {
+                        preview = true;
+                    }
+                    let idle = self.retention.running.is_none();
+                    if ui.add_enabled(active && idle, egui::Button::new("🧹 Clean up now")).clicked() {
+                        run_now = true;
+                    }
+                });
+
+                match &self.retention.preview {
+                    Some(Ok(plan)) if plan.removals.is_empty() => {
+                        ui.label(format!("Nothing to remove; the folder holds {}.", locale::bytes(plan.folder_size as f64)));
+                    }
+                    Some(Ok(plan)) => {
+                        ui.label(format!(
+                            "Would move {} file(s) to the trash, freeing {} of {}:",
+                            plan.removals.len(),
+                            locale::bytes(plan.freed() as f64),
+                            locale::bytes(plan.folder_size as f64)
+                        ));
+                        egui::ScrollArea::vertical()
+                            .id_source("retention_preview")
+                            .max_height(150.0)
+                            .show(ui, |ui| {
+                                for removal in &plan.removals {
+                                    let reason = match removal.reason {
+                                        Reason::TooOld => "too old",
+                                        Reason::OverSize => "over the size limit",
+                                    };
+                                    ui.label(egui::RichText::new(format!(
+                                        "{} ({}, {})",
+                                        removal.path.display(),
+                                        locale::bytes(removal.size as f64),
+                                        reason
+                                    )).small());
+                                }
+                            });
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Could not read the folder: {}", e));
+                    }
+                    None => {}
+                }
+            });
+        });
+
+        if preview {
+            let temporary = self.temporary_files();
+            self.retention.preview = Some(plan(&self.settings.retention, temporary.as_ref()).map_err(|e| e.to_string()));
+        }
+        if run_now {
+            self.start_cleanup();
+        }
+    }
+}
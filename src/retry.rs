@@ -0,0 +1,227 @@
+use anyhow::Result;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::a11y::AccessibleName;
+use crate::channel;
+use crate::locale;
+use crate::settings::Settings;
+use crate::{database, get_video_info, AutoDownload, DownloadFormat, YtMp3App};
+
+// Errors for videos that may well work later: (marker in yt-dlp's error, reason shown to the user)
+const TEMPORARY_MARKERS: &[(&str, &str)] = &[
+    ("private video", "Private"),
+    ("premieres in", "Premiere not started"),
+    ("this live event will begin", "Live stream not started"),
+    ("is upcoming", "Not released yet"),
+    ("still being processed", "Still processing"),
+    ("is processing", "Still processing"),
+    ("not available in your country", "Not available in your region"),
+    ("not made this video available in your country", "Not available in your region"),
+    ("geo restriction", "Not available in your region"),
+];
+
+// Why the error looks temporary, None for errors retrying won't fix
+pub fn temporary_reason(error: &str) -> Option<&'static str> {
+    let error = error.to_lowercase();
+    TEMPORARY_MARKERS
+        .iter()
+        .find(|(marker, _)| error.contains(marker))
+        .map(|(_, reason)| *reason)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryEntry {
+    pub url: String,
+    pub format: DownloadFormat,
+    pub output_path: String,
+    pub reason: String,
+    // Unix timestamps (seconds); 0 = not checked yet
+    pub added_at: i64,
+    pub last_checked: i64,
+    pub checks: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RetryList {
+    pub entries: Vec<RetryEntry>,
+}
+
+impl RetryList {
+    pub fn load() -> Self {
+        match database::load_list(database::RETRY_LATER) {
+            Ok(entries) => Self { entries },
+            Err(e) => {
+                log::warn!("Could not read the retry list: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        database::save_list(database::RETRY_LATER, &self.entries)
+    }
+}
+
+// Outcome of a background check: the URL and, if it's still unavailable, why
+pub type RetryCheck = mpsc::Receiver<(String, Result<(), String>)>;
+
+fn check_entry(url: String, settings: Settings, ctx: egui::Context) -> RetryCheck {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        // The fetch's console output isn't wanted for a background check; with the receiver
+        // dropped right away the sends just fail
+        let (console_tx, _) = channel::app_channel();
+        let result = get_video_info(&url, &settings, &console_tx)
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+        tx.send((url, result)).ok();
+        ctx.request_repaint();
+    });
+    rx
+}
+
+fn checked_at_text(timestamp: i64) -> String {
+    if timestamp == 0 {
+        return "not yet".to_string();
+    }
+    locale::timestamp(timestamp).unwrap_or_else(|| "unknown".to_string())
+}
+
+impl YtMp3App {
+    pub(crate) fn add_to_retry_later(&mut self, reason: &str) {
+        let url = self.url_input.clone();
+        if self.retry_later.entries.iter().any(|entry| entry.url == url) {
+            return;
+        }
+        let now = chrono::Utc::now().timestamp();
+        self.retry_later.entries.push(RetryEntry {
+            url,
+            format: self.download_format,
+            output_path: self.output_path.clone(),
+            reason: reason.to_string(),
+            added_at: now,
+            last_checked: now,
+            checks: 0,
+        });
+        self.save_retry_later();
+        self.log_console(format!(
+            "Added to Retry later, checked again every {} minutes",
+            self.settings.retry_check_interval_mins
+        ));
+    }
+
+    fn save_retry_later(&mut self) {
+        if let Err(e) = self.retry_later.save() {
+            self.log_console(format!("WARNING: Could not save the Retry later list: {}", e));
+        }
+    }
+
+    // Called every frame: runs one due check at a time and queues the videos that became available
+    pub(crate) fn poll_retry_later(&mut self, ctx: &egui::Context) {
+        if let Some(rx) = &self.retry_check {
+            match rx.try_recv() {
+                Ok((url, result)) => {
+                    self.retry_check = None;
+                    let now = chrono::Utc::now().timestamp();
+                    match result {
+                        Ok(()) => {
+                            if let Some(index) = self.retry_later.entries.iter().position(|entry| entry.url == url) {
+                                let entry = self.retry_later.entries.remove(index);
+                                self.log_console(format!("Retry later: {} is available now", entry.url));
+                                self.download_queue.push_back(AutoDownload {
+                                    url: entry.url,
+                                    format: entry.format,
+                                    output_path: entry.output_path,
+                                });
+                            }
+                        }
+                        Err(error) => {
+                            if let Some(entry) = self.retry_later.entries.iter_mut().find(|entry| entry.url == url) {
+                                entry.last_checked = now;
+                                entry.checks += 1;
+                                if let Some(reason) = temporary_reason(&error) {
+                                    entry.reason = reason.to_string();
+                                }
+                            }
+                        }
+                    }
+                    self.save_retry_later();
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.retry_check = None,
+            }
+        }
+
+        if self.retry_check.is_none() {
+            let interval = self.settings.retry_check_interval_mins as i64 * 60;
+            let now = chrono::Utc::now().timestamp();
+            if let Some(entry) = self.retry_later.entries.iter().find(|entry| now - entry.last_checked >= interval) {
+                self.retry_check = Some(check_entry(entry.url.clone(), self.settings.clone(), ctx.clone()));
+            }
+        }
+    }
+
+    pub(crate) fn show_retry_later_window(&mut self, ctx: &egui::Context) {
+        if !self.show_retry_later {
+            return;
+        }
+
+        let mut open = true;
+        let mut remove = None;
+        let mut check_now = None;
+
+        egui::Window::new("⏰ Retry later")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(550.0)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Checked every {} minutes and downloaded as soon as they become available.",
+                    self.settings.retry_check_interval_mins
+                ));
+                ui.add_space(5.0);
+
+                if self.retry_later.entries.is_empty() {
+                    ui.label("Nothing waiting.");
+                    return;
+                }
+
+                let checking = self.retry_check.is_some();
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("retry_later_grid")
+                        .num_columns(4)
+                        .spacing([10.0, 6.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (index, entry) in self.retry_later.entries.iter().enumerate() {
+                                ui.label(&entry.url).on_hover_text(&entry.output_path);
+                                ui.label(&entry.reason);
+                                ui.label(format!("Checked: {} ({}×)", checked_at_text(entry.last_checked), entry.checks));
+                                ui.horizontal(|ui| {
+                                    if ui.add_enabled(!checking, egui::Button::new("🔄 Check now").small()).clicked() {
+                                        check_now = Some(index);
+                                    }
+                                    if ui.small_button("🗑").accessible_name("Remove").clicked() {
+                                        remove = Some(index);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        self.show_retry_later = open;
+
+        if let Some(index) = check_now {
+            // Makes it due, so the next poll picks it up
+            self.retry_later.entries[index].last_checked = 0;
+        }
+        if let Some(index) = remove {
+            self.retry_later.entries.remove(index);
+            self.save_retry_later();
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use eframe::egui;
+use std::borrow::Cow;
+use unicode_bidi::{BidiInfo, Direction};
+
+// egui lays text out left to right and doesn't shape Arabic, so right-to-left titles are put in
+// visual order and Arabic letters swapped for their joined forms before they're drawn
+
+// Arabic letter -> (isolated, final, initial, medial) presentation forms; 0 = the letter doesn't
+// join on that side. Letters with only isolated and final forms only join the letter before them
+const ARABIC_FORMS: &[(char, [u32; 4])] = &[
+    ('\u{0621}', [0xFE80, 0, 0, 0]),
+    ('\u{0622}', [0xFE81, 0xFE82, 0, 0]),
+    ('\u{0623}', [0xFE83, 0xFE84, 0, 0]),
+    ('\u{0624}', [0xFE85, 0xFE86, 0, 0]),
+    ('\u{0625}', [0xFE87, 0xFE88, 0, 0]),
+    ('\u{0626}', [0xFE89, 0xFE8A, 0xFE8B, 0xFE8C]),
+    ('\u{0627}', [0xFE8D, 0xFE8E, 0, 0]),
+    ('\u{0628}', [0xFE8F, 0xFE90, 0xFE91, 0xFE92]),
+    ('\u{0629}', [0xFE93, 0xFE94, 0, 0]),
+    ('\u{062A}', [0xFE95, 0xFE96, 0xFE97, 0xFE98]),
+    ('\u{062B}', [0xFE99, 0xFE9A, 0xFE9B, 0xFE9C]),
+    ('\u{062C}', [0xFE9D, 0xFE9E, 0xFE9F, 0xFEA0]),
+    ('\u{062D}', [0xFEA1, 0xFEA2, 0xFEA3, 0xFEA4]),
+    ('\u{062E}', [0xFEA5, 0xFEA6, 0xFEA7, 0xFEA8]),
+    ('\u{062F}', [0xFEA9, 0xFEAA, 0, 0]),
+    ('\u{0630}', [0xFEAB, 0xFEAC, 0, 0]),
+    ('\u{0631}', [0xFEAD, 0xFEAE, 0, 0]),
+    ('\u{0632}', [0xFEAF, 0xFEB0, 0, 0]),
+    ('\u{0633}', [0xFEB1, 0xFEB2, 0xFEB3, 0xFEB4]),
+    ('\u{0634}', [0xFEB5, 0xFEB6, 0xFEB7, 0xFEB8]),
+    ('\u{0635}', [0xFEB9, 0xFEBA, 0xFEBB, 0xFEBC]),
+    ('\u{0636}', [0xFEBD, 0xFEBE, 0xFEBF, 0xFEC0]),
+    ('\u{0637}', [0xFEC1, 0xFEC2, 0xFEC3, 0xFEC4]),
+    ('\u{0638}', [0xFEC5, 0xFEC6, 0xFEC7, 0xFEC8]),
+    ('\u{0639}', [0xFEC9, 0xFECA, 0xFECB, 0xFECC]),
+    ('\u{063A}', [0xFECD, 0xFECE, 0xFECF, 0xFED0]),
+    ('\u{0641}', [0xFED1, 0xFED2, 0xFED3, 0xFED4]),
+    ('\u{0642}', [0xFED5, 0xFED6, 0xFED7, 0xFED8]),
+    ('\u{0643}', [0xFED9, 0xFEDA, 0xFEDB, 0xFEDC]),
+    ('\u{0644}', [0xFEDD, 0xFEDE, 0xFEDF, 0xFEE0]),
+    ('\u{0645}', [0xFEE1, 0xFEE2, 0xFEE3, 0xFEE4]),
+    ('\u{0646}', [0xFEE5, 0xFEE6, 0xFEE7, 0xFEE8]),
+    ('\u{0647}', [0xFEE9, 0xFEEA, 0xFEEB, 0xFEEC]),
+    ('\u{0648}', [0xFEED, 0xFEEE, 0, 0]),
+    ('\u{0649}', [0xFEEF, 0xFEF0, 0, 0]),
+    ('\u{064A}', [0xFEF1, 0xFEF2, 0xFEF3, 0xFEF4]),
+    // Persian and Urdu letters
+    ('\u{067E}', [0xFB56, 0xFB57, 0xFB58, 0xFB59]),
+    ('\u{0686}', [0xFB7A, 0xFB7B, 0xFB7C, 0xFB7D]),
+    ('\u{0698}', [0xFB8A, 0xFB8B, 0, 0]),
+    ('\u{06A9}', [0xFB8E, 0xFB8F, 0xFB90, 0xFB91]),
+    ('\u{06AF}', [0xFB92, 0xFB93, 0xFB94, 0xFB95]),
+    ('\u{06CC}', [0xFBFC, 0xFBFD, 0xFBFE, 0xFBFF]),
+];
+
+// Lam followed by an alef is written as one ligature: alef -> (isolated, final)
+const LAM_ALEF: &[(char, [u32; 2])] = &[
+    ('\u{0622}', [0xFEF5, 0xFEF6]),
+    ('\u{0623}', [0xFEF7, 0xFEF8]),
+    ('\u{0625}', [0xFEF9, 0xFEFA]),
+    ('\u{0627}', [0xFEFB, 0xFEFC]),
+];
+
+const LAM: char = '\u{0644}';
+const TATWEEL: char = '\u{0640}';
+const ZWJ: char = '\u{200D}';
+
+// Marks drawn on top of the letter before them (Arabic harakat, Hebrew niqqud, Latin accents)
+fn is_combining_mark(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{0591}'..='\u{05BD}' | '\u{05BF}' | '\u{05C1}'..='\u{05C2}' | '\u{05C4}'..='\u{05C5}' | '\u{05C7}'
+        | '\u{0610}'..='\u{061A}' | '\u{064B}'..='\u{065F}' | '\u{0670}'
+        | '\u{06D6}'..='\u{06DC}' | '\u{06DF}'..='\u{06E4}' | '\u{06E7}'..='\u{06E8}' | '\u{06EA}'..='\u{06ED}')
+}
+
+fn forms(c: char) -> Option<[u32; 4]> {
+    ARABIC_FORMS.iter().find(|(letter, _)| *letter == c).map(|(_, forms)| *forms)
+}
+
+// Whether the letter connects to the one after it (in reading order)
+fn joins_next(c: char) -> bool {
+    c == TATWEEL || c == ZWJ || forms(c).is_some_and(|forms| forms[2] != 0)
+}
+
+// Whether the letter connects to the one before it
+fn joins_previous(c: char) -> bool {
+    c == TATWEEL || c == ZWJ || forms(c).is_some_and(|forms| forms[1] != 0)
+}
+
+fn to_char(code: u32, fallback: char) -> char {
+    char::from_u32(code).unwrap_or(fallback)
+}
+
+// Swaps Arabic letters for the form that matches their neighbours, in reading order
+fn shape_arabic(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    // The closest letter before/after `index`, skipping marks
+    let neighbour = |index: usize, forward: bool| -> Option<char> {
+        let mut i = index;
+        loop {
+            i = if forward { i.checked_add(1).filter(|i| *i < chars.len())? } else { i.checked_sub(1)? };
+            if !is_combining_mark(chars[i]) {
+                return Some(chars[i]);
+            }
+        }
+    };
+
+    let mut shaped = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        let Some(letter_forms) = forms(c) else {
+            shaped.push(c);
+            index += 1;
+            continue;
+        };
+        let after_joining = neighbour(index, false).is_some_and(joins_next) && letter_forms[1] != 0;
+
+        if c == LAM {
+            if let Some((_, ligature)) = chars.get(index + 1).and_then(|next| LAM_ALEF.iter().find(|(alef, _)| alef == next)) {
+                shaped.push(to_char(ligature[usize::from(after_joining)], c));
+                index += 2;
+                continue;
+            }
+        }
+
+        let before_joining = letter_forms[2] != 0 && neighbour(index, true).is_some_and(joins_previous);
+        let form = match (after_joining, before_joining) {
+            (true, true) => letter_forms[3],
+            (true, false) => letter_forms[1],
+            (false, true) => letter_forms[2],
+            (false, false) => letter_forms[0],
+        };
+        shaped.push(to_char(form, c));
+        index += 1;
+    }
+    shaped
+}
+
+fn mirrored(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        _ => c,
+    }
+}
+
+// Reverses a right-to-left run, keeping marks after their letter and mirroring brackets
+fn push_reversed(out: &mut String, run: &str) {
+    let mut clusters: Vec<String> = Vec::new();
+    for c in run.chars() {
+        match clusters.last_mut() {
+            Some(cluster) if is_combining_mark(c) => cluster.push(c),
+            _ => clusters.push(mirrored(c).to_string()),
+        }
+    }
+    for cluster in clusters.iter().rev() {
+        out.push_str(cluster);
+    }
+}
+
+pub fn is_rtl(text: &str) -> bool {
+    unicode_bidi::get_base_direction(text) == Direction::Rtl
+}
+
+// The text in the order egui should draw it. Plain left-to-right text is returned untouched
+pub fn display(text: &str) -> Cow<'_, str> {
+    let bidi_info = BidiInfo::new(text, None);
+    if !bidi_info.has_rtl() {
+        return Cow::Borrowed(text);
+    }
+
+    let shaped = shape_arabic(text);
+    let bidi_info = BidiInfo::new(&shaped, None);
+    let mut out = String::with_capacity(shaped.len());
+    for paragraph in &bidi_info.paragraphs {
+        let line = paragraph.range.clone();
+        let (levels, runs) = bidi_info.visual_runs(paragraph, line);
+        for run in runs {
+            if levels[run.start].is_rtl() {
+                push_reversed(&mut out, &shaped[run]);
+            } else {
+                out.push_str(&shaped[run]);
+            }
+        }
+    }
+    Cow::Owned(out)
+}
+
+// A title label that reads correctly for right-to-left titles and sits against the right edge
+pub fn title_label(ui: &mut egui::Ui, text: &str) -> egui::Response {
+    let label = egui::Label::new(display(text).into_owned());
+    if is_rtl(text) {
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| ui.add(label)).inner
+    } else {
+        ui.add(label)
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::a11y::AccessibleName;
+use crate::rtl;
+use crate::{database, AppState, AutoDownload, DownloadFormat, VideoInfo, YtMp3App};
+
+// A fetched video parked for later, with the info shown when it was fetched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedVideo {
+    pub url: String,
+    pub video_info: VideoInfo,
+    pub format: DownloadFormat,
+    pub output_path: String,
+    // Unix timestamp (seconds)
+    pub saved_at: i64,
+    #[serde(skip)]
+    pub selected: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedList {
+    pub entries: Vec<SavedVideo>,
+}
+
+impl SavedList {
+    pub fn load() -> Self {
+        match database::load_list(database::SAVED_FOR_LATER) {
+            Ok(entries) => Self { entries },
+            Err(e) => {
+                log::warn!("Could not read the saved videos: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        database::save_list(database::SAVED_FOR_LATER, &self.entries)
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.entries.iter().any(|entry| entry.url == url)
+    }
+}
+
+impl YtMp3App {
+    pub(crate) fn save_for_later(&mut self) {
+        let AppState::VideoInfo(video_info) = &self.state else {
+            return;
+        };
+        if self.saved.contains(&self.url_input) {
+            return;
+        }
+        self.saved.entries.push(SavedVideo {
+            url: self.url_input.clone(),
+            video_info: video_info.as_ref().clone(),
+            format: self.download_format,
+            output_path: self.output_path.clone(),
+            saved_at: chrono::Utc::now().timestamp(),
+            selected: true,
+        });
+        self.save_saved_list();
+    }
+
+    fn save_saved_list(&mut self) {
+        if let Err(e) = self.saved.save() {
+            self.log_console(format!("WARNING: Could not save the Saved for later list: {}", e));
+        }
+    }
+
+    pub(crate) fn show_saved_window(&mut self, ctx: &egui::Context) {
+        if !self.show_saved {
+            return;
+        }
+
+        let mut open = true;
+        let mut remove = None;
+        let mut download = false;
+
+        egui::Window::new("🔖 Saved for later")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                if self.saved.entries.is_empty() {
+                    ui.label("Nothing saved. Fetch a video and press \"🔖 Save for later\".");
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Select all").clicked() {
+                        self.saved.entries.iter_mut().for_each(|entry| entry.selected = true);
+                    }
+                    if ui.button("Select none").clicked() {
+                        self.saved.entries.iter_mut().for_each(|entry| entry.selected = false);
+                    }
+                    let selected = self.saved.entries.iter().filter(|entry| entry.selected).count();
+                    if ui.add_enabled(selected > 0, egui::Button::new(format!("⬇ Download {} selected", selected))).clicked() {
+                        download = true;
+                    }
+                });
+                ui.add_space(5.0);
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("saved_grid")
+                        .num_columns(5)
+                        .spacing([10.0, 6.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (index, entry) in self.saved.entries.iter_mut().enumerate() {
+                                ui.checkbox(&mut entry.selected, "");
+                                rtl::title_label(ui, &entry.video_info.title).on_hover_text(&entry.url);
+                                ui.label(&entry.video_info.uploader);
+                                ui.label(format!("{}  {}", entry.video_info.duration, entry.format.label()))
+                                    .on_hover_text(&entry.output_path);
+                                if ui.small_button("🗑").accessible_name("Remove").clicked() {
+                                    remove = Some(index);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        self.show_saved = open;
+
+        if let Some(index) = remove {
+            self.saved.entries.remove(index);
+            self.save_saved_list();
+        }
+        if download {
+            let (queued, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.saved.entries)
+                .into_iter()
+                .partition(|entry| entry.selected);
+            self.saved.entries = kept;
+            self.save_saved_list();
+
+            self.log_console(format!("Downloading {} saved video(s)", queued.len()));
+            self.download_queue.extend(queued.into_iter().map(|entry| AutoDownload {
+                url: entry.url,
+                format: entry.format,
+                output_path: entry.output_path,
+            }));
+            // Asked for explicitly, so a video that's only being looked at makes way
+            if !self.is_busy() && self.receiver.is_none() {
+                self.state = AppState::Input;
+                self.url_input.clear();
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use eframe::egui;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::encoders::ffmpeg_command;
+use crate::history::History;
+use crate::queue::ItemStatus;
+use crate::settings::{get_config_dir, get_data_dir};
+use crate::{process, profiles, simulate, verify, AutoDownload, DownloadFormat, YtMp3App};
+
+// `ytmp3 --self-test` checks the whole pipeline on this machine without touching YouTube:
+// fixture media is served from 127.0.0.1 and goes through the queue, the real yt-dlp and ffmpeg,
+// verification and the history like any other download. Prints a line per case and exits with
+// 0 when all of them pass, for CI and for trying a new yt-dlp or ffmpeg
+pub const SELF_TEST_ARG: &str = "--self-test";
+
+// Its own profile, emptied at the start of each run and left behind for a look afterwards
+const PROFILE: &str = "self-test";
+const TIMEOUT: Duration = Duration::from_secs(300);
+const TICK: Duration = Duration::from_millis(100);
+
+const FIXTURE_SECONDS: u32 = 3;
+const SAMPLE_RATE: u32 = 22050;
+const VIDEO_HEIGHT: u32 = 144;
+// Allowed difference between the fixture's length and the downloaded file's
+const DURATION_TOLERANCE_SECS: f64 = 0.5;
+
+struct Fixture {
+    name: &'static str,
+    content_type: &'static str,
+    data: Vec<u8>,
+}
+
+// A mono 440 Hz tone as 16-bit PCM, built here so no media has to ship with the app
+fn tone_wav() -> Vec<u8> {
+    let samples = SAMPLE_RATE * FIXTURE_SECONDS;
+    let data_len = samples * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for index in 0..samples {
+        let t = index as f32 / SAMPLE_RATE as f32;
+        let sample = (t * 440.0 * std::f32::consts::TAU).sin() * 0.3 * i16::MAX as f32;
+        wav.extend_from_slice(&(sample as i16).to_le_bytes());
+    }
+    wav
+}
+
+// A test pattern with the same tone, encoded by the ffmpeg that post-processes the downloads
+fn test_pattern_mp4(folder: &Path) -> Result<Vec<u8>, String> {
+    let path = folder.join("clip.mp4");
+    let output = ffmpeg_command()
+        .args(["-v", "error", "-y", "-f", "lavfi", "-i"])
+        .arg(format!("testsrc=size=256x{}:rate=15:duration={}", VIDEO_HEIGHT, FIXTURE_SECONDS))
+        .args(["-f", "lavfi", "-i"])
+        .arg(format!("sine=frequency=440:duration={}", FIXTURE_SECONDS))
+        .args(["-c:v", "mpeg4", "-c:a", "aac", "-shortest", "-movflags", "+faststart"])
+        .arg(&path)
+        .output()
+        .map_err(|e| format!("ffmpeg could not be started: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("ffmpeg could not make the MP4 fixture: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    std::fs::read(&path).map_err(|e| e.to_string())
+}
+
+// Serves the fixtures until the process exits. Only GET and HEAD of a fixture's name; yt-dlp's
+// generic extractor takes them for direct media links by their content type
+fn start_fixture_server(fixtures: Vec<Fixture>) -> std::io::Result<u16> {
+    let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))?;
+    let port = listener.local_addr()?.port();
+    let fixtures: &'static [Fixture] = Vec::leak(fixtures);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || serve_fixture(stream, fixtures));
+        }
+    });
+    Ok(port)
+}
+
+fn serve_fixture(mut stream: TcpStream, fixtures: &[Fixture]) {
+    stream.set_read_timeout(Some(Duration::from_secs(10))).ok();
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => return,
+            Ok(read) => head.extend_from_slice(&buffer[..read]),
+        }
+        if head.len() > 64 * 1024 {
+            return;
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+
+    let fixture = fixtures.iter().find(|fixture| path.strip_prefix('/') == Some(fixture.name));
+    let (status, content_type, body): (&str, &str, &[u8]) = match (method, fixture) {
+        ("GET" | "HEAD", Some(fixture)) => ("200 OK", fixture.content_type, &fixture.data),
+        ("GET" | "HEAD", None) => ("404 Not Found", "text/plain", b"Not found"),
+        _ => ("405 Method Not Allowed", "text/plain", b"Use GET"),
+    };
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccept-Ranges: none\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).ok();
+    if method != "HEAD" {
+        stream.write_all(body).ok();
+    }
+}
+
+struct Case {
+    fixture: &'static str,
+    format: DownloadFormat,
+    extension: &'static str,
+    // Height of the video stream, None for audio
+    height: Option<u32>,
+}
+
+const CASES: &[Case] = &[
+    Case { fixture: "tone.wav", format: DownloadFormat::Mp3, extension: "mp3", height: None },
+    Case { fixture: "clip.mp4", format: DownloadFormat::Mp4, extension: "mp4", height: Some(VIDEO_HEIGHT) },
+];
+
+// Removes what an earlier run left in the self-test profile's folders, and nothing else
+fn empty_profile() -> Result<(), String> {
+    if profiles::Profiles::load().names.iter().any(|name| name == PROFILE) {
+        return Err(format!("There is a profile called \"{}\"; rename it so the self-test doesn't empty it", PROFILE));
+    }
+    for dir in [get_data_dir(), get_config_dir()] {
+        let dir = dir.map_err(|e| e.to_string())?;
+        if !dir.ends_with(Path::new("profiles").join(PROFILE)) {
+            return Err(format!("Refusing to empty {}", dir.display()));
+        }
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).map_err(|e| format!("Could not empty {}: {}", dir.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+fn say(line: &str) {
+    writeln!(std::io::stdout(), "{}", line).ok();
+}
+
+// What's wrong with a case's download, None when it passed
+fn check_case(case: &Case, url: &str, status: Option<&ItemStatus>, history: &History) -> Option<String> {
+    let path = match status {
+        Some(ItemStatus::Done(Some(path))) => path,
+        Some(ItemStatus::Done(None)) => return Some("no file was downloaded".to_string()),
+        Some(ItemStatus::Failed(error)) => return Some(error.clone()),
+        Some(ItemStatus::Waiting | ItemStatus::Active { .. }) => return Some("didn't finish in time".to_string()),
+        None => return Some("dropped from the queue".to_string()),
+    };
+    if Path::new(path).extension().and_then(|extension| extension.to_str()) != Some(case.extension) {
+        return Some(format!("expected a .{} file, got {}", case.extension, path));
+    }
+    let size = std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+    if size == 0 {
+        return Some(format!("{} is missing or empty", path));
+    }
+    match verify::probed_duration(path) {
+        Some(duration) if (duration - FIXTURE_SECONDS as f64).abs() <= DURATION_TOLERANCE_SECS => {}
+        Some(duration) => return Some(format!("{} is {:.1}s long instead of {}s", path, duration, FIXTURE_SECONDS)),
+        None => return Some(format!("ffprobe could not read the length of {}", path)),
+    }
+    if let Some(height) = case.height {
+        if verify::probe_height(path) != Some(height) {
+            return Some(format!("{} has no {}p video stream", path, height));
+        }
+    }
+    match history.entries.iter().find(|entry| entry.url == url) {
+        Some(entry) if entry.path == *path && entry.format == case.format => None,
+        Some(entry) => Some(format!("the history has {} instead of {}", entry.path, path)),
+        None => Some("the download is missing from the history".to_string()),
+    }
+}
+
+pub fn run() -> i32 {
+    if simulate::enabled() {
+        say(&format!("Unset {} first; the self-test needs the real yt-dlp and ffmpeg", simulate::ENV));
+        return 2;
+    }
+    profiles::set_active(Some(PROFILE));
+    if let Err(e) = empty_profile() {
+        say(&e);
+        return 2;
+    }
+    let Ok(data_dir) = get_data_dir() else {
+        say("Could not determine the data directory");
+        return 2;
+    };
+    let fixture_dir = data_dir.join("fixtures");
+    let download_dir = data_dir.join("downloads");
+    if let Err(e) = std::fs::create_dir_all(&fixture_dir).and_then(|_| std::fs::create_dir_all(&download_dir)) {
+        say(&format!("Could not create {}: {}", data_dir.display(), e));
+        return 2;
+    }
+
+    let clip = match test_pattern_mp4(&fixture_dir) {
+        Ok(clip) => clip,
+        Err(e) => {
+            say(&e);
+            return 2;
+        }
+    };
+    let fixtures = vec![
+        Fixture { name: "tone.wav", content_type: "audio/wav", data: tone_wav() },
+        Fixture { name: "clip.mp4", content_type: "video/mp4", data: clip },
+    ];
+    let port = match start_fixture_server(fixtures) {
+        Ok(port) => port,
+        Err(e) => {
+            say(&format!("Could not start the fixture server: {}", e));
+            return 2;
+        }
+    };
+
+    let ctx = egui::Context::default();
+    let mut app = YtMp3App { daemon: true, ..YtMp3App::default() };
+    app.log_console(format!("Self-test: serving fixtures on 127.0.0.1:{}, downloading to {}", port, download_dir.display()));
+    let urls: Vec<String> = CASES.iter().map(|case| format!("http://127.0.0.1:{}/{}", port, case.fixture)).collect();
+    let downloads = CASES.iter().zip(&urls).map(|(case, url)| {
+        app.allow_direct_media(url);
+        AutoDownload { url: url.clone(), format: case.format, output_path: download_dir.display().to_string() }
+    }).collect();
+    app.queue_auto_downloads(downloads);
+
+    let started = Instant::now();
+    while (!app.download_queue.is_empty() || app.download_queue.is_running()) && started.elapsed() < TIMEOUT {
+        app.run_engine(&ctx);
+        app.print_console();
+        thread::sleep(TICK);
+    }
+    app.print_console();
+    process::kill_all();
+
+    // Read back from the database, so the check covers what a restart would see
+    let history = History::load();
+    let mut failed = 0;
+    say("");
+    for (case, url) in CASES.iter().zip(&urls) {
+        let status = app.download_queue.all().iter().find(|item| item.download.url == *url).map(|item| &item.status);
+        match check_case(case, url, status, &history) {
+            None => say(&format!("PASS  {}  {}", case.extension.to_uppercase(), case.fixture)),
+            Some(problem) => {
+                failed += 1;
+                say(&format!("FAIL  {}  {}: {}", case.extension.to_uppercase(), case.fixture, problem));
+            }
+        }
+    }
+    say(&format!("{} of {} passed. Files and the database are in {}", CASES.len() - failed, CASES.len(), data_dir.display()));
+    if failed == 0 { 0 } else { 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Needs yt-dlp and ffmpeg installed and stops every process the app started, so it only runs
+    // when asked for, on its own: `cargo test self_test -- --ignored`
+    #[test]
+    #[ignore]
+    fn self_test_passes() {
+        assert_eq!(run(), 0);
+    }
+}
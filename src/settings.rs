@@ -0,0 +1,1497 @@
+use anyhow::Result;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::a11y::AccessibleName;
+use crate::help;
+use crate::locale;
+use crate::journal::JournalFormat;
+use crate::mirror::MirrorKind;
+use crate::naming;
+use crate::pairing::{self, PairedExtension};
+use crate::plugins;
+use crate::metered::MeteredAction;
+use crate::presets::DevicePreset;
+use crate::profiles;
+use crate::remote;
+use crate::simulate::{self, Scenario};
+use crate::webui;
+use crate::DownloadFormat;
+use crate::restricted::COOKIE_BROWSERS;
+use crate::retention::RetentionPolicy;
+use crate::subtitles::SubtitleTrack;
+use crate::system_proxy::SYSTEM_PROXY_NAME;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StallAction {
+    Restart,
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OverwritePolicy {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+impl OverwritePolicy {
+    pub fn ytdlp_args(&self) -> &'static [&'static str] {
+        match self {
+            // Passed explicitly so a yt-dlp config file can't change the behavior behind our back
+            OverwritePolicy::Skip => &["--no-force-overwrites"],
+            OverwritePolicy::Overwrite => &["--force-overwrites"],
+            // Renaming is handled by re-running with a suffixed template when the file exists
+            OverwritePolicy::Rename => &["--no-force-overwrites"],
+        }
+    }
+}
+
+// Whether the video ID goes into file names, so two videos with the same title don't collide
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum IdInFilename {
+    Never,
+    // Only when the output folder already has a file with this title from another video
+    OnCollision,
+    Always,
+}
+
+// Where the upload date goes in file names
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum UploadDateInFilename {
+    Off,
+    // "2024-05-31 - Title", sorts by date in any file manager
+    Before,
+    After,
+}
+
+// Whether yt-dlp also reads the user's own yt-dlp.conf. Its options mix with the app's, which
+// can change file names, formats or output in ways the app doesn't expect
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SystemConfig {
+    // --ignore-config
+    Ignore,
+    Inherit,
+}
+
+// strftime formats, which yt-dlp's "%(upload_date>FORMAT)s" and chrono both understand
+const UPLOAD_DATE_FORMATS: &[(&str, &str)] = &[
+    ("%Y-%m-%d", "YYYY-MM-DD"),
+    ("%Y.%m.%d", "YYYY.MM.DD"),
+    ("%d.%m.%Y", "DD.MM.YYYY"),
+    ("%d-%m-%Y", "DD-MM-YYYY"),
+    ("%m-%d-%Y", "MM-DD-YYYY"),
+    ("%Y%m%d", "YYYYMMDD"),
+];
+
+// Clutter removed from video titles before they become file names and metadata
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TitleCleanup {
+    // "(Official Music Video)", "[Lyrics]", "(Audio)"
+    pub official_tags: bool,
+    // "[4K]", "(HD)", "1080p"
+    pub quality_tags: bool,
+    // Anything in () or []
+    pub all_brackets: bool,
+    pub emoji: bool,
+    // Extra regular expressions (Python syntax, as yt-dlp runs them) whose matches are removed
+    pub custom_patterns: Vec<String>,
+}
+
+// A named login, e.g. "personal" or "member account", picked per download instead of the default cookies
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CookieProfile {
+    pub name: String,
+    pub browser: String,
+    // Profile inside the browser ("Profile 1", "work"); empty = the browser's default profile
+    pub browser_profile: String,
+    pub file: String,
+}
+
+impl CookieProfile {
+    // Value for --cookies-from-browser: BROWSER[:PROFILE]
+    fn browser_arg(&self) -> String {
+        if self.browser.is_empty() || self.browser_profile.is_empty() {
+            self.browser.clone()
+        } else {
+            format!("{}:{}", self.browser, self.browser_profile)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyProfile {
+    pub name: String,
+    // Anything yt-dlp's --proxy accepts: http://, https://, socks5://, socks5h://
+    pub url: String,
+    // Hosts reached directly, in NO_PROXY form; only the system proxy has any
+    #[serde(skip)]
+    pub no_proxy: String,
+}
+
+impl ProxyProfile {
+    // For the app's own requests
+    pub fn client_proxy(&self) -> reqwest::Result<reqwest::Proxy> {
+        Ok(reqwest::Proxy::all(&self.url)?.no_proxy(reqwest::NoProxy::from_string(&self.no_proxy)))
+    }
+}
+
+// Downloads tagged with `tag` go to `folder`, relative to the output folder unless absolute
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagFolder {
+    pub tag: String,
+    pub folder: String,
+}
+
+// Where downloads made with a device preset go, instead of the format's folder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresetFolder {
+    pub preset: DevicePreset,
+    pub folder: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum YtDlpChannel {
+    Stable,
+    Nightly,
+}
+
+impl YtDlpChannel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            YtDlpChannel::Stable => "Stable",
+            YtDlpChannel::Nightly => "Nightly",
+        }
+    }
+
+    pub fn latest_release_url(&self) -> &'static str {
+        match self {
+            YtDlpChannel::Stable => "https://api.github.com/repos/yt-dlp/yt-dlp/releases/latest",
+            YtDlpChannel::Nightly => "https://api.github.com/repos/yt-dlp/yt-dlp-nightly-builds/releases/latest",
+        }
+    }
+}
+
+// H.264 encoder ffmpeg uses when MP4 downloads are re-encoded for compatibility
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum VideoEncoder {
+    Software,
+    Nvenc,
+    Qsv,
+    Amf,
+    VideoToolbox,
+}
+
+impl VideoEncoder {
+    pub const ALL: [VideoEncoder; 5] = [
+        VideoEncoder::Software,
+        VideoEncoder::Nvenc,
+        VideoEncoder::Qsv,
+        VideoEncoder::Amf,
+        VideoEncoder::VideoToolbox,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            VideoEncoder::Software => "Software (libx264)",
+            VideoEncoder::Nvenc => "NVIDIA NVENC",
+            VideoEncoder::Qsv => "Intel Quick Sync",
+            VideoEncoder::Amf => "AMD AMF",
+            VideoEncoder::VideoToolbox => "Apple VideoToolbox",
+        }
+    }
+
+    pub fn ffmpeg_name(&self) -> &'static str {
+        match self {
+            VideoEncoder::Software => "libx264",
+            VideoEncoder::Nvenc => "h264_nvenc",
+            VideoEncoder::Qsv => "h264_qsv",
+            VideoEncoder::Amf => "h264_amf",
+            VideoEncoder::VideoToolbox => "h264_videotoolbox",
+        }
+    }
+
+    // Video output options for a roughly comparable quality on every encoder
+    pub fn video_args(&self) -> String {
+        let quality = match self {
+            VideoEncoder::Software => "-preset medium -crf 23",
+            VideoEncoder::Nvenc => "-preset p5 -rc vbr -cq 23",
+            VideoEncoder::Qsv => "-preset medium -global_quality 23",
+            VideoEncoder::Amf => "-quality balanced -rc cqp -qp_i 23 -qp_p 23",
+            VideoEncoder::VideoToolbox => "-q:v 60",
+        };
+        format!("-c:v {} {} -pix_fmt yuv420p", self.ffmpeg_name(), quality)
+    }
+
+    pub fn ffmpeg_args(&self) -> String {
+        format!("{} -c:a aac -b:a 192k", self.video_args())
+    }
+}
+
+// (label, player_client) presets for the YouTube extractor; YouTube changes often break one client but not others
+const YOUTUBE_CLIENT_PRESETS: &[(&str, &str)] = &[
+    ("yt-dlp default", ""),
+    ("TV (often works without a PO token)", "tv"),
+    ("Web + PO token", "web"),
+    ("Mobile web + PO token", "mweb"),
+    ("Web Safari (HLS formats)", "web_safari"),
+    ("TV + Web Safari", "tv,web_safari"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Seconds without any progress before a download counts as stalled (0 disables the check)
+    pub stall_timeout_secs: u64,
+    pub stall_action: StallAction,
+    pub stall_max_restarts: u32,
+    // Start yt-dlp and ffmpeg below normal priority so conversions don't slow down everything else
+    pub low_priority: bool,
+    // yt-dlp's --socket-timeout in seconds; 0 = yt-dlp's default
+    pub socket_timeout_secs: u32,
+    // How long fetching the video info may take before it's retried or given up; 0 = no limit
+    pub fetch_timeout_secs: u32,
+    pub fetch_retries: u32,
+    // Watch for the internet going down and hold the queue until it's back
+    pub offline_detection: bool,
+    pub metered_action: MeteredAction,
+    // yt-dlp --limit-rate value used on metered connections, e.g. "500K"
+    pub metered_rate_limit: String,
+    // ffmpeg's -threads; 0 = ffmpeg decides
+    pub ffmpeg_threads: u32,
+    // Re-encodes running at the same time, per software/hardware encoder
+    pub max_conversions: u32,
+    // Queued downloads running side by side, each with its own yt-dlp
+    pub max_downloads: u32,
+    pub compact_mode: bool,
+    // Locked-down layout for setting the app up for someone else: advanced options hidden,
+    // every download saved to one folder in one format
+    pub simple_mode: bool,
+    // Operator mode for shared machines: the window only takes URLs and shows the queue; uses
+    // the same fixed folder and format
+    pub kiosk_mode: bool,
+    // Empty = the format's default folder
+    pub simple_folder: String,
+    pub simple_format: DownloadFormat,
+    // SHA-256 of the password asked before the settings open; empty = no password
+    pub settings_password_hash: String,
+    // Keep the window above others while a download is running
+    pub pin_while_downloading: bool,
+    pub hotkey_enabled: bool,
+    // Parsed by global-hotkey, e.g. "Ctrl+Alt+D" or "Shift+Super+KeyY"
+    pub hotkey: String,
+    // Hotkeys for the queue actions, for macro pads; empty = not bound
+    pub start_queue_hotkey: String,
+    pub pause_queue_hotkey: String,
+    // Queue and progress events over a WebSocket on 127.0.0.1, for OBS overlays and dashboards,
+    // and actions from controllers over the same port
+    pub event_stream_enabled: bool,
+    pub event_stream_port: u16,
+    // Browser extensions allowed to queue URLs through the local API
+    pub paired_extensions: Vec<PairedExtension>,
+    // Listen on all interfaces; other computers need a paired token
+    pub event_stream_lan: bool,
+    // Serve a page on the local API's port for queueing from phones; always on with --daemon
+    pub web_ui_enabled: bool,
+    // Another instance's local API this one acts as a remote for, as host:port; empty = off
+    pub remote_address: String,
+    // Its token from pairing
+    pub remote_token: String,
+    pub launch_at_login: bool,
+    // Only applies when launched at login, so opening the app by hand still shows the window
+    pub start_minimized: bool,
+    // Whether "Paste & Go" starts the download right away or stops at the video info
+    pub paste_and_go_downloads: bool,
+    // Passed as --cookies-from-browser / --cookies; empty means not used
+    pub cookies_browser: String,
+    pub cookies_file: String,
+    pub cookie_profiles: Vec<CookieProfile>,
+    pub proxies: Vec<ProxyProfile>,
+    // Name of the proxy used unless a download picks another; empty = the system proxy, or a
+    // direct connection
+    pub default_proxy: String,
+    // Look up the operating system's proxy and use it when no proxy is chosen
+    pub use_system_proxy: bool,
+    // What that lookup found; not saved, it's looked up again at every start
+    #[serde(skip)]
+    pub system_proxy: Option<ProxyProfile>,
+    // For proxies that decrypt HTTPS: a PEM file with their CA to trust, or no checks at all
+    pub ca_bundle: String,
+    pub no_check_certificates: bool,
+    // --impersonate target such as "chrome" or "safari-17.0"; empty = off
+    pub impersonate: String,
+    // YouTube --extractor-args; empty values are left to yt-dlp
+    pub youtube_player_client: String,
+    pub youtube_po_token: String,
+    pub youtube_lang: String,
+    // Invidious or Piped instance asked for the video info when YouTube answers with its bot
+    // check or a rate limit; empty = off
+    pub mirror_kind: MirrorKind,
+    pub mirror_url: String,
+    // Members-only/premium items in a batch are skipped instead of stopping on the error
+    pub skip_locked_content: bool,
+    // What to do when the downloaded file already exists in the output folder
+    pub overwrite_policy: OverwritePolicy,
+    pub id_in_filename: IdInFilename,
+    pub title_cleanup: TitleCleanup,
+    // Notice History files that other programs renamed or deleted
+    pub watch_history_files: bool,
+    // Old downloads removed from a folder automatically
+    pub retention: RetentionPolicy,
+    // Open the digest window when a queue template run finds new videos
+    pub show_run_digest: bool,
+    // Each run's digest is POSTed here as JSON; empty = off
+    pub digest_webhook_url: String,
+    // Run before yt-dlp starts, through the shell; empty = off
+    pub pre_download_command: String,
+    // A non-zero exit code from the command cancels the download
+    pub pre_download_can_reject: bool,
+    pub upload_date_in_filename: UploadDateInFilename,
+    pub upload_date_format: String,
+    // yt-dlp output template for the file name, e.g. "%(uploader)s - %(title)s"; empty = built
+    // from the options above
+    pub filename_template: String,
+    // Hash each finished file into a manifest in its output folder
+    pub write_hash_manifest: bool,
+    // A line per finished download in a JSON Lines or CSV file; an empty path = the data folder
+    pub journal_format: JournalFormat,
+    pub journal_path: String,
+    pub tag_folders: Vec<TagFolder>,
+    // Default output folders per format; empty = the system Videos/Music folder
+    pub mp4_output_dir: String,
+    pub mp3_output_dir: String,
+    // Only presets with a folder of their own are listed
+    pub preset_folders: Vec<PresetFolder>,
+    // Output folders offered next to the Browse button, most recent first
+    pub pinned_folders: Vec<String>,
+    pub recent_folders: Vec<String>,
+    // Where .part fragments and intermediate files go (--paths temp:); empty = output folder
+    pub temp_dir: String,
+    // Which managed yt-dlp build is used and kept up to date
+    pub ytdlp_channel: YtDlpChannel,
+    // Extra folder yt-dlp loads extractor/postprocessor plugins from (--plugin-dirs); empty = none
+    pub plugin_dir: String,
+    // Passed to yt-dlp as they are for every download, in command line syntax
+    pub extra_args: String,
+    pub system_config: SystemConfig,
+    // Re-encode MP4 downloads to H.264/AAC for players that can't handle VP9/AV1
+    pub h264_reencode: bool,
+    pub video_encoder: VideoEncoder,
+    // Target device the next download is prepared for; remembered between runs
+    pub device_preset: DevicePreset,
+    // How often videos in the Retry later list are checked again
+    pub retry_check_interval_mins: u64,
+    // Notification sound; an empty file = the built-in chime
+    pub sound_on_download: bool,
+    pub sound_on_queue_done: bool,
+    pub sound_file: String,
+    pub sound_muted: bool,
+    pub high_contrast: bool,
+    // No spinners, sliding sections or blinking cursor
+    pub reduced_motion: bool,
+    // .ttf/.otf file for the interface text; empty = egui's default font
+    pub ui_font: String,
+    // Locale tag like "de-DE" for numbers, sizes and dates; empty = the system's
+    pub locale: String,
+    // Chosen per video, never saved
+    #[serde(skip)]
+    pub burn_subtitles: Option<SubtitleTrack>,
+    // --format picked in the "Choose format" table, per video as well
+    #[serde(skip)]
+    pub format_selector: Option<String>,
+    // Run against the fake yt-dlp, from YTMP3_SIMULATE at startup
+    #[serde(skip)]
+    pub simulation: Option<Scenario>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            stall_timeout_secs: 60,
+            stall_action: StallAction::Restart,
+            stall_max_restarts: 3,
+            low_priority: false,
+            socket_timeout_secs: 0,
+            fetch_timeout_secs: 60,
+            fetch_retries: 1,
+            offline_detection: true,
+            metered_action: MeteredAction::Pause,
+            metered_rate_limit: "500K".to_string(),
+            ffmpeg_threads: 0,
+            max_conversions: 1,
+            max_downloads: 1,
+            compact_mode: false,
+            simple_mode: false,
+            kiosk_mode: false,
+            simple_folder: String::new(),
+            simple_format: DownloadFormat::Mp4,
+            settings_password_hash: String::new(),
+            pin_while_downloading: false,
+            hotkey_enabled: false,
+            hotkey: DEFAULT_HOTKEY.to_string(),
+            start_queue_hotkey: String::new(),
+            pause_queue_hotkey: String::new(),
+            event_stream_enabled: false,
+            event_stream_port: 38518,
+            paired_extensions: Vec::new(),
+            event_stream_lan: false,
+            web_ui_enabled: false,
+            remote_address: String::new(),
+            remote_token: String::new(),
+            launch_at_login: false,
+            start_minimized: false,
+            paste_and_go_downloads: true,
+            cookies_browser: String::new(),
+            cookies_file: String::new(),
+            cookie_profiles: Vec::new(),
+            proxies: Vec::new(),
+            default_proxy: String::new(),
+            use_system_proxy: false,
+            system_proxy: None,
+            ca_bundle: String::new(),
+            no_check_certificates: false,
+            impersonate: String::new(),
+            youtube_player_client: String::new(),
+            youtube_po_token: String::new(),
+            youtube_lang: String::new(),
+            mirror_kind: MirrorKind::Invidious,
+            mirror_url: String::new(),
+            skip_locked_content: true,
+            overwrite_policy: OverwritePolicy::Skip,
+            id_in_filename: IdInFilename::OnCollision,
+            title_cleanup: TitleCleanup::default(),
+            watch_history_files: true,
+            retention: RetentionPolicy::default(),
+            show_run_digest: true,
+            digest_webhook_url: String::new(),
+            pre_download_command: String::new(),
+            pre_download_can_reject: true,
+            upload_date_in_filename: UploadDateInFilename::Off,
+            upload_date_format: "%Y-%m-%d".to_string(),
+            filename_template: String::new(),
+            write_hash_manifest: false,
+            journal_format: JournalFormat::Off,
+            journal_path: String::new(),
+            tag_folders: Vec::new(),
+            mp4_output_dir: String::new(),
+            mp3_output_dir: String::new(),
+            preset_folders: Vec::new(),
+            pinned_folders: Vec::new(),
+            recent_folders: Vec::new(),
+            temp_dir: String::new(),
+            ytdlp_channel: YtDlpChannel::Stable,
+            plugin_dir: String::new(),
+            extra_args: String::new(),
+            system_config: SystemConfig::Ignore,
+            h264_reencode: false,
+            video_encoder: VideoEncoder::Software,
+            device_preset: DevicePreset::Off,
+            retry_check_interval_mins: 30,
+            sound_on_download: false,
+            sound_on_queue_done: false,
+            sound_file: String::new(),
+            sound_muted: false,
+            high_contrast: false,
+            reduced_motion: false,
+            ui_font: String::new(),
+            locale: String::new(),
+            burn_subtitles: None,
+            format_selector: None,
+            simulation: simulate::from_env(),
+        }
+    }
+}
+
+// Ctrl+Option is VoiceOver's and ⌘+Option+D hides the Dock, so Macs get one with all three
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_HOTKEY: &str = "Ctrl+Alt+D";
+#[cfg(target_os = "macos")]
+const DEFAULT_HOTKEY: &str = "Cmd+Shift+Option+D";
+
+// Tests get folders of their own instead of the user's, removed when the test binary exits
+#[cfg(test)]
+static TEST_ROOT: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+#[cfg(test)]
+#[ctor::dtor]
+fn remove_test_root() {
+    if let Some(root) = TEST_ROOT.get() {
+        std::fs::remove_dir_all(root).ok();
+    }
+}
+
+// The platform's config, data or cache folder
+#[cfg(not(test))]
+fn base_dir(dir: Option<PathBuf>, kind: &str) -> Result<PathBuf> {
+    dir.ok_or_else(|| anyhow::anyhow!("Could not determine {} directory", kind))
+}
+
+#[cfg(test)]
+fn base_dir(_dir: Option<PathBuf>, kind: &str) -> Result<PathBuf> {
+    let root = TEST_ROOT.get_or_init(|| {
+        tempfile::Builder::new().prefix("ytmp3-test-").tempdir().expect("no temporary folder for the tests").keep()
+    });
+    Ok(root.join(kind))
+}
+
+// Shared by all profiles
+pub fn get_root_config_dir() -> Result<PathBuf> {
+    Ok(base_dir(dirs::config_dir(), "config")?.join("ytmp3"))
+}
+
+// The active profile's folders
+pub fn get_config_dir() -> Result<PathBuf> {
+    Ok(profiles::scoped(get_root_config_dir()?))
+}
+
+pub fn get_data_dir() -> Result<PathBuf> {
+    Ok(profiles::scoped(base_dir(dirs::data_dir(), "data")?.join("ytmp3")))
+}
+
+// ~/Library/Caches on a Mac, $XDG_CACHE_HOME (~/.cache) on Linux
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub fn get_cache_dir() -> Result<PathBuf> {
+    Ok(base_dir(dirs::cache_dir(), "cache")?.join("ytmp3"))
+}
+
+fn get_settings_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("config.toml"))
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let path = match get_settings_path() {
+            Ok(path) => path,
+            Err(_) => return Self::default(),
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                log::warn!("Could not parse {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    // Reads config.toml again, failing on a syntax error instead of falling back to the defaults
+    pub fn reload() -> Result<Self> {
+        let contents = std::fs::read_to_string(get_settings_path()?)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    pub fn modified_time() -> Option<std::time::SystemTime> {
+        std::fs::metadata(get_settings_path().ok()?).ok()?.modified().ok()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = get_settings_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // Settings for one download with yt-dlp's --limit-rate added, e.g. "500K"; it comes after
+    // the extra arguments so it wins over a limit set there
+    pub fn with_rate_limit(&self, rate: Option<&str>) -> Settings {
+        let mut settings = self.clone();
+        if let Some(rate) = rate {
+            settings.extra_args = format!("{} --limit-rate {}", settings.extra_args, rate).trim().to_string();
+        }
+        settings
+    }
+
+    // Settings for one fetch/download, with the named cookie profile in place of the default cookies
+    pub fn with_cookie_profile(&self, profile: Option<&str>) -> Settings {
+        let mut settings = self.clone();
+        if let Some(profile) = profile.and_then(|name| self.cookie_profiles.iter().find(|p| p.name == name)) {
+            settings.cookies_browser = profile.browser_arg();
+            settings.cookies_file = profile.file.clone();
+        }
+        settings
+    }
+
+    // Settings for one fetch/download going through the named proxy; Some("") = direct connection
+    pub fn with_proxy(&self, proxy: Option<&str>) -> Settings {
+        let mut settings = self.clone();
+        match proxy {
+            Some("") => {
+                settings.default_proxy.clear();
+                settings.system_proxy = None;
+            }
+            Some(SYSTEM_PROXY_NAME) => settings.default_proxy.clear(),
+            Some(name) => settings.default_proxy = name.to_string(),
+            None => {}
+        }
+        settings
+    }
+
+    // Settings for one download, with the collision check already done: OnCollision becomes Always or Never
+    pub fn with_id_in_filename(&self, include_id: bool) -> Settings {
+        let mut settings = self.clone();
+        if settings.id_in_filename == IdInFilename::OnCollision {
+            settings.id_in_filename = if include_id { IdInFilename::Always } else { IdInFilename::Never };
+        }
+        settings
+    }
+
+    // Settings for one download with the given subtitle track burned into the picture
+    pub fn with_burned_subtitles(&self, track: Option<&SubtitleTrack>) -> Settings {
+        let mut settings = self.clone();
+        settings.burn_subtitles = track.cloned();
+        settings
+    }
+
+    // Settings for one download of exactly the formats picked, instead of the best ones
+    pub fn with_format_selector(&self, selector: Option<String>) -> Settings {
+        let mut settings = self.clone();
+        settings.format_selector = selector;
+        settings
+    }
+
+    // A preset's own folder, or "" when it uses the format's
+    pub fn preset_folder(&self, preset: DevicePreset) -> &str {
+        self.preset_folders
+            .iter()
+            .find(|entry| entry.preset == preset)
+            .map_or("", |entry| entry.folder.as_str())
+    }
+
+    fn set_preset_folder(&mut self, preset: DevicePreset, folder: String) {
+        self.preset_folders.retain(|entry| entry.preset != preset);
+        if !folder.trim().is_empty() {
+            self.preset_folders.push(PresetFolder { preset, folder });
+        }
+    }
+
+    pub fn default_output_dir(&self, format: DownloadFormat) -> String {
+        // The device preset for this format, when it has a folder of its own
+        if self.device_preset.download_format() == Some(format) {
+            let folder = self.preset_folder(self.device_preset);
+            if !folder.is_empty() {
+                return folder.to_string();
+            }
+        }
+        let (configured, system) = match format {
+            DownloadFormat::Mp4 => (&self.mp4_output_dir, dirs::video_dir()),
+            DownloadFormat::Mp3 => (&self.mp3_output_dir, dirs::audio_dir()),
+        };
+        if !configured.is_empty() {
+            return configured.clone();
+        }
+        system
+            .or_else(dirs::download_dir)
+            .unwrap_or_else(|| std::env::current_dir().unwrap())
+            .to_string_lossy()
+            .to_string()
+    }
+
+    pub fn active_proxy(&self) -> Option<&ProxyProfile> {
+        if self.default_proxy.is_empty() {
+            return self.system_proxy.as_ref();
+        }
+        self.proxies.iter().find(|proxy| proxy.name == self.default_proxy)
+    }
+
+    // Environment for yt-dlp: Python reads the extra CA bundle from SSL_CERT_FILE
+    pub fn ytdlp_env(&self) -> Vec<(&'static str, String)> {
+        let mut env = Vec::new();
+        let bundle = self.ca_bundle.trim();
+        if !self.no_check_certificates && !bundle.is_empty() {
+            env.push(("SSL_CERT_FILE", bundle.to_string()));
+        }
+        if !self.plugin_dir.is_empty() && !plugins::supports_plugin_dirs(self) {
+            if let Some(python_path) = plugins::python_path(&self.plugin_dir) {
+                env.push(("PYTHONPATH", python_path));
+            }
+        }
+        // yt-dlp only skips the proxy for NO_PROXY hosts when it takes the proxy from the environment
+        if let Some(proxy) = self.active_proxy().filter(|proxy| !proxy.no_proxy.is_empty()) {
+            for name in ["http_proxy", "https_proxy", "all_proxy"] {
+                env.push((name, proxy.url.clone()));
+            }
+            env.push(("no_proxy", proxy.no_proxy.clone()));
+        }
+        env
+    }
+
+    // "youtube:player_client=tv,web;po_token=web.gvs+TOKEN;lang=en"
+    pub fn youtube_extractor_args(&self) -> Option<String> {
+        let options: Vec<String> = [
+            ("player_client", &self.youtube_player_client),
+            ("po_token", &self.youtube_po_token),
+            ("lang", &self.youtube_lang),
+        ]
+        .iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .map(|(key, value)| format!("{}={}", key, value.trim()))
+        .collect();
+
+        if options.is_empty() {
+            None
+        } else {
+            Some(format!("youtube:{}", options.join(";")))
+        }
+    }
+
+    // Arguments shared by every yt-dlp invocation (info fetches and downloads)
+    pub fn ytdlp_common_args(&self) -> Vec<String> {
+        // Piped output otherwise uses the console code page on Windows, mangling non-ASCII titles
+        let mut args = vec!["--encoding".to_string(), "utf-8".to_string()];
+        // Kept with the app's other caches, apart from a yt-dlp run by hand that may be another version
+        #[cfg(any(target_os = "macos", target_os = "linux"))]
+        if let Ok(cache_dir) = get_cache_dir() {
+            args.push("--cache-dir".to_string());
+            args.push(cache_dir.join("yt-dlp").display().to_string());
+        }
+        if self.system_config == SystemConfig::Ignore {
+            args.push("--ignore-config".to_string());
+        }
+        if !self.cookies_browser.is_empty() {
+            args.push("--cookies-from-browser".to_string());
+            args.push(self.cookies_browser.clone());
+        } else if !self.cookies_file.is_empty() {
+            args.push("--cookies".to_string());
+            args.push(self.cookies_file.clone());
+        }
+        if let Some(proxy) = self.active_proxy().filter(|proxy| proxy.no_proxy.is_empty()) {
+            args.push("--proxy".to_string());
+            args.push(proxy.url.clone());
+        }
+        if self.no_check_certificates {
+            args.push("--no-check-certificates".to_string());
+        } else if !self.ca_bundle.trim().is_empty() {
+            // yt-dlp trusts only certifi's bundle otherwise, and ignores SSL_CERT_FILE
+            args.push("--compat-options".to_string());
+            args.push("no-certifi".to_string());
+        }
+        if !self.impersonate.is_empty() {
+            args.push("--impersonate".to_string());
+            args.push(self.impersonate.clone());
+        }
+        if let Some(extractor_args) = self.youtube_extractor_args() {
+            args.push("--extractor-args".to_string());
+            args.push(extractor_args);
+        }
+        // Older releases fail on the unknown option; ytdlp_env hands them the folder instead
+        if !self.plugin_dir.is_empty() && plugins::supports_plugin_dirs(self) {
+            args.push("--plugin-dirs".to_string());
+            args.push(self.plugin_dir.clone());
+        }
+        if self.socket_timeout_secs > 0 {
+            args.push("--socket-timeout".to_string());
+            args.push(self.socket_timeout_secs.to_string());
+        }
+        args
+    }
+
+    // Extra ffmpeg arguments for every conversion
+    pub fn ffmpeg_thread_args(&self) -> Vec<String> {
+        if self.ffmpeg_threads == 0 {
+            return Vec::new();
+        }
+        vec!["-threads".to_string(), self.ffmpeg_threads.to_string()]
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🔍 Fetching Video Info:", "fetch_timeout");
+                ui.add_space(5.0);
+                egui::Grid::new("fetch_settings_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        ui.label("Give up after (seconds, 0 = never):");
+                        ui.add(egui::DragValue::new(&mut self.fetch_timeout_secs).range(0..=600));
+                        ui.end_row();
+
+                        ui.label("Retries after a timeout:");
+                        ui.add_enabled(self.fetch_timeout_secs > 0, egui::DragValue::new(&mut self.fetch_retries).range(0..=5));
+                        ui.end_row();
+
+                        ui.label("Network timeout (seconds, 0 = yt-dlp default):");
+                        ui.add(egui::DragValue::new(&mut self.socket_timeout_secs).range(0..=300));
+                        ui.end_row();
+                    });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.offline_detection, "Notice when the internet is down and wait for it to come back");
+                    help::help_button(ui, "offline");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("On a metered connection:");
+                    egui::ComboBox::from_id_source("metered_action")
+                        .selected_text(self.metered_action.label())
+                        .show_ui(ui, |ui| {
+                            for action in MeteredAction::ALL {
+                                ui.selectable_value(&mut self.metered_action, action, action.label());
+                            }
+                        });
+                    if self.metered_action == MeteredAction::LimitRate {
+                        ui.add(egui::TextEdit::singleline(&mut self.metered_rate_limit)
+                            .desired_width(60.0)
+                            .hint_text("500K"));
+                        ui.label("per second");
+                    }
+                    help::help_button(ui, "metered");
+                });
+            });
+        });
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "⏳ Stall Detection:", "stall_detection");
+                ui.add_space(5.0);
+                egui::Grid::new("stall_settings_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        ui.label("No progress timeout (seconds, 0 = off):");
+                        ui.add(egui::DragValue::new(&mut self.stall_timeout_secs).range(0..=3600));
+                        ui.end_row();
+
+                        ui.label("When stalled:");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut self.stall_action, StallAction::Restart, "🔄 Restart");
+                            ui.radio_value(&mut self.stall_action, StallAction::Fail, "❌ Fail");
+                        });
+                        ui.end_row();
+
+                        if self.stall_action == StallAction::Restart {
+                            ui.label("Maximum restarts:");
+                            ui.add(egui::DragValue::new(&mut self.stall_max_restarts).range(0..=20));
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🐢 Performance:", "performance");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.low_priority, "Run downloads and conversions at low priority")
+                    .on_hover_text(help::summary("performance"));
+                ui.horizontal(|ui| {
+                    ui.label("ffmpeg threads (0 = automatic):");
+                    ui.add(egui::DragValue::new(&mut self.ffmpeg_threads).range(0..=64));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Conversions at the same time:");
+                    ui.add(egui::DragValue::new(&mut self.max_conversions).range(1..=8));
+                })
+                .response
+                .on_hover_text("Re-encodes and subtitle burn-in beyond this wait their turn; downloads aren't limited by it");
+                ui.horizontal(|ui| {
+                    ui.label("Downloads at the same time:");
+                    ui.add(egui::DragValue::new(&mut self.max_downloads).range(1..=8));
+                })
+                .response
+                .on_hover_text("Queued downloads start while others are still running, up to this many");
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "📄 When the file already exists:", "existing_files");
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.overwrite_policy, OverwritePolicy::Skip, "⏭ Skip");
+                    ui.radio_value(&mut self.overwrite_policy, OverwritePolicy::Overwrite, "♻ Overwrite");
+                    ui.radio_value(&mut self.overwrite_policy, OverwritePolicy::Rename, "🔢 Save as \"Title (1)\"");
+                });
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("📅 Upload date in file names:");
+                    ui.radio_value(&mut self.upload_date_in_filename, UploadDateInFilename::Off, "Off");
+                    ui.radio_value(&mut self.upload_date_in_filename, UploadDateInFilename::Before, "Before the title");
+                    ui.radio_value(&mut self.upload_date_in_filename, UploadDateInFilename::After, "After the title");
+                });
+                if self.upload_date_in_filename != UploadDateInFilename::Off {
+                    ui.horizontal(|ui| {
+                        ui.label("Date format:");
+                        let selected = UPLOAD_DATE_FORMATS
+                            .iter()
+                            .find(|(format, _)| *format == self.upload_date_format)
+                            .map(|(_, label)| *label)
+                            .unwrap_or("Custom");
+                        egui::ComboBox::from_id_source("upload_date_format")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for (format, label) in UPLOAD_DATE_FORMATS {
+                                    ui.selectable_value(&mut self.upload_date_format, format.to_string(), *label);
+                                }
+                            });
+                        // Custom formats can only come from config.toml
+                        if !naming::is_valid_date_format(&self.upload_date_format) {
+                            ui.colored_label(egui::Color32::YELLOW, format!("⚠ \"{}\" is not a valid date format", self.upload_date_format));
+                        }
+                    });
+                }
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("🆔 Add the video ID to file names:");
+                    ui.radio_value(&mut self.id_in_filename, IdInFilename::Never, "Never");
+                    ui.radio_value(&mut self.id_in_filename, IdInFilename::OnCollision, "When the title is taken")
+                        .on_hover_text(help::summary("id_on_collision"));
+                    ui.radio_value(&mut self.id_in_filename, IdInFilename::Always, "Always");
+                });
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.write_hash_manifest, "🔏 Record a SHA-256 of each file in ytmp3-manifest.json")
+                    .on_hover_text(help::summary("hash_manifest"));
+                ui.horizontal(|ui| {
+                    ui.label("🧾 Download journal:");
+                    ui.radio_value(&mut self.journal_format, JournalFormat::Off, "Off");
+                    ui.radio_value(&mut self.journal_format, JournalFormat::JsonLines, "JSON Lines");
+                    ui.radio_value(&mut self.journal_format, JournalFormat::Csv, "CSV");
+                    help::help_button(ui, "journal");
+                });
+                if self.journal_format != JournalFormat::Off {
+                    ui.horizontal(|ui| {
+                        ui.label("Journal file:");
+                        ui.add(egui::TextEdit::singleline(&mut self.journal_path)
+                            .desired_width(250.0)
+                            .hint_text("download-journal in the app's data folder"));
+                        if ui.button("📂").accessible_name("Choose journal file").clicked() {
+                            let extension = match self.journal_format {
+                                JournalFormat::Csv => "csv",
+                                _ => "jsonl",
+                            };
+                            if let Some(path) = rfd::FileDialog::new()
+                                .set_file_name(format!("download-journal.{}", extension))
+                                .add_filter("Journal", &[extension])
+                                .save_file()
+                            {
+                                self.journal_path = path.display().to_string();
+                            }
+                        }
+                    });
+                }
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("🗂 Temporary files:")
+                        .on_hover_text(help::summary("temp_files"));
+                    ui.add(egui::TextEdit::singleline(&mut self.temp_dir)
+                        .desired_width(220.0)
+                        .hint_text("Same as output folder"));
+                    if ui.button("📂").accessible_name("Choose folder").clicked() {
+                        if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                            self.temp_dir = path.display().to_string();
+                        }
+                    }
+                });
+            });
+        });
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "📁 Default output folders:", "default_folders");
+                ui.add_space(5.0);
+                egui::Grid::new("format_folders_grid")
+                    .num_columns(3)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        for format in [DownloadFormat::Mp4, DownloadFormat::Mp3] {
+                            let system = match format {
+                                DownloadFormat::Mp4 => dirs::video_dir(),
+                                DownloadFormat::Mp3 => dirs::audio_dir(),
+                            };
+                            let hint = system.or_else(dirs::download_dir).map(|dir| dir.display().to_string()).unwrap_or_default();
+                            let folder = match format {
+                                DownloadFormat::Mp4 => &mut self.mp4_output_dir,
+                                DownloadFormat::Mp3 => &mut self.mp3_output_dir,
+                            };
+                            ui.label(format.label());
+                            ui.add(egui::TextEdit::singleline(folder)
+                                .desired_width(220.0)
+                                .hint_text(hint));
+                            if ui.button("📂").accessible_name("Choose folder").clicked() {
+                                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                    *folder = path.display().to_string();
+                                }
+                            }
+                            ui.end_row();
+                        }
+
+                        // Empty = the folder of the preset's format
+                        for preset in DevicePreset::ALL.into_iter().filter(|preset| *preset != DevicePreset::Off) {
+                            let Some(format) = preset.download_format() else {
+                                continue;
+                            };
+                            let mut folder = self.preset_folder(preset).to_string();
+                            ui.label(preset.label());
+                            let hint = format!("Same as {}", if format == DownloadFormat::Mp3 { "MP3" } else { "MP4" });
+                            let mut changed = ui.add(egui::TextEdit::singleline(&mut folder)
+                                .desired_width(220.0)
+                                .hint_text(hint))
+                                .changed();
+                            if ui.button("📂").accessible_name(&format!("Choose folder for {}", preset.label())).clicked() {
+                                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                    folder = path.display().to_string();
+                                    changed = true;
+                                }
+                            }
+                            if changed {
+                                self.set_preset_folder(preset, folder);
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🏷 Tag folders (downloads with the tag are saved in this subfolder):", "tag_folders");
+                ui.add_space(5.0);
+                let mut remove = None;
+                for (index, route) in self.tag_folders.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut route.tag)
+                            .desired_width(110.0)
+                            .hint_text("Tag"));
+                        ui.label("→");
+                        ui.add(egui::TextEdit::singleline(&mut route.folder)
+                            .desired_width(200.0)
+                            .hint_text("Subfolder, e.g. Workout"));
+                        if ui.button("🗑").accessible_name("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = remove {
+                    self.tag_folders.remove(index);
+                }
+                if ui.button("➕ Add tag folder").clicked() {
+                    self.tag_folders.push(TagFolder::default());
+                }
+            });
+        });
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "⌨ Global Hotkey:", "hotkey");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.hotkey_enabled, "Control downloads with system-wide hotkeys");
+                ui.add_enabled_ui(self.hotkey_enabled, |ui| {
+                    egui::Grid::new("hotkey_settings_grid")
+                        .num_columns(2)
+                        .spacing([10.0, 5.0])
+                        .show(ui, |ui| {
+                            ui.label("Download clipboard URL:");
+                            ui.add(egui::TextEdit::singleline(&mut self.hotkey)
+                                .desired_width(150.0)
+                                .hint_text(DEFAULT_HOTKEY));
+                            ui.end_row();
+                            ui.label("Start queue:");
+                            ui.add(egui::TextEdit::singleline(&mut self.start_queue_hotkey)
+                                .desired_width(150.0)
+                                .hint_text("Not bound"));
+                            ui.end_row();
+                            ui.label("Pause queue:");
+                            ui.add(egui::TextEdit::singleline(&mut self.pause_queue_hotkey)
+                                .desired_width(150.0)
+                                .hint_text("Not bound"));
+                            ui.end_row();
+                        });
+                });
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "📡 Local API:", "event_stream");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.event_stream_enabled, "Stream download events and accept actions from apps on this computer");
+                ui.add_enabled_ui(self.event_stream_enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Port:");
+                        ui.add(egui::DragValue::new(&mut self.event_stream_port).range(1024..=65535));
+                        ui.label(format!("ws://127.0.0.1:{}", self.event_stream_port));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.event_stream_lan, "Let paired computers on the network control this app");
+                        help::help_button(ui, "remote_mode");
+                    });
+                    if self.event_stream_lan {
+                        let address = remote::lan_address()
+                            .map(|ip| format!("{}:{}", ip, self.event_stream_port))
+                            .unwrap_or_else(|| "this computer's address".to_string());
+                        ui.label(egui::RichText::new(format!("Other computers connect to {}", address)).small().color(egui::Color32::GRAY));
+                    }
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.web_ui_enabled, "Serve a web page for queueing downloads from phones");
+                        help::help_button(ui, "web_ui");
+                    });
+                    if self.web_ui_enabled {
+                        let url = webui::page_url(self.event_stream_lan, self.event_stream_port);
+                        ui.label(egui::RichText::new(format!("Open {} in a browser", url)).small().color(egui::Color32::GRAY));
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("🔗 Browser extensions and remotes:");
+                        match pairing::active_code() {
+                            Some((code, left)) => {
+                                ui.label(egui::RichText::new(code).monospace().strong().size(18.0));
+                                ui.label(format!("Enter this code on the other side ({}s left)", left.as_secs()));
+                                if ui.button("Cancel").clicked() {
+                                    pairing::cancel();
+                                }
+                            }
+                            None => {
+                                if ui.button("Pair a device").clicked() {
+                                    pairing::start();
+                                }
+                            }
+                        }
+                        help::help_button(ui, "browser_extension");
+                    });
+                    let mut unpair = None;
+                    for (index, extension) in self.paired_extensions.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let paired = locale::timestamp(extension.paired_at).unwrap_or_default();
+                            ui.label(format!("{} (paired {})", extension.name, paired));
+                            if ui.small_button("Unpair").accessible_name(&format!("Unpair {}", extension.name)).clicked() {
+                                unpair = Some(index);
+                            }
+                        });
+                    }
+                    if let Some(index) = unpair {
+                        self.paired_extensions.remove(index);
+                    }
+                });
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🚀 Startup:", "startup");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.launch_at_login, "Launch when I log in");
+                ui.add_enabled_ui(self.launch_at_login, |ui| {
+                    ui.checkbox(&mut self.start_minimized, "Start minimized");
+                });
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "📋 Paste & Go:", "paste_and_go");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.paste_and_go_downloads, "Start downloading immediately after fetching info");
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🍪 Cookies (for age-restricted or signed-in content):", "cookies");
+                ui.add_space(5.0);
+                egui::Grid::new("cookie_settings_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        ui.label("From browser:");
+                        let selected = if self.cookies_browser.is_empty() {
+                            "None".to_string()
+                        } else {
+                            self.cookies_browser.clone()
+                        };
+                        egui::ComboBox::from_id_source("settings_cookies_browser")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.cookies_browser, String::new(), "None");
+                                for browser in COOKIE_BROWSERS {
+                                    ui.selectable_value(&mut self.cookies_browser, browser.to_string(), *browser);
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("cookies.txt file:");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.cookies_file)
+                                .desired_width(220.0)
+                                .hint_text("None"));
+                            if ui.button("📂").accessible_name("Choose file").clicked() {
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .add_filter("Cookies", &["txt"])
+                                    .pick_file()
+                                {
+                                    self.cookies_file = path.display().to_string();
+                                }
+                            }
+                        });
+                        ui.end_row();
+                    });
+
+                ui.add_space(5.0);
+                help::section_label(ui, "👥 Account profiles (choose one per download instead of the cookies above):", "account_profiles");
+                let mut remove = None;
+                for (index, profile) in self.cookie_profiles.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut profile.name)
+                            .desired_width(90.0)
+                            .hint_text("Name"));
+                        let selected = if profile.browser.is_empty() {
+                            "No browser".to_string()
+                        } else {
+                            profile.browser.clone()
+                        };
+                        egui::ComboBox::from_id_source(("cookie_profile_browser", index))
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut profile.browser, String::new(), "No browser");
+                                for browser in COOKIE_BROWSERS {
+                                    ui.selectable_value(&mut profile.browser, browser.to_string(), *browser);
+                                }
+                            });
+                        ui.add_enabled(!profile.browser.is_empty(), egui::TextEdit::singleline(&mut profile.browser_profile)
+                            .desired_width(80.0)
+                            .hint_text("Browser profile"));
+                        ui.add_enabled(profile.browser.is_empty(), egui::TextEdit::singleline(&mut profile.file)
+                            .desired_width(110.0)
+                            .hint_text("cookies.txt"));
+                        if ui.add_enabled(profile.browser.is_empty(), egui::Button::new("📂")).accessible_name("Choose file").clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Cookies", &["txt"])
+                                .pick_file()
+                            {
+                                profile.file = path.display().to_string();
+                            }
+                        }
+                        if ui.button("🗑").accessible_name("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = remove {
+                    self.cookie_profiles.remove(index);
+                }
+                if ui.button("➕ Add profile").clicked() {
+                    self.cookie_profiles.push(CookieProfile {
+                        name: format!("Account {}", self.cookie_profiles.len() + 1),
+                        ..Default::default()
+                    });
+                }
+
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.skip_locked_content, "Skip members-only and premium videos when downloading several URLs");
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "▶ YouTube extractor options (try these when YouTube downloads suddenly fail):", "youtube_extractor");
+                ui.add_space(5.0);
+                egui::Grid::new("youtube_extractor_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 5.0])
+                    .show(ui, |ui| {
+                        ui.label("Preset:");
+                        let preset = YOUTUBE_CLIENT_PRESETS
+                            .iter()
+                            .find(|(_, client)| *client == self.youtube_player_client)
+                            .map(|(label, _)| *label)
+                            .unwrap_or("Custom");
+                        egui::ComboBox::from_id_source("youtube_client_preset")
+                            .selected_text(preset)
+                            .show_ui(ui, |ui| {
+                                for (label, client) in YOUTUBE_CLIENT_PRESETS {
+                                    if ui.selectable_label(self.youtube_player_client == *client, *label).clicked() {
+                                        self.youtube_player_client = client.to_string();
+                                    }
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Player client(s):");
+                        ui.add(egui::TextEdit::singleline(&mut self.youtube_player_client)
+                            .desired_width(220.0)
+                            .hint_text("default"));
+                        ui.end_row();
+
+                        ui.label("PO token:");
+                        ui.add(egui::TextEdit::singleline(&mut self.youtube_po_token)
+                            .desired_width(220.0)
+                            .hint_text("web.gvs+TOKEN"))
+                            .on_hover_text(help::summary("po_token"));
+                        ui.end_row();
+
+                        ui.label("Metadata language:");
+                        ui.add(egui::TextEdit::singleline(&mut self.youtube_lang)
+                            .desired_width(80.0)
+                            .hint_text("e.g. en"));
+                        ui.end_row();
+                    });
+
+                if let Some(extractor_args) = self.youtube_extractor_args() {
+                    ui.label(egui::RichText::new(format!("--extractor-args \"{}\"", extractor_args))
+                        .font(egui::FontId::monospace(11.0))
+                        .color(egui::Color32::GRAY));
+                }
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🪞 Mirror fallback (when YouTube asks to confirm you're not a bot):", "mirror_fallback");
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.mirror_kind, MirrorKind::Invidious, MirrorKind::Invidious.label());
+                    ui.radio_value(&mut self.mirror_kind, MirrorKind::Piped, MirrorKind::Piped.label());
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Instance URL:");
+                    ui.add(egui::TextEdit::singleline(&mut self.mirror_url)
+                        .desired_width(250.0)
+                        .hint_text(self.mirror_kind.url_hint()));
+                });
+                ui.label(egui::RichText::new("Leave empty to turn the fallback off.")
+                    .small()
+                    .color(egui::Color32::GRAY));
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🔧 yt-dlp release channel:", "release_channel");
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.ytdlp_channel, YtDlpChannel::Stable, YtDlpChannel::Stable.label());
+                    ui.radio_value(&mut self.ytdlp_channel, YtDlpChannel::Nightly, YtDlpChannel::Nightly.label())
+                        .on_hover_text(help::summary("release_channel"));
+                });
+                ui.label(egui::RichText::new("Both builds are kept side by side, switching takes effect with the next fetch.")
+                    .small()
+                    .color(egui::Color32::GRAY));
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "⏰ Retry later:", "retry_later");
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("Check unavailable videos again every");
+                    ui.add(egui::DragValue::new(&mut self.retry_check_interval_mins).range(5..=1440));
+                    ui.label("minutes");
+                });
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🔔 Completion sound:", "completion_sound");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.sound_on_download, "Play after every download");
+                ui.checkbox(&mut self.sound_on_queue_done, "Play when the whole queue is done");
+                ui.horizontal(|ui| {
+                    ui.label("Sound file:");
+                    ui.add(egui::TextEdit::singleline(&mut self.sound_file)
+                        .desired_width(250.0)
+                        .hint_text("Built-in chime"));
+                    if ui.button("📂").accessible_name("Choose sound file").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Audio", &["wav", "mp3", "ogg", "flac"])
+                            .pick_file()
+                        {
+                            self.sound_file = path.display().to_string();
+                        }
+                    }
+                    if ui.button("▶ Test").clicked() {
+                        crate::sound::play_in_background(&self.sound_file);
+                    }
+                });
+                ui.checkbox(&mut self.sound_muted, "Muted (also toggled with 🔔 in the header)");
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "♿ Accessibility:", "accessibility");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.high_contrast, "High contrast (white on black)");
+                ui.checkbox(&mut self.reduced_motion, "Reduce motion")
+                    .on_hover_text(help::summary("reduced_motion"));
+                ui.horizontal(|ui| {
+                    ui.label("UI font:");
+                    ui.add(egui::TextEdit::singleline(&mut self.ui_font)
+                        .desired_width(250.0)
+                        .hint_text("Default"));
+                    if ui.button("📂").accessible_name("Choose font file").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Fonts", &["ttf", "otf", "ttc"])
+                            .pick_file()
+                        {
+                            self.ui_font = path.display().to_string();
+                        }
+                    }
+                });
+                ui.label(egui::RichText::new("Chinese, Japanese and Korean titles use a system font when one is installed")
+                    .small()
+                    .color(egui::Color32::GRAY));
+            });
+        });
+
+        ui.add_space(10.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "🌍 Region:", "region");
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("Numbers and dates:");
+                    egui::ComboBox::from_id_source("locale")
+                        .selected_text(crate::locale::name(&self.locale))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.locale, String::new(), "System default");
+                            for (tag, name) in crate::locale::choices() {
+                                ui.selectable_value(&mut self.locale, tag.to_string(), name);
+                            }
+                        });
+                });
+                ui.label(egui::RichText::new(format!(
+                    "e.g. {} views · {} · {}",
+                    crate::locale::number(1234567),
+                    crate::locale::speed(3.4 * 1024.0 * 1024.0),
+                    crate::locale::date_time(&chrono::Local::now())
+                ))
+                .small()
+                .color(egui::Color32::GRAY));
+            });
+        });
+    }
+}
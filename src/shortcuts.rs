@@ -0,0 +1,34 @@
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+
+use crate::YtMp3App;
+
+// COMMAND is ⌘ on a Mac and Ctrl everywhere else; egui shows them the same way in hover texts
+pub const SETTINGS: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Comma);
+pub const HISTORY: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Y);
+// Only with no text field focused. It arrives as a paste rather than a key press
+pub const PASTE_AND_GO: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::V);
+
+// "Settings (⌘,)" or "Settings (Ctrl+,)"
+pub fn with_shortcut(ctx: &egui::Context, text: &str, shortcut: &KeyboardShortcut) -> String {
+    format!("{} ({})", text, ctx.format_shortcut(shortcut))
+}
+
+impl YtMp3App {
+    // Only in the full window; the kiosk and compact layouts leave these out on purpose
+    pub(crate) fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_shortcut(&SETTINGS)) {
+            self.open_settings();
+        }
+        // A focused text field keeps its own shortcuts, like Ctrl+Y for redo on Windows
+        if self.is_simple() || ctx.memory(|memory| memory.focused().is_some()) {
+            return;
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&HISTORY)) {
+            self.show_history = !self.show_history;
+        }
+        let pasted = ctx.input(|i| i.events.iter().any(|event| matches!(event, egui::Event::Paste(_))));
+        if pasted && !self.is_offline() {
+            self.paste_and_go();
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use eframe::egui;
+use sha2::{Digest, Sha256};
+
+use crate::a11y::AccessibleName;
+use crate::help;
+use crate::kiosk;
+use crate::{DownloadFormat, YtMp3App};
+
+// Stored instead of the password itself, so it can't be read from config.toml
+pub fn password_hash(password: &str) -> String {
+    format!("{:x}", Sha256::digest(format!("ytmp3-settings:{}", password).as_bytes()))
+}
+
+// Password entry for the locked settings
+#[derive(Default)]
+pub struct SettingsLock {
+    // Unlocked until the settings window is closed
+    pub unlocked: bool,
+    pub show_prompt: bool,
+    pub password_input: String,
+    pub wrong_password: bool,
+    // Typed in the settings to set a new password
+    pub new_password: String,
+}
+
+impl YtMp3App {
+    pub(crate) fn is_simple(&self) -> bool {
+        self.settings.simple_mode
+    }
+
+    // The folder downloads go to in simple mode
+    pub(crate) fn simple_folder(&self) -> String {
+        if self.settings.simple_folder.trim().is_empty() {
+            self.settings.default_output_dir(self.settings.simple_format)
+        } else {
+            self.settings.simple_folder.trim().to_string()
+        }
+    }
+
+    // Called every frame and before a download starts, so nothing else (queued downloads,
+    // URLs from another launch) can pick a different folder or format
+    pub(crate) fn apply_simple_mode(&mut self) {
+        if !self.is_simple() && !self.settings.kiosk_mode {
+            return;
+        }
+        self.download_format = self.settings.simple_format;
+        self.output_path = self.simple_folder();
+        self.tags_input.clear();
+        self.burn_subtitles = None;
+        self.format_picker.clear();
+    }
+
+    pub(crate) fn settings_locked(&self) -> bool {
+        !self.settings.settings_password_hash.is_empty() && !self.settings_lock.unlocked
+    }
+
+    pub(crate) fn open_settings(&mut self) {
+        if self.show_settings {
+            self.close_settings();
+        } else if self.settings_locked() {
+            self.settings_lock.show_prompt = true;
+        } else {
+            self.show_settings = true;
+        }
+    }
+
+    // Locks the settings again when they have a password
+    pub(crate) fn close_settings(&mut self) {
+        self.show_settings = false;
+        self.settings_lock.unlocked = false;
+    }
+
+    pub(crate) fn show_unlock_window(&mut self, ctx: &egui::Context) {
+        if !self.settings_lock.show_prompt {
+            return;
+        }
+
+        let mut open = true;
+        let mut unlock = false;
+        egui::Window::new("🔒 Settings are locked")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Enter the settings password to change how downloads are saved.");
+                ui.add_space(5.0);
+                let response = ui.add(egui::TextEdit::singleline(&mut self.settings_lock.password_input)
+                    .password(true)
+                    .desired_width(200.0));
+                response.request_focus();
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    unlock = true;
+                }
+                if self.settings_lock.wrong_password {
+                    ui.colored_label(egui::Color32::RED, "Wrong password");
+                }
+                ui.add_space(5.0);
+                if ui.button("🔓 Unlock").clicked() {
+                    unlock = true;
+                }
+            });
+
+        if unlock {
+            let input = std::mem::take(&mut self.settings_lock.password_input);
+            if password_hash(&input) == self.settings.settings_password_hash {
+                self.settings_lock.unlocked = true;
+                self.settings_lock.show_prompt = false;
+                self.settings_lock.wrong_password = false;
+                self.show_settings = true;
+            } else {
+                self.settings_lock.wrong_password = true;
+            }
+        }
+        if !open {
+            self.settings_lock.show_prompt = false;
+            self.settings_lock.password_input.clear();
+            self.settings_lock.wrong_password = false;
+        }
+    }
+
+    // Takes the place of the output folder and format sections
+    pub(crate) fn show_simple_destination(&self, ui: &mut egui::Ui) {
+        let format = match self.settings.simple_format {
+            DownloadFormat::Mp4 => "🎥 Video (MP4)",
+            DownloadFormat::Mp3 => "🎵 Music (MP3)",
+        };
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.label(format!("Downloads are saved as {} in:", format));
+                ui.label(egui::RichText::new(&self.output_path).strong());
+            });
+        });
+    }
+
+    pub(crate) fn show_simple_mode_settings(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "👪 Simple Mode:", "simple_mode");
+                ui.add_space(5.0);
+                ui.checkbox(&mut self.settings.simple_mode, "Hide advanced options and always save to one folder and format");
+                ui.checkbox(&mut self.settings.kiosk_mode, "Operator mode: only take URLs and show the queue, no settings")
+                    .on_hover_text(format!(
+                        "For shared machines. Unlock with the settings password, or launch with {} if there is none",
+                        kiosk::ADMIN_ARG
+                    ));
+                ui.add_enabled_ui(self.settings.simple_mode || self.settings.kiosk_mode, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Format:");
+                        ui.radio_value(&mut self.settings.simple_format, DownloadFormat::Mp4, "🎥 MP4");
+                        ui.radio_value(&mut self.settings.simple_format, DownloadFormat::Mp3, "🎵 MP3");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Folder:");
+                        let default_folder = self.settings.default_output_dir(self.settings.simple_format);
+                        ui.add(egui::TextEdit::singleline(&mut self.settings.simple_folder)
+                            .desired_width(250.0)
+                            .hint_text(default_folder));
+                        if ui.button("📂").accessible_name("Choose simple mode folder").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                self.settings.simple_folder = path.display().to_string();
+                            }
+                        }
+                    });
+                });
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label("Settings password:");
+                    ui.add(egui::TextEdit::singleline(&mut self.settings_lock.new_password)
+                        .password(true)
+                        .desired_width(150.0)
+                        .hint_text(if self.settings.settings_password_hash.is_empty() { "None" } else { "Set" }));
+                    if ui.add_enabled(!self.settings_lock.new_password.is_empty(), egui::Button::new("Set")).clicked() {
+                        let password = std::mem::take(&mut self.settings_lock.new_password);
+                        self.settings.settings_password_hash = password_hash(&password);
+                        // Stays open for the rest of this visit
+                        self.settings_lock.unlocked = true;
+                    }
+                    if !self.settings.settings_password_hash.is_empty() && ui.button("Remove").clicked() {
+                        self.settings.settings_password_hash.clear();
+                    }
+                });
+            });
+        });
+    }
+}
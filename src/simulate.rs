@@ -0,0 +1,406 @@
+use regex::Regex;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use crate::history::youtube_video_id;
+
+// YTMP3_SIMULATE=<scenario> runs the app against a fake yt-dlp, for demos and for exercising the
+// UI, progress parsing, queue and error handling without a network or a real yt-dlp binary.
+// The fake is this executable itself: each yt-dlp process is started with CHILD_ENV set to the
+// scenario, and main() hands over to `run_fake_ytdlp` when it finds it set
+pub const ENV: &str = "YTMP3_SIMULATE";
+const CHILD_ENV: &str = "YTMP3_SIMULATED_YTDLP";
+
+const FAKE_VERSION: &str = "2099.01.01";
+const FAKE_TITLE: &str = "Simulated video";
+const FAKE_DURATION_SECS: u64 = 212;
+const FAKE_SIZE_MIB: f64 = 24.0;
+const PLAYLIST_LENGTH: usize = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scenario {
+    // Downloads finish in a few seconds
+    Success,
+    // Like Success at a tenth of the speed, to look at progress and ETA
+    Slow,
+    // The download fails part way with a yt-dlp error
+    Fail,
+    // The download stops making progress, for stall detection
+    Stall,
+    // Fetching the video info fails with YouTube's bot check
+    BotCheck,
+}
+
+impl Scenario {
+    fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "slow" => Scenario::Slow,
+            "fail" => Scenario::Fail,
+            "stall" => Scenario::Stall,
+            "bot" => Scenario::BotCheck,
+            _ => Scenario::Success,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Scenario::Success => "success",
+            Scenario::Slow => "slow",
+            Scenario::Fail => "fail",
+            Scenario::Stall => "stall",
+            Scenario::BotCheck => "bot",
+        }
+    }
+}
+
+pub fn enabled() -> bool {
+    std::env::var_os(ENV).is_some()
+}
+
+// The scenario the app was started with, kept in the settings
+pub fn from_env() -> Option<Scenario> {
+    std::env::var(ENV).ok().map(|name| Scenario::from_name(&name))
+}
+
+// Turns a yt-dlp command, already pointed at `fake_ytdlp_path`, into a run of the fake
+pub fn mark_child(command: &mut Command, scenario: Scenario) {
+    command.env(CHILD_ENV, scenario.name());
+}
+
+// The "yt-dlp" the app runs while simulating
+pub fn fake_ytdlp_path() -> std::path::PathBuf {
+    std::env::current_exe().unwrap_or_else(|_| "ytmp3".into())
+}
+
+// Called first thing in main(). Returns normally in the GUI process; in a fake yt-dlp
+// process it never returns
+pub fn run_fake_ytdlp_if_child() {
+    if let Ok(scenario) = std::env::var(CHILD_ENV) {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        std::process::exit(run_fake_ytdlp(&args, Scenario::from_name(&scenario)));
+    }
+    if let Some(scenario) = from_env() {
+        log::info!("Simulation mode ({:?}): yt-dlp is faked, nothing is downloaded", scenario);
+    }
+}
+
+fn has_arg(args: &[String], name: &str) -> bool {
+    args.iter().any(|arg| arg == name)
+}
+
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter().position(|arg| arg == name).and_then(|index| args.get(index + 1)).map(String::as_str)
+}
+
+fn video_id(args: &[String]) -> String {
+    args.last()
+        .and_then(|url| youtube_video_id(url))
+        .unwrap_or_else(|| "simulated01".to_string())
+}
+
+fn sleep_ms(ms: u64) {
+    thread::sleep(Duration::from_millis(ms));
+}
+
+fn say(line: &str) {
+    let mut stdout = std::io::stdout();
+    writeln!(stdout, "{}", line).ok();
+    stdout.flush().ok();
+}
+
+fn fail(line: &str) -> i32 {
+    eprintln!("{}", line);
+    1
+}
+
+fn run_fake_ytdlp(args: &[String], scenario: Scenario) -> i32 {
+    if has_arg(args, "--version") {
+        say(&format!("{} (simulated)", FAKE_VERSION));
+        return 0;
+    }
+    if has_arg(args, "--help") {
+        say("Usage: yt-dlp [OPTIONS] URL [URL...]\n  --plugin-dirs PATH  (simulated)");
+        return 0;
+    }
+    if has_arg(args, "--list-impersonate-targets") {
+        say("[info] Available impersonate targets\nClient  OS         Source\n------------------------------\nChrome  Windows-10 curl_cffi (simulated)");
+        return 0;
+    }
+
+    let id = video_id(args);
+    if has_arg(args, "--flat-playlist") {
+        let length = arg_value(args, "--playlist-end")
+            .and_then(|end| end.parse().ok())
+            .unwrap_or(PLAYLIST_LENGTH)
+            .min(PLAYLIST_LENGTH);
+        for index in 0..length {
+            let entry = serde_json::json!({
+                "id": format!("simulate{:03}", index),
+                "url": format!("https://www.youtube.com/watch?v=simulate{:03}", index),
+                "title": format!("{} #{}", FAKE_TITLE, index + 1),
+                "duration": FAKE_DURATION_SECS,
+            });
+            say(&entry.to_string());
+            sleep_ms(40);
+        }
+        return 0;
+    }
+    if has_arg(args, "--dump-json") {
+        // --dump-json implies --quiet, only the JSON goes to stdout
+        sleep_ms(300);
+        if scenario == Scenario::BotCheck {
+            return fail(&format!(
+                "ERROR: [youtube] {}: Sign in to confirm you're not a bot. Use --cookies-from-browser or --cookies for the authentication",
+                id
+            ));
+        }
+        say(&video_json(&id).to_string());
+        return 0;
+    }
+
+    fake_download(args, &id, scenario)
+}
+
+fn video_json(id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "title": FAKE_TITLE,
+        "duration": FAKE_DURATION_SECS,
+        "uploader": "Simulation",
+        "channel_url": "https://www.youtube.com/channel/UCsimulation000000000000",
+        "view_count": 123456,
+        "upload_date": "20240101",
+        "availability": "public",
+        "live_status": "not_live",
+        "formats": [
+            { "format_id": "140", "vcodec": "none", "acodec": "mp4a.40.2" },
+            { "format_id": "137", "vcodec": "avc1.640028", "height": 1080, "fps": 30 },
+        ],
+        "chapters": [
+            { "start_time": 0.0, "end_time": 60.0, "title": "Intro" },
+            { "start_time": 60.0, "end_time": FAKE_DURATION_SECS, "title": "Main part" },
+        ],
+    })
+}
+
+// Fills in the --output template the way yt-dlp would for the fake video
+fn output_path(args: &[String], id: &str, ext: &str) -> String {
+    let folder = arg_value(args, "--paths")
+        .and_then(|paths| paths.strip_prefix("home:"))
+        .unwrap_or(".");
+    let template = arg_value(args, "--output").unwrap_or("%(title)s.%(ext)s");
+    let field = Regex::new(r"%\(([a-z_]+)[^)]*\)[-.0-9]*s").unwrap();
+    let name = field.replace_all(template, |captures: &regex::Captures| match &captures[1] {
+        "title" => FAKE_TITLE.to_string(),
+        "id" => id.to_string(),
+        "ext" => ext.to_string(),
+        "upload_date" => "2024-01-01".to_string(),
+        other => format!("NA-{}", other),
+    });
+    Path::new(folder).join(name.as_ref()).display().to_string()
+}
+
+fn fake_download(args: &[String], id: &str, scenario: Scenario) -> i32 {
+    let audio_only = has_arg(args, "-x");
+    let download_path = output_path(args, id, if audio_only { "webm" } else { "mp4" });
+
+    say(&format!("[youtube] Extracting URL: https://www.youtube.com/watch?v={}", id));
+    say(&format!("[youtube] {}: Downloading webpage", id));
+    say(&format!("[info] {}: Downloading 1 format(s): {}", id, if audio_only { "251" } else { "137+140" }));
+    if let Some(parent) = Path::new(&download_path).parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    say(&format!("[download] Destination: {}", download_path));
+
+    // Like yt-dlp, carry on from the .part file of a download that was stopped; this one only
+    // holds the percentage reached
+    let part_path = format!("{}.part", download_path);
+    let resume_from = std::fs::read_to_string(&part_path).ok().and_then(|part| part.trim().parse::<u32>().ok()).unwrap_or(0);
+    if resume_from > 0 {
+        let bytes = (FAKE_SIZE_MIB * 1024.0 * 1024.0 * resume_from as f64 / 100.0) as u64;
+        say(&format!("[download] Resuming download at byte {}", bytes));
+    }
+
+    let step_ms = if scenario == Scenario::Slow { 500 } else { 50 };
+    let speed = FAKE_SIZE_MIB / (100.0 * step_ms as f64 / 1000.0);
+    for percent in resume_from.min(100)..=100u32 {
+        if scenario == Scenario::Fail && percent == 40 {
+            return fail(&format!("ERROR: [download] {}: Simulated failure: HTTP Error 403: Forbidden", id));
+        }
+        if scenario == Scenario::Stall && percent == 30 {
+            // Stall detection is expected to kill the process long before this ends
+            sleep_ms(60 * 60 * 1000);
+        }
+        let remaining = (100 - percent) as u64 * step_ms / 1000;
+        say(&format!(
+            "[download] {:5.1}% of {:.2}MiB at {:.2}MiB/s ETA {:02}:{:02}",
+            percent as f64, FAKE_SIZE_MIB, speed, remaining / 60, remaining % 60
+        ));
+        std::fs::write(&part_path, percent.to_string()).ok();
+        sleep_ms(step_ms);
+    }
+    std::fs::remove_file(&part_path).ok();
+    say(&format!("[download] 100% of {:.2}MiB in 00:00:{:02} at {:.2}MiB/s", FAKE_SIZE_MIB, step_ms / 10, speed));
+
+    let final_path = if audio_only {
+        let mp3 = output_path(args, id, "mp3");
+        say(&format!("[ExtractAudio] Destination: {}", mp3));
+        sleep_ms(300);
+        mp3
+    } else {
+        download_path
+    };
+    // A small placeholder, not a playable file
+    if let Err(e) = std::fs::write(&final_path, b"ytmp3 simulated download\n") {
+        return fail(&format!("ERROR: Unable to write {}: {}", final_path, e));
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui;
+    use std::time::Instant;
+
+    use crate::jobs::JobStatus;
+    use crate::queue::ItemStatus;
+    use crate::settings::StallAction;
+    use crate::{AppState, AutoDownload, DownloadFormat, YtMp3App};
+
+    // The test harness stands in for the app's executable here: it's what fake_ytdlp_path()
+    // finds, so the yt-dlp it starts has to turn into the fake before libtest reads the arguments
+    #[ctor::ctor]
+    fn fake_ytdlp_in_tests() {
+        run_fake_ytdlp_if_child();
+    }
+
+    const TICK: Duration = Duration::from_millis(50);
+
+    // An app playing `scenario` with one download queued into a folder of its own. The settings
+    // carry the scenario to the fake, and the database is under the tests' own root
+    fn start(scenario: Scenario) -> (YtMp3App, tempfile::TempDir) {
+        let folder = tempfile::tempdir().unwrap();
+        let mut app = YtMp3App { daemon: true, ..YtMp3App::default() };
+        app.settings.simulation = Some(scenario);
+        app.settings.max_downloads = 1;
+        app.settings.stall_timeout_secs = 1;
+        app.settings.stall_action = StallAction::Fail;
+        app.queue_auto_downloads(vec![AutoDownload {
+            url: format!("https://www.youtube.com/watch?v=sim{}", scenario.name()),
+            format: DownloadFormat::Mp4,
+            output_path: folder.path().display().to_string(),
+        }]);
+        (app, folder)
+    }
+
+    // Runs the engine like the daemon does until `done` holds, false when it didn't in time
+    fn run_until(app: &mut YtMp3App, timeout: Duration, mut done: impl FnMut(&YtMp3App) -> bool) -> bool {
+        let ctx = egui::Context::default();
+        let started = Instant::now();
+        while started.elapsed() < timeout {
+            app.run_engine(&ctx);
+            if done(app) {
+                return true;
+            }
+            thread::sleep(TICK);
+        }
+        false
+    }
+
+    fn finished(app: &YtMp3App) -> bool {
+        app.download_queue.all().iter().all(|item| matches!(item.status, ItemStatus::Done(_) | ItemStatus::Failed(_)))
+            && !app.download_queue.is_running()
+    }
+
+    fn item_status(app: &YtMp3App) -> ItemStatus {
+        app.download_queue.all().first().expect("the download left the queue").status.clone()
+    }
+
+    fn job_status(app: &YtMp3App) -> Option<JobStatus> {
+        app.jobs.first().map(|job| job.status.clone())
+    }
+
+    #[test]
+    fn success_downloads_the_file() {
+        let (mut app, folder) = start(Scenario::Success);
+        assert!(run_until(&mut app, Duration::from_secs(60), finished), "still running: {:?}", item_status(&app));
+
+        let Some(JobStatus::Completed(path)) = job_status(&app) else {
+            panic!("job ended as {:?}", job_status(&app));
+        };
+        assert_eq!(Path::new(&path), folder.path().join(format!("{}.mp4", FAKE_TITLE)));
+        assert!(Path::new(&path).is_file());
+        assert!(matches!(&app.state, AppState::Success(message) if message.contains(&path)), "{:?}", app.state);
+        assert_eq!(item_status(&app), ItemStatus::Done(Some(path)));
+    }
+
+    #[test]
+    fn slow_reports_progress_while_running() {
+        let (mut app, _folder) = start(Scenario::Slow);
+        let progressing = run_until(&mut app, Duration::from_secs(60), |app| {
+            matches!(job_status(app), Some(JobStatus::Running { progress, status }) if progress > 0.0 && status.contains("ETA"))
+        });
+        assert!(progressing, "no progress: {:?}", job_status(&app));
+        assert!(matches!(item_status(&app), ItemStatus::Active { progress, .. } if progress > 0.0), "{:?}", item_status(&app));
+        assert!(matches!(&app.state, AppState::Downloading { progress, .. } if *progress > 0.0), "{:?}", app.state);
+
+        // Pausing stops the fake yt-dlp, so it doesn't outlive the test
+        app.downloads[0].set_paused(true);
+        let paused = run_until(&mut app, Duration::from_secs(30), |app| {
+            matches!(job_status(app), Some(JobStatus::Running { status, .. }) if status.contains("Paused"))
+        });
+        assert!(paused, "not paused: {:?}", job_status(&app));
+    }
+
+    #[test]
+    fn fail_reports_the_ytdlp_error() {
+        let (mut app, _folder) = start(Scenario::Fail);
+        assert!(run_until(&mut app, Duration::from_secs(60), finished), "still running: {:?}", item_status(&app));
+
+        assert!(matches!(job_status(&app), Some(JobStatus::Failed(error)) if error.contains("403")), "{:?}", job_status(&app));
+        assert!(matches!(item_status(&app), ItemStatus::Failed(error) if error.contains("403")), "{:?}", item_status(&app));
+        assert!(matches!(&app.state, AppState::Error(error) if error.contains("403")), "{:?}", app.state);
+    }
+
+    #[test]
+    fn retry_runs_with_the_same_arguments() {
+        let (mut app, folder) = start(Scenario::Fail);
+        assert!(run_until(&mut app, Duration::from_secs(60), finished), "still running: {:?}", item_status(&app));
+        let first = &app.jobs[0];
+        assert!(first.args.iter().any(|arg| arg.ends_with(&folder.path().display().to_string())), "{:?}", first.args);
+        assert!(first.args.iter().any(|arg| arg.contains("ffmpeg-progress")), "{:?}", first.args);
+
+        // Whatever is selected now doesn't change what the retry runs with
+        app.download_format = DownloadFormat::Mp3;
+        app.settings.simulation = Some(Scenario::Success);
+        app.retry_job(first.id);
+        assert!(run_until(&mut app, Duration::from_secs(60), |app| app.jobs.len() == 2 && app.downloads.is_empty()));
+        let (first, retry) = (&app.jobs[0], &app.jobs[1]);
+        assert_eq!(retry.format, first.format);
+        assert!(matches!(&retry.status, JobStatus::Failed(error) if error.contains("403")), "{:?}", retry.status);
+        assert_eq!(retry.args.len(), first.args.len());
+    }
+
+    #[test]
+    fn stall_is_detected() {
+        let (mut app, _folder) = start(Scenario::Stall);
+        assert!(run_until(&mut app, Duration::from_secs(60), finished), "still running: {:?}", item_status(&app));
+
+        assert!(matches!(job_status(&app), Some(JobStatus::Stalled(error)) if error.starts_with("Download stalled")), "{:?}", job_status(&app));
+        assert!(matches!(item_status(&app), ItemStatus::Failed(error) if error.contains("stalled")), "{:?}", item_status(&app));
+    }
+
+    #[test]
+    fn bot_check_fails_before_downloading() {
+        let (mut app, _folder) = start(Scenario::BotCheck);
+        assert!(run_until(&mut app, Duration::from_secs(60), finished), "still running: {:?}", item_status(&app));
+
+        assert!(job_status(&app).is_none(), "a download was started");
+        assert!(matches!(item_status(&app), ItemStatus::Failed(error) if error.contains("not a bot")), "{:?}", item_status(&app));
+        assert!(matches!(&app.state, AppState::Error(error) if error.contains("not a bot")), "{:?}", app.state);
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use eframe::egui;
+use rodio::Source;
+use std::fs::File;
+use std::io::BufReader;
+use std::thread;
+use std::time::Duration;
+
+use crate::a11y::AccessibleName;
+use crate::YtMp3App;
+
+// Plays the custom sound file, or a short two-note chime when none is set
+fn play(sound_file: &str) -> Result<()> {
+    let (_stream, handle) = rodio::OutputStream::try_default()?;
+    let sink = rodio::Sink::try_new(&handle)?;
+    if sound_file.is_empty() {
+        sink.append(rodio::source::SineWave::new(880.0).take_duration(Duration::from_millis(120)).amplify(0.2));
+        sink.append(rodio::source::SineWave::new(1320.0).take_duration(Duration::from_millis(220)).amplify(0.2));
+    } else {
+        let file = BufReader::new(File::open(sound_file)?);
+        sink.append(rodio::Decoder::new(file)?);
+    }
+    sink.sleep_until_end();
+    Ok(())
+}
+
+// Plays in the background; a missing sound device or a broken file only gets logged
+pub fn play_in_background(sound_file: &str) {
+    let sound_file = sound_file.to_string();
+    thread::spawn(move || {
+        if let Err(e) = play(&sound_file) {
+            log::warn!("Could not play the completion sound: {}", e);
+        }
+    });
+}
+
+impl YtMp3App {
+    // Called when a download finished successfully; `queue_done` = nothing else is waiting
+    pub(crate) fn play_completion_sound(&self, queue_done: bool) {
+        if self.settings.sound_muted {
+            return;
+        }
+        if self.settings.sound_on_download || (queue_done && self.settings.sound_on_queue_done) {
+            play_in_background(&self.settings.sound_file);
+        }
+    }
+
+    pub(crate) fn mute_button(&mut self, ui: &mut egui::Ui) {
+        let muted = self.settings.sound_muted;
+        if ui.selectable_label(muted, if muted { "🔇" } else { "🔔" })
+            .accessible_name("Mute the completion sound")
+            .on_hover_text(if muted { "Completion sound muted" } else { "Mute the completion sound" })
+            .clicked()
+        {
+            self.settings.sound_muted = !muted;
+            if let Err(e) = self.settings.save() {
+                self.log_console(format!("WARNING: Could not save settings: {}", e));
+            }
+        }
+    }
+}
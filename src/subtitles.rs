@@ -0,0 +1,151 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::AtomicBool;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::channel::AppSender;
+use crate::conversions::{self, ConversionPool};
+use crate::metadata::SubtitleLanguages;
+use crate::process;
+use crate::settings::Settings;
+use crate::AppMessage;
+
+const SUBTITLE_EXTENSIONS: &[&str] = &["srt", "vtt", "ass", "ssa", "ttml", "srv1", "srv2", "srv3", "json3"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubtitleTrack {
+    // yt-dlp language key, e.g. "en", "pt-BR" or "en-orig"
+    pub lang: String,
+    pub name: String,
+    // Generated captions have to be requested with --write-auto-subs
+    pub automatic: bool,
+    // Machine translation of the generated captions into another language
+    pub translated: bool,
+}
+
+impl SubtitleTrack {
+    pub fn label(&self) -> String {
+        let kind = if self.translated {
+            " (auto-translated)"
+        } else if self.automatic {
+            " (auto-generated)"
+        } else {
+            ""
+        };
+        format!("{} [{}]{}", self.name, self.lang, kind)
+    }
+
+    pub fn ytdlp_args(&self) -> Vec<String> {
+        let write = if self.automatic { "--write-auto-subs" } else { "--write-subs" };
+        // --sub-langs takes regexes, so the key is escaped to match only itself
+        vec![
+            write.to_string(),
+            "--sub-langs".to_string(),
+            regex::escape(&self.lang),
+            "--convert-subs".to_string(),
+            "srt".to_string(),
+        ]
+    }
+}
+
+// Builds the track list from yt-dlp's subtitle fields, uploaded ones first
+pub fn parse_tracks(subtitles: &SubtitleLanguages, automatic_captions: &SubtitleLanguages) -> Vec<SubtitleTrack> {
+    let mut tracks = Vec::new();
+    for (languages, automatic) in [(subtitles, false), (automatic_captions, true)] {
+        // YouTube marks the spoken language "<lang>-orig"; every other automatic caption
+        // ("de", or "de-en" for German translated from English) is a machine translation of it
+        let has_original = automatic && languages.keys().any(|lang| lang.ends_with("-orig"));
+        for (lang, formats) in languages {
+            if lang == "live_chat" {
+                continue;
+            }
+            let name = formats
+                .iter()
+                .find_map(|format| format.name.as_deref())
+                .unwrap_or(lang)
+                .to_string();
+            let translated = has_original && !lang.ends_with("-orig");
+            tracks.push(SubtitleTrack { lang: lang.clone(), name, automatic, translated });
+        }
+    }
+    // Originals stay on top, the long list of translations is sorted by language name
+    tracks.sort_by(|a, b| (a.translated, a.automatic, &a.name).cmp(&(b.translated, b.automatic, &b.name)));
+    tracks
+}
+
+// Subtitle downloads print Destination/Moving lines too, which must not be mistaken for the video
+pub fn is_subtitle_file(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| SUBTITLE_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known)))
+}
+
+// Re-encodes the video with the subtitles drawn onto the picture, replacing the original file.
+// Returns the path of the new file, which is always an .mp4
+pub fn burn_in(
+    video_path: &str,
+    track: &SubtitleTrack,
+    settings: &Settings,
+    duration_seconds: Option<f64>,
+    paused: &AtomicBool,
+    progress_sender: &AppSender,
+) -> Result<String> {
+    let video = Path::new(video_path);
+    let folder = video.parent().map(Path::to_path_buf).unwrap_or_default();
+    // yt-dlp names the subtitle file after the video: "<title>.<lang>.srt"
+    let subtitle_path = video.with_extension(format!("{}.srt", track.lang));
+    if !subtitle_path.exists() {
+        return Err(anyhow::anyhow!(
+            "The {} subtitles were not downloaded, the video was saved without them",
+            track.name
+        ));
+    }
+
+    // The subtitles filter needs its own escaping for paths, so ffmpeg runs inside the folder
+    // and reads the subtitles under a plain name
+    let plain_subtitles = format!("ytmp3-burn-{}.srt", std::process::id());
+    std::fs::rename(&subtitle_path, folder.join(&plain_subtitles))?;
+    let burned_path = if video.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("mp4")) {
+        video.to_path_buf()
+    } else {
+        conversions::unique_sibling(&video.with_extension("mp4"), "subtitled")
+    };
+    let temp_output = folder.join(format!("ytmp3-burn-{}.mp4", std::process::id()));
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .current_dir(&folder)
+        .args(["-hide_banner", "-y", "-i"])
+        .arg(video)
+        .args(["-vf", &format!("subtitles={}", plain_subtitles)])
+        .args(settings.video_encoder.video_args().split_whitespace())
+        .args(["-c:a", "aac", "-b:a", "192k", "-progress", "pipe:1", "-nostats"])
+        .args(settings.ffmpeg_thread_args())
+        .arg(&temp_output)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    if settings.low_priority {
+        process::lower_priority(&mut command);
+    }
+
+    let pool = ConversionPool::for_encoder(settings.video_encoder);
+    let label = format!("Burning in {} subtitles", track.name);
+    let result = conversions::convert(&mut command, pool, &label, duration_seconds, settings, paused, progress_sender);
+    std::fs::remove_file(folder.join(&plain_subtitles)).ok();
+    if let Err(e) = result {
+        std::fs::remove_file(&temp_output).ok();
+        return Err(e);
+    }
+
+    std::fs::remove_file(video)?;
+    std::fs::rename(&temp_output, &burned_path)?;
+    progress_sender.send(AppMessage::ConsoleOutput(format!("Burned {} subtitles into {}", track.name, burned_path.display()))).ok();
+    Ok(burned_path.display().to_string())
+}
+
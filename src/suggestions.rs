@@ -0,0 +1,304 @@
+use anyhow::Result;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc;
+
+use crate::history::History;
+use crate::playlist::{self, PlaylistEvent};
+use crate::{database, AppState, AutoDownload, DownloadFormat, YtMp3App};
+
+// Failed downloads are offered for a retry for this long
+const FAILED_KEEP_SECS: i64 = 7 * 24 * 60 * 60;
+const MAX_FAILED: usize = 50;
+// A channel counts as followed with this many downloads in the recent past
+const CHANNEL_MIN_DOWNLOADS: usize = 2;
+const CHANNEL_RECENT_SECS: i64 = 60 * 24 * 60 * 60;
+// Channels looked up per launch, and how many of their latest uploads are compared to the history
+const MAX_CHANNEL_CHECKS: usize = 3;
+const CHANNEL_LATEST_UPLOADS: usize = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedDownload {
+    pub url: String,
+    pub title: String,
+    pub format: DownloadFormat,
+    pub output_path: String,
+    pub error: String,
+    // Unix timestamp (seconds)
+    pub failed_at: i64,
+}
+
+// Downloads that failed recently, kept so they can be suggested for a retry after a restart
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FailedList {
+    pub entries: Vec<FailedDownload>,
+}
+
+impl FailedList {
+    pub fn load() -> Self {
+        let mut list = Self {
+            entries: database::load_list(database::FAILED_DOWNLOADS).unwrap_or_else(|e| {
+                log::warn!("Could not read the failed downloads: {}", e);
+                Vec::new()
+            }),
+        };
+        let cutoff = chrono::Utc::now().timestamp() - FAILED_KEEP_SECS;
+        list.entries.retain(|entry| entry.failed_at >= cutoff);
+        list
+    }
+
+    pub fn save(&self) -> Result<()> {
+        database::save_list(database::FAILED_DOWNLOADS, &self.entries)
+    }
+
+    pub fn record(&mut self, entry: FailedDownload) -> Result<()> {
+        self.entries.retain(|existing| existing.url != entry.url);
+        self.entries.push(entry);
+        if self.entries.len() > MAX_FAILED {
+            self.entries.remove(0);
+        }
+        self.save()
+    }
+
+    // A later successful download takes the video off the list
+    pub fn resolve(&mut self, url: &str) -> Result<()> {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.url != url);
+        if self.entries.len() == before {
+            return Ok(());
+        }
+        self.save()
+    }
+}
+
+// "today", "yesterday" or "this week", for the most recent of the failures
+fn when_text(timestamp: i64) -> &'static str {
+    let today = chrono::Local::now().date_naive();
+    let day = chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.with_timezone(&chrono::Local).date_naive())
+        .unwrap_or(today);
+    match (today - day).num_days() {
+        0 => "today",
+        1 => "yesterday",
+        _ => "this week",
+    }
+}
+
+// A channel the user downloads from regularly
+#[derive(Debug, Clone)]
+struct FollowedChannel {
+    uploader: String,
+    channel_url: String,
+    downloads: usize,
+}
+
+// Channels with the most downloads in the last two months, most downloaded first
+fn followed_channels(history: &History) -> Vec<FollowedChannel> {
+    let cutoff = chrono::Utc::now().timestamp() - CHANNEL_RECENT_SECS;
+    let mut channels: HashMap<&str, FollowedChannel> = HashMap::new();
+    for entry in history.entries.iter().filter(|entry| entry.completed_at >= cutoff) {
+        let Some(channel_url) = entry.channel_url.as_deref() else {
+            continue;
+        };
+        channels
+            .entry(channel_url)
+            .or_insert_with(|| FollowedChannel {
+                uploader: entry.uploader.clone(),
+                channel_url: channel_url.to_string(),
+                downloads: 0,
+            })
+            .downloads += 1;
+    }
+
+    let mut channels: Vec<FollowedChannel> = channels
+        .into_values()
+        .filter(|channel| channel.downloads >= CHANNEL_MIN_DOWNLOADS)
+        .collect();
+    channels.sort_by(|a, b| b.downloads.cmp(&a.downloads).then_with(|| a.uploader.cmp(&b.uploader)));
+    channels.truncate(MAX_CHANNEL_CHECKS);
+    channels
+}
+
+// The channel's uploads tab lists newest first
+fn uploads_url(channel_url: &str) -> String {
+    let channel_url = channel_url.trim_end_matches('/');
+    if channel_url.ends_with("/videos") {
+        channel_url.to_string()
+    } else {
+        format!("{}/videos", channel_url)
+    }
+}
+
+struct ChannelCheck {
+    channel: FollowedChannel,
+    receiver: mpsc::Receiver<PlaylistEvent>,
+    // Latest uploads that aren't in the history
+    new_urls: Vec<String>,
+}
+
+struct ChannelSuggestion {
+    channel: FollowedChannel,
+    new_urls: Vec<String>,
+}
+
+// Suggestions on the input screen, built from the download history and the failed list
+#[derive(Default)]
+pub struct Suggestions {
+    started: bool,
+    checks: Vec<ChannelCheck>,
+    channels: Vec<ChannelSuggestion>,
+    // Channel URLs dismissed this session
+    dismissed: HashSet<String>,
+}
+
+impl YtMp3App {
+    // Called every frame: looks up the followed channels' latest uploads once per launch,
+    // in the background, and collects the ones not downloaded yet
+    pub(crate) fn poll_suggestions(&mut self) {
+        if !self.suggestions.started {
+            self.suggestions.started = true;
+            for channel in followed_channels(&self.history) {
+                let receiver = playlist::stream_entries(
+                    uploads_url(&channel.channel_url),
+                    self.settings.clone(),
+                    Some(CHANNEL_LATEST_UPLOADS),
+                );
+                self.suggestions.checks.push(ChannelCheck { channel, receiver, new_urls: Vec::new() });
+            }
+        }
+
+        let history = &self.history;
+        let mut finished = Vec::new();
+        for (index, check) in self.suggestions.checks.iter_mut().enumerate() {
+            while let Ok(event) = check.receiver.try_recv() {
+                match event {
+                    PlaylistEvent::Entry(entry) => {
+                        let url = entry.watch_url();
+                        if history.find(Some(&entry.id), &url).is_none() {
+                            check.new_urls.push(url);
+                        }
+                    }
+                    PlaylistEvent::Skipped(_) => {}
+                    PlaylistEvent::Finished(result) => {
+                        if let Err(error) = result {
+                            log::info!("Could not look up new videos of {}: {}", check.channel.uploader, error);
+                        }
+                        finished.push(index);
+                        break;
+                    }
+                }
+            }
+        }
+
+        for index in finished.into_iter().rev() {
+            let check = self.suggestions.checks.remove(index);
+            if !check.new_urls.is_empty() {
+                self.suggestions.channels.push(ChannelSuggestion { channel: check.channel, new_urls: check.new_urls });
+            }
+        }
+    }
+
+    pub(crate) fn queue_auto_downloads(&mut self, downloads: Vec<AutoDownload>) {
+        self.download_queue.extend(downloads);
+        if !self.is_busy() && self.receiver.is_none() {
+            self.state = AppState::Input;
+            self.url_input.clear();
+        }
+    }
+
+    pub(crate) fn show_suggestions(&mut self, ui: &mut egui::Ui) {
+        let channels: Vec<usize> = self.suggestions.channels
+            .iter()
+            .enumerate()
+            .filter(|(_, suggestion)| !self.suggestions.dismissed.contains(&suggestion.channel.channel_url))
+            .map(|(index, _)| index)
+            .collect();
+        if channels.is_empty() && self.failed_downloads.entries.is_empty() {
+            return;
+        }
+
+        let mut continue_channel = None;
+        let mut dismiss_channel = None;
+        let mut retry_failed = false;
+        let mut dismiss_failed = false;
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.label("💡 Suggestions:");
+                ui.add_space(5.0);
+
+                if let Some(latest) = self.failed_downloads.entries.iter().map(|entry| entry.failed_at).max() {
+                    let count = self.failed_downloads.entries.len();
+                    let details = self.failed_downloads.entries
+                        .iter()
+                        .map(|entry| format!("{}\n    {}", entry.title, entry.error))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    ui.horizontal(|ui| {
+                        ui.label(format!("🔁 Retry {} failed download(s) from {}", count, when_text(latest)))
+                            .on_hover_text(details);
+                        if ui.button("Retry").clicked() {
+                            retry_failed = true;
+                        }
+                        if ui.button("Dismiss").clicked() {
+                            dismiss_failed = true;
+                        }
+                    });
+                }
+
+                for index in channels {
+                    let suggestion = &self.suggestions.channels[index];
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "📺 Continue downloading {} ({} new video(s))",
+                            suggestion.channel.uploader,
+                            suggestion.new_urls.len()
+                        ))
+                        .on_hover_text(format!(
+                            "You downloaded {} of their videos recently",
+                            suggestion.channel.downloads
+                        ));
+                        if ui.button("Add to queue").clicked() {
+                            continue_channel = Some(index);
+                        }
+                        if ui.button("Dismiss").clicked() {
+                            dismiss_channel = Some(suggestion.channel.channel_url.clone());
+                        }
+                    });
+                }
+            });
+        });
+        ui.add_space(10.0);
+
+        if retry_failed {
+            // Ones that fail again are put back on the list
+            let failed = self.failed_downloads.entries.clone();
+            self.log_console(format!("Retrying {} failed download(s)", failed.len()));
+            self.queue_auto_downloads(failed.into_iter().map(|entry| AutoDownload {
+                url: entry.url,
+                format: entry.format,
+                output_path: entry.output_path,
+            }).collect());
+        }
+        if retry_failed || dismiss_failed {
+            self.failed_downloads.entries.clear();
+            if let Err(e) = self.failed_downloads.save() {
+                self.log_console(format!("WARNING: Could not save the failed downloads list: {}", e));
+            }
+        }
+        if let Some(index) = continue_channel {
+            let suggestion = self.suggestions.channels.remove(index);
+            self.log_console(format!(
+                "Queueing {} new video(s) from {}",
+                suggestion.new_urls.len(),
+                suggestion.channel.uploader
+            ));
+            // Oldest first, so they download in the order they were published
+            self.receive_urls(suggestion.new_urls.into_iter().rev().collect());
+        }
+        if let Some(channel_url) = dismiss_channel {
+            self.suggestions.dismissed.insert(channel_url);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::settings::ProxyProfile;
+use crate::YtMp3App;
+
+// Name the detected proxy goes by in the proxy pickers and in jobs
+pub const SYSTEM_PROXY_NAME: &str = "System proxy";
+// Always reached directly: the local API, the self-test's fixture server
+const LOOPBACK: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+#[derive(Debug, Clone)]
+pub struct SystemProxy {
+    pub url: String,
+    // Where it was found, shown in the settings
+    pub source: String,
+    // Hosts reached directly, comma-separated like NO_PROXY
+    pub no_proxy: String,
+}
+
+fn with_scheme(address: &str) -> String {
+    if address.contains("://") {
+        address.to_string()
+    } else {
+        format!("http://{}", address)
+    }
+}
+
+// An entry of an OS bypass list in NO_PROXY form: "*.corp.example" becomes ".corp.example";
+// Windows' "<local>" and other wildcards have no equivalent
+fn bypass_entry(host: &str) -> Option<String> {
+    let host = host.trim();
+    let host = host.strip_prefix('*').filter(|rest| rest.starts_with('.')).unwrap_or(host);
+    (!host.is_empty() && !host.contains(['*', '<'])).then(|| host.to_string())
+}
+
+// Loopback, then NO_PROXY, then the OS bypass list, without repeats
+fn no_proxy_list(os_bypass: &[String]) -> String {
+    let from_env: Vec<String> = ["NO_PROXY", "no_proxy"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .flat_map(|value| value.split(',').map(|host| host.trim().to_string()).collect::<Vec<_>>())
+        .collect();
+    let mut hosts: Vec<String> = Vec::new();
+    for host in LOOPBACK.iter().map(|host| host.to_string()).chain(from_env).chain(os_bypass.iter().cloned()) {
+        if !host.is_empty() && !hosts.contains(&host) {
+            hosts.push(host);
+        }
+    }
+    hosts.join(",")
+}
+
+fn from_environment() -> Option<SystemProxy> {
+    ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy", "HTTP_PROXY", "http_proxy"]
+        .iter()
+        .find_map(|name| {
+            let value = std::env::var(name).ok().filter(|value| !value.trim().is_empty())?;
+            Some(SystemProxy {
+                url: with_scheme(value.trim()),
+                source: format!("the {} environment variable", name),
+                no_proxy: no_proxy_list(&[]),
+            })
+        })
+}
+
+// Auto-config scripts pick a proxy per address by running JavaScript, which isn't done here
+fn skip_pac(source: &str) -> Option<SystemProxy> {
+    log::warn!("{} use an auto-config script, which is not read; choose a proxy in the settings instead", source);
+    None
+}
+
+fn command_output(command: &mut Command) -> Option<String> {
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+    let output = command.output().ok().filter(|output| output.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// "proxy:8080", or per protocol: "http=proxy:8080;https=proxy:8443;socks=proxy:1080"
+#[cfg(target_os = "windows")]
+fn windows_proxy_server(value: &str) -> Option<String> {
+    if !value.contains('=') {
+        return Some(with_scheme(value));
+    }
+    let servers: Vec<(&str, &str)> = value.split(';').filter_map(|part| part.split_once('=')).collect();
+    ["https", "http"]
+        .iter()
+        .find_map(|protocol| servers.iter().find(|(name, _)| name.eq_ignore_ascii_case(protocol)))
+        .map(|(_, address)| with_scheme(address))
+}
+
+#[cfg(target_os = "windows")]
+fn from_os() -> Option<SystemProxy> {
+    const KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings";
+    let output = command_output(Command::new("reg").args(["query", KEY]))?;
+    let value = |name: &str| {
+        output.lines().find_map(|line| {
+            let mut fields = line.split_whitespace();
+            (fields.next() == Some(name)).then(|| fields.skip(1).collect::<Vec<_>>().join(" "))
+        })
+    };
+    if value("ProxyEnable").as_deref() == Some("0x1") {
+        if let Some(url) = value("ProxyServer").and_then(|server| windows_proxy_server(&server)) {
+            // "localhost;*.corp.example;<local>"
+            let bypass: Vec<String> = value("ProxyOverride")
+                .map(|hosts| hosts.split(';').filter_map(bypass_entry).collect())
+                .unwrap_or_default();
+            return Some(SystemProxy {
+                url,
+                source: "the Windows proxy settings".to_string(),
+                no_proxy: no_proxy_list(&bypass),
+            });
+        }
+    }
+    value("AutoConfigURL").and_then(|_| skip_pac("The Windows proxy settings"))
+}
+
+#[cfg(target_os = "macos")]
+fn from_os() -> Option<SystemProxy> {
+    let output = command_output(Command::new("scutil").arg("--proxy"))?;
+    let value = |name: &str| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(" : ")?;
+            (key.trim() == name).then(|| value.trim().to_string())
+        })
+    };
+    // "ExceptionsList : <array> {", then one "0 : *.local" line per host up to "}"
+    let bypass: Vec<String> = output
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("ExceptionsList"))
+        .skip(1)
+        .take_while(|line| line.trim() != "}")
+        .filter_map(|line| line.split_once(" : ").and_then(|(_, host)| bypass_entry(host)))
+        .collect();
+    for protocol in ["HTTPS", "HTTP"] {
+        if value(&format!("{}Enable", protocol)).as_deref() == Some("1") {
+            if let (Some(host), Some(port)) = (value(&format!("{}Proxy", protocol)), value(&format!("{}Port", protocol))) {
+                return Some(SystemProxy {
+                    url: format!("http://{}:{}", host, port),
+                    source: "the macOS network settings".to_string(),
+                    no_proxy: no_proxy_list(&bypass),
+                });
+            }
+        }
+    }
+    if value("ProxyAutoConfigEnable").as_deref() == Some("1") {
+        return skip_pac("The macOS network settings");
+    }
+    None
+}
+
+// GNOME's settings; other desktops set the environment variables
+#[cfg(target_os = "linux")]
+fn from_os() -> Option<SystemProxy> {
+    let get = |schema: &str, key: &str| {
+        command_output(Command::new("gsettings").args(["get", schema, key]))
+            .map(|value| value.trim().trim_matches('\'').to_string())
+    };
+    match get("org.gnome.system.proxy", "mode")?.as_str() {
+        "manual" => {
+            // "['localhost', '127.0.0.0/8', '::1']"
+            let bypass: Vec<String> = get("org.gnome.system.proxy", "ignore-hosts")
+                .map(|hosts| {
+                    hosts.trim_matches(['[', ']']).split(',').filter_map(|host| bypass_entry(host.trim().trim_matches('\''))).collect()
+                })
+                .unwrap_or_default();
+            ["org.gnome.system.proxy.https", "org.gnome.system.proxy.http"].iter().find_map(|schema| {
+                let host = get(schema, "host").filter(|host| !host.is_empty())?;
+                let port = get(schema, "port").filter(|port| port != "0")?;
+                Some(SystemProxy {
+                    url: format!("http://{}:{}", host, port),
+                    source: "the GNOME proxy settings".to_string(),
+                    no_proxy: no_proxy_list(&bypass),
+                })
+            })
+        }
+        "auto" => get("org.gnome.system.proxy", "autoconfig-url")
+            .filter(|pac_url| !pac_url.is_empty())
+            .and_then(|_| skip_pac("The GNOME proxy settings")),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn from_os() -> Option<SystemProxy> {
+    None
+}
+
+// Blocking: asks the OS tools
+pub fn detect() -> Option<SystemProxy> {
+    from_environment().or_else(from_os)
+}
+
+#[derive(Default)]
+pub struct SystemProxyState {
+    detecting: Option<mpsc::Receiver<Option<SystemProxy>>>,
+    pub found: Option<SystemProxy>,
+}
+
+impl SystemProxyState {
+    pub fn is_detecting(&self) -> bool {
+        self.detecting.is_some()
+    }
+}
+
+impl YtMp3App {
+    pub(crate) fn apply_system_proxy_settings(&mut self) {
+        self.system_proxy = SystemProxyState::default();
+        self.settings.system_proxy = None;
+        if !self.settings.use_system_proxy {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            tx.send(detect()).ok();
+        });
+        self.system_proxy.detecting = Some(rx);
+    }
+
+    // Called every frame until detection finishes
+    pub(crate) fn poll_system_proxy(&mut self) {
+        let Some(detecting) = &self.system_proxy.detecting else {
+            return;
+        };
+        let found = match detecting.try_recv() {
+            Ok(found) => found,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => None,
+        };
+        self.system_proxy.detecting = None;
+        if let Some(proxy) = &found {
+            self.log_console(format!("Using the proxy {} from {}, except for {}", proxy.url, proxy.source, proxy.no_proxy));
+            self.settings.system_proxy = Some(ProxyProfile {
+                name: SYSTEM_PROXY_NAME.to_string(),
+                url: proxy.url.clone(),
+                no_proxy: proxy.no_proxy.clone(),
+            });
+        }
+        self.system_proxy.found = found;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    #[test]
+    fn bypass_entries_become_no_proxy_hosts() {
+        assert_eq!(bypass_entry(" *.corp.example "), Some(".corp.example".to_string()));
+        assert_eq!(bypass_entry("intranet"), Some("intranet".to_string()));
+        assert_eq!(bypass_entry("<local>"), None);
+        assert_eq!(bypass_entry("127.*"), None);
+        assert!(no_proxy_list(&["intranet".to_string()]).starts_with("localhost,127.0.0.1,::1,"));
+    }
+
+    #[test]
+    fn system_proxy_leaves_local_addresses_alone() {
+        let mut settings = Settings {
+            system_proxy: Some(ProxyProfile {
+                name: SYSTEM_PROXY_NAME.to_string(),
+                url: "http://proxy.example:3128".to_string(),
+                no_proxy: no_proxy_list(&[]),
+            }),
+            ..Settings::default()
+        };
+        assert!(!settings.ytdlp_common_args().contains(&"--proxy".to_string()));
+        let env = settings.ytdlp_env();
+        assert!(env.contains(&("https_proxy", "http://proxy.example:3128".to_string())));
+        assert!(env.iter().any(|(name, hosts)| *name == "no_proxy" && hosts.split(',').any(|host| host == "127.0.0.1")));
+
+        // A proxy the user chose applies to everything
+        settings.proxies.push(ProxyProfile { name: "Work".to_string(), url: "socks5://work:1080".to_string(), ..Default::default() });
+        settings.default_proxy = "Work".to_string();
+        assert!(settings.ytdlp_common_args().contains(&"socks5://work:1080".to_string()));
+        assert!(!settings.ytdlp_env().iter().any(|(name, _)| *name == "no_proxy"));
+    }
+}
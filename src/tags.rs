@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use crate::settings::TagFolder;
+
+// "workout, Lectures ,band X" -> ["workout", "Lectures", "band X"], without empty or repeated tags
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+pub fn has_tag(tags: &[String], tag: &str) -> bool {
+    tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag))
+}
+
+// The subfolder of the first tag that has one; relative folders go inside the output folder
+pub fn routed_folder<'a>(tags: &[String], tag_folders: &'a [TagFolder]) -> Option<&'a TagFolder> {
+    tags.iter().find_map(|tag| {
+        tag_folders
+            .iter()
+            .find(|route| !route.folder.is_empty() && route.tag.eq_ignore_ascii_case(tag))
+    })
+}
+
+pub fn routed_output_path(output_path: &str, tags: &[String], tag_folders: &[TagFolder]) -> String {
+    match routed_folder(tags, tag_folders) {
+        Some(route) => Path::new(output_path).join(&route.folder).display().to_string(),
+        None => output_path.to_string(),
+    }
+}
@@ -0,0 +1,73 @@
+use eframe::egui;
+use egui::{Color32, Stroke};
+
+use crate::YtMp3App;
+
+// Black background, white text and outlines, yellow for hover and selection
+fn high_contrast_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::from_gray(30);
+    visuals.hyperlink_color = Color32::from_rgb(0, 255, 255);
+    visuals.warn_fg_color = Color32::YELLOW;
+    visuals.error_fg_color = Color32::from_rgb(255, 80, 80);
+    visuals.selection.bg_fill = Color32::YELLOW;
+    visuals.selection.stroke = Stroke::new(2.0, Color32::BLACK);
+
+    let widgets = &mut visuals.widgets;
+    for state in [&mut widgets.noninteractive, &mut widgets.inactive] {
+        state.bg_fill = Color32::BLACK;
+        state.weak_bg_fill = Color32::BLACK;
+        state.bg_stroke = Stroke::new(1.5, Color32::WHITE);
+        state.fg_stroke = Stroke::new(1.5, Color32::WHITE);
+    }
+    for state in [&mut widgets.hovered, &mut widgets.active, &mut widgets.open] {
+        state.bg_fill = Color32::from_gray(40);
+        state.weak_bg_fill = Color32::from_gray(40);
+        state.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+        state.fg_stroke = Stroke::new(2.0, Color32::YELLOW);
+    }
+    visuals
+}
+
+impl YtMp3App {
+    // Called every frame, so the look also survives eframe following a system theme change
+    pub(crate) fn apply_display_settings(&self, ctx: &egui::Context) {
+        let style = ctx.style();
+        let reduced_motion = self.settings.reduced_motion;
+
+        let mut current = style.visuals.clone();
+        current.text_cursor.blink = true;
+        let mut visuals = if self.settings.high_contrast {
+            high_contrast_visuals()
+        } else if current == high_contrast_visuals() {
+            egui::Visuals::dark()
+        } else {
+            current
+        };
+        // Collapsing sections and windows snap instead of sliding, the text cursor doesn't blink
+        visuals.text_cursor.blink = !reduced_motion;
+        let animation_time = if reduced_motion { 0.0 } else { egui::Style::default().animation_time };
+        if style.visuals == visuals && style.animation_time == animation_time {
+            return;
+        }
+
+        let mut style = (*style).clone();
+        style.visuals = visuals;
+        style.animation_time = animation_time;
+        ctx.set_style(style);
+    }
+}
+
+// A spinner, or a still hourglass with reduced motion on
+pub fn busy_indicator(ui: &mut egui::Ui, reduced_motion: bool) {
+    if reduced_motion {
+        ui.label("⏳");
+    } else {
+        ui.spinner();
+    }
+}
@@ -0,0 +1,194 @@
+use eframe::egui;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::jobs::{JobId, JobStatus};
+use crate::progress::parse_download_line;
+use crate::{format_duration, locale, AppState, YtMp3App};
+
+// Speed samples older than this don't count towards the average
+const SPEED_WINDOW: Duration = Duration::from_secs(20);
+
+// Figures of one download, from its progress lines
+#[derive(Debug, Default)]
+struct Stream {
+    // (when, bytes per second)
+    speed_samples: VecDeque<(Instant, f64)>,
+    // Size and downloaded fraction of the file being downloaded right now
+    file_size: Option<f64>,
+    file_fraction: f64,
+    // Files of the download that already finished (video and audio are separate files)
+    finished_files_size: f64,
+}
+
+impl Stream {
+    fn average_speed(&self) -> Option<f64> {
+        if self.speed_samples.is_empty() {
+            return None;
+        }
+        let total: f64 = self.speed_samples.iter().map(|(_, speed)| speed).sum();
+        Some(total / self.speed_samples.len() as f64).filter(|speed| *speed > 0.0)
+    }
+}
+
+// Live download figures taken from yt-dlp's progress lines, used for the queue's ETA.
+// Downloads running side by side are kept apart by job and add up
+#[derive(Debug, Default)]
+pub struct TransferStats {
+    streams: HashMap<Option<JobId>, Stream>,
+    // Total size of each download finished this session, to guess the size of queued ones
+    finished_downloads: Vec<f64>,
+}
+
+impl TransferStats {
+    // Feeds one line of a download's yt-dlp output, e.g. "[download]  45.2% of ~ 123.45MiB at 1.23MiB/s ETA 00:30"
+    pub fn observe(&mut self, job_id: Option<JobId>, line: &str) {
+        let Some(progress) = parse_download_line(line) else {
+            return;
+        };
+        let Some(percent) = progress.percent else {
+            return;
+        };
+        let fraction = (percent as f64 / 100.0).clamp(0.0, 1.0);
+        let stream = self.streams.entry(job_id).or_default();
+
+        // Progress going backwards means the next file of the same download started
+        if fraction < stream.file_fraction {
+            stream.finished_files_size += stream.file_size.unwrap_or(0.0);
+        }
+        stream.file_fraction = fraction;
+        stream.file_size = progress.total_size;
+
+        let now = Instant::now();
+        if let Some(speed) = progress.speed {
+            stream.speed_samples.push_back((now, speed));
+        }
+        while stream.speed_samples.front().is_some_and(|(when, _)| now.duration_since(*when) > SPEED_WINDOW) {
+            stream.speed_samples.pop_front();
+        }
+    }
+
+    // Called when a download ends, successful or not
+    pub fn finish_download(&mut self, job_id: Option<JobId>, succeeded: bool) {
+        let Some(stream) = self.streams.remove(&job_id) else {
+            return;
+        };
+        let size = stream.finished_files_size + stream.file_size.unwrap_or(0.0);
+        if succeeded && size > 0.0 {
+            self.finished_downloads.push(size);
+        }
+    }
+
+    // Of all running downloads together
+    pub fn average_speed(&self) -> Option<f64> {
+        let speeds: Vec<f64> = self.streams.values().filter_map(Stream::average_speed).collect();
+        if speeds.is_empty() {
+            return None;
+        }
+        Some(speeds.iter().sum())
+    }
+
+    // Time left for the running downloads plus `queued` more. Their sizes aren't known before
+    // they're fetched, so each counts as the average of this session's downloads
+    pub fn queue_eta(&self, queued: usize) -> Option<f64> {
+        let speed = self.average_speed()?;
+        let sized: Vec<&Stream> = self.streams.values().filter(|stream| stream.file_size.is_some()).collect();
+        if sized.is_empty() {
+            return None;
+        }
+        let running_remaining: f64 = sized
+            .iter()
+            .map(|stream| stream.file_size.unwrap_or(0.0) * (1.0 - stream.file_fraction))
+            .sum();
+        let typical_size = if self.finished_downloads.is_empty() {
+            sized.iter().map(|stream| stream.finished_files_size + stream.file_size.unwrap_or(0.0)).sum::<f64>() / sized.len() as f64
+        } else {
+            self.finished_downloads.iter().sum::<f64>() / self.finished_downloads.len() as f64
+        };
+        Some((running_remaining + typical_size * queued as f64) / speed)
+    }
+}
+
+impl YtMp3App {
+    // Downloads waiting to start
+    pub(crate) fn queued_download_count(&self) -> usize {
+        self.download_queue.len() + self.pending_urls.len()
+    }
+
+    // Something is downloading, whether or not the main view shows it
+    fn transferring(&self) -> bool {
+        matches!(self.state, AppState::Downloading { .. }) || !self.downloads.is_empty()
+    }
+
+    // The main view's progress, or the average of the running downloads when there are several
+    fn overall_progress(&self) -> f32 {
+        let running: Vec<f32> = self.jobs
+            .iter()
+            .filter(|job| self.downloads.iter().any(|download| download.job_id == job.id))
+            .filter_map(|job| match &job.status {
+                JobStatus::Running { progress, .. } => Some(*progress),
+                _ => None,
+            })
+            .collect();
+        match &self.state {
+            AppState::Downloading { progress, .. } if running.len() <= 1 => *progress,
+            _ if running.is_empty() => 0.0,
+            _ => running.iter().sum::<f32>() / running.len() as f32,
+        }
+    }
+
+    pub(crate) fn queue_eta_text(&self) -> Option<String> {
+        if !self.transferring() {
+            return None;
+        }
+        let queued = self.queued_download_count();
+        let eta = self.transfer.queue_eta(queued)?;
+        let running = self.downloads.len();
+        Some(match (queued, running) {
+            (0, _) => format!("{} left", format_duration(eta)),
+            (_, 0 | 1) => format!("~{} left for this and {} queued", format_duration(eta), queued),
+            _ => format!("~{} left for these {} and {} queued", format_duration(eta), running, queued),
+        })
+    }
+
+    pub(crate) fn show_transfer_footer(&self, ctx: &egui::Context) {
+        if !self.transferring() {
+            return;
+        }
+        egui::TopBottomPanel::bottom("transfer_footer").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                match self.transfer.average_speed() {
+                    Some(speed) => ui.label(format!("⏬ {}", locale::speed(speed))),
+                    None => ui.label("⏬ Waiting for data..."),
+                };
+                if self.downloads.len() > 1 {
+                    ui.separator();
+                    ui.label(format!("{} downloads running", self.downloads.len()));
+                }
+                if let Some(eta) = self.queue_eta_text() {
+                    ui.separator();
+                    ui.label(format!("⏱ {}", eta));
+                }
+            });
+        });
+    }
+
+    // Keeps the OS window title in sync, only sending a command when the text changes.
+    // During a download it reads like "42% ▼ 3.2 MB/s · 1:05 left — ...", which shows in the taskbar and alt-tab
+    pub(crate) fn update_window_title(&mut self, ctx: &egui::Context) {
+        let title = if self.transferring() {
+            let mut parts = vec![format!("{:.0}%", self.overall_progress() * 100.0)];
+            if let Some(speed) = self.transfer.average_speed() {
+                parts[0].push_str(&format!(" ▼ {}", locale::speed(speed)));
+            }
+            parts.extend(self.queue_eta_text());
+            format!("{} — {}", parts.join(" · "), crate::APP_TITLE)
+        } else {
+            crate::APP_TITLE.to_string()
+        };
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use eframe::egui;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::presets::DevicePreset;
+use crate::recycle;
+use crate::rtl;
+use crate::settings::{OverwritePolicy, Settings};
+use crate::verify;
+use crate::{AutoDownload, DownloadFormat, YtMp3App};
+
+// Heights offered as the "downloaded below" threshold
+const THRESHOLDS: [u32; 4] = [720, 1080, 1440, 2160];
+
+// How a History entry is downloaded again, looked up by URL when its turn in the queue comes
+#[derive(Debug, Clone)]
+pub struct UpgradePlan {
+    history_index: usize,
+    old_path: String,
+    preset: DevicePreset,
+    // Replace the old file, or keep it and save the new one next to it
+    replace: bool,
+}
+
+impl UpgradePlan {
+    pub fn apply(&self, settings: &Settings) -> Settings {
+        let mut settings = settings.clone();
+        settings.device_preset = self.preset;
+        settings.overwrite_policy = if self.replace { OverwritePolicy::Overwrite } else { OverwritePolicy::Rename };
+        settings
+    }
+}
+
+// The History window's "Upgrade quality" view
+pub struct QualityUpgrade {
+    pub open: bool,
+    below: u32,
+    preset: DevicePreset,
+    replace: bool,
+    selected: HashSet<usize>,
+    // Entries probed this session, including the ones whose file couldn't be read
+    scanned: HashSet<usize>,
+    scan: Option<mpsc::Receiver<(usize, Option<u32>)>>,
+    pending: HashMap<String, UpgradePlan>,
+}
+
+impl Default for QualityUpgrade {
+    fn default() -> Self {
+        Self {
+            open: false,
+            below: 1080,
+            preset: DevicePreset::Off,
+            replace: true,
+            selected: HashSet::new(),
+            scanned: HashSet::new(),
+            scan: None,
+            pending: HashMap::new(),
+        }
+    }
+}
+
+impl YtMp3App {
+    // Reads the video height of MP4 downloads that don't have it recorded yet
+    fn start_height_scan(&mut self, ctx: &egui::Context) {
+        let files: Vec<(usize, String)> = self.history.entries
+            .iter()
+            .enumerate()
+            .filter(|(index, entry)| {
+                entry.format == DownloadFormat::Mp4
+                    && !entry.deleted
+                    && !entry.replaced
+                    && !entry.missing
+                    && entry.height.is_none()
+                    && !self.quality_upgrade.scanned.contains(index)
+            })
+            .map(|(index, entry)| (index, entry.path.clone()))
+            .collect();
+        if files.is_empty() {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            for (index, path) in files {
+                let height = Path::new(&path).is_file().then(|| verify::probe_height(&path)).flatten();
+                if tx.send((index, height)).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+        });
+        self.quality_upgrade.scan = Some(rx);
+    }
+
+    fn poll_height_scan(&mut self) {
+        let Some(scan) = &self.quality_upgrade.scan else {
+            return;
+        };
+        let mut finished = false;
+        let mut measured = Vec::new();
+        loop {
+            match scan.try_recv() {
+                Ok((index, height)) => {
+                    self.quality_upgrade.scanned.insert(index);
+                    if let Some(entry) = self.history.entries.get_mut(index) {
+                        entry.height = height;
+                        measured.push(index);
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+        if finished {
+            self.quality_upgrade.scan = None;
+        }
+        if !measured.is_empty() {
+            if let Err(e) = self.history.save(measured) {
+                self.log_console(format!("WARNING: Could not save history: {}", e));
+            }
+        }
+    }
+
+    fn queue_upgrades(&mut self) {
+        let mut indices: Vec<usize> = self.quality_upgrade.selected.drain().collect();
+        indices.sort_unstable();
+        let mut downloads = Vec::new();
+        for index in indices {
+            let entry = &self.history.entries[index];
+            let output_path = Path::new(&entry.path)
+                .parent()
+                .map(|folder| folder.display().to_string())
+                .unwrap_or_else(|| self.settings.default_output_dir(DownloadFormat::Mp4));
+            self.quality_upgrade.pending.insert(entry.url.clone(), UpgradePlan {
+                history_index: index,
+                old_path: entry.path.clone(),
+                preset: self.quality_upgrade.preset,
+                replace: self.quality_upgrade.replace,
+            });
+            downloads.push(AutoDownload { url: entry.url.clone(), format: DownloadFormat::Mp4, output_path });
+        }
+        self.log_console(format!("Queued {} download(s) again at a higher quality", downloads.len()));
+        self.queue_auto_downloads(downloads);
+    }
+
+    pub(crate) fn take_upgrade_plan(&mut self, url: &str) -> Option<UpgradePlan> {
+        self.quality_upgrade.pending.remove(url)
+    }
+
+    // Retires the old download once the better one is in
+    pub(crate) fn finish_upgrade(&mut self, plan: UpgradePlan, new_path: &str) {
+        if !plan.replace {
+            return;
+        }
+        if plan.old_path != new_path && Path::new(&plan.old_path).exists() {
+            if let Err(e) = recycle::move_to_trash(&plan.old_path) {
+                self.log_console(format!("WARNING: Could not move the old file {} to the trash: {}", plan.old_path, e));
+                return;
+            }
+            self.log_console(format!("Moved the old file {} to the trash", plan.old_path));
+        }
+        if let Some(entry) = self.history.entries.get_mut(plan.history_index) {
+            entry.replaced = true;
+        }
+        if let Err(e) = self.history.save([plan.history_index]) {
+            self.log_console(format!("WARNING: Could not save history: {}", e));
+        }
+    }
+
+    pub(crate) fn show_upgrade_window(&mut self, ctx: &egui::Context) {
+        if !self.quality_upgrade.open {
+            return;
+        }
+        self.poll_height_scan();
+        if self.quality_upgrade.scan.is_none() {
+            self.start_height_scan(ctx);
+        }
+
+        let below = self.quality_upgrade.below;
+        let candidates: Vec<usize> = self.history.entries
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, entry)| !entry.deleted && !entry.replaced && !entry.missing && entry.height.is_some_and(|height| height < below))
+            .map(|(index, _)| index)
+            .collect();
+        self.quality_upgrade.selected.retain(|index| candidates.contains(index));
+
+        let mut open = true;
+        let mut queue = false;
+        egui::Window::new("⬆ Upgrade quality")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                let upgrade = &mut self.quality_upgrade;
+                ui.horizontal(|ui| {
+                    ui.label("MP4 downloads below");
+                    egui::ComboBox::from_id_source("upgrade_below")
+                        .selected_text(format!("{}p", upgrade.below))
+                        .show_ui(ui, |ui| {
+                            for height in THRESHOLDS {
+                                ui.selectable_value(&mut upgrade.below, height, format!("{}p", height));
+                            }
+                        });
+                });
+                if upgrade.scan.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Reading the resolution of downloaded files...");
+                    });
+                }
+
+                if candidates.is_empty() {
+                    if upgrade.scan.is_none() {
+                        ui.label(format!("No downloads in the history are below {}p.", upgrade.below));
+                    }
+                } else {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Select all").clicked() {
+                            upgrade.selected.extend(candidates.iter().copied());
+                        }
+                        if ui.small_button("Select none").clicked() {
+                            upgrade.selected.clear();
+                        }
+                    });
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        egui::Grid::new("upgrade_grid")
+                            .num_columns(3)
+                            .spacing([10.0, 4.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for &index in &candidates {
+                                    let entry = &self.history.entries[index];
+                                    let mut selected = upgrade.selected.contains(&index);
+                                    if ui.checkbox(&mut selected, rtl::display(&entry.title)).on_hover_text(&entry.path).changed() {
+                                        if selected {
+                                            upgrade.selected.insert(index);
+                                        } else {
+                                            upgrade.selected.remove(&index);
+                                        }
+                                    }
+                                    ui.label(format!("{}p", entry.height.unwrap_or(0)));
+                                    ui.label(entry.completed_at_text());
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Download again with:");
+                    egui::ComboBox::from_id_source("upgrade_preset")
+                        .selected_text(if upgrade.preset == DevicePreset::Off { "Best available" } else { upgrade.preset.label() })
+                        .width(220.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut upgrade.preset, DevicePreset::Off, "Best available");
+                            for preset in DevicePreset::ALL {
+                                if preset.download_format() == Some(DownloadFormat::Mp4) {
+                                    ui.selectable_value(&mut upgrade.preset, preset, preset.label());
+                                }
+                            }
+                        });
+                });
+                ui.radio_value(&mut upgrade.replace, true, "Replace the old files");
+                ui.radio_value(&mut upgrade.replace, false, "Keep the old files and save the new ones next to them");
+                ui.add_space(5.0);
+                let count = upgrade.selected.len();
+                if ui.add_enabled(count > 0, egui::Button::new(format!("⬆ Queue {} download(s)", count))).clicked() {
+                    queue = true;
+                }
+            });
+        self.quality_upgrade.open = open;
+
+        if queue {
+            self.queue_upgrades();
+        }
+    }
+}
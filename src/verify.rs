@@ -0,0 +1,121 @@
+use std::path::Path;
+use std::process::Command;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::channel::AppSender;
+use crate::settings::Settings;
+use crate::AppMessage;
+
+// Allowed difference between the probed duration and the one reported by the site
+const MIN_DURATION_TOLERANCE_SECS: f64 = 3.0;
+const DURATION_TOLERANCE_RATIO: f64 = 0.02;
+
+// Checks that a finished download is a playable file of the expected length.
+// Returns a description of the problem if the file looks broken
+pub fn verify_download(
+    path: &str,
+    expected_duration: Option<f64>,
+    settings: &Settings,
+    progress_sender: &AppSender,
+) -> Option<String> {
+    if settings.simulation.is_some() {
+        progress_sender.send(AppMessage::ConsoleOutput("Simulation mode: the file is a placeholder, skipping verification".to_string())).ok();
+        return None;
+    }
+    progress_sender.send(AppMessage::ConsoleOutput(format!("Verifying {}...", path))).ok();
+
+    let size = match std::fs::metadata(Path::new(path)) {
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        Ok(_) => return Some("The downloaded path is not a file".to_string()),
+        Err(e) => return Some(format!("The downloaded file could not be read: {}", e)),
+    };
+    if size == 0 {
+        return Some("The downloaded file is empty".to_string());
+    }
+
+    let probed_duration = match probe_duration(path) {
+        Ok(duration) => duration,
+        Err(ProbeError::NotInstalled) => {
+            progress_sender.send(AppMessage::ConsoleOutput(
+                "ffprobe not found, skipping container and duration checks".to_string()
+            )).ok();
+            return None;
+        }
+        Err(ProbeError::Failed(error)) => {
+            return Some(format!("ffprobe could not read the file: {}", error));
+        }
+    };
+
+    if let (Some(expected), Some(actual)) = (expected_duration.filter(|d| *d > 0.0), probed_duration) {
+        let tolerance = (expected * DURATION_TOLERANCE_RATIO).max(MIN_DURATION_TOLERANCE_SECS);
+        if (expected - actual).abs() > tolerance {
+            return Some(format!(
+                "The file is {:.0}s long but the video is {:.0}s",
+                actual, expected
+            ));
+        }
+    }
+
+    progress_sender.send(AppMessage::ConsoleOutput(format!(
+        "Verified: {} bytes{}",
+        size,
+        probed_duration.map(|d| format!(", {:.0}s", d)).unwrap_or_default()
+    ))).ok();
+    None
+}
+
+// Height of the first video stream, None for audio or an unreadable file
+pub fn probe_height(path: &str) -> Option<u32> {
+    let mut command = Command::new("ffprobe");
+    command.args([
+        "-v", "error",
+        "-select_streams", "v:0",
+        "-show_entries", "stream=height",
+        "-of", "default=noprint_wrappers=1:nokey=1",
+        path,
+    ]);
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+
+    let output = command.output().ok().filter(|output| output.status.success())?;
+    String::from_utf8_lossy(&output.stdout).trim().parse::<u32>().ok()
+}
+
+// Length in seconds, None if ffprobe can't tell
+pub fn probed_duration(path: &str) -> Option<f64> {
+    probe_duration(path).ok().flatten()
+}
+
+enum ProbeError {
+    NotInstalled,
+    Failed(String),
+}
+
+// Duration of the container in seconds, None if the container doesn't report one
+fn probe_duration(path: &str) -> Result<Option<f64>, ProbeError> {
+    let mut command = Command::new("ffprobe");
+    command.args([
+        "-v", "error",
+        "-show_entries", "format=duration",
+        "-of", "default=noprint_wrappers=1:nokey=1",
+        path,
+    ]);
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000);
+
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(ProbeError::NotInstalled),
+        Err(e) => return Err(ProbeError::Failed(e.to_string())),
+    };
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() || !stderr.is_empty() {
+        let error = if stderr.is_empty() { format!("exit code {:?}", output.status.code()) } else { stderr };
+        return Err(ProbeError::Failed(error));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().parse::<f64>().ok())
+}
@@ -0,0 +1,53 @@
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::remote;
+
+// A page for phones and other devices on the network: the queue, the current download and a
+// box to queue URLs. It pairs like a browser extension and keeps its token in the browser
+const PAGE: &str = include_str!("webui.html");
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+// Served by the daemon, and by the window when turned on in Settings
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+// Where other devices find the page, or this computer when the API isn't open to the network
+pub fn page_url(lan: bool, port: u16) -> String {
+    let host = match remote::lan_address() {
+        Some(ip) if lan => ip.to_string(),
+        _ => "127.0.0.1".to_string(),
+    };
+    format!("http://{}:{}/", host, port)
+}
+
+// The page itself holds nothing, so it's open to everyone who can connect
+fn is_page_path(path: &str) -> bool {
+    ENABLED.load(Ordering::Relaxed) && matches!(path, "/" | "/index.html")
+}
+
+pub fn serve(method: &str, path: &str) -> Option<(&'static str, &'static str)> {
+    if !is_page_path(path) {
+        return None;
+    }
+    Some(match method {
+        "GET" | "HEAD" => ("200 OK", PAGE),
+        _ => ("405 Method Not Allowed", ""),
+    })
+}
+
+// The page's own requests carry its origin. Only an address typed as an IP counts: a site
+// that points its own name at this computer would otherwise pass as the page
+pub fn is_page_origin(origin: &str, host: Option<&str>) -> bool {
+    let Some(host) = host.map(|host| host.trim().to_ascii_lowercase()) else {
+        return false;
+    };
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host.as_str(),
+    };
+    let is_address = name.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().is_ok();
+    ENABLED.load(Ordering::Relaxed) && is_address && origin.trim().to_ascii_lowercase() == format!("http://{}", host)
+}
@@ -0,0 +1,433 @@
+use eframe::egui;
+use std::path::PathBuf;
+
+use crate::help;
+use crate::settings::{OverwritePolicy, ProxyProfile, Settings, SystemConfig};
+use crate::YtMp3App;
+
+// Options the app sets itself for every download; taking them from a config file would break
+// finding the downloaded file, the progress bar or the format choice. (option, takes a value)
+const APP_CONTROLLED: &[(&str, bool)] = &[
+    ("-o", true),
+    ("--output", true),
+    ("-f", true),
+    ("--format", true),
+    ("-x", false),
+    ("--extract-audio", false),
+    ("--audio-format", true),
+    ("--audio-quality", true),
+    ("--merge-output-format", true),
+    ("--remux-video", true),
+    ("--encoding", true),
+    ("--newline", false),
+    ("--no-newline", false),
+    ("-q", false),
+    ("--quiet", false),
+    ("--progress-template", true),
+    ("-j", false),
+    ("--dump-json", false),
+    ("--print", true),
+    ("--ignore-config", false),
+    ("--config-location", true),
+    ("--config-locations", true),
+];
+
+// Splits a command line the way yt-dlp reads its config files: whitespace separates
+// arguments, quotes group them, "#" outside quotes starts a comment up to the end of the line
+pub fn split_args(text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_arg = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    current.push(c);
+                }
+            }
+            '"' => {
+                in_arg = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' if matches!(chars.peek(), Some('"') | Some('\\')) => current.push(chars.next().unwrap()),
+                        _ => current.push(c),
+                    }
+                }
+            }
+            '#' if !in_arg => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            _ => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+// Joins arguments back into text for the extra options field, quoted so split_args reads them
+// back unchanged
+pub fn join_args(args: &[String]) -> String {
+    args.iter().map(|arg| quote_arg(arg)).collect::<Vec<_>>().join(" ")
+}
+
+// Double quotes, as split_args keeps backslashes outside them for Windows paths
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "'\"#\\".contains(c)) {
+        return arg.to_string();
+    }
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    // Described like "Cookies from firefox"
+    pub applied: Vec<String>,
+    // Added to the extra options as they were
+    pub extra: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "Imported {} setting(s), {} extra option(s), skipped {}",
+            self.applied.len(),
+            self.extra.len(),
+            self.skipped.len()
+        )];
+        lines.extend(self.applied.iter().map(|line| format!("✔ {}", line)));
+        if !self.extra.is_empty() {
+            lines.push(format!("➕ Extra options: {}", join_args(&self.extra)));
+        }
+        lines.extend(self.skipped.iter().map(|line| format!("✖ {}", line)));
+        lines.join("\n")
+    }
+}
+
+// "--opt=value" -> ("--opt", Some("value"))
+fn split_inline_value(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+        _ => (arg, None),
+    }
+}
+
+// "youtube:player_client=tv;lang=de" goes into the YouTube extractor settings; anything they
+// don't cover is handed back for the extra options
+fn import_youtube_extractor_args(value: &str, settings: &mut Settings, report: &mut ImportReport) -> Option<String> {
+    let options = value.strip_prefix("youtube:").unwrap_or(value);
+    let mut leftover = Vec::new();
+    for option in options.split(';').filter(|option| !option.is_empty()) {
+        match option.split_once('=') {
+            Some(("player_client", clients)) => {
+                settings.youtube_player_client = clients.to_string();
+                report.applied.push(format!("YouTube player client: {}", clients));
+            }
+            Some(("po_token", token)) => {
+                settings.youtube_po_token = token.to_string();
+                report.applied.push("YouTube PO token".to_string());
+            }
+            Some(("lang", lang)) => {
+                settings.youtube_lang = lang.to_string();
+                report.applied.push(format!("YouTube language: {}", lang));
+            }
+            _ => leftover.push(option),
+        }
+    }
+    if leftover.is_empty() {
+        None
+    } else {
+        Some(format!("youtube:{}", leftover.join(";")))
+    }
+}
+
+// Applies the options of a yt-dlp config file to the settings
+pub fn import(text: &str, settings: &mut Settings) -> ImportReport {
+    let mut report = ImportReport::default();
+    let mut extra = split_args(&settings.extra_args);
+    let args = split_args(text);
+    let mut index = 0;
+
+    while index < args.len() {
+        let (name, inline_value) = split_inline_value(&args[index]);
+        index += 1;
+        // The option's value, from "--opt=value" or the next argument
+        let mut take_value = || -> Option<String> {
+            if let Some(value) = inline_value {
+                return Some(value.to_string());
+            }
+            let value = args.get(index).cloned();
+            index += 1;
+            value
+        };
+
+        match name {
+            "--cookies-from-browser" => {
+                if let Some(browser) = take_value() {
+                    report.applied.push(format!("Cookies from {}", browser));
+                    settings.cookies_browser = browser;
+                }
+            }
+            "--cookies" => {
+                if let Some(file) = take_value() {
+                    report.applied.push(format!("Cookies file {}", file));
+                    settings.cookies_file = file;
+                }
+            }
+            "--proxy" => {
+                if let Some(url) = take_value() {
+                    if url.is_empty() {
+                        continue;
+                    }
+                    let name = "Imported".to_string();
+                    settings.proxies.retain(|proxy| proxy.name != name);
+                    settings.proxies.push(ProxyProfile { name: name.clone(), url: url.clone(), ..Default::default() });
+                    settings.default_proxy = name;
+                    report.applied.push(format!("Default proxy {}", url));
+                }
+            }
+            "--impersonate" => {
+                if let Some(target) = take_value() {
+                    report.applied.push(format!("Impersonate {}", target));
+                    settings.impersonate = target;
+                }
+            }
+            "--plugin-dirs" => {
+                if let Some(folder) = take_value() {
+                    report.applied.push(format!("Plugin folder {}", folder));
+                    settings.plugin_dir = folder;
+                }
+            }
+            "-P" | "--paths" => {
+                let Some(value) = take_value() else {
+                    continue;
+                };
+                if let Some(folder) = value.strip_prefix("temp:") {
+                    settings.temp_dir = folder.to_string();
+                    report.applied.push(format!("Temporary folder {}", folder));
+                } else if !value.contains(':') || value.starts_with("home:") || value.chars().nth(1) == Some(':') {
+                    // A bare path (possibly with a drive letter) is the home path
+                    let folder = value.strip_prefix("home:").unwrap_or(&value).to_string();
+                    settings.mp4_output_dir = folder.clone();
+                    settings.mp3_output_dir = folder.clone();
+                    report.applied.push(format!("Output folder {}", folder));
+                } else {
+                    report.skipped.push(format!("--paths {} (only home: and temp: are used)", value));
+                }
+            }
+            "-w" | "--no-overwrites" | "--no-force-overwrites" => {
+                settings.overwrite_policy = OverwritePolicy::Skip;
+                report.applied.push("Keep existing files".to_string());
+            }
+            "--force-overwrites" => {
+                settings.overwrite_policy = OverwritePolicy::Overwrite;
+                report.applied.push("Overwrite existing files".to_string());
+            }
+            "--recode-video" => {
+                match take_value() {
+                    Some(format) if format == "mp4" => {
+                        settings.h264_reencode = true;
+                        report.applied.push("Re-encode MP4 downloads".to_string());
+                    }
+                    Some(format) => report.skipped.push(format!("--recode-video {} (only mp4 is supported)", format)),
+                    None => {}
+                }
+            }
+            "--extractor-args" => {
+                let Some(value) = take_value() else {
+                    continue;
+                };
+                let leftover = if value.starts_with("youtube:") {
+                    import_youtube_extractor_args(&value, settings, &mut report)
+                } else {
+                    Some(value)
+                };
+                if let Some(leftover) = leftover {
+                    extra.extend(["--extractor-args".to_string(), leftover.clone()]);
+                    report.extra.extend(["--extractor-args".to_string(), leftover]);
+                }
+            }
+            _ => {
+                if let Some((_, takes_value)) = APP_CONTROLLED.iter().find(|(option, _)| *option == name) {
+                    let value = if *takes_value { take_value() } else { None };
+                    let shown = match value {
+                        Some(value) => format!("{} {}", name, value),
+                        None => name.to_string(),
+                    };
+                    report.skipped.push(format!("{} (set by the app)", shown));
+                } else if !name.starts_with('-') {
+                    report.skipped.push(format!("{} (URLs in config files aren't used)", name));
+                } else {
+                    // Kept together with the value, if it has one: a following argument that
+                    // isn't an option belongs to it
+                    let mut option = vec![args[index - 1].clone()];
+                    if inline_value.is_none() && args.get(index).is_some_and(|next| !next.starts_with('-')) {
+                        option.push(args[index].clone());
+                        index += 1;
+                    }
+                    extra.extend(option.iter().cloned());
+                    report.extra.extend(option);
+                }
+            }
+        }
+    }
+
+    settings.extra_args = join_args(&extra);
+    report
+}
+
+// The user config files yt-dlp would load, in the order it looks for them
+fn system_config_candidates() -> Vec<PathBuf> {
+    let mut folders = Vec::new();
+    if let Some(config) = dirs::config_dir() {
+        folders.push(config);
+    }
+    if let Some(home) = dirs::home_dir() {
+        // yt-dlp uses ~/.config on macOS too
+        folders.push(home.join(".config"));
+    }
+    let mut candidates = Vec::new();
+    for folder in folders {
+        candidates.push(folder.join("yt-dlp.conf"));
+        candidates.push(folder.join("yt-dlp").join("config"));
+        candidates.push(folder.join("yt-dlp").join("config.txt"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        candidates.push(home.join("yt-dlp.conf"));
+        candidates.push(home.join("yt-dlp.conf.txt"));
+        candidates.push(home.join(".yt-dlp").join("config"));
+        candidates.push(home.join(".yt-dlp").join("config.txt"));
+    }
+    #[cfg(unix)]
+    {
+        candidates.push(PathBuf::from("/etc/yt-dlp.conf"));
+        candidates.push(PathBuf::from("/etc/yt-dlp/config"));
+        candidates.push(PathBuf::from("/etc/yt-dlp/config.txt"));
+    }
+    candidates
+}
+
+pub fn find_system_config() -> Option<PathBuf> {
+    system_config_candidates().into_iter().find(|path| path.is_file())
+}
+
+// Where yt-dlp looks for the user's config file, to start the file dialog there
+fn default_config_folder() -> Option<PathBuf> {
+    if let Some(parent) = find_system_config().and_then(|path| path.parent().map(PathBuf::from)) {
+        return Some(parent);
+    }
+    let folder = dirs::config_dir()?.join("yt-dlp");
+    folder.is_dir().then_some(folder)
+}
+
+impl YtMp3App {
+    pub(crate) fn show_ytdlp_options_settings(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                help::section_label(ui, "⌨ Extra yt-dlp options:", "extra_options");
+                ui.add_space(5.0);
+                ui.add(egui::TextEdit::singleline(&mut self.settings.extra_args)
+                    .desired_width(400.0)
+                    .hint_text("e.g. --limit-rate 2M --embed-metadata"));
+                ui.horizontal(|ui| {
+                    ui.label("Your own yt-dlp config:");
+                    ui.radio_value(&mut self.settings.system_config, SystemConfig::Ignore, "Ignore");
+                    ui.radio_value(&mut self.settings.system_config, SystemConfig::Inherit, "Inherit");
+                    help::help_button(ui, "system_config");
+                });
+                if self.system_config_path.is_none() {
+                    self.system_config_path = Some(find_system_config());
+                }
+                if let Some(Some(path)) = &self.system_config_path {
+                    let note = match self.settings.system_config {
+                        SystemConfig::Ignore => format!("Found {}, not used", path.display()),
+                        SystemConfig::Inherit => format!("Found {}, its options apply to every download", path.display()),
+                    };
+                    ui.label(egui::RichText::new(note).small().color(egui::Color32::GRAY));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("📥 Import yt-dlp.conf…").clicked() {
+                        let mut dialog = rfd::FileDialog::new()
+                            .add_filter("yt-dlp config", &["conf", "txt"])
+                            .add_filter("All files", &["*"]);
+                        if let Some(folder) = default_config_folder() {
+                            dialog = dialog.set_directory(folder);
+                        }
+                        if let Some(path) = dialog.pick_file() {
+                            self.config_import_report = Some(match std::fs::read_to_string(&path) {
+                                Ok(text) => import(&text, &mut self.settings).summary(),
+                                Err(e) => format!("Could not read {}: {}", path.display(), e),
+                            });
+                        }
+                    }
+                    if self.config_import_report.is_some() && ui.button("Dismiss").clicked() {
+                        self.config_import_report = None;
+                    }
+                });
+                if let Some(report) = &self.config_import_report {
+                    ui.label(egui::RichText::new(report).small());
+                }
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn round_trip(args: &[&str]) {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let text = join_args(&args);
+        assert_eq!(split_args(&text), args, "{}", text);
+    }
+
+    #[test]
+    fn quotes_survive_the_round_trip() {
+        round_trip(&["--replace-in-metadata", "title", "'", ""]);
+        round_trip(&["--replace-in-metadata", "title", "\"", "'"]);
+        round_trip(&["-o", "%(title)s - it's \"live\".%(ext)s"]);
+    }
+
+    #[test]
+    fn paths_and_comments_survive_the_round_trip() {
+        round_trip(&["--ffmpeg-location", "C:\\Program Files\\ffmpeg\\bin\\"]);
+        round_trip(&["--ffmpeg-location", "C:\\ffmpeg\\bin"]);
+        round_trip(&["#not-a-comment", "a#b", "tab\there", "line\nbreak"]);
+    }
+
+    #[test]
+    fn reads_what_was_typed() {
+        assert_eq!(split_args("--ffmpeg-location C:\\ffmpeg\\bin # local build"), ["--ffmpeg-location", "C:\\ffmpeg\\bin"]);
+        assert_eq!(split_args("-o 'a \"b\"' \"c \\\"d\\\"\""), ["-o", "a \"b\"", "c \"d\""]);
+    }
+
+    proptest! {
+        #[test]
+        fn any_arguments_survive_the_round_trip(args in prop::collection::vec("\\PC*", 0..6)) {
+            prop_assert_eq!(split_args(&join_args(&args)), args);
+        }
+    }
+}